@@ -0,0 +1,446 @@
+use crate::lbgk::{self, parameters::d2q5, Lbgk};
+
+use itertools::{iproduct, izip};
+
+/// Boundary schemes of the scalar on the faces of the lattice (or the surfaces of objects).
+#[derive(Clone, Copy, PartialEq)]
+pub enum ScalarBoundaryScheme {
+    Periodic,
+    /// Fixed value ([Dirichlet](https://en.wikipedia.org/wiki/Dirichlet_boundary_condition)) of
+    /// a wall midway beyond the face, by anti-bounce-back.
+    Value(f32),
+    /// Zero flux (e.g. adiabatic) wall midway beyond the face, by bounce-back.
+    ZeroFlux,
+    /// Fixed flux ([Neumann](https://en.wikipedia.org/wiki/Neumann_boundary_condition)) into the
+    /// lattice through each link of a wall midway beyond the face (e.g. a heat flux), by
+    /// bounce-back with the flux added.
+    Flux(f32),
+}
+
+/// Steps of the scalar transport per iteration of the flow, so a scalar of a much higher or
+/// lower diffusivity than the momentum keeps a relaxation time of stable, accurate steps.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Subcycling {
+    /// Sub-steps of each iteration of the flow, of a fraction of its time step (e.g. of a high
+    /// diffusivity).
+    Substeps(usize),
+    /// A step every number of iterations of the flow, of the multiple of its time step,
+    /// skipping the others (e.g. of a low diffusivity).
+    Interval(usize),
+}
+
+/// Transport of a passive scalar (e.g. temperature) by the flow of a two dimensional lattice,
+/// by a D2Q5 lattice Boltzmann model of
+/// [advection–diffusion](https://en.wikipedia.org/wiki/Convection%E2%80%93diffusion_equation).
+///
+/// The scalar may act back on the flow by the buoyancy of the
+/// [Boussinesq approximation](https://en.wikipedia.org/wiki/Boussinesq_approximation_(buoyancy)),
+/// e.g. for natural convection. Object lattice positions are insulating (zero flux), unless the
+/// solids conduct (conjugate heat transfer).
+pub struct AdvectionDiffusion {
+    size: [usize; 2],
+    boundary_schemes: [[ScalarBoundaryScheme; 2]; 2],
+    // note: the boundary scheme of the surfaces of the objects of each ID (zero flux if none)
+    object_boundary_schemes: Vec<ScalarBoundaryScheme>,
+    // note: flat vectors with x varying fastest, as the lattice
+    distributions: Vec<[f32; 5]>,
+    collision_distributions: Vec<[f32; 5]>,
+    values: Vec<f32>,
+    /// Buoyancy force density per unit density and difference of the value from the reference
+    /// value (i.e. the gravitational acceleration multiplied by minus the thermal expansion
+    /// coefficient).
+    buoyancy: [f32; 2],
+    reference_value: f32,
+    subcycling: Subcycling,
+    solid_conductivity: Option<f32>,
+    // note: the iterations of the flow since the last step, of an interval of the subcycling
+    skipped_iterations: usize,
+}
+
+impl AdvectionDiffusion {
+    /// Create a new `AdvectionDiffusion` of a lattice size and boundary schemes, at rest with a
+    /// value at all lattice positions.
+    pub fn new(
+        size: &[usize; 2],
+        boundary_schemes: &[[ScalarBoundaryScheme; 2]; 2],
+        value: f32,
+    ) -> Self {
+        let len = size[0] * size[1];
+        Self {
+            size: *size,
+            boundary_schemes: *boundary_schemes,
+            object_boundary_schemes: Vec::new(),
+            distributions: vec![Self::equilibrium_distributions(value, &[0.0; 2]); len],
+            collision_distributions: vec![[0.0; 5]; len],
+            values: vec![value; len],
+            buoyancy: [0.0; 2],
+            reference_value: value,
+            subcycling: Subcycling::Substeps(1),
+            solid_conductivity: None,
+            skipped_iterations: 0,
+        }
+    }
+
+    /// Flat array index for a lattice position.
+    fn index(&self, pos: &[usize; 2]) -> usize {
+        self.size[0] * pos[1] + pos[0]
+    }
+
+    /// Size of the lattice.
+    pub fn size(&self) -> [usize; 2] {
+        self.size
+    }
+
+    /// Value at lattice position.
+    pub fn value(&self, pos: &[usize; 2]) -> f32 {
+        self.values[self.index(pos)]
+    }
+
+    /// Value at a position, bilinearly interpolated between lattice positions.
+    pub fn interpolated_value(&self, pos: &[f32; 2]) -> f32 {
+        lbgk::bilinear_weights(&self.size, pos)
+            .iter()
+            .map(|(other_pos, weight)| weight * self.value(other_pos))
+            .sum()
+    }
+
+    /// Values at all lattice positions, with x varying fastest.
+    pub fn values(&self) -> &[f32] {
+        &self.values
+    }
+
+    /// Set the value at lattice position, with equilibrium distributions at rest (e.g. for an
+    /// initial condition).
+    pub fn set_value(&mut self, pos: &[usize; 2], value: f32) {
+        let index = self.index(pos);
+        self.values[index] = value;
+        self.distributions[index] = Self::equilibrium_distributions(value, &[0.0; 2]);
+    }
+
+    /// Distributions at lattice position.
+    pub fn distributions(&self, pos: &[usize; 2]) -> [f32; 5] {
+        self.distributions[self.index(pos)]
+    }
+
+    /// Set the distributions at lattice position, with the value from them (e.g. to restore a
+    /// checkpoint).
+    pub fn set_distributions(&mut self, pos: &[usize; 2], distributions: &[f32; 5]) {
+        let index = self.index(pos);
+        self.distributions[index] = *distributions;
+        self.values[index] = distributions.iter().sum();
+    }
+
+    /// Set boundary schemes of each dimension.
+    pub fn set_boundary_schemes(&mut self, boundary_schemes: &[[ScalarBoundaryScheme; 2]; 2]) {
+        self.boundary_schemes = *boundary_schemes;
+    }
+
+    /// Boundary scheme of the surfaces of the objects of an ID.
+    pub fn object_boundary_scheme(&self, id: u16) -> ScalarBoundaryScheme {
+        self.object_boundary_schemes
+            .get(id as usize)
+            .copied()
+            .unwrap_or(ScalarBoundaryScheme::ZeroFlux)
+    }
+
+    /// Set the boundary scheme of the surfaces of the objects of an ID (zero flux by default,
+    /// and periodic taken as zero flux), e.g. a fixed temperature or heat flux of a heated
+    /// object.
+    ///
+    /// note: not applied while the solids conduct, as their surfaces are then within the
+    /// transport
+    pub fn set_object_boundary_scheme(&mut self, id: u16, boundary_scheme: ScalarBoundaryScheme) {
+        let len = id as usize + 1;
+        if self.object_boundary_schemes.len() < len {
+            self.object_boundary_schemes
+                .resize(len, ScalarBoundaryScheme::ZeroFlux);
+        }
+        self.object_boundary_schemes[id as usize] = boundary_scheme;
+    }
+
+    /// Set the buoyancy force density per unit density and difference of the value from the
+    /// reference value, e.g. `[0.0, g * beta]` for a gravitational acceleration `g` against y
+    /// and a thermal expansion coefficient `beta` (zero by default).
+    pub fn set_buoyancy(&mut self, buoyancy: &[f32; 2]) {
+        self.buoyancy = *buoyancy;
+    }
+
+    /// Set the reference value of no buoyancy (the initial value by default).
+    pub fn set_reference_value(&mut self, reference_value: f32) {
+        self.reference_value = reference_value;
+    }
+
+    /// Subcycling of the steps per iteration of the flow.
+    pub fn subcycling(&self) -> Subcycling {
+        self.subcycling
+    }
+
+    /// Set the subcycling of the steps per iteration of the flow (a step each by default).
+    ///
+    /// note: a count of zero is taken as one
+    pub fn set_subcycling(&mut self, subcycling: Subcycling) {
+        self.subcycling = match subcycling {
+            Subcycling::Substeps(count) => Subcycling::Substeps(count.max(1)),
+            Subcycling::Interval(count) => Subcycling::Interval(count.max(1)),
+        };
+        self.skipped_iterations = 0;
+    }
+
+    /// Conductivity of the solid objects relative to that of the fluid, if the scalar diffuses
+    /// inside them.
+    pub fn solid_conductivity(&self) -> Option<f32> {
+        self.solid_conductivity
+    }
+
+    /// Set the conductivity of the solid objects relative to that of the fluid, if the scalar
+    /// diffuses inside them (insulating by default), unless not positive and finite (as the
+    /// relaxation time of the solids would not be above one half).
+    ///
+    /// note: the solids are at rest, of the same heat capacity as the fluid, so their
+    /// diffusivity is of the same ratio, and the value and flux are continuous across the
+    /// surfaces as the distributions stream across them
+    pub fn set_solid_conductivity(
+        &mut self,
+        solid_conductivity: Option<f32>,
+    ) -> Result<(), String> {
+        if let Some(val) = solid_conductivity.filter(|val| !(val.is_finite() && *val > 0.0)) {
+            return Err(format!(
+                "solid conductivity {val} should be positive and finite"
+            ));
+        }
+        self.solid_conductivity = solid_conductivity;
+        Ok(())
+    }
+
+    /// Whether the scalar is transported at a lattice position, i.e. of fluid or of a
+    /// conducting solid.
+    fn transported<const B: usize>(&self, lbgk: &Lbgk<2, B>, pos: &[usize; 2]) -> bool {
+        self.solid_conductivity.is_some() || !lbgk.object(pos)
+    }
+
+    /// Calculate relaxation time, for a
+    /// [Péclet number](https://en.wikipedia.org/wiki/P%C3%A9clet_number) (e.g. the product of
+    /// the Reynolds and Prandtl numbers).
+    pub fn relaxation_time(
+        &self,
+        velocity: f32,
+        characteristic_length: f32,
+        peclet_number: f32,
+    ) -> f32 {
+        characteristic_length * velocity / (d2q5::CS2 * peclet_number) + 0.5
+    }
+
+    /// Diffusivity of a relaxation time.
+    pub fn diffusivity(&self, relaxation_time: f32) -> f32 {
+        d2q5::CS2 * (relaxation_time - 0.5)
+    }
+
+    /// Add the buoyancy force densities to the fluid lattice positions of the lattice.
+    pub fn add_buoyancy<const B: usize>(&self, lbgk: &mut Lbgk<2, B>) {
+        if self.buoyancy == [0.0; 2] {
+            return;
+        }
+        for pos in lbgk.positions() {
+            if lbgk.object(&pos) {
+                continue;
+            }
+            let val = lbgk.density(&pos) * (self.value(&pos) - self.reference_value);
+            lbgk.add_force_density(&pos, &self.buoyancy.map(|buoyancy| buoyancy * val));
+        }
+    }
+
+    /// [Nusselt number](https://en.wikipedia.org/wiki/Nusselt_number) of the transport along a
+    /// dimension between fixed value boundaries at its start and end (e.g. the hot and cold
+    /// walls of Rayleigh–Bénard convection), if any.
+    ///
+    /// note: the ratio of the total flux to that of diffusion alone, from the advective flux
+    /// averaged over the fluid lattice positions
+    pub fn nusselt_number<const B: usize>(
+        &self,
+        lbgk: &Lbgk<2, B>,
+        relaxation_time: f32,
+        dim: usize,
+    ) -> Option<f32> {
+        let [ScalarBoundaryScheme::Value(start), ScalarBoundaryScheme::Value(end)] =
+            self.boundary_schemes[dim]
+        else {
+            return None;
+        };
+
+        let (mut flux, mut count) = (0.0, 0);
+        for pos in lbgk.positions() {
+            if !lbgk.object(&pos) {
+                flux += lbgk.velocity_vector(&pos)[dim] * self.value(&pos);
+                count += 1;
+            }
+        }
+        let diffusive_flux =
+            self.diffusivity(relaxation_time) * (start - end) / self.size[dim] as f32;
+        Some(1.0 + flux / (count.max(1) as f32 * diffusive_flux))
+    }
+
+    /// Local [Nusselt numbers](https://en.wikipedia.org/wiki/Nusselt_number) along a face of a
+    /// fixed value or flux (e.g. a heated wall), at each of its lattice positions (zero at
+    /// objects), of a characteristic length and a reference difference of the value, if any.
+    ///
+    /// note: the flux into the lattice relative to that of diffusion of the reference difference
+    /// across the characteristic length, from the difference of the value of a fixed value
+    /// face to that of the lattice position midway from it
+    pub fn wall_nusselt_numbers<const B: usize>(
+        &self,
+        lbgk: &Lbgk<2, B>,
+        relaxation_time: f32,
+        dim: usize,
+        side: usize,
+        characteristic_length: f32,
+        difference: f32,
+    ) -> Option<Vec<f32>> {
+        let diffusivity = self.diffusivity(relaxation_time);
+        let diffusive_flux = diffusivity * difference / characteristic_length;
+        lbgk.face_positions(dim, side)
+            .map(|pos| {
+                let flux = match self.boundary_schemes[dim][side] {
+                    ScalarBoundaryScheme::Value(value) => {
+                        diffusivity * (value - self.value(&pos)) / 0.5
+                    }
+                    ScalarBoundaryScheme::Flux(flux) => flux,
+                    ScalarBoundaryScheme::Periodic | ScalarBoundaryScheme::ZeroFlux => return None,
+                };
+                Some(match lbgk.object(&pos) {
+                    true => 0.0,
+                    false => flux / diffusive_flux,
+                })
+            })
+            .collect()
+    }
+
+    /// Perform iteration, advected by the velocity of the lattice, of a relaxation time of the
+    /// time step of the flow, by the steps of the subcycling.
+    pub fn iterate<const B: usize>(&mut self, lbgk: &Lbgk<2, B>, relaxation_time: f32) {
+        // note: the ratio of the time step of a step to that of the flow, which scales the
+        // velocity and the diffusivity in lattice units (exactly unchanged for a single step)
+        let (steps, ratio) = match self.subcycling {
+            Subcycling::Substeps(count) => (count, 1.0 / count as f32),
+            Subcycling::Interval(count) => {
+                self.skipped_iterations += 1;
+                match self.skipped_iterations < count {
+                    true => return,
+                    false => {
+                        self.skipped_iterations = 0;
+                        (1, count as f32)
+                    }
+                }
+            }
+        };
+        let relaxation_time = match ratio == 1.0 {
+            true => relaxation_time,
+            false => (relaxation_time - 0.5) * ratio + 0.5,
+        };
+
+        for _ in 0..steps {
+            self.collision_step(lbgk, relaxation_time, ratio);
+            self.streaming_step(lbgk);
+            for (value, distributions) in izip!(&mut self.values, &self.distributions) {
+                *value = distributions.iter().sum();
+            }
+        }
+    }
+
+    /// Perform collision step of iteration, of a ratio of the time step to that of the flow.
+    fn collision_step<const B: usize>(
+        &mut self,
+        lbgk: &Lbgk<2, B>,
+        relaxation_time: f32,
+        ratio: f32,
+    ) {
+        for (y, x) in iproduct!(0..self.size[1], 0..self.size[0]) {
+            let pos = [x, y];
+            let (velocity_vector, relaxation_time) =
+                match (lbgk.object(&pos), self.solid_conductivity) {
+                    (false, _) => (
+                        lbgk.velocity_vector(&pos).map(|val| val * ratio),
+                        relaxation_time,
+                    ),
+                    (true, Some(solid_conductivity)) => {
+                        ([0.0; 2], (relaxation_time - 0.5) * solid_conductivity + 0.5)
+                    }
+                    (true, None) => continue,
+                };
+
+            let index = self.index(&pos);
+            let equilibrium_distributions =
+                Self::equilibrium_distributions(self.values[index], &velocity_vector);
+            for (collision_distribution, distribution, equilibrium_distribution) in izip!(
+                &mut self.collision_distributions[index],
+                self.distributions[index],
+                equilibrium_distributions
+            ) {
+                *collision_distribution =
+                    distribution - (distribution - equilibrium_distribution) / relaxation_time;
+            }
+        }
+    }
+
+    /// Perform streaming step of iteration, reflecting the distributions leaving through a wall
+    /// or into an insulating object.
+    fn streaming_step<const B: usize>(&mut self, lbgk: &Lbgk<2, B>) {
+        for (y, x) in iproduct!(0..self.size[1], 0..self.size[0]) {
+            let pos = [x, y];
+            if !self.transported(lbgk, &pos) {
+                continue;
+            }
+
+            let index = self.index(&pos);
+            for (i, (c, w)) in izip!(d2q5::C, d2q5::W).enumerate() {
+                let distribution = self.collision_distributions[index][i];
+                // note: the opposite lattice vector of the D2Q5 model
+                let opposite = match i {
+                    0 => 0,
+                    _ => (i + 1) % 4 + 1,
+                };
+
+                let mut new_pos = [0; 2];
+                let mut boundary_scheme = None;
+                for (d, (new_pos, pos, c, size)) in
+                    izip!(&mut new_pos, pos, c, self.size).enumerate()
+                {
+                    let val = pos as isize + c;
+                    *new_pos = val.rem_euclid(size as isize) as usize;
+                    if !(0..size as isize).contains(&val) {
+                        boundary_scheme = Some(self.boundary_schemes[d][(c > 0) as usize]);
+                    }
+                }
+
+                // note: the surface of an insulating object is midway beyond the lattice position
+                let boundary_scheme = match boundary_scheme {
+                    None | Some(ScalarBoundaryScheme::Periodic) => {
+                        match self.transported(lbgk, &new_pos) {
+                            true => {
+                                let new_index = self.index(&new_pos);
+                                self.distributions[new_index][i] = distribution;
+                                continue;
+                            }
+                            false => self.object_boundary_scheme(lbgk.object_id(&new_pos)),
+                        }
+                    }
+                    Some(boundary_scheme) => boundary_scheme,
+                };
+                self.distributions[index][opposite] = match boundary_scheme {
+                    ScalarBoundaryScheme::Value(value) => -distribution + 2.0 * w * value,
+                    ScalarBoundaryScheme::Flux(flux) => distribution + flux,
+                    ScalarBoundaryScheme::Periodic | ScalarBoundaryScheme::ZeroFlux => distribution,
+                };
+            }
+        }
+    }
+
+    /// Equilibrium distributions of a value advected by a velocity vector.
+    fn equilibrium_distributions(value: f32, velocity_vector: &[f32; 2]) -> [f32; 5] {
+        let mut result = [0.0; 5];
+        for (result, c, w) in izip!(&mut result, d2q5::C, d2q5::W) {
+            let cu = c[0] as f32 * velocity_vector[0] + c[1] as f32 * velocity_vector[1];
+            *result = w * value * (1.0 + cu / d2q5::CS2);
+        }
+        result
+    }
+}
@@ -0,0 +1,100 @@
+use crate::lbgk::Lbgk;
+
+use itertools::izip;
+
+/// Positions along the vertical centreline of the lid-driven cavity, relative to its size, of
+/// the reference horizontal velocities.
+const GHIA_Y: [f32; 17] = [
+    1.0, 0.9766, 0.9688, 0.9609, 0.9531, 0.8516, 0.7344, 0.6172, 0.5, 0.4531, 0.2813, 0.1719,
+    0.1016, 0.0703, 0.0625, 0.0547, 0.0,
+];
+/// Positions along the horizontal centreline of the lid-driven cavity, relative to its size, of
+/// the reference vertical velocities.
+const GHIA_X: [f32; 17] = [
+    1.0, 0.9688, 0.9609, 0.9531, 0.9453, 0.9063, 0.8594, 0.8047, 0.5, 0.2344, 0.2266, 0.1563,
+    0.0938, 0.0781, 0.0703, 0.0625, 0.0,
+];
+/// Reynolds numbers of the reference data, with the horizontal velocities along the vertical
+/// centreline and the vertical velocities along the horizontal centreline, relative to the lid
+/// velocity.
+#[allow(clippy::type_complexity)]
+const GHIA: [(f32, [f32; 17], [f32; 17]); 3] = [
+    (
+        100.0,
+        [
+            1.0, 0.84123, 0.78871, 0.73722, 0.68717, 0.23151, 0.00332, -0.13641, -0.20581, -0.2109,
+            -0.15662, -0.1015, -0.06434, -0.04775, -0.04192, -0.03717, 0.0,
+        ],
+        [
+            0.0, -0.05906, -0.07391, -0.08864, -0.10313, -0.16914, -0.22445, -0.24533, 0.05454,
+            0.17527, 0.17507, 0.16077, 0.12317, 0.1089, 0.10091, 0.09233, 0.0,
+        ],
+    ),
+    (
+        400.0,
+        [
+            1.0, 0.75837, 0.68439, 0.61756, 0.55892, 0.29093, 0.16256, 0.02135, -0.11477, -0.17119,
+            -0.32726, -0.24299, -0.14612, -0.10338, -0.09266, -0.08186, 0.0,
+        ],
+        [
+            0.0, -0.12146, -0.15663, -0.19254, -0.22847, -0.23827, -0.44993, -0.38598, 0.05186,
+            0.30174, 0.30203, 0.28124, 0.22965, 0.2092, 0.19713, 0.1836, 0.0,
+        ],
+    ),
+    (
+        1000.0,
+        [
+            1.0, 0.65928, 0.57492, 0.51117, 0.46604, 0.33304, 0.18719, 0.05702, -0.0608, -0.10648,
+            -0.27805, -0.38289, -0.2973, -0.2222, -0.20196, -0.18109, 0.0,
+        ],
+        [
+            0.0, -0.21388, -0.27669, -0.33714, -0.39188, -0.5155, -0.42665, -0.31966, 0.02526,
+            0.32235, 0.33075, 0.37095, 0.32627, 0.30353, 0.29012, 0.27485, 0.0,
+        ],
+    ),
+];
+
+/// Largest difference of a Reynolds number from that of the reference data, relative to it, for
+/// the reference data to be compared with.
+const REYNOLDS_NUMBER_TOLERANCE: f32 = 1.0e-3;
+
+/// Reynolds numbers of the reference data of the lid-driven cavity.
+pub fn lid_driven_cavity_reynolds_numbers() -> [f32; 3] {
+    GHIA.map(|(reynolds_number, _, _)| reynolds_number)
+}
+
+/// [Root mean square](https://en.wikipedia.org/wiki/Root_mean_square) errors of the horizontal
+/// velocities along the vertical centreline and the vertical velocities along the horizontal
+/// centreline of a lid-driven cavity, relative to the lid velocity, compared with the reference
+/// data of Ghia, Ghia and Shin ([reference](https://doi.org/10.1016/0021-9991(82)90058-4)), if
+/// there is reference data for the Reynolds number (within a small relative tolerance, so any
+/// other Reynolds number has no errors).
+///
+/// note: the cavity is square, of a size in the bottom left of the lattice, with the walls and
+/// lid midway between lattice positions, and the velocities at the walls and lid (boundary
+/// conditions) are excluded
+pub fn lid_driven_cavity_errors<const B: usize>(
+    lbgk: &Lbgk<2, B>,
+    cavity_size: f32,
+    lid_velocity: f32,
+    reynolds_number: f32,
+) -> Option<[f32; 2]> {
+    let (_, reference_u, reference_v) = GHIA
+        .iter()
+        .find(|(val, _, _)| (reynolds_number - val).abs() <= REYNOLDS_NUMBER_TOLERANCE * val)?;
+    let lattice_position = |val: f32| val * cavity_size - 0.5;
+    let interior = GHIA_Y.len() - 2;
+    let rms = |squared_errors: &mut dyn Iterator<Item = f32>| {
+        (squared_errors.skip(1).take(interior).sum::<f32>() / interior as f32).sqrt()
+    };
+    Some([
+        rms(&mut izip!(GHIA_Y, reference_u).map(|(y, reference)| {
+            let pos = [lattice_position(0.5), lattice_position(y)];
+            (lbgk.interpolated_velocity_vector(&pos)[0] / lid_velocity - reference).powi(2)
+        })),
+        rms(&mut izip!(GHIA_X, reference_v).map(|(x, reference)| {
+            let pos = [lattice_position(x), lattice_position(0.5)];
+            (lbgk.interpolated_velocity_vector(&pos)[1] / lid_velocity - reference).powi(2)
+        })),
+    ])
+}
@@ -0,0 +1,374 @@
+use crate::advection_diffusion::AdvectionDiffusion;
+use crate::indexed_db::IndexedDb;
+use crate::lbgk::Lbgk;
+use crate::snapshot::Snapshot;
+
+use std::collections::VecDeque;
+
+const MAGIC: &[u8; 8] = b"LBMCHKP1";
+const OBJECT_ID_NAME: &str = "object id";
+const FORCE_DENSITY_NAMES: [&str; 2] = ["force density x", "force density y"];
+
+/// Name of the values of a distribution (of the temperature, if thermal).
+fn distribution_name(i: usize, thermal: bool) -> String {
+    match thermal {
+        true => format!("temperature distribution {i}"),
+        false => format!("distribution {i}"),
+    }
+}
+
+/// Checkpoint of the state of a simulation at an iteration, i.e. the distributions, object IDs
+/// and force densities of its lattice (and the distributions of its temperature, if thermal),
+/// from which it can be resumed.
+///
+/// The binary format is the magic bytes `LBMCHKP1`, the iteration (u64, little endian), then a
+/// snapshot (see `Snapshot`) of the values.
+///
+/// note: the resumed flow is exact only without state beyond the distributions, as the
+/// surfaces and wall velocities of objects, objects responding to the flow, immersed
+/// boundaries, the feedback of pressure outlets, the iterations skipped by the subcycling of
+/// the temperature transport, particles and time series are not included
+#[derive(Clone, PartialEq)]
+pub struct Checkpoint {
+    iteration: usize,
+    snapshot: Snapshot,
+}
+
+impl Checkpoint {
+    /// Create a new `Checkpoint` of a lattice (and its temperature, if thermal) at an iteration.
+    pub fn new<const B: usize>(
+        iteration: usize,
+        lbgk: &Lbgk<2, B>,
+        advection_diffusion: Option<&AdvectionDiffusion>,
+    ) -> Self {
+        let mut fields = (0..B)
+            .map(|i| {
+                let values = lbgk
+                    .positions()
+                    .map(|pos| lbgk.distributions(&pos)[i])
+                    .collect();
+                (distribution_name(i, false), values)
+            })
+            .collect::<Vec<_>>();
+        // note: object IDs are exact as f32
+        fields.push((
+            OBJECT_ID_NAME.to_string(),
+            lbgk.positions()
+                .map(|pos| lbgk.object_id(&pos) as f32)
+                .collect(),
+        ));
+        // note: the velocity includes half the force density
+        fields.extend(FORCE_DENSITY_NAMES.iter().enumerate().map(|(d, name)| {
+            let values = lbgk
+                .positions()
+                .map(|pos| lbgk.force_density(&pos)[d])
+                .collect();
+            (name.to_string(), values)
+        }));
+        if let Some(advection_diffusion) = advection_diffusion {
+            fields.extend((0..5).map(|i| {
+                let values = lbgk
+                    .positions()
+                    .map(|pos| advection_diffusion.distributions(&pos)[i])
+                    .collect();
+                (distribution_name(i, true), values)
+            }));
+        }
+
+        Self {
+            iteration,
+            snapshot: Snapshot::from_values(&lbgk.size(), fields),
+        }
+    }
+
+    /// Iteration of the checkpoint.
+    pub fn iteration(&self) -> usize {
+        self.iteration
+    }
+
+    /// Restore the state of a lattice (and its temperature, if thermal) from the checkpoint,
+    /// returning whether restored (i.e. of the same lattice size and model, thermal if and only
+    /// if the checkpoint is).
+    pub fn restore<const B: usize>(
+        &self,
+        lbgk: &mut Lbgk<2, B>,
+        advection_diffusion: Option<&mut AdvectionDiffusion>,
+    ) -> bool {
+        if self.snapshot.size() != lbgk.size() {
+            return false;
+        }
+        let values = |i: usize, thermal: bool| self.snapshot.values(&distribution_name(i, thermal));
+        let (Some(distributions), Some(object_ids), Some(force_densities)) = (
+            (0..B).map(|i| values(i, false)).collect::<Option<Vec<_>>>(),
+            self.snapshot.values(OBJECT_ID_NAME),
+            FORCE_DENSITY_NAMES
+                .iter()
+                .map(|name| self.snapshot.values(name))
+                .collect::<Option<Vec<_>>>(),
+        ) else {
+            return false;
+        };
+        let temperature_distributions = (0..5).map(|i| values(i, true)).collect::<Option<Vec<_>>>();
+        // note: a thermal checkpoint is not restored without its temperature, nor vice versa
+        if advection_diffusion.is_some() != temperature_distributions.is_some() {
+            return false;
+        }
+
+        // note: the object IDs and force densities are set first, as the distributions are only
+        // set at fluid lattice positions, with the velocity including half the force density
+        lbgk.clear_force_densities();
+        for (index, pos) in lbgk.positions().enumerate() {
+            lbgk.set_object_id(&pos, object_ids[index] as u16);
+            lbgk.add_force_density(
+                &pos,
+                &[force_densities[0][index], force_densities[1][index]],
+            );
+        }
+        for (index, pos) in lbgk.positions().enumerate() {
+            lbgk.set_distributions(&pos, &std::array::from_fn(|i| distributions[i][index]));
+        }
+        if let (Some(advection_diffusion), Some(temperature_distributions)) =
+            (advection_diffusion, temperature_distributions)
+        {
+            for (index, pos) in lbgk.positions().enumerate() {
+                advection_diffusion.set_distributions(
+                    &pos,
+                    &std::array::from_fn(|i| temperature_distributions[i][index]),
+                );
+            }
+        }
+        true
+    }
+
+    /// Binary representation of the checkpoint.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut result = MAGIC.to_vec();
+        result.extend_from_slice(&(self.iteration as u64).to_le_bytes());
+        result.extend_from_slice(&self.snapshot.to_bytes());
+        result
+    }
+
+    /// Checkpoint from its binary representation, if valid.
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let (magic, bytes) = bytes.split_at_checked(MAGIC.len())?;
+        let (iteration, bytes) = bytes.split_at_checked(8)?;
+        if magic != MAGIC {
+            return None;
+        }
+        Some(Self {
+            iteration: u64::from_le_bytes(iteration.try_into().ok()?) as usize,
+            snapshot: Snapshot::from_bytes(bytes)?,
+        })
+    }
+}
+
+/// Storage of checkpoints, by their iteration.
+pub trait CheckpointStorage {
+    /// Name of the storage.
+    fn name(&self) -> &'static str;
+
+    /// Save a checkpoint, replacing any of its iteration.
+    fn save(&mut self, checkpoint: &Checkpoint) -> Result<(), String>;
+
+    /// Remove the checkpoint of an iteration, if any.
+    fn remove(&mut self, iteration: usize) -> Result<(), String>;
+}
+
+/// Storage of checkpoints in memory.
+#[derive(Default)]
+pub struct MemoryStorage {
+    checkpoints: Vec<Checkpoint>,
+}
+
+impl MemoryStorage {
+    /// Checkpoints, in the order saved.
+    pub fn checkpoints(&self) -> &[Checkpoint] {
+        &self.checkpoints
+    }
+
+    /// Checkpoint of the latest iteration, if any.
+    pub fn latest(&self) -> Option<&Checkpoint> {
+        self.checkpoints
+            .iter()
+            .max_by_key(|checkpoint| checkpoint.iteration())
+    }
+}
+
+impl CheckpointStorage for MemoryStorage {
+    fn name(&self) -> &'static str {
+        "Memory"
+    }
+
+    fn save(&mut self, checkpoint: &Checkpoint) -> Result<(), String> {
+        self.remove(checkpoint.iteration())?;
+        self.checkpoints.push(checkpoint.clone());
+        Ok(())
+    }
+
+    fn remove(&mut self, iteration: usize) -> Result<(), String> {
+        self.checkpoints
+            .retain(|checkpoint| checkpoint.iteration() != iteration);
+        Ok(())
+    }
+}
+
+/// Storage of checkpoints in an IndexedDB database of the browser dedicated to them (e.g. to
+/// recover from a reload), with keys ordered by iteration.
+pub struct IndexedDbStorage {
+    indexed_db: IndexedDb,
+}
+
+impl IndexedDbStorage {
+    /// Create a new `IndexedDbStorage` of an IndexedDB database.
+    pub fn new(indexed_db: IndexedDb) -> Self {
+        Self { indexed_db }
+    }
+
+    /// Key of the checkpoint of an iteration.
+    ///
+    /// note: zero padded, so the keys sort by iteration
+    fn key(iteration: usize) -> String {
+        format!("checkpoint {iteration:012}")
+    }
+
+    /// Remove all checkpoints (e.g. of a previous run).
+    pub fn clear(&self) -> Result<(), String> {
+        self.indexed_db.clear().map_err(|err| format!("{err:?}"))
+    }
+
+    /// Load the checkpoint of the latest iteration, passing it (if any) to a callback.
+    pub fn latest(&self, f: impl FnOnce(Option<Checkpoint>) + 'static) -> Result<(), String> {
+        let indexed_db = self.indexed_db.clone();
+        self.indexed_db
+            .keys(move |keys| match keys.last() {
+                Some(key) => {
+                    let _ = indexed_db.get(key, move |bytes| {
+                        f(bytes.and_then(|bytes| Checkpoint::from_bytes(&bytes)))
+                    });
+                }
+                None => f(None),
+            })
+            .map_err(|err| format!("{err:?}"))
+    }
+}
+
+impl CheckpointStorage for IndexedDbStorage {
+    fn name(&self) -> &'static str {
+        "IndexedDB"
+    }
+
+    fn save(&mut self, checkpoint: &Checkpoint) -> Result<(), String> {
+        self.indexed_db
+            .put(&Self::key(checkpoint.iteration()), &checkpoint.to_bytes())
+            .map_err(|err| format!("{err:?}"))
+    }
+
+    fn remove(&mut self, iteration: usize) -> Result<(), String> {
+        self.indexed_db
+            .delete(&Self::key(iteration))
+            .map_err(|err| format!("{err:?}"))
+    }
+}
+
+/// Storage of checkpoints as files `checkpoint-<iteration>.lbm` in a directory, on native
+/// targets.
+#[cfg(not(target_arch = "wasm32"))]
+pub struct DiskStorage {
+    directory: std::path::PathBuf,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl DiskStorage {
+    /// Create a new `DiskStorage` of a directory, creating it if needed.
+    pub fn new(directory: impl Into<std::path::PathBuf>) -> Result<Self, String> {
+        let directory = directory.into();
+        std::fs::create_dir_all(&directory).map_err(|err| err.to_string())?;
+        Ok(Self { directory })
+    }
+
+    /// Path of the checkpoint of an iteration.
+    fn path(&self, iteration: usize) -> std::path::PathBuf {
+        self.directory.join(format!("checkpoint-{iteration}.lbm"))
+    }
+
+    /// Checkpoint of the latest iteration in the directory, if any.
+    pub fn latest(&self) -> Option<Checkpoint> {
+        let iteration = std::fs::read_dir(&self.directory)
+            .ok()?
+            .filter_map(|entry| {
+                let name = entry.ok()?.file_name().into_string().ok()?;
+                name.strip_prefix("checkpoint-")?
+                    .strip_suffix(".lbm")?
+                    .parse::<usize>()
+                    .ok()
+            })
+            .max()?;
+        Checkpoint::from_bytes(&std::fs::read(self.path(iteration)).ok()?)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl CheckpointStorage for DiskStorage {
+    fn name(&self) -> &'static str {
+        "Disk"
+    }
+
+    fn save(&mut self, checkpoint: &Checkpoint) -> Result<(), String> {
+        std::fs::write(self.path(checkpoint.iteration()), checkpoint.to_bytes())
+            .map_err(|err| err.to_string())
+    }
+
+    fn remove(&mut self, iteration: usize) -> Result<(), String> {
+        match std::fs::remove_file(self.path(iteration)) {
+            Err(err) if err.kind() != std::io::ErrorKind::NotFound => Err(err.to_string()),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Automatic checkpointing of a simulation every interval of iterations to a storage, retaining
+/// a number of the latest checkpoints (e.g. to resume a long run after a crash or reload).
+pub struct AutoCheckpoint<S: CheckpointStorage> {
+    interval: usize,
+    retention: usize,
+    storage: S,
+    /// Iterations of the checkpoints saved, oldest first.
+    iterations: VecDeque<usize>,
+}
+
+impl<S: CheckpointStorage> AutoCheckpoint<S> {
+    /// Create a new `AutoCheckpoint` of an interval (iterations) and a retention (at least one
+    /// checkpoint), to a storage.
+    pub fn new(interval: usize, retention: usize, storage: S) -> Self {
+        Self {
+            interval: interval.max(1),
+            retention: retention.max(1),
+            storage,
+            iterations: VecDeque::new(),
+        }
+    }
+
+    /// Storage of the checkpoints.
+    pub fn storage(&self) -> &S {
+        &self.storage
+    }
+
+    /// Whether a checkpoint is due at an iteration.
+    pub fn due(&self, iteration: usize) -> bool {
+        iteration > 0 && iteration.is_multiple_of(self.interval)
+    }
+
+    /// Save a checkpoint, removing the oldest beyond the retention.
+    pub fn save(&mut self, checkpoint: &Checkpoint) -> Result<(), String> {
+        self.storage.save(checkpoint)?;
+        self.iterations
+            .retain(|iteration| *iteration != checkpoint.iteration());
+        self.iterations.push_back(checkpoint.iteration());
+        while self.iterations.len() > self.retention {
+            if let Some(iteration) = self.iterations.pop_front() {
+                self.storage.remove(iteration)?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -18,3 +18,399 @@ pub fn hsv_to_rgb(h: f32, s: f32, v: f32) -> (f32, f32, f32) {
     let m = v - c;
     (r1 + m, g1 + m, b1 + m)
 }
+
+/// Convert from RGB colour to HSV colour
+/// ([reference](https://en.wikipedia.org/wiki/HSL_and_HSV)).
+///
+/// Input RGB range is ([0,1], [0,1], [0,1]).
+/// Output HSV range is ([0,360), [0,1], [0,1]).
+pub fn rgb_to_hsv(r: f32, g: f32, b: f32) -> (f32, f32, f32) {
+    let (h, c, max, _) = hue_chroma(r, g, b);
+    let s = match max > 0.0 {
+        true => c / max,
+        false => 0.0,
+    };
+    (h, s, max)
+}
+
+/// Convert from HSL colour to RGB colour
+/// ([reference](https://en.wikipedia.org/wiki/HSL_and_HSV)).
+///
+/// Input HSL range is ([0,360], [0,1], [0,1]).
+/// Output RGB range is ([0,1], [0,1], [0,1]).
+pub fn hsl_to_rgb(h: f32, s: f32, l: f32) -> (f32, f32, f32) {
+    // note: HSL maps to HSV with the same hue
+    let v = l + s * l.min(1.0 - l);
+    let s = match v > 0.0 {
+        true => 2.0 * (1.0 - l / v),
+        false => 0.0,
+    };
+    hsv_to_rgb(h, s, v)
+}
+
+/// Convert from RGB colour to HSL colour
+/// ([reference](https://en.wikipedia.org/wiki/HSL_and_HSV)).
+///
+/// Input RGB range is ([0,1], [0,1], [0,1]).
+/// Output HSL range is ([0,360), [0,1], [0,1]).
+pub fn rgb_to_hsl(r: f32, g: f32, b: f32) -> (f32, f32, f32) {
+    let (h, c, max, min) = hue_chroma(r, g, b);
+    let l = 0.5 * (max + min);
+    let s = match l > 0.0 && l < 1.0 {
+        true => c / (1.0 - (2.0 * l - 1.0).abs()),
+        false => 0.0,
+    };
+    (h, s, l)
+}
+
+/// Hue, chroma, maximum and minimum of an RGB colour.
+///
+/// note: the hue of a grey (zero chroma) is 0
+fn hue_chroma(r: f32, g: f32, b: f32) -> (f32, f32, f32, f32) {
+    let (max, min) = (r.max(g).max(b), r.min(g).min(b));
+    let c = max - min;
+    let h = match c > 0.0 {
+        false => 0.0,
+        true if max == r => 60.0 * ((g - b) / c).rem_euclid(6.0),
+        true if max == g => 60.0 * ((b - r) / c + 2.0),
+        true => 60.0 * ((r - g) / c + 4.0),
+    };
+    (h, c, max, min)
+}
+
+/// Convert from RGB colour of range ([0,1], [0,1], [0,1]) to range ([0,255], [0,255], [0,255])
+/// (clamped and rounded).
+pub fn rgb_to_u8(rgb: (f32, f32, f32)) -> (u8, u8, u8) {
+    let channel = |val: f32| (val.clamp(0.0, 1.0) * u8::MAX as f32).round() as u8;
+    (channel(rgb.0), channel(rgb.1), channel(rgb.2))
+}
+
+/// Convert from RGB colour of range ([0,255], [0,255], [0,255]) to range ([0,1], [0,1], [0,1]).
+pub fn rgb_from_u8(rgb: (u8, u8, u8)) -> (f32, f32, f32) {
+    let channel = |val: u8| val as f32 / u8::MAX as f32;
+    (channel(rgb.0), channel(rgb.1), channel(rgb.2))
+}
+
+/// Convert from HSV colour to RGB colour of range ([0,255], [0,255], [0,255]).
+pub fn hsv_to_rgb_u8(h: f32, s: f32, v: f32) -> (u8, u8, u8) {
+    rgb_to_u8(hsv_to_rgb(h, s, v))
+}
+
+/// Convert from RGB colour of range ([0,255], [0,255], [0,255]) to HSV colour.
+pub fn rgb_u8_to_hsv(r: u8, g: u8, b: u8) -> (f32, f32, f32) {
+    let (r, g, b) = rgb_from_u8((r, g, b));
+    rgb_to_hsv(r, g, b)
+}
+
+/// Convert from HSL colour to RGB colour of range ([0,255], [0,255], [0,255]).
+pub fn hsl_to_rgb_u8(h: f32, s: f32, l: f32) -> (u8, u8, u8) {
+    rgb_to_u8(hsl_to_rgb(h, s, l))
+}
+
+/// Convert from RGB colour of range ([0,255], [0,255], [0,255]) to HSL colour.
+pub fn rgb_u8_to_hsl(r: u8, g: u8, b: u8) -> (f32, f32, f32) {
+    let (r, g, b) = rgb_from_u8((r, g, b));
+    rgb_to_hsl(r, g, b)
+}
+
+/// Convert from (gamma encoded) [sRGB](https://en.wikipedia.org/wiki/SRGB) colour to linear
+/// RGB colour.
+///
+/// Input and output RGB range is ([0,1], [0,1], [0,1]).
+pub fn srgb_to_linear(r: f32, g: f32, b: f32) -> (f32, f32, f32) {
+    let channel = |val: f32| match val <= 0.04045 {
+        true => val / 12.92,
+        false => ((val + 0.055) / 1.055).powf(2.4),
+    };
+    (channel(r), channel(g), channel(b))
+}
+
+/// Convert from linear RGB colour to (gamma encoded) [sRGB](https://en.wikipedia.org/wiki/SRGB)
+/// colour.
+///
+/// Input and output RGB range is ([0,1], [0,1], [0,1]) (clamped).
+pub fn linear_to_srgb(r: f32, g: f32, b: f32) -> (f32, f32, f32) {
+    let channel = |val: f32| {
+        let val = val.clamp(0.0, 1.0);
+        match val <= 0.0031308 {
+            true => 12.92 * val,
+            false => 1.055 * val.powf(1.0 / 2.4) - 0.055,
+        }
+    };
+    (channel(r), channel(g), channel(b))
+}
+
+/// Convert from sRGB colour to Oklab colour
+/// ([reference](https://bottosson.github.io/posts/oklab/)).
+///
+/// Input RGB range is ([0,1], [0,1], [0,1]).
+/// Output lightness range is [0,1].
+pub fn rgb_to_oklab(r: f32, g: f32, b: f32) -> (f32, f32, f32) {
+    let (r, g, b) = srgb_to_linear(r, g, b);
+    let l = (0.41222146 * r + 0.53633255 * g + 0.051445995 * b).cbrt();
+    let m = (0.2119035 * r + 0.6806995 * g + 0.10739696 * b).cbrt();
+    let s = (0.08830246 * r + 0.28171885 * g + 0.6299787 * b).cbrt();
+    (
+        0.21045426 * l + 0.7936178 * m - 0.004072047 * s,
+        1.9779985 * l - 2.4285922 * m + 0.4505937 * s,
+        0.025904037 * l + 0.78277177 * m - 0.80867577 * s,
+    )
+}
+
+/// Convert from Oklab colour to sRGB colour
+/// ([reference](https://bottosson.github.io/posts/oklab/)).
+///
+/// Input lightness range is [0,1].
+/// Output RGB range is ([0,1], [0,1], [0,1]) (clamped).
+pub fn oklab_to_rgb(l: f32, a: f32, b: f32) -> (f32, f32, f32) {
+    let l_ = (l + 0.39633778 * a + 0.21580376 * b).powi(3);
+    let m_ = (l - 0.105561346 * a - 0.06385417 * b).powi(3);
+    let s_ = (l - 0.08948418 * a - 1.2914855 * b).powi(3);
+    linear_to_srgb(
+        4.0767417 * l_ - 3.3077116 * m_ + 0.23096994 * s_,
+        -1.268438 * l_ + 2.6097574 * m_ - 0.34131938 * s_,
+        -0.0041960863 * l_ - 0.7034186 * m_ + 1.7076147 * s_,
+    )
+}
+
+// note: CIE XYZ tristimulus values of the D65 white point
+const D65: [f32; 3] = [0.95047, 1.0, 1.08883];
+
+/// Convert from sRGB colour to [CIELAB](https://en.wikipedia.org/wiki/CIELAB_color_space)
+/// colour (D65 white point).
+///
+/// Input RGB range is ([0,1], [0,1], [0,1]).
+/// Output lightness range is [0,100].
+pub fn rgb_to_lab(r: f32, g: f32, b: f32) -> (f32, f32, f32) {
+    let (r, g, b) = srgb_to_linear(r, g, b);
+    let [x, y, z] = [
+        0.4124564 * r + 0.3575761 * g + 0.1804375 * b,
+        0.2126729 * r + 0.7151522 * g + 0.072175 * b,
+        0.0193339 * r + 0.119192 * g + 0.9503041 * b,
+    ];
+    let f = |t: f32| match t > 216.0 / 24389.0 {
+        true => t.cbrt(),
+        false => (24389.0 / 27.0 * t + 16.0) / 116.0,
+    };
+    let [fx, fy, fz] = [f(x / D65[0]), f(y / D65[1]), f(z / D65[2])];
+    (116.0 * fy - 16.0, 500.0 * (fx - fy), 200.0 * (fy - fz))
+}
+
+/// Convert from [CIELAB](https://en.wikipedia.org/wiki/CIELAB_color_space) colour (D65 white
+/// point) to sRGB colour.
+///
+/// Input lightness range is [0,100].
+/// Output RGB range is ([0,1], [0,1], [0,1]) (clamped).
+pub fn lab_to_rgb(l: f32, a: f32, b: f32) -> (f32, f32, f32) {
+    let fy = (l + 16.0) / 116.0;
+    let (fx, fz) = (fy + a / 500.0, fy - b / 200.0);
+    let f_inv = |t: f32| match t > 6.0 / 29.0 {
+        true => t.powi(3),
+        false => 27.0 / 24389.0 * (116.0 * t - 16.0),
+    };
+    let [x, y, z] = [D65[0] * f_inv(fx), D65[1] * f_inv(fy), D65[2] * f_inv(fz)];
+    linear_to_srgb(
+        3.2404542 * x - 1.5371385 * y - 0.4985314 * z,
+        -0.969266 * x + 1.8760108 * y + 0.041556 * z,
+        0.0556434 * x - 0.2040259 * y + 1.0572252 * z,
+    )
+}
+
+/// Convert from RGB colour to a hexadecimal colour (e.g. `#ff8000`).
+///
+/// Input RGB range is ([0,1], [0,1], [0,1]) (clamped).
+pub fn rgb_to_hex(rgb: (f32, f32, f32)) -> String {
+    let (r, g, b) = rgb_to_u8(rgb);
+    format!("#{r:02x}{g:02x}{b:02x}")
+}
+
+/// Convert from a hexadecimal colour (e.g. `#ff8000`) to RGB colour, if valid.
+///
+/// Output RGB range is ([0,1], [0,1], [0,1]).
+pub fn rgb_from_hex(hex: &str) -> Option<(f32, f32, f32)> {
+    let hex = hex.strip_prefix('#')?;
+    if hex.len() != 6 {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+    Some(rgb_from_u8((channel(0)?, channel(2)?, channel(4)?)))
+}
+
+/// Colour maps from values in [0,1] to RGB colours.
+#[derive(Clone, PartialEq)]
+pub enum Colormap {
+    /// Diverging map of two hues with value increasing away from the middle.
+    TwoHue,
+    /// Perceptually uniform sequential map
+    /// ([reference](https://bids.github.io/colormap/)).
+    Viridis,
+    /// Perceptually uniform sequential map
+    /// ([reference](https://bids.github.io/colormap/)).
+    Plasma,
+    /// Diverging blue to red map of Moreland
+    /// ([reference](https://www.kennethmoreland.com/color-maps/)).
+    Coolwarm,
+    /// Rainbow map of MATLAB.
+    Jet,
+    /// Black to white map.
+    Grayscale,
+    /// User-defined map of control points of a value and an RGB colour, in increasing order of
+    /// value.
+    Custom(Vec<(f32, (f32, f32, f32))>),
+}
+
+// note: control points evenly spaced over [0,1] and interpolated in Oklab
+const VIRIDIS: [(f32, f32, f32); 9] = [
+    (0.267004, 0.004874, 0.329415),
+    (0.282327, 0.140926, 0.457517),
+    (0.253935, 0.265254, 0.529983),
+    (0.206756, 0.371758, 0.553117),
+    (0.163625, 0.471133, 0.558148),
+    (0.127568, 0.566949, 0.550556),
+    (0.134692, 0.658636, 0.517649),
+    (0.477504, 0.821444, 0.318195),
+    (0.993248, 0.906157, 0.143936),
+];
+const PLASMA: [(f32, f32, f32); 9] = [
+    (0.050383, 0.029803, 0.527975),
+    (0.287076, 0.010855, 0.627295),
+    (0.494877, 0.011990, 0.657865),
+    (0.665129, 0.138566, 0.585582),
+    (0.798216, 0.280197, 0.469538),
+    (0.897757, 0.418120, 0.361722),
+    (0.973416, 0.585761, 0.251176),
+    (0.994141, 0.771030, 0.155961),
+    (0.940015, 0.975158, 0.131326),
+];
+const COOLWARM: [(f32, f32, f32); 9] = [
+    (0.229806, 0.298718, 0.753683),
+    (0.353569, 0.472234, 0.893289),
+    (0.484582, 0.628065, 0.975265),
+    (0.667400, 0.779200, 0.992900),
+    (0.865003, 0.865003, 0.865003),
+    (0.968800, 0.721200, 0.612200),
+    (0.958852, 0.602842, 0.481691),
+    (0.880737, 0.402435, 0.318987),
+    (0.705673, 0.015556, 0.150233),
+];
+
+impl Colormap {
+    /// All colour maps.
+    pub const ALL: [Colormap; 6] = [
+        Colormap::TwoHue,
+        Colormap::Viridis,
+        Colormap::Plasma,
+        Colormap::Coolwarm,
+        Colormap::Jet,
+        Colormap::Grayscale,
+    ];
+
+    /// Name of the colour map.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Colormap::TwoHue => "Two hue",
+            Colormap::Viridis => "Viridis",
+            Colormap::Plasma => "Plasma",
+            Colormap::Coolwarm => "Coolwarm",
+            Colormap::Jet => "Jet",
+            Colormap::Grayscale => "Grayscale",
+            Colormap::Custom(_) => "Custom",
+        }
+    }
+
+    /// Create a custom colour map from control points of a value and an RGB colour, if there
+    /// are at least two points of finite values.
+    ///
+    /// note: values below the first and above the last point have the colour of that point
+    pub fn custom(points: &[(f32, (f32, f32, f32))]) -> Option<Self> {
+        if points.len() < 2 || points.iter().any(|(value, _)| !value.is_finite()) {
+            return None;
+        }
+        let mut points = points.to_vec();
+        points.sort_by(|first, second| first.0.total_cmp(&second.0));
+        Some(Colormap::Custom(points))
+    }
+
+    /// Custom colour map from its text representation, if valid.
+    ///
+    /// note: control points are separated by commas, each of a value and a hexadecimal colour
+    /// (e.g. `0 #000000, 0.5 #ff0000, 1 #ffffff`)
+    pub fn from_text(text: &str) -> Option<Self> {
+        let points = text
+            .split(',')
+            .map(|point| {
+                let (value, hex) = point.trim().split_once(' ')?;
+                Some((value.parse().ok()?, rgb_from_hex(hex.trim())?))
+            })
+            .collect::<Option<Vec<_>>>()?;
+        Self::custom(&points)
+    }
+
+    /// Text representation of a custom colour map, if custom.
+    pub fn to_text(&self) -> Option<String> {
+        match self {
+            Colormap::Custom(points) => Some(
+                points
+                    .iter()
+                    .map(|(value, rgb)| format!("{value} {}", rgb_to_hex(*rgb)))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            ),
+            _ => None,
+        }
+    }
+
+    /// Map a value to a colour.
+    ///
+    /// Input range is [0,1] (clamped).
+    /// Output RGB range is ([0,1], [0,1], [0,1]).
+    pub fn rgb(&self, t: f32) -> (f32, f32, f32) {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Colormap::TwoHue => match t < 0.5 {
+                true => hsv_to_rgb(180.0, 1.0, 1.0 - 2.0 * t),
+                false => hsv_to_rgb(360.0, 1.0, 2.0 * t - 1.0),
+            },
+            Colormap::Viridis => interpolate(&VIRIDIS, t),
+            Colormap::Plasma => interpolate(&PLASMA, t),
+            Colormap::Coolwarm => interpolate(&COOLWARM, t),
+            Colormap::Jet => {
+                let channel = |offset: f32| (1.5 - (4.0 * t - offset).abs()).clamp(0.0, 1.0);
+                (channel(3.0), channel(2.0), channel(1.0))
+            }
+            Colormap::Grayscale => (t, t, t),
+            Colormap::Custom(points) => {
+                let i = points
+                    .partition_point(|(value, _)| *value <= t)
+                    .clamp(1, points.len() - 1);
+                let ((first_value, first), (second_value, second)) = (points[i - 1], points[i]);
+                let s = match second_value > first_value {
+                    true => ((t - first_value) / (second_value - first_value)).clamp(0.0, 1.0),
+                    false => 0.0,
+                };
+                mix(first, second, s)
+            }
+        }
+    }
+}
+
+/// Interpolate evenly spaced control points of RGB colours.
+///
+/// note: colours are linearly interpolated in Oklab, which is perceptually uniform, so there is
+/// no banding of lightness between control points
+fn interpolate(points: &[(f32, f32, f32)], t: f32) -> (f32, f32, f32) {
+    let s = t * (points.len() - 1) as f32;
+    let i = (s as usize).min(points.len() - 2);
+    mix(points[i], points[i + 1], s - i as f32)
+}
+
+/// Linearly interpolate between two RGB colours in Oklab.
+fn mix(first: (f32, f32, f32), second: (f32, f32, f32), s: f32) -> (f32, f32, f32) {
+    let first = rgb_to_oklab(first.0, first.1, first.2);
+    let second = rgb_to_oklab(second.0, second.1, second.2);
+    oklab_to_rgb(
+        first.0 + s * (second.0 - first.0),
+        first.1 + s * (second.1 - first.1),
+        first.2 + s * (second.2 - first.2),
+    )
+}
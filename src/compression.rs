@@ -0,0 +1,103 @@
+/// Bytes of a value of the shuffle (i.e. of an f32).
+const STRIDE: usize = 4;
+/// Longest run (and literal sequence) of a header byte.
+const MAX_RUN: usize = 128;
+
+/// Lossless compression of bytes of f32 values (e.g. of a snapshot), by shuffling the bytes
+/// into planes of significance (so the slowly varying sign and exponent bytes of smooth fields
+/// are consecutive) and [PackBits](https://en.wikipedia.org/wiki/PackBits) run-length encoding.
+pub fn compress(bytes: &[u8]) -> Vec<u8> {
+    pack_bits(&shuffle(bytes))
+}
+
+/// Bytes decompressed (see `compress`), if valid.
+pub fn decompress(bytes: &[u8]) -> Option<Vec<u8>> {
+    Some(unshuffle(&unpack_bits(bytes)?))
+}
+
+/// Bytes shuffled into planes of the bytes of each value of the stride, with any remaining
+/// bytes last.
+fn shuffle(bytes: &[u8]) -> Vec<u8> {
+    let count = bytes.len() / STRIDE;
+    let mut result = (0..STRIDE)
+        .flat_map(|b| (0..count).map(move |i| bytes[STRIDE * i + b]))
+        .collect::<Vec<_>>();
+    result.extend_from_slice(&bytes[STRIDE * count..]);
+    result
+}
+
+/// Bytes unshuffled (see `shuffle`).
+fn unshuffle(bytes: &[u8]) -> Vec<u8> {
+    let count = bytes.len() / STRIDE;
+    let mut result = (0..count)
+        .flat_map(|i| (0..STRIDE).map(move |b| bytes[count * b + i]))
+        .collect::<Vec<_>>();
+    result.extend_from_slice(&bytes[STRIDE * count..]);
+    result
+}
+
+/// Bytes run-length encoded, with a header byte of either a count of literal bytes following
+/// (0 to 127 for 1 to 128 bytes) or a count of repeats of the byte following (-1 to -127 for 2
+/// to 128 repeats).
+fn pack_bits(bytes: &[u8]) -> Vec<u8> {
+    let mut result = Vec::with_capacity(bytes.len());
+    let mut literals: Vec<u8> = Vec::with_capacity(MAX_RUN);
+    let flush = |result: &mut Vec<u8>, literals: &mut Vec<u8>| {
+        if !literals.is_empty() {
+            result.push((literals.len() - 1) as u8);
+            result.append(literals);
+        }
+    };
+
+    let mut i = 0;
+    while i < bytes.len() {
+        let run = bytes[i..]
+            .iter()
+            .take(MAX_RUN)
+            .take_while(|val| **val == bytes[i])
+            .count();
+        // note: a run of two is encoded as literal bytes after other literal bytes, as it
+        // would take as many bytes and split the literal bytes
+        match run > 2 || (run == 2 && literals.is_empty()) {
+            true => {
+                flush(&mut result, &mut literals);
+                result.push((1 - run as isize) as i8 as u8);
+                result.push(bytes[i]);
+                i += run;
+            }
+            false => {
+                literals.push(bytes[i]);
+                if literals.len() == MAX_RUN {
+                    flush(&mut result, &mut literals);
+                }
+                i += 1;
+            }
+        }
+    }
+    flush(&mut result, &mut literals);
+    result
+}
+
+/// Bytes run-length decoded (see `pack_bits`), if valid.
+fn unpack_bits(bytes: &[u8]) -> Option<Vec<u8>> {
+    let mut result = Vec::with_capacity(2 * bytes.len());
+    let mut i = 0;
+    while let Some(&header) = bytes.get(i) {
+        let header = header as i8;
+        match header {
+            0.. => {
+                let len = header as usize + 1;
+                result.extend_from_slice(bytes.get(i + 1..i + 1 + len)?);
+                i += 1 + len;
+            }
+            // note: no operation
+            -128 => i += 1,
+            _ => {
+                let len = 1 - header as isize;
+                result.extend(std::iter::repeat_n(*bytes.get(i + 1)?, len as usize));
+                i += 2;
+            }
+        }
+    }
+    Some(result)
+}
@@ -0,0 +1,344 @@
+use crate::lbgk::Field;
+use crate::log;
+use crate::random;
+use crate::resolution;
+use crate::scenario::Scenario;
+use crate::simulation::{PartialConfig, Simulation};
+use crate::stats::Stats;
+
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+use std::cell::RefCell;
+
+/// Version of the embedding API, following [semantic versioning](https://semver.org/), i.e.
+/// the functions and methods of `LbmSimulation` are only removed or changed incompatibly with
+/// a new major version.
+pub const API_VERSION: &str = "1.5.0";
+/// Smallest width and height of a lattice.
+const MIN_SIZE: usize = 16;
+
+/// Version of the embedding API (see `LbmSimulation`).
+#[wasm_bindgen(js_name = apiVersion)]
+pub fn api_version() -> String {
+    API_VERSION.to_string()
+}
+
+/// Names of the scenarios, to create a `LbmSimulation` of.
+#[wasm_bindgen(js_name = scenarioNames)]
+pub fn scenario_names() -> Vec<String> {
+    Scenario::ALL.iter().map(Scenario::name).collect()
+}
+
+/// Throughput in MLUPS (millions of lattice updates per second) of this device, measured by
+/// a micro-benchmark of a few hundred iterations on a small lattice.
+#[wasm_bindgen(js_name = measureThroughput)]
+pub fn measure_throughput() -> f64 {
+    resolution::measure_throughput(
+        &resolution::BENCHMARK_SIZE,
+        resolution::BENCHMARK_ITERATIONS,
+    )
+}
+
+/// Largest lattice size, of the aspect ratio of a largest size, which iterates at a target rate
+/// (iterations per second) on this device, measured by a micro-benchmark at startup (e.g. to
+/// choose the default size of a `LbmSimulation`), as `[width, height]`.
+#[wasm_bindgen(js_name = recommendedSize)]
+pub fn recommended_size(max_width: usize, max_height: usize, target_rate: f64) -> Vec<usize> {
+    let throughput = measure_throughput();
+    let size =
+        resolution::recommended_size(&[max_width, max_height], MIN_SIZE, throughput, target_rate);
+    log::info(
+        "embedding",
+        &format!(
+            "throughput {throughput:.1} MLUPS, so lattice size {} by {} is recommended",
+            size[0], size[1]
+        ),
+    );
+    size.to_vec()
+}
+
+/// Error of a lattice size, if smaller than the smallest.
+fn validate_size(width: usize, height: usize) -> Result<(), String> {
+    match width < MIN_SIZE || height < MIN_SIZE {
+        true => Err(format!(
+            "lattice size {width} by {height} is smaller than {MIN_SIZE} by {MIN_SIZE}"
+        )),
+        false => Ok(()),
+    }
+}
+
+/// Configuration of a `LbmSimulation`, accepted from JavaScript as an object with camel case
+/// keys (e.g. `reynoldsNumber`).
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct Config {
+    pub width: usize,
+    pub height: usize,
+    /// Name of the scenario (see `scenarioNames`).
+    pub scenario: String,
+    /// Magnitude of the velocity of the flow.
+    pub velocity: f32,
+    pub reynolds_number: f32,
+    /// Seed of the random numbers of the scenario, if not the default.
+    #[serde(default)]
+    pub seed: Option<u32>,
+}
+
+/// Simulation embedded in a web application without the built-in user interface, the stable
+/// API (see `apiVersion`) for packaging on npm: create, configure, step, read fields and
+/// statistics, register callbacks and destroy.
+///
+/// note: build without the built-in user interface by `wasm-pack build --target bundler
+/// --no-default-features`
+#[wasm_bindgen(js_name = LbmSimulation)]
+pub struct EmbeddedSimulation {
+    // note: borrowed only while iterating, not while calling the callbacks, so they may read
+    // the simulation
+    simulation: RefCell<Simulation>,
+    config: Config,
+    /// Callbacks of steps, with their handle and interval (iterations).
+    callbacks: Vec<(usize, js_sys::Function, usize)>,
+    next_handle: usize,
+}
+
+#[wasm_bindgen(js_class = LbmSimulation)]
+impl EmbeddedSimulation {
+    /// Create a new `LbmSimulation` of a lattice size, for a scenario (a name of the
+    /// scenarios), a velocity of the flow, a Reynolds number and optionally a seed of random
+    /// numbers, throwing an error if invalid.
+    #[wasm_bindgen(constructor)]
+    pub fn create(
+        width: usize,
+        height: usize,
+        scenario: &str,
+        velocity: f32,
+        reynolds_number: f32,
+        seed: Option<u32>,
+    ) -> Result<EmbeddedSimulation, String> {
+        Self::from_config(&Config {
+            width,
+            height,
+            scenario: scenario.to_string(),
+            velocity,
+            reynolds_number,
+            seed,
+        })
+    }
+
+    /// Create a new `LbmSimulation` of a configuration object (see `Config`), throwing an
+    /// error if invalid.
+    #[wasm_bindgen(js_name = fromConfig)]
+    pub fn from_config_object(config: JsValue) -> Result<EmbeddedSimulation, String> {
+        let config = serde_wasm_bindgen::from_value(config).map_err(|err| err.to_string())?;
+        Self::from_config(&config)
+    }
+
+    /// Configuration as an object (see `Config`), with the current flow parameters.
+    #[wasm_bindgen(js_name = config)]
+    pub fn config_object(&self) -> Result<JsValue, String> {
+        serde_wasm_bindgen::to_value(&self.config).map_err(|err| err.to_string())
+    }
+
+    /// Width of the lattice.
+    pub fn width(&self) -> usize {
+        self.simulation.borrow().width()
+    }
+
+    /// Height of the lattice.
+    pub fn height(&self) -> usize {
+        self.simulation.borrow().height()
+    }
+
+    /// Name of the scenario.
+    pub fn scenario(&self) -> String {
+        self.config.scenario.clone()
+    }
+
+    /// Number of iterations performed.
+    pub fn iteration(&self) -> usize {
+        self.simulation.borrow().iteration()
+    }
+
+    /// Set the velocity of the flow and the Reynolds number without resetting the flow,
+    /// throwing an error if invalid.
+    pub fn configure(&mut self, velocity: f32, reynolds_number: f32) -> Result<(), String> {
+        self.update_config(&PartialConfig {
+            velocity: Some(velocity),
+            reynolds_number: Some(reynolds_number),
+            ..Default::default()
+        })
+    }
+
+    /// Apply a partial configuration object without resetting the flow, as `updateConfig`.
+    ///
+    /// @deprecated Use `updateConfig`, of which this is an alias.
+    #[wasm_bindgen(js_name = configureWith)]
+    pub fn configure_object(&mut self, partial_config: JsValue) -> Result<(), String> {
+        self.update_config_object(partial_config)
+    }
+
+    /// Apply a partial configuration object (see `PartialConfig`) in place without resetting
+    /// the flow (e.g. swapping boundary schemes), throwing an error (and applying none of it)
+    /// if invalid.
+    #[wasm_bindgen(js_name = updateConfig)]
+    pub fn update_config_object(&mut self, partial_config: JsValue) -> Result<(), String> {
+        let partial_config =
+            serde_wasm_bindgen::from_value(partial_config).map_err(|err| err.to_string())?;
+        self.update_config(&partial_config)
+    }
+
+    /// Resize the lattice without resetting the flow, interpolating the fields (see
+    /// `Simulation.resize`), throwing an error if smaller than the smallest size.
+    pub fn resize(&mut self, width: usize, height: usize) -> Result<(), String> {
+        validate_size(width, height)?;
+        self.simulation.get_mut().resize(width, height);
+        self.config.width = width;
+        self.config.height = height;
+        Ok(())
+    }
+
+    /// Whether in the coarse preview mode.
+    pub fn preview(&self) -> bool {
+        self.simulation.borrow().preview()
+    }
+
+    /// Enter or leave the coarse preview mode, of the lattice size halved in each dimension
+    /// (see `Simulation.setPreview`), keeping interactivity on slow devices.
+    ///
+    /// note: the configuration keeps the full lattice size
+    #[wasm_bindgen(js_name = setPreview)]
+    pub fn set_preview(&mut self, preview: bool) {
+        self.simulation.get_mut().set_preview(preview);
+    }
+
+    /// Set the iteration rate (iterations per second) below which the preview mode is entered
+    /// automatically (none if not positive).
+    #[wasm_bindgen(js_name = setPreviewThreshold)]
+    pub fn set_preview_threshold(&mut self, threshold: f64) {
+        self.simulation.get_mut().set_preview_threshold(threshold);
+    }
+
+    /// Iteration rate (iterations per second), as a moving average.
+    #[wasm_bindgen(js_name = iterationRate)]
+    pub fn iteration_rate(&self) -> f64 {
+        self.simulation.borrow().iteration_rate()
+    }
+
+    /// Perform a number of iterations, calling the callbacks due after each, returning the
+    /// number of iterations performed in total.
+    ///
+    /// note: the callbacks may read the simulation (e.g. `stats` or `field`), but not change it
+    pub fn step(&self, iterations: usize) -> usize {
+        for _ in 0..iterations {
+            let iteration = {
+                let mut simulation = self.simulation.borrow_mut();
+                simulation.run_for(1);
+                simulation.iteration()
+            };
+            for (_, callback, interval) in &self.callbacks {
+                if iteration.is_multiple_of(*interval) {
+                    if let Err(err) = callback.call1(&JsValue::NULL, &JsValue::from(iteration)) {
+                        log::warn("embedding", &format!("callback failed: {err:?}"));
+                    }
+                }
+            }
+        }
+        self.iteration()
+    }
+
+    /// Names of the fields.
+    #[wasm_bindgen(js_name = fieldNames)]
+    pub fn field_names(&self) -> Vec<String> {
+        self.simulation.borrow().field_names()
+    }
+
+    /// Values of a field (a name of the fields) at the lattice positions as a `Float32Array`,
+    /// with x varying fastest, if any.
+    pub fn field(&self, name: &str) -> Option<Vec<f32>> {
+        let field = Field::ALL.iter().position(|field| field.name() == name)?;
+        self.simulation.borrow().field_values(field)
+    }
+
+    /// Drag and lift coefficients of the objects during the last iteration.
+    #[wasm_bindgen(js_name = forceCoefficients)]
+    pub fn force_coefficients(&self) -> Vec<f32> {
+        self.simulation.borrow().force_coefficients()
+    }
+
+    /// Statistics of the current iteration, as an object (see `Stats`).
+    #[wasm_bindgen(js_name = stats)]
+    pub fn stats_object(&self) -> Result<JsValue, String> {
+        serde_wasm_bindgen::to_value(&self.stats()).map_err(|err| err.to_string())
+    }
+
+    /// Register a callback called with the iteration every interval of iterations stepped,
+    /// returning a handle to remove it by.
+    #[wasm_bindgen(js_name = onStep)]
+    pub fn on_step(&mut self, callback: js_sys::Function, interval: usize) -> usize {
+        let handle = self.next_handle;
+        self.next_handle += 1;
+        self.callbacks.push((handle, callback, interval.max(1)));
+        handle
+    }
+
+    /// Remove a callback by its handle, returning whether registered.
+    #[wasm_bindgen(js_name = removeCallback)]
+    pub fn remove_callback(&mut self, handle: usize) -> bool {
+        let len = self.callbacks.len();
+        self.callbacks
+            .retain(|(callback_handle, ..)| *callback_handle != handle);
+        self.callbacks.len() < len
+    }
+
+    /// Destroy the simulation, freeing its memory (the `LbmSimulation` is unusable after).
+    pub fn destroy(self) {}
+}
+
+impl EmbeddedSimulation {
+    /// Create a new `EmbeddedSimulation` of a configuration, if valid.
+    pub fn from_config(config: &Config) -> Result<Self, String> {
+        let Config { width, height, .. } = *config;
+        validate_size(width, height)?;
+        let scenario = Scenario::from_name(&config.scenario)
+            .ok_or(format!("scenario {} is unknown", config.scenario))?;
+        PartialConfig {
+            velocity: Some(config.velocity),
+            reynolds_number: Some(config.reynolds_number),
+            ..Default::default()
+        }
+        .validate()?;
+        Ok(Self {
+            simulation: RefCell::new(Simulation::with_scenario(
+                &[width, height],
+                scenario,
+                config.velocity,
+                config.reynolds_number,
+                config.seed.unwrap_or(random::DEFAULT_SEED),
+            )),
+            config: config.clone(),
+            callbacks: Vec::new(),
+            next_handle: 0,
+        })
+    }
+
+    /// Configuration, with the current flow parameters.
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Apply a partial configuration in place without resetting the flow, if physically
+    /// sensible.
+    pub fn update_config(&mut self, partial_config: &PartialConfig) -> Result<(), String> {
+        let simulation = self.simulation.get_mut();
+        simulation.update_config(partial_config)?;
+        self.config.velocity = simulation.velocity();
+        self.config.reynolds_number = simulation.reynolds_number();
+        Ok(())
+    }
+
+    /// Statistics of the current iteration.
+    pub fn stats(&self) -> Stats {
+        self.simulation.borrow().stats()
+    }
+}
@@ -0,0 +1,205 @@
+use crate::image_values::ImageValues;
+
+use wasm_bindgen::prelude::*;
+
+const COLORBAR_HEIGHT: u32 = 16;
+const TEXT_HEIGHT: u32 = 18;
+const FONT: &str = "12px sans-serif";
+
+/// Download a URL (e.g. a data URL) as a file.
+pub fn download(url: &str, filename: &str) -> Result<(), JsValue> {
+    let document = web_sys::window()
+        .and_then(|window| window.document())
+        .ok_or("should have document")?;
+    let anchor = document
+        .create_element("a")?
+        .dyn_into::<web_sys::HtmlAnchorElement>()?;
+    anchor.set_href(url);
+    anchor.set_download(filename);
+    anchor.click();
+    Ok(())
+}
+
+/// Download text of a [media type](https://en.wikipedia.org/wiki/Media_type) as a file.
+pub fn download_text(text: &str, media_type: &str, filename: &str) -> Result<(), JsValue> {
+    let options = web_sys::BlobPropertyBag::new();
+    options.set_type(media_type);
+    let blob = web_sys::Blob::new_with_str_sequence_and_options(
+        &js_sys::Array::of1(&JsValue::from_str(text)),
+        &options,
+    )?;
+    // note: the object URL is not revoked, as the download may not have started
+    download(&web_sys::Url::create_object_url_with_blob(&blob)?, filename)
+}
+
+/// Download bytes of a [media type](https://en.wikipedia.org/wiki/Media_type) as a file.
+pub fn download_bytes(bytes: &[u8], media_type: &str, filename: &str) -> Result<(), JsValue> {
+    let options = web_sys::BlobPropertyBag::new();
+    options.set_type(media_type);
+    let blob = web_sys::Blob::new_with_u8_array_sequence_and_options(
+        &js_sys::Array::of1(&js_sys::Uint8Array::from(bytes)),
+        &options,
+    )?;
+    // note: the object URL is not revoked, as the download may not have started
+    download(&web_sys::Url::create_object_url_with_blob(&blob)?, filename)
+}
+
+/// Download [CSV](https://en.wikipedia.org/wiki/Comma-separated_values) text as a file.
+pub fn download_csv(csv: &str, filename: &str) -> Result<(), JsValue> {
+    download_text(csv, "text/csv", filename)
+}
+
+/// Download stacked canvases (e.g. a field canvas and its overlays) as a
+/// [PNG](https://en.wikipedia.org/wiki/PNG) image, with a colour bar of the image values and
+/// their range below, and an annotation (e.g. the iteration and parameters).
+pub fn download_png(
+    canvases: &[web_sys::HtmlCanvasElement],
+    image_values: &ImageValues,
+    annotation: &str,
+    filename: &str,
+) -> Result<(), JsValue> {
+    let document = web_sys::window()
+        .and_then(|window| window.document())
+        .ok_or("should have document")?;
+    let first = canvases.first().ok_or("should have canvas")?;
+    let (width, height) = (first.width(), first.height());
+
+    let canvas = document
+        .create_element("canvas")?
+        .dyn_into::<web_sys::HtmlCanvasElement>()?;
+    canvas.set_width(width);
+    canvas.set_height(height + COLORBAR_HEIGHT + 2 * TEXT_HEIGHT);
+    let canvas_rendering_context = canvas
+        .get_context("2d")?
+        .ok_or("should have 2d context")?
+        .dyn_into::<web_sys::CanvasRenderingContext2d>()?;
+
+    canvas_rendering_context.set_fill_style_str("white");
+    canvas_rendering_context.fill_rect(0.0, 0.0, canvas.width() as f64, canvas.height() as f64);
+    for source in canvases {
+        canvas_rendering_context.draw_image_with_html_canvas_element(source, 0.0, 0.0)?;
+    }
+
+    let mut y = height as f64;
+    image_values.draw_colorbar(
+        &canvas_rendering_context,
+        0.0,
+        y,
+        width as f64,
+        COLORBAR_HEIGHT as f64,
+    );
+    y += COLORBAR_HEIGHT as f64;
+
+    // note: text is drawn from its top
+    canvas_rendering_context.set_font(FONT);
+    canvas_rendering_context.set_text_baseline("top");
+    canvas_rendering_context.set_fill_style_str("black");
+    let [min, max] = image_values.range();
+    canvas_rendering_context.set_text_align("left");
+    canvas_rendering_context.fill_text(&format!("{min:.4}"), 2.0, y + 2.0)?;
+    canvas_rendering_context.set_text_align("right");
+    canvas_rendering_context.fill_text(&format!("{max:.4}"), width as f64 - 2.0, y + 2.0)?;
+    y += TEXT_HEIGHT as f64;
+    canvas_rendering_context.set_text_align("left");
+    canvas_rendering_context.fill_text(annotation, 2.0, y + 2.0)?;
+
+    download(&canvas.to_data_url_with_type("image/png")?, filename)
+}
+
+/// Recorder of groups of stacked canvases, tiled in a grid, into a
+/// [WebM](https://en.wikipedia.org/wiki/WebM) video downloaded when stopped.
+pub struct VideoRecorder {
+    canvas_rendering_context: web_sys::CanvasRenderingContext2d,
+    media_recorder: web_sys::MediaRecorder,
+    /// Width and height of each tile.
+    tile_size: [u32; 2],
+    columns: u32,
+}
+
+impl VideoRecorder {
+    /// Create a new `VideoRecorder` of a number of tiles, and start recording.
+    ///
+    /// note: the grid is as square as possible
+    pub fn new(tile_size: [u32; 2], tiles: u32, filename: &str) -> Result<Self, JsValue> {
+        let columns = (tiles.max(1) as f64).sqrt().ceil() as u32;
+        let rows = tiles.max(1).div_ceil(columns);
+        let document = web_sys::window()
+            .and_then(|window| window.document())
+            .ok_or("should have document")?;
+        let canvas = document
+            .create_element("canvas")?
+            .dyn_into::<web_sys::HtmlCanvasElement>()?;
+        canvas.set_width(tile_size[0] * columns);
+        canvas.set_height(tile_size[1] * rows);
+        let canvas_rendering_context = canvas
+            .get_context("2d")?
+            .ok_or("should have 2d context")?
+            .dyn_into::<web_sys::CanvasRenderingContext2d>()?;
+
+        let options = web_sys::MediaRecorderOptions::new();
+        options.set_mime_type("video/webm");
+        let media_recorder =
+            web_sys::MediaRecorder::new_with_media_stream_and_media_recorder_options(
+                &canvas.capture_stream()?,
+                &options,
+            )?;
+
+        let chunks = js_sys::Array::new();
+        let chunks_clone = chunks.clone();
+        let closure = Closure::<dyn FnMut(_)>::new(move |event: web_sys::BlobEvent| {
+            if let Some(data) = event.data() {
+                chunks_clone.push(&data);
+            }
+        });
+        media_recorder.set_ondataavailable(Some(closure.as_ref().unchecked_ref()));
+        closure.forget();
+
+        let filename = filename.to_string();
+        let closure = Closure::<dyn FnMut(_)>::new(move |_: web_sys::Event| {
+            let options = web_sys::BlobPropertyBag::new();
+            options.set_type("video/webm");
+            if let Ok(url) = web_sys::Blob::new_with_blob_sequence_and_options(&chunks, &options)
+                .and_then(|blob| web_sys::Url::create_object_url_with_blob(&blob))
+            {
+                let _ = download(&url, &filename);
+            }
+        });
+        media_recorder.set_onstop(Some(closure.as_ref().unchecked_ref()));
+        closure.forget();
+
+        media_recorder.start()?;
+
+        Ok(Self {
+            canvas_rendering_context,
+            media_recorder,
+            tile_size,
+            columns,
+        })
+    }
+
+    /// Draw a frame from groups of stacked canvases, each scaled to fill its tile.
+    pub fn draw(&self, groups: &[Vec<web_sys::HtmlCanvasElement>]) -> Result<(), JsValue> {
+        let [width, height] = self.tile_size.map(|val| val as f64);
+        for (i, canvases) in groups.iter().enumerate() {
+            let i = i as u32;
+            let (x, y) = (
+                (i % self.columns) as f64 * width,
+                (i / self.columns) as f64 * height,
+            );
+            self.canvas_rendering_context.set_fill_style_str("white");
+            self.canvas_rendering_context.fill_rect(x, y, width, height);
+            for canvas in canvases {
+                self.canvas_rendering_context
+                    .draw_image_with_html_canvas_element_and_dw_and_dh(
+                        canvas, x, y, width, height,
+                    )?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Stop recording, and download the video.
+    pub fn stop(&self) -> Result<(), JsValue> {
+        self.media_recorder.stop()
+    }
+}
@@ -0,0 +1,226 @@
+use crate::lbgk::Lbgk;
+use crate::particles::{advect_tracer, fluid, line_positions};
+
+use wasm_bindgen::prelude::*;
+
+use std::collections::VecDeque;
+
+/// [Pathlines](https://en.wikipedia.org/wiki/Streamlines,_streaklines,_and_pathlines) traced by
+/// the histories of tracer particles.
+pub struct Pathlines {
+    /// Number of positions retained for each pathline.
+    length: usize,
+    lines: Vec<Pathline>,
+}
+
+/// Pathline state.
+struct Pathline {
+    // note: the front is the current position of the particle
+    positions: VecDeque<[f32; 2]>,
+    /// Whether the particle remains within the fluid.
+    active: bool,
+}
+
+impl Pathlines {
+    /// Create a new `Pathlines` retaining `length` positions for each pathline.
+    pub fn new(length: usize) -> Self {
+        Self {
+            length,
+            lines: Vec::new(),
+        }
+    }
+
+    /// Set the number of positions retained for each pathline.
+    pub fn set_length(&mut self, length: usize) {
+        self.length = length.max(1);
+        for line in &mut self.lines {
+            line.positions.truncate(self.length);
+        }
+    }
+
+    /// Add a pathline starting at a position.
+    pub fn seed(&mut self, pos: [f32; 2]) {
+        self.lines.push(Pathline {
+            positions: VecDeque::from([pos]),
+            active: true,
+        });
+    }
+
+    /// Add pathlines evenly spaced along a line.
+    pub fn seed_line(&mut self, start: [f32; 2], end: [f32; 2], count: usize) {
+        for pos in line_positions(start, end, count) {
+            self.seed(pos);
+        }
+    }
+
+    /// Remove all pathlines.
+    pub fn clear(&mut self) {
+        self.lines.clear();
+    }
+
+    /// Advect the particles over an iteration, extending their pathlines.
+    ///
+    /// Pathlines of particles that have left the fluid shorten until removed.
+    pub fn advect<const B: usize>(&mut self, lbgk: &Lbgk<2, B>) {
+        for line in &mut self.lines {
+            match line.active {
+                true => {
+                    let pos = advect_tracer(lbgk, &line.positions[0]);
+                    line.active = fluid(lbgk, &pos);
+                    if line.active {
+                        line.positions.push_front(pos);
+                    }
+                    line.positions.truncate(self.length);
+                }
+                false => {
+                    line.positions.pop_back();
+                }
+            }
+        }
+        self.lines.retain(|line| !line.positions.is_empty());
+    }
+
+    /// Draw the pathlines, optionally fading with age.
+    pub fn draw(
+        &self,
+        colour: &str,
+        fade: bool,
+        canvas_rendering_context: &web_sys::CanvasRenderingContext2d,
+    ) -> Result<(), JsValue> {
+        for line in &self.lines {
+            draw_line(
+                line.positions.iter().map(|pos| Some(*pos)),
+                self.length,
+                colour,
+                fade,
+                canvas_rendering_context,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// [Streaklines](https://en.wikipedia.org/wiki/Streamlines,_streaklines,_and_pathlines) traced by
+/// tracer particles continuously released from sources.
+pub struct Streaklines {
+    /// Number of particles retained for each streakline.
+    length: usize,
+    /// Iterations between particle releases.
+    release_interval: usize,
+    iteration: usize,
+    sources: Vec<[f32; 2]>,
+    // note: the front of each line is the most recently released particle, and particles
+    // that have left the fluid are kept as gaps
+    lines: Vec<VecDeque<Option<[f32; 2]>>>,
+}
+
+impl Streaklines {
+    /// Create a new `Streaklines` retaining `length` particles for each streakline, released
+    /// every `release_interval` iterations.
+    pub fn new(length: usize, release_interval: usize) -> Self {
+        Self {
+            length,
+            release_interval: release_interval.max(1),
+            iteration: 0,
+            sources: Vec::new(),
+            lines: Vec::new(),
+        }
+    }
+
+    /// Set the number of particles retained for each streakline.
+    pub fn set_length(&mut self, length: usize) {
+        self.length = length.max(1);
+        for line in &mut self.lines {
+            line.truncate(self.length);
+        }
+    }
+
+    /// Add a source of particles at a position.
+    pub fn add_source(&mut self, pos: [f32; 2]) {
+        self.sources.push(pos);
+        self.lines.push(VecDeque::new());
+    }
+
+    /// Add sources evenly spaced along a line.
+    pub fn add_sources_line(&mut self, start: [f32; 2], end: [f32; 2], count: usize) {
+        for pos in line_positions(start, end, count) {
+            self.add_source(pos);
+        }
+    }
+
+    /// Remove all sources and streaklines.
+    pub fn clear(&mut self) {
+        self.sources.clear();
+        self.lines.clear();
+    }
+
+    /// Advect the particles over an iteration, releasing particles from the sources.
+    pub fn advect<const B: usize>(&mut self, lbgk: &Lbgk<2, B>) {
+        for line in &mut self.lines {
+            for pos in line.iter_mut() {
+                if let Some(val) = pos {
+                    let new_pos = advect_tracer(lbgk, val);
+                    *pos = match fluid(lbgk, &new_pos) {
+                        true => Some(new_pos),
+                        false => None,
+                    };
+                }
+            }
+        }
+
+        if self.iteration.is_multiple_of(self.release_interval) {
+            for (line, source) in self.lines.iter_mut().zip(&self.sources) {
+                line.push_front(Some(*source));
+                line.truncate(self.length);
+            }
+        }
+        self.iteration += 1;
+    }
+
+    /// Draw the streaklines, optionally fading with age.
+    pub fn draw(
+        &self,
+        colour: &str,
+        fade: bool,
+        canvas_rendering_context: &web_sys::CanvasRenderingContext2d,
+    ) -> Result<(), JsValue> {
+        for line in &self.lines {
+            draw_line(
+                line.iter().copied(),
+                self.length,
+                colour,
+                fade,
+                canvas_rendering_context,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Draw a line through positions ordered newest first (in lattice coordinates), broken at gaps.
+fn draw_line(
+    positions: impl Iterator<Item = Option<[f32; 2]>>,
+    length: usize,
+    colour: &str,
+    fade: bool,
+    canvas_rendering_context: &web_sys::CanvasRenderingContext2d,
+) -> Result<(), JsValue> {
+    canvas_rendering_context.set_stroke_style_str(colour);
+    let mut previous: Option<(f64, f64)> = None;
+    for (age, pos) in positions.enumerate() {
+        let pos = pos.map(|pos| (pos[0] as f64, pos[1] as f64));
+        if let (Some(first), Some(second)) = (previous, pos) {
+            if fade {
+                canvas_rendering_context.set_global_alpha(1.0 - age as f64 / length as f64);
+            }
+            canvas_rendering_context.begin_path();
+            canvas_rendering_context.move_to(first.0, first.1);
+            canvas_rendering_context.line_to(second.0, second.1);
+            canvas_rendering_context.stroke();
+        }
+        previous = pos;
+    }
+    canvas_rendering_context.set_global_alpha(1.0);
+
+    Ok(())
+}
@@ -1,9 +1,65 @@
-use crate::colour::hsv_to_rgb;
+pub mod contours;
+pub mod line_integral_convolution;
+pub mod quiver;
+pub mod streamlines;
+
+use crate::colour::Colormap;
+use crate::lbgk::slice::Slice;
+use crate::viewport::Viewport;
 
 use itertools::iproduct;
 use wasm_bindgen::{prelude::*, Clamped};
 
-const HUE_RANGE: [f32; 2] = [180.0, 360.0];
+const LOG_DECADES: f32 = 3.0;
+
+/// Scalings of the deviation of values from the standard value, applied symmetrically about
+/// the standard value.
+#[derive(Clone, Copy, PartialEq)]
+pub enum ScaleMode {
+    /// Proportional to the deviation.
+    Linear,
+    /// Square root of the deviation, amplifying small deviations.
+    Sqrt,
+    /// [Logarithm](https://en.wikipedia.org/wiki/Logarithmic_scale) of the deviation over a
+    /// fixed number of decades.
+    Log,
+    /// Deviation raised to a configurable exponent
+    /// ([reference](https://en.wikipedia.org/wiki/Gamma_correction)).
+    Gamma,
+}
+
+impl ScaleMode {
+    /// All scale modes.
+    pub const ALL: [ScaleMode; 4] = [
+        ScaleMode::Linear,
+        ScaleMode::Sqrt,
+        ScaleMode::Log,
+        ScaleMode::Gamma,
+    ];
+
+    /// Name of the scale mode.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ScaleMode::Linear => "Linear",
+            ScaleMode::Sqrt => "Sqrt",
+            ScaleMode::Log => "Log",
+            ScaleMode::Gamma => "Gamma",
+        }
+    }
+
+    /// Scale a normalised deviation in [-1,1].
+    pub fn scale(&self, v: f32, exponent: f32) -> f32 {
+        let magnitude = match self {
+            ScaleMode::Linear => v.abs(),
+            ScaleMode::Sqrt => v.abs().sqrt(),
+            ScaleMode::Log => {
+                (1.0 + (10.0f32.powf(LOG_DECADES) - 1.0) * v.abs()).log10() / LOG_DECADES
+            }
+            ScaleMode::Gamma => v.abs().powf(exponent),
+        };
+        v.signum() * magnitude
+    }
+}
 
 /// Image data.
 pub struct ImageValues {
@@ -14,6 +70,25 @@ pub struct ImageValues {
     standard_value: f32,
     minimum_value: f32,
     maximum_value: f32,
+    /// Fixed minimum and maximum values overriding those set, if any.
+    fixed_range: Option<[f32; 2]>,
+    /// Weight of each new minimum and maximum value in their exponential moving averages, if
+    /// smoothed.
+    range_smoothing: Option<f32>,
+    // note: the first minimum and maximum values set are not smoothed
+    range_set: bool,
+    colormap: Colormap,
+    scale_mode: ScaleMode,
+    /// Exponent for the gamma scale mode.
+    exponent: f32,
+    /// RGB colour of the outlines of positions without values, if drawn.
+    outline_colour: Option<[u8; 3]>,
+    /// RGB colours filling positions without values.
+    fill_colours: Vec<[u8; 3]>,
+    /// Display pixels per lattice position in each direction.
+    display_scale: usize,
+    viewport: Viewport,
+    scaled_data: Vec<u8>, // RGBA data for the upscaled image
 }
 
 impl ImageValues {
@@ -26,6 +101,17 @@ impl ImageValues {
             standard_value: 0.0,
             minimum_value: 0.0,
             maximum_value: 0.0,
+            fixed_range: None,
+            range_smoothing: None,
+            range_set: false,
+            colormap: Colormap::TwoHue,
+            scale_mode: ScaleMode::Linear,
+            exponent: 1.0,
+            outline_colour: None,
+            fill_colours: vec![[u8::MAX; 3]; size[0] * size[1]],
+            display_scale: 1,
+            viewport: Viewport::new(size),
+            scaled_data: Vec::new(),
         }
     }
 
@@ -34,65 +120,234 @@ impl ImageValues {
         self.values[self.size[0] * pos[1] + pos[0]] = value;
     }
 
+    /// Set values from a slice of the image size.
+    pub fn set_slice(&mut self, slice: &Slice) {
+        self.values.copy_from_slice(slice.values());
+    }
+
     /// Set standard value.
     pub fn set_standard_value(&mut self, value: f32) {
         self.standard_value = value;
     }
 
-    /// Set minimum value.
+    /// Set minimum value, smoothed if enabled.
     pub fn set_minimum_value(&mut self, value: f32) {
-        self.minimum_value = value;
+        self.minimum_value = self.smoothed(self.minimum_value, value);
     }
 
-    /// Set maximum value.
+    /// Set maximum value, smoothed if enabled.
     pub fn set_maximum_value(&mut self, value: f32) {
-        self.maximum_value = value;
+        self.maximum_value = self.smoothed(self.maximum_value, value);
+        self.range_set = true;
+    }
+
+    /// Set the weight in (0,1] of each new minimum and maximum value in their
+    /// [exponential moving averages](https://en.wikipedia.org/wiki/Exponential_smoothing), or
+    /// `None` to disable smoothing.
+    pub fn set_range_smoothing(&mut self, weight: Option<f32>) {
+        self.range_smoothing = weight.map(|weight| weight.clamp(f32::EPSILON, 1.0));
+    }
+
+    /// Exponential moving average of a previous and new value.
+    fn smoothed(&self, previous: f32, value: f32) -> f32 {
+        match (self.range_smoothing, self.range_set) {
+            (Some(weight), true) => previous + weight * (value - previous),
+            _ => value,
+        }
+    }
+
+    /// Set fixed minimum and maximum values, or `None` to use the values set for each frame.
+    pub fn set_fixed_range(&mut self, range: Option<[f32; 2]>) {
+        self.fixed_range = range;
+    }
+
+    /// Minimum and maximum values used for the colour scale.
+    pub fn range(&self) -> [f32; 2] {
+        self.fixed_range
+            .unwrap_or([self.minimum_value, self.maximum_value])
+    }
+
+    /// Set colour map.
+    pub fn set_colormap(&mut self, colormap: Colormap) {
+        self.colormap = colormap;
+    }
+
+    /// Set scale mode.
+    pub fn set_scale_mode(&mut self, scale_mode: ScaleMode) {
+        self.scale_mode = scale_mode;
+    }
+
+    /// Set exponent for the gamma scale mode.
+    pub fn set_exponent(&mut self, exponent: f32) {
+        self.exponent = exponent;
+    }
+
+    /// Set RGB colour of the outlines of positions without values (e.g. objects), or `None` to
+    /// not draw outlines.
+    pub fn set_outline_colour(&mut self, colour: Option<[u8; 3]>) {
+        self.outline_colour = colour;
+    }
+
+    /// Set RGB colour filling a position without a value (e.g. of the object at the position).
+    pub fn set_fill_colour(&mut self, pos: &[usize; 2], colour: [u8; 3]) {
+        self.fill_colours[self.size[0] * pos[1] + pos[0]] = colour;
+    }
+
+    /// Set display pixels per lattice position in each direction, with the image upscaled by
+    /// [bilinear interpolation](https://en.wikipedia.org/wiki/Bilinear_interpolation).
+    pub fn set_display_scale(&mut self, scale: usize) {
+        self.display_scale = scale.max(1);
+    }
+
+    /// Set viewport, with the zoomed image resampled by bilinear interpolation.
+    pub fn set_viewport(&mut self, viewport: Viewport) {
+        self.viewport = viewport;
+    }
+
+    /// Resample the image data for the display scale and viewport.
+    ///
+    /// note: positions without values (and their outlines) are not interpolated, keeping object
+    /// edges crisp
+    fn resample(&mut self) {
+        let [width, height] = self.size;
+        let scale = self.display_scale;
+        let len = width * height * scale * scale * 4;
+        if self.scaled_data.len() != len {
+            self.scaled_data = vec![u8::MAX; len];
+        }
+        let has_value =
+            |col: usize, row: usize| self.values[width * (height - 1 - row) + col].is_some();
+
+        for (row, col) in iproduct!(0..height * scale, 0..width * scale) {
+            let [x, y] = self
+                .viewport
+                .lattice_position(&[col as f64, row as f64], scale as f64);
+            // note: image rows are ordered from the top
+            let source = [(x, width), (height as f64 - 1.0 - y, height)]
+                .map(|(val, size)| (val as f32).clamp(0.0, (size - 1) as f32));
+            let pos0 = source.map(|val| val.floor() as usize);
+            let pos1 = [(pos0[0] + 1).min(width - 1), (pos0[1] + 1).min(height - 1)];
+            let [tx, ty] = [0, 1].map(|i| source[i] - pos0[i] as f32);
+
+            let corners = [
+                ([pos0[0], pos0[1]], (1.0 - tx) * (1.0 - ty)),
+                ([pos1[0], pos0[1]], tx * (1.0 - ty)),
+                ([pos0[0], pos1[1]], (1.0 - tx) * ty),
+                ([pos1[0], pos1[1]], tx * ty),
+            ];
+            let scaled_idx = (width * scale * row + col) * 4;
+            match corners.iter().all(|(pos, _)| has_value(pos[0], pos[1])) {
+                true => {
+                    for c in 0..3 {
+                        self.scaled_data[scaled_idx + c] = corners
+                            .iter()
+                            .map(|(pos, weight)| {
+                                weight * self.data[(width * pos[1] + pos[0]) * 4 + c] as f32
+                            })
+                            .sum::<f32>()
+                            .round()
+                            as u8;
+                    }
+                }
+                false => {
+                    let [x, y] = source.map(|val| val.round() as usize);
+                    let data_idx = (width * y + x) * 4;
+                    self.scaled_data[scaled_idx..scaled_idx + 3]
+                        .copy_from_slice(&self.data[data_idx..data_idx + 3]);
+                }
+            }
+        }
+    }
+
+    /// Divisor normalising the deviations of values from the standard value over the range.
+    fn val_divisor(&self) -> f32 {
+        let [min, max] = self.range();
+        (max - self.standard_value)
+            .abs()
+            .max((min - self.standard_value).abs())
+    }
+
+    /// RGB colour of a value.
+    ///
+    /// note: the standard value maps to the middle of the colour map
+    fn rgb(&self, value: f32, val_divisor: f32) -> [u8; 3] {
+        let (r, g, b) = self.colormap.rgb({
+            let v = (value - self.standard_value) / val_divisor;
+            0.5 + 0.5 * self.scale_mode.scale(v, self.exponent)
+        });
+        [r, g, b].map(|val| (val * u8::MAX as f32) as u8)
+    }
+
+    /// Draw a horizontal colour bar over the range of values, from the minimum value on the
+    /// left, filling a rectangle (in canvas coordinates).
+    pub fn draw_colorbar(
+        &self,
+        canvas_rendering_context: &web_sys::CanvasRenderingContext2d,
+        x: f64,
+        y: f64,
+        width: f64,
+        height: f64,
+    ) {
+        let [min, max] = self.range();
+        let val_divisor = self.val_divisor();
+        let columns = width.max(1.0) as usize;
+        for i in 0..columns {
+            let value = min + (max - min) * (i as f32 + 0.5) / columns as f32;
+            let [r, g, b] = self.rgb(value, val_divisor);
+            canvas_rendering_context.set_fill_style_str(&format!("rgb({r}, {g}, {b})"));
+            canvas_rendering_context.fill_rect(x + i as f64, y, 1.0, height);
+        }
+    }
+
+    /// Whether a position without a value borders a position with a value.
+    fn outline(&self, x: usize, y: usize) -> bool {
+        [[-1, 0], [1, 0], [0, -1], [0, 1]].iter().any(|[dx, dy]| {
+            match (x.checked_add_signed(*dx), y.checked_add_signed(*dy)) {
+                (Some(x), Some(y)) if x < self.size[0] && y < self.size[1] => {
+                    self.values[self.size[0] * y + x].is_some()
+                }
+                _ => false,
+            }
+        })
     }
 
     /// Draw values.
     pub fn draw(
         &mut self,
-        amplify: bool,
         canvas_rendering_context: &web_sys::CanvasRenderingContext2d,
     ) -> Result<(), JsValue> {
-        let val_divisor = (self.maximum_value - self.standard_value)
-            .abs()
-            .max((self.minimum_value - self.standard_value).abs());
+        let val_divisor = self.val_divisor();
 
         for (x, y) in iproduct!(0..self.size[0], 0..self.size[1]) {
             let data_idx = (self.size[0] * (self.size[1] - 1 - y) + x) * 4;
             match self.values[self.size[0] * y + x] {
                 None => {
-                    self.data[data_idx] = u8::MAX;
-                    self.data[data_idx + 1] = u8::MAX;
-                    self.data[data_idx + 2] = u8::MAX;
+                    let [r, g, b] = match self.outline_colour {
+                        Some(colour) if self.outline(x, y) => colour,
+                        _ => self.fill_colours[self.size[0] * y + x],
+                    };
+                    self.data[data_idx] = r;
+                    self.data[data_idx + 1] = g;
+                    self.data[data_idx + 2] = b;
                 }
                 Some(value) => {
-                    let (r, g, b) = hsv_to_rgb(
-                        match value < self.standard_value {
-                            true => HUE_RANGE[0],
-                            false => HUE_RANGE[1],
-                        },
-                        1.0,
-                        {
-                            let v = (value - self.standard_value).abs() / val_divisor;
-                            match amplify {
-                                true => v.sqrt(),
-                                false => v,
-                            }
-                        },
-                    );
-                    self.data[data_idx] = (r * u8::MAX as f32) as u8;
-                    self.data[data_idx + 1] = (g * u8::MAX as f32) as u8;
-                    self.data[data_idx + 2] = (b * u8::MAX as f32) as u8;
+                    let rgb = self.rgb(value, val_divisor);
+                    self.data[data_idx..data_idx + 3].copy_from_slice(&rgb);
                 }
             }
         }
 
+        let data = match self.display_scale == 1 && self.viewport.is_identity() {
+            true => &self.data,
+            false => {
+                self.resample();
+                &self.scaled_data
+            }
+        };
         canvas_rendering_context.put_image_data(
             &web_sys::ImageData::new_with_u8_clamped_array(
-                Clamped(&self.data),
-                self.size[0] as u32,
+                Clamped(data),
+                (self.size[0] * self.display_scale) as u32,
             )
             .unwrap(),
             0.0,
@@ -0,0 +1,124 @@
+use super::ImageValues;
+
+use itertools::iproduct;
+use wasm_bindgen::prelude::*;
+
+/// [Contour lines](https://en.wikipedia.org/wiki/Contour_line) of image values extracted with
+/// [marching squares](https://en.wikipedia.org/wiki/Marching_squares).
+pub struct Contours {
+    /// Number of levels evenly spaced over the range of values.
+    count: usize,
+    /// Fixed levels overriding the evenly spaced levels.
+    levels: Option<Vec<f32>>,
+}
+
+impl Contours {
+    /// Create a new `Contours` of `count` levels evenly spaced over the range of values.
+    pub fn new(count: usize) -> Self {
+        Self {
+            count,
+            levels: None,
+        }
+    }
+
+    /// Set the number of levels evenly spaced over the range of values.
+    pub fn set_count(&mut self, count: usize) {
+        self.count = count;
+    }
+
+    /// Set fixed levels, or `None` for evenly spaced levels.
+    pub fn set_levels(&mut self, levels: Option<Vec<f32>>) {
+        self.levels = levels;
+    }
+
+    /// Levels for the range of image values.
+    pub fn levels(&self, image_values: &ImageValues) -> Vec<f32> {
+        match &self.levels {
+            Some(levels) => levels.clone(),
+            None => {
+                // note: the extremes of the range are excluded
+                let [min, max] = image_values.range();
+                (1..=self.count)
+                    .map(|i| min + (max - min) * i as f32 / (self.count + 1) as f32)
+                    .collect()
+            }
+        }
+    }
+
+    /// Draw the contour lines of image values (in lattice coordinates).
+    pub fn draw(
+        &self,
+        image_values: &ImageValues,
+        colour: &str,
+        canvas_rendering_context: &web_sys::CanvasRenderingContext2d,
+    ) -> Result<(), JsValue> {
+        canvas_rendering_context.set_stroke_style_str(colour);
+        canvas_rendering_context.begin_path();
+        for level in self.levels(image_values) {
+            for [first, second] in isolines(image_values, level) {
+                canvas_rendering_context.move_to(first[0] as f64, first[1] as f64);
+                canvas_rendering_context.line_to(second[0] as f64, second[1] as f64);
+            }
+        }
+        canvas_rendering_context.stroke();
+
+        Ok(())
+    }
+}
+
+/// Line segments of the isoline of image values at a level.
+///
+/// Cells with any corner without a value are skipped, and saddles are resolved with the average
+/// of the corner values.
+pub fn isolines(image_values: &ImageValues, level: f32) -> Vec<[[f32; 2]; 2]> {
+    let size = image_values.size;
+    let value = |x: usize, y: usize| image_values.values[size[0] * y + x];
+
+    let mut result = Vec::new();
+    for (x, y) in iproduct!(0..size[0] - 1, 0..size[1] - 1) {
+        // note: corners counterclockwise from the bottom left
+        let corners = [[x, y], [x + 1, y], [x + 1, y + 1], [x, y + 1]];
+        let Some(values) = corners
+            .iter()
+            .map(|pos| value(pos[0], pos[1]))
+            .collect::<Option<Vec<_>>>()
+        else {
+            continue;
+        };
+
+        // crossings on the edges from each corner to the next
+        let crossings = (0..4)
+            .map(|i| {
+                let j = (i + 1) % 4;
+                match (values[i] >= level) != (values[j] >= level) {
+                    true => {
+                        let s = (level - values[i]) / (values[j] - values[i]);
+                        Some([0, 1].map(|d| {
+                            corners[i][d] as f32 + s * (corners[j][d] as f32 - corners[i][d] as f32)
+                        }))
+                    }
+                    false => None,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        match crossings.iter().flatten().collect::<Vec<_>>()[..] {
+            [first, second] => result.push([*first, *second]),
+            [_, _, _, _] => {
+                let centre = values.iter().sum::<f32>() / 4.0;
+                let pairs = match (values[0] >= level) == (centre >= level) {
+                    // note: the first corner connects through the centre to the third
+                    true => [(0, 1), (2, 3)],
+                    false => [(3, 0), (1, 2)],
+                };
+                for (i, j) in pairs {
+                    if let (Some(first), Some(second)) = (crossings[i], crossings[j]) {
+                        result.push([first, second]);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    result
+}
@@ -0,0 +1,102 @@
+use super::streamlines::direction;
+use crate::lbgk::Lbgk;
+use crate::particles::fluid;
+use crate::random::Random;
+
+use itertools::iproduct;
+use wasm_bindgen::{prelude::*, Clamped};
+
+const NOISE_SEED: u32 = 0x9e37_79b9;
+
+/// [Line integral convolution](https://en.wikipedia.org/wiki/Line_integral_convolution) of white
+/// noise along the streamlines of the velocity field, giving a dense texture of the flow
+/// direction.
+pub struct LineIntegralConvolution {
+    size: [usize; 2],
+    /// Integration steps in each direction along the streamlines.
+    length: usize,
+    /// Distance advanced per integration step.
+    step_size: f32,
+    // note: flat vectors reduce cache loads
+    noise: Vec<f32>,
+    data: Vec<u8>, // RGBA data for the image
+}
+
+impl LineIntegralConvolution {
+    /// Create a new `LineIntegralConvolution`.
+    pub fn new(size: &[usize; 2], length: usize, step_size: f32) -> Self {
+        // note: a fixed seed keeps the noise reproducible
+        let mut random = Random::new(NOISE_SEED);
+        let noise = (0..size[0] * size[1]).map(|_| random.uniform()).collect();
+        Self {
+            size: *size,
+            length,
+            step_size,
+            noise,
+            data: vec![u8::MAX; size[0] * size[1] * 4],
+        }
+    }
+
+    /// Set the integration steps in each direction along the streamlines.
+    pub fn set_length(&mut self, length: usize) {
+        self.length = length;
+    }
+
+    /// Noise value at the lattice position nearest a position.
+    fn noise(&self, pos: &[f32; 2]) -> f32 {
+        let [x, y] = pos.map(|val| val.round() as usize);
+        self.noise[self.size[0] * y + x]
+    }
+
+    /// Convolve the noise along the streamline through a position with a box kernel.
+    fn convolve<const B: usize>(&self, lbgk: &Lbgk<2, B>, pos: &[f32; 2]) -> f32 {
+        let (mut sum, mut count) = (self.noise(pos), 1);
+        for step_size in [self.step_size, -self.step_size] {
+            let mut pos = *pos;
+            for _ in 0..self.length {
+                // midpoint method
+                let Some(k1) = direction(lbgk, &pos) else {
+                    break;
+                };
+                let mid_pos = [0, 1].map(|d| pos[d] + 0.5 * step_size * k1[d]);
+                let Some(k2) = direction(lbgk, &mid_pos) else {
+                    break;
+                };
+                pos = [0, 1].map(|d| pos[d] + step_size * k2[d]);
+                if !fluid(lbgk, &pos) {
+                    break;
+                }
+                sum += self.noise(&pos);
+                count += 1;
+            }
+        }
+        sum / count as f32
+    }
+
+    /// Calculate and draw the texture for the current velocity field.
+    pub fn draw<const B: usize>(
+        &mut self,
+        lbgk: &Lbgk<2, B>,
+        canvas_rendering_context: &web_sys::CanvasRenderingContext2d,
+    ) -> Result<(), JsValue> {
+        for (x, y) in iproduct!(0..self.size[0], 0..self.size[1]) {
+            let data_idx = (self.size[0] * (self.size[1] - 1 - y) + x) * 4;
+            let val = match lbgk.object(&[x, y]) {
+                true => u8::MAX,
+                false => (self.convolve(lbgk, &[x as f32, y as f32]) * u8::MAX as f32) as u8,
+            };
+            self.data[data_idx] = val;
+            self.data[data_idx + 1] = val;
+            self.data[data_idx + 2] = val;
+        }
+
+        canvas_rendering_context.put_image_data(
+            &web_sys::ImageData::new_with_u8_clamped_array(
+                Clamped(&self.data),
+                self.size[0] as u32,
+            )?,
+            0.0,
+            0.0,
+        )
+    }
+}
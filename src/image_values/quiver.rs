@@ -0,0 +1,84 @@
+use crate::lbgk::Lbgk;
+
+use itertools::iproduct;
+use wasm_bindgen::prelude::*;
+
+const HEAD_ANGLE: f64 = std::f64::consts::PI / 6.0;
+const HEAD_LENGTH_FRACTION: f64 = 0.3;
+
+/// [Quiver plot](https://en.wikipedia.org/wiki/Vector_field#Representation) of velocity vectors
+/// subsampled from the lattice.
+pub struct Quiver {
+    /// Lattice positions between arrows in each direction.
+    stride: usize,
+    /// Arrow length per unit velocity.
+    scale: f32,
+}
+
+impl Quiver {
+    /// Create a new `Quiver`.
+    pub fn new(stride: usize, scale: f32) -> Self {
+        Self {
+            stride: stride.max(1),
+            scale,
+        }
+    }
+
+    /// Set the lattice positions between arrows in each direction.
+    pub fn set_stride(&mut self, stride: usize) {
+        self.stride = stride.max(1);
+    }
+
+    /// Set the arrow length per unit velocity.
+    pub fn set_scale(&mut self, scale: f32) {
+        self.scale = scale;
+    }
+
+    /// Draw the velocity vectors as arrows (in lattice coordinates).
+    pub fn draw<const B: usize>(
+        &self,
+        lbgk: &Lbgk<2, B>,
+        colour: &str,
+        canvas_rendering_context: &web_sys::CanvasRenderingContext2d,
+    ) -> Result<(), JsValue> {
+        let size = lbgk.size();
+
+        // note: arrows are centred within the strides
+        let offset = self.stride / 2;
+        canvas_rendering_context.set_stroke_style_str(colour);
+        canvas_rendering_context.begin_path();
+        for (x, y) in iproduct!(
+            (offset..size[0]).step_by(self.stride),
+            (offset..size[1]).step_by(self.stride)
+        ) {
+            let pos = [x, y];
+            if lbgk.object(&pos) {
+                continue;
+            }
+            let u = lbgk.velocity_vector(&pos);
+            let [dx, dy] = u.map(|val| (self.scale * val) as f64);
+            let length = (dx * dx + dy * dy).sqrt();
+            if length == 0.0 {
+                continue;
+            }
+
+            let (x0, y0) = (x as f64 - 0.5 * dx, y as f64 - 0.5 * dy);
+            let (x1, y1) = (x0 + dx, y0 + dy);
+            canvas_rendering_context.move_to(x0, y0);
+            canvas_rendering_context.line_to(x1, y1);
+
+            let angle = dy.atan2(dx);
+            let head_length = HEAD_LENGTH_FRACTION * length;
+            for head_angle in [angle + HEAD_ANGLE, angle - HEAD_ANGLE] {
+                canvas_rendering_context.move_to(x1, y1);
+                canvas_rendering_context.line_to(
+                    x1 - head_length * head_angle.cos(),
+                    y1 - head_length * head_angle.sin(),
+                );
+            }
+        }
+        canvas_rendering_context.stroke();
+
+        Ok(())
+    }
+}
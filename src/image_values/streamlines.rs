@@ -0,0 +1,171 @@
+use crate::lbgk::Lbgk;
+use crate::linear_algebra::VectorOps;
+use crate::particles::{fluid, line_positions};
+
+use wasm_bindgen::prelude::*;
+
+const STAGNATION_VELOCITY: f32 = 1.0e-6;
+
+/// Numerical integration methods.
+#[derive(Clone, Copy)]
+pub enum Integrator {
+    /// Second order [Runge–Kutta](https://en.wikipedia.org/wiki/Runge%E2%80%93Kutta_methods)
+    /// (midpoint) method.
+    RungeKutta2,
+    /// Fourth order [Runge–Kutta](https://en.wikipedia.org/wiki/Runge%E2%80%93Kutta_methods)
+    /// method.
+    RungeKutta4,
+}
+
+/// Instantaneous [streamlines](https://en.wikipedia.org/wiki/Streamlines,_streaklines,_and_pathlines)
+/// integrated through the interpolated velocity field from seed positions.
+pub struct Streamlines {
+    integrator: Integrator,
+    /// Distance advanced per integration step.
+    step_size: f32,
+    /// Maximum integration steps in each direction from a seed.
+    max_steps: usize,
+    seeds: Vec<[f32; 2]>,
+    lines: Vec<Vec<[f32; 2]>>,
+}
+
+impl Streamlines {
+    /// Create a new `Streamlines`.
+    pub fn new(integrator: Integrator, step_size: f32, max_steps: usize) -> Self {
+        Self {
+            integrator,
+            step_size,
+            max_steps,
+            seeds: Vec::new(),
+            lines: Vec::new(),
+        }
+    }
+
+    /// Set the integration method.
+    pub fn set_integrator(&mut self, integrator: Integrator) {
+        self.integrator = integrator;
+    }
+
+    /// Add a seed position.
+    pub fn seed(&mut self, pos: [f32; 2]) {
+        self.seeds.push(pos);
+    }
+
+    /// Add seed positions evenly spaced along a line.
+    pub fn seed_line(&mut self, start: [f32; 2], end: [f32; 2], count: usize) {
+        for pos in line_positions(start, end, count) {
+            self.seed(pos);
+        }
+    }
+
+    /// Remove all seeds and streamlines.
+    pub fn clear(&mut self) {
+        self.seeds.clear();
+        self.lines.clear();
+    }
+
+    /// Whether there are no seeds.
+    pub fn is_empty(&self) -> bool {
+        self.seeds.is_empty()
+    }
+
+    /// Calculated streamlines.
+    pub fn lines(&self) -> &[Vec<[f32; 2]>] {
+        &self.lines
+    }
+
+    /// Calculate the streamlines through the seeds for the current velocity field.
+    pub fn calculate<const B: usize>(&mut self, lbgk: &Lbgk<2, B>) {
+        self.lines = self
+            .seeds
+            .iter()
+            .map(|seed| {
+                let mut backward = self.integrate(lbgk, seed, -self.step_size);
+                backward.reverse();
+                backward.pop();
+                backward.extend(self.integrate(lbgk, seed, self.step_size));
+                backward
+            })
+            .collect();
+    }
+
+    /// Integrate from a position along the (normalised) velocity field until leaving the fluid
+    /// or reaching a stagnation point.
+    fn integrate<const B: usize>(
+        &self,
+        lbgk: &Lbgk<2, B>,
+        pos: &[f32; 2],
+        step_size: f32,
+    ) -> Vec<[f32; 2]> {
+        let mut result = vec![*pos];
+        if !fluid(lbgk, pos) {
+            return result;
+        }
+        for _ in 0..self.max_steps {
+            let pos = *result.last().unwrap();
+            let new_pos = self.step(lbgk, &pos, step_size);
+            match new_pos {
+                Some(new_pos) if fluid(lbgk, &new_pos) => result.push(new_pos),
+                _ => break,
+            }
+        }
+        result
+    }
+
+    /// Advance a position by an integration step.
+    fn step<const B: usize>(
+        &self,
+        lbgk: &Lbgk<2, B>,
+        pos: &[f32; 2],
+        step_size: f32,
+    ) -> Option<[f32; 2]> {
+        let offset = |pos: &[f32; 2], k: [f32; 2], h: f32| [pos[0] + h * k[0], pos[1] + h * k[1]];
+        match self.integrator {
+            Integrator::RungeKutta2 => {
+                let k1 = direction(lbgk, pos)?;
+                let k2 = direction(lbgk, &offset(pos, k1, 0.5 * step_size))?;
+                Some(offset(pos, k2, step_size))
+            }
+            Integrator::RungeKutta4 => {
+                let k1 = direction(lbgk, pos)?;
+                let k2 = direction(lbgk, &offset(pos, k1, 0.5 * step_size))?;
+                let k3 = direction(lbgk, &offset(pos, k2, 0.5 * step_size))?;
+                let k4 = direction(lbgk, &offset(pos, k3, step_size))?;
+                let k = [0, 1].map(|d| (k1[d] + 2.0 * (k2[d] + k3[d]) + k4[d]) / 6.0);
+                Some(offset(pos, k, step_size))
+            }
+        }
+    }
+
+    /// Draw the streamlines (in lattice coordinates).
+    pub fn draw(
+        &self,
+        colour: &str,
+        canvas_rendering_context: &web_sys::CanvasRenderingContext2d,
+    ) -> Result<(), JsValue> {
+        canvas_rendering_context.set_stroke_style_str(colour);
+        canvas_rendering_context.begin_path();
+        for line in &self.lines {
+            for (i, pos) in line.iter().enumerate() {
+                let (x, y) = (pos[0] as f64, pos[1] as f64);
+                match i {
+                    0 => canvas_rendering_context.move_to(x, y),
+                    _ => canvas_rendering_context.line_to(x, y),
+                }
+            }
+        }
+        canvas_rendering_context.stroke();
+
+        Ok(())
+    }
+}
+
+/// Direction of the interpolated velocity at a position, if not stagnant.
+pub fn direction<const B: usize>(lbgk: &Lbgk<2, B>, pos: &[f32; 2]) -> Option<[f32; 2]> {
+    let u = lbgk.interpolated_velocity_vector(pos);
+    let magnitude = u.norm();
+    match magnitude > STAGNATION_VELOCITY {
+        true => Some(u.map(|val| val / magnitude)),
+        false => None,
+    }
+}
@@ -0,0 +1,239 @@
+use crate::lbgk::Lbgk;
+use crate::linear_algebra::VectorOps;
+
+use itertools::{iproduct, izip};
+use wasm_bindgen::prelude::*;
+
+/// Kernels approximating the Dirac delta function for spreading and interpolation.
+#[derive(Clone, Copy)]
+pub enum Kernel {
+    /// Two point (bilinear) kernel.
+    TwoPoint,
+    /// Three point kernel of Roma, Peskin and Berger.
+    ThreePoint,
+    /// Four point cosine kernel of Peskin.
+    FourPoint,
+}
+
+impl Kernel {
+    /// Half width of the kernel support.
+    fn half_width(&self) -> f32 {
+        match self {
+            Kernel::TwoPoint => 1.0,
+            Kernel::ThreePoint => 1.5,
+            Kernel::FourPoint => 2.0,
+        }
+    }
+
+    /// Kernel value in one dimension.
+    fn value(&self, r: f32) -> f32 {
+        let r = r.abs();
+        match self {
+            Kernel::TwoPoint => (1.0 - r).max(0.0),
+            Kernel::ThreePoint => match r {
+                _ if r <= 0.5 => (1.0 + (1.0 - 3.0 * r * r).sqrt()) / 3.0,
+                _ if r <= 1.5 => {
+                    (5.0 - 3.0 * r - (1.0 - 3.0 * (1.0 - r) * (1.0 - r)).max(0.0).sqrt()) / 6.0
+                }
+                _ => 0.0,
+            },
+            Kernel::FourPoint => match r {
+                _ if r <= 2.0 => 0.25 * (1.0 + (std::f32::consts::FRAC_PI_2 * r).cos()),
+                _ => 0.0,
+            },
+        }
+    }
+
+    /// Lattice positions and weights within the support of the kernel about a position.
+    fn weights(&self, pos: &[f32; 2], size: &[usize; 2]) -> Vec<([usize; 2], f32)> {
+        let half_width = self.half_width();
+        let clamp = |val: f32, i: usize| val.clamp(0.0, (size[i] - 1) as f32) as usize;
+        let min = [0, 1].map(|i| clamp((pos[i] - half_width).ceil(), i));
+        let max = [0, 1].map(|i| clamp((pos[i] + half_width).floor(), i));
+        iproduct!(min[0]..=max[0], min[1]..=max[1])
+            .map(|(x, y)| {
+                (
+                    [x, y],
+                    self.value(x as f32 - pos[0]) * self.value(y as f32 - pos[1]),
+                )
+            })
+            .filter(|(_, weight)| *weight > 0.0)
+            .collect()
+    }
+}
+
+/// A deformable boundary represented by a chain of Lagrangian marker points for the
+/// [immersed boundary method](https://en.wikipedia.org/wiki/Immersed_boundary_method).
+///
+/// Markers move with the interpolated fluid velocity, and the elastic forces from stretching,
+/// bending and tethering are spread to the fluid.
+#[derive(Clone)]
+pub struct ImmersedBoundary {
+    kernel: Kernel,
+    markers: Vec<[f32; 2]>,
+    rest_lengths: Vec<f32>,
+    rest_curvatures: Vec<[f32; 2]>,
+    stretching_stiffness: f32,
+    bending_stiffness: f32,
+    // note: tethered markers are held near their anchors by stiff springs
+    tethers: Vec<(usize, [f32; 2])>,
+    tether_stiffness: f32,
+}
+
+impl ImmersedBoundary {
+    /// Create a new `ImmersedBoundary` from marker positions, resting in the given shape.
+    pub fn new(
+        kernel: Kernel,
+        markers: Vec<[f32; 2]>,
+        stretching_stiffness: f32,
+        bending_stiffness: f32,
+    ) -> Self {
+        let rest_lengths = markers
+            .windows(2)
+            .map(|markers| distance(&markers[0], &markers[1]))
+            .collect();
+        let rest_curvatures = markers.windows(3).map(curvature).collect();
+        Self {
+            kernel,
+            markers,
+            rest_lengths,
+            rest_curvatures,
+            stretching_stiffness,
+            bending_stiffness,
+            tethers: Vec::new(),
+            tether_stiffness: 0.0,
+        }
+    }
+
+    /// Create a new `ImmersedBoundary` of markers along a straight line, e.g. a filament.
+    pub fn new_line(
+        kernel: Kernel,
+        start: [f32; 2],
+        end: [f32; 2],
+        count: usize,
+        stretching_stiffness: f32,
+        bending_stiffness: f32,
+    ) -> Self {
+        let markers = (0..count)
+            .map(|i| {
+                let s = i as f32 / (count - 1).max(1) as f32;
+                [0, 1].map(|d| start[d] + s * (end[d] - start[d]))
+            })
+            .collect();
+        Self::new(kernel, markers, stretching_stiffness, bending_stiffness)
+    }
+
+    /// Tether markers to their current positions.
+    pub fn set_tethers(&mut self, markers: &[usize], stiffness: f32) {
+        self.tethers = markers
+            .iter()
+            .map(|&marker| (marker, self.markers[marker]))
+            .collect();
+        self.tether_stiffness = stiffness;
+    }
+
+    /// Marker positions.
+    pub fn markers(&self) -> &[[f32; 2]] {
+        &self.markers
+    }
+
+    /// Calculate the elastic forces on the markers.
+    fn forces(&self) -> Vec<[f32; 2]> {
+        let mut result = vec![[0.0; 2]; self.markers.len()];
+
+        // stretching
+        for (i, rest_length) in self.rest_lengths.iter().enumerate() {
+            let (first, second) = (self.markers[i], self.markers[i + 1]);
+            let length = distance(&first, &second);
+            if length > 0.0 {
+                let tension = self.stretching_stiffness * (length - rest_length) / length;
+                for d in 0..2 {
+                    let val = tension * (second[d] - first[d]);
+                    result[i][d] += val;
+                    result[i + 1][d] -= val;
+                }
+            }
+        }
+
+        // bending (relative to the resting shape)
+        for (i, (markers, rest_curvature)) in
+            izip!(self.markers.windows(3), &self.rest_curvatures).enumerate()
+        {
+            for (d, (curvature, rest_curvature)) in
+                izip!(curvature(markers), rest_curvature).enumerate()
+            {
+                let val = self.bending_stiffness * (curvature - rest_curvature);
+                result[i][d] -= val;
+                result[i + 1][d] += 2.0 * val;
+                result[i + 2][d] -= val;
+            }
+        }
+
+        // tethering
+        for (marker, anchor) in &self.tethers {
+            for d in 0..2 {
+                result[*marker][d] -=
+                    self.tether_stiffness * (self.markers[*marker][d] - anchor[d]);
+            }
+        }
+
+        result
+    }
+
+    /// Spread the elastic forces on the markers to the force densities of the lattice.
+    pub fn spread<const B: usize>(&self, lbgk: &mut Lbgk<2, B>) {
+        let size = lbgk.size();
+        for (marker, force) in izip!(&self.markers, self.forces()) {
+            for (pos, weight) in self.kernel.weights(marker, &size) {
+                if !lbgk.object(&pos) {
+                    lbgk.add_force_density(&pos, &force.map(|val| val * weight));
+                }
+            }
+        }
+    }
+
+    /// Move the markers with the fluid velocity interpolated from the lattice.
+    pub fn advect<const B: usize>(&mut self, lbgk: &Lbgk<2, B>) {
+        let size = lbgk.size();
+        for marker in &mut self.markers {
+            let mut velocity_vector = [0.0; 2];
+            for (pos, weight) in self.kernel.weights(marker, &size) {
+                for (u, other_u) in izip!(&mut velocity_vector, lbgk.velocity_vector(&pos)) {
+                    *u += weight * other_u;
+                }
+            }
+            for (pos, u, size) in izip!(marker, velocity_vector, size) {
+                *pos = (*pos + u).clamp(0.0, (size - 1) as f32);
+            }
+        }
+    }
+
+    /// Draw the boundary as a line (in lattice coordinates).
+    pub fn draw(
+        &self,
+        canvas_rendering_context: &web_sys::CanvasRenderingContext2d,
+    ) -> Result<(), JsValue> {
+        canvas_rendering_context.set_stroke_style_str("black");
+        canvas_rendering_context.begin_path();
+        for (i, marker) in self.markers.iter().enumerate() {
+            let (x, y) = (marker[0] as f64, marker[1] as f64);
+            match i {
+                0 => canvas_rendering_context.move_to(x, y),
+                _ => canvas_rendering_context.line_to(x, y),
+            }
+        }
+        canvas_rendering_context.stroke();
+
+        Ok(())
+    }
+}
+
+/// Discrete curvature (second difference) at the middle of three positions.
+fn curvature(markers: &[[f32; 2]]) -> [f32; 2] {
+    [0, 1].map(|d| markers[0][d] - 2.0 * markers[1][d] + markers[2][d])
+}
+
+/// Distance between positions.
+fn distance(first: &[f32; 2], second: &[f32; 2]) -> f32 {
+    [second[0] - first[0], second[1] - first[1]].norm()
+}
@@ -0,0 +1,184 @@
+use crate::log;
+
+use wasm_bindgen::prelude::*;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+const VERSION: u32 = 1;
+const STORE_NAME: &str = "values";
+
+/// Store of bytes by key in an [IndexedDB](https://en.wikipedia.org/wiki/IndexedDB) database of
+/// the browser, for values too large for local storage (e.g. checkpoints and recordings).
+///
+/// note: requests are asynchronous, so values are passed to callbacks, and requests made
+/// before the database is open are ignored
+#[derive(Clone)]
+pub struct IndexedDb {
+    name: String,
+    database: Rc<RefCell<Option<web_sys::IdbDatabase>>>,
+}
+
+impl IndexedDb {
+    /// Open a new `IndexedDb` of a database name, creating the database if needed.
+    pub fn open(name: &str) -> Result<Self, JsValue> {
+        let factory = web_sys::window()
+            .ok_or("should have window")?
+            .indexed_db()?
+            .ok_or("should have IndexedDB")?;
+        let request = factory.open_with_u32(name, VERSION)?;
+
+        let request_clone = request.clone();
+        let on_upgrade_needed = Closure::once_into_js(move |_: web_sys::Event| {
+            let _ = request_clone
+                .result()
+                .and_then(|result| result.dyn_into::<web_sys::IdbDatabase>())
+                .and_then(|database| database.create_object_store(STORE_NAME));
+        });
+        request.set_onupgradeneeded(Some(on_upgrade_needed.unchecked_ref()));
+
+        let database = Rc::new(RefCell::new(None));
+        let database_clone = database.clone();
+        let request_clone = request.clone();
+        let on_success = Closure::once_into_js(move |_: web_sys::Event| {
+            if let Ok(result) = request_clone.result() {
+                *database_clone.borrow_mut() = result.dyn_into().ok();
+            }
+        });
+        request.set_onsuccess(Some(on_success.unchecked_ref()));
+
+        let name_clone = name.to_string();
+        let on_error = Closure::once_into_js(move |_: web_sys::Event| {
+            log::warn(
+                "indexed_db",
+                &format!("database {name_clone} could not be opened"),
+            );
+        });
+        request.set_onerror(Some(on_error.unchecked_ref()));
+
+        Ok(Self {
+            name: name.to_string(),
+            database,
+        })
+    }
+
+    /// Whether the database is open.
+    pub fn is_open(&self) -> bool {
+        self.database.borrow().is_some()
+    }
+
+    /// Object store of the database in a transaction of a mode, if open.
+    fn store(
+        &self,
+        mode: web_sys::IdbTransactionMode,
+    ) -> Result<Option<web_sys::IdbObjectStore>, JsValue> {
+        let database = self.database.borrow();
+        let Some(database) = database.as_ref() else {
+            log::warn(
+                "indexed_db",
+                &format!(
+                    "database {} is not open, so the request is ignored",
+                    self.name
+                ),
+            );
+            return Ok(None);
+        };
+        database
+            .transaction_with_str_and_mode(STORE_NAME, mode)?
+            .object_store(STORE_NAME)
+            .map(Some)
+    }
+
+    /// Put bytes at a key, replacing any.
+    pub fn put(&self, key: &str, bytes: &[u8]) -> Result<(), JsValue> {
+        if let Some(store) = self.store(web_sys::IdbTransactionMode::Readwrite)? {
+            store.put_with_key(&js_sys::Uint8Array::from(bytes), &JsValue::from_str(key))?;
+        }
+        Ok(())
+    }
+
+    /// Delete the bytes at a key, if any.
+    pub fn delete(&self, key: &str) -> Result<(), JsValue> {
+        if let Some(store) = self.store(web_sys::IdbTransactionMode::Readwrite)? {
+            store.delete(&JsValue::from_str(key))?;
+        }
+        Ok(())
+    }
+
+    /// Delete the bytes at all keys.
+    pub fn clear(&self) -> Result<(), JsValue> {
+        if let Some(store) = self.store(web_sys::IdbTransactionMode::Readwrite)? {
+            store.clear()?;
+        }
+        Ok(())
+    }
+
+    /// Get the bytes at a key, passing them (if any) to a callback.
+    pub fn get(&self, key: &str, f: impl FnOnce(Option<Vec<u8>>) + 'static) -> Result<(), JsValue> {
+        if let Some(store) = self.store(web_sys::IdbTransactionMode::Readonly)? {
+            let request = store.get(&JsValue::from_str(key))?;
+            let request_clone = request.clone();
+            let on_success = Closure::once_into_js(move |_: web_sys::Event| {
+                let bytes = request_clone
+                    .result()
+                    .ok()
+                    .and_then(|result| result.dyn_into::<js_sys::Uint8Array>().ok())
+                    .map(|array| array.to_vec());
+                f(bytes);
+            });
+            request.set_onsuccess(Some(on_success.unchecked_ref()));
+        }
+        Ok(())
+    }
+
+    /// Get all keys in ascending order, passing them to a callback.
+    pub fn keys(&self, f: impl FnOnce(Vec<String>) + 'static) -> Result<(), JsValue> {
+        if let Some(store) = self.store(web_sys::IdbTransactionMode::Readonly)? {
+            let request = store.get_all_keys()?;
+            let request_clone = request.clone();
+            let on_success = Closure::once_into_js(move |_: web_sys::Event| {
+                let keys = request_clone
+                    .result()
+                    .ok()
+                    .and_then(|result| result.dyn_into::<js_sys::Array>().ok())
+                    .map(|array| array.iter().filter_map(|key| key.as_string()).collect())
+                    .unwrap_or_default();
+                f(keys);
+            });
+            request.set_onsuccess(Some(on_success.unchecked_ref()));
+        }
+        Ok(())
+    }
+
+    /// Get all keys and their bytes in ascending order of key, passing them to a callback.
+    pub fn entries(&self, f: impl FnOnce(Vec<(String, Vec<u8>)>) + 'static) -> Result<(), JsValue> {
+        if let Some(store) = self.store(web_sys::IdbTransactionMode::Readonly)? {
+            // note: the requests of a transaction succeed in order, so the keys and values
+            // correspond
+            let keys_request = store.get_all_keys()?;
+            let values_request = store.get_all()?;
+            let values_request_clone = values_request.clone();
+            let on_success = Closure::once_into_js(move |_: web_sys::Event| {
+                let array = |request: &web_sys::IdbRequest| {
+                    request
+                        .result()
+                        .ok()
+                        .and_then(|result| result.dyn_into::<js_sys::Array>().ok())
+                        .map(|array| array.iter().collect::<Vec<_>>())
+                        .unwrap_or_default()
+                };
+                let entries = array(&keys_request)
+                    .into_iter()
+                    .zip(array(&values_request_clone))
+                    .filter_map(|(key, value)| {
+                        let bytes = value.dyn_into::<js_sys::Uint8Array>().ok()?;
+                        Some((key.as_string()?, bytes.to_vec()))
+                    })
+                    .collect();
+                f(entries);
+            });
+            values_request.set_onsuccess(Some(on_success.unchecked_ref()));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,292 @@
+use crate::{lbgk::Lbgk, random::Random, spectrum};
+
+use itertools::{iproduct, izip};
+
+use std::f32::consts::PI;
+
+/// Set a doubly periodic [Taylor–Green vortex](https://en.wikipedia.org/wiki/Taylor%E2%80%93Green_vortex)
+/// of a single period in each dimension of the lattice, with a peak horizontal velocity about
+/// a mean density.
+///
+/// note: the density follows the pressure of the vortex, by the isothermal equation of state
+pub fn taylor_green_vortex<const B: usize>(lbgk: &mut Lbgk<2, B>, density: f32, velocity: f32) {
+    let [kx, ky] = lbgk.size().map(|size| 2.0 * PI / size as f32);
+    // note: the vertical velocity is scaled to be divergence free
+    let [u, v] = [velocity, velocity * kx / ky];
+    let sound_speed_squared = lbgk.sound_speed_squared();
+    for pos in lbgk.positions() {
+        let [x, y] = pos.map(|val| val as f32);
+        let velocity_vector = [
+            -u * (kx * x).cos() * (ky * y).sin(),
+            v * (kx * x).sin() * (ky * y).cos(),
+        ];
+        let pressure =
+            -0.25 * density * (u * u * (2.0 * kx * x).cos() + v * v * (2.0 * ky * y).cos());
+        lbgk.set_equilibrium(
+            &pos,
+            density + pressure / sound_speed_squared,
+            &velocity_vector,
+        );
+    }
+}
+
+/// Add a pulse of density (i.e. pressure) with a
+/// [Gaussian](https://en.wikipedia.org/wiki/Gaussian_function) profile of standard deviation
+/// `radius` about a centre, radiating sound (e.g. to measure the speed of sound).
+pub fn gaussian_pulse<const B: usize>(
+    lbgk: &mut Lbgk<2, B>,
+    centre: &[f32; 2],
+    amplitude: f32,
+    radius: f32,
+) {
+    // note: the profile is truncated at three standard deviations
+    let extent = 3.0 * radius;
+    let size = lbgk.size();
+    let [x0, y0] = [0, 1].map(|d| (centre[d] - extent).floor().max(0.0) as usize);
+    let [x1, y1] = [0, 1].map(|d| ((centre[d] + extent).ceil() as usize).min(size[d] - 1));
+    for pos in iproduct!(x0..=x1, y0..=y1).map(|(x, y)| [x, y]) {
+        let [dx, dy] = [0, 1].map(|d| pos[d] as f32 - centre[d]);
+        let weight = (-(dx * dx + dy * dy) / (2.0 * radius * radius)).exp();
+        lbgk.add_density(&pos, amplitude * weight);
+    }
+}
+
+/// [Lamb–Oseen vortex](https://en.wikipedia.org/wiki/Lamb%E2%80%93Oseen_vortex) of a
+/// circulation (positive anticlockwise) and a core radius about a centre.
+#[derive(Clone, Copy)]
+pub struct LambOseenVortex {
+    pub centre: [f32; 2],
+    pub circulation: f32,
+    pub core_radius: f32,
+}
+
+impl LambOseenVortex {
+    /// Tangential velocity at a distance from the centre.
+    fn tangential_velocity(&self, r: f32) -> f32 {
+        match r > 0.0 {
+            true => {
+                self.circulation / (2.0 * PI * r)
+                    * (1.0 - (-(r * r) / (self.core_radius * self.core_radius)).exp())
+            }
+            false => 0.0,
+        }
+    }
+
+    /// Velocity vector induced at a position.
+    pub fn velocity_vector(&self, pos: &[f32; 2]) -> [f32; 2] {
+        let [dx, dy] = [0, 1].map(|d| pos[d] - self.centre[d]);
+        let r = (dx * dx + dy * dy).sqrt();
+        match r > 0.0 {
+            true => {
+                let u = self.tangential_velocity(r) / r;
+                [-u * dy, u * dx]
+            }
+            false => [0.0; 2],
+        }
+    }
+
+    /// Pressure relative to that far from the centre, in
+    /// [cyclostrophic balance](https://en.wikipedia.org/wiki/Balanced_flow) with the rotation
+    /// of a fluid of a density.
+    ///
+    /// note: integrated numerically out to many core radii, beyond which the vortex is
+    /// irrotational
+    pub fn pressure(&self, pos: &[f32; 2], density: f32) -> f32 {
+        const STEPS: usize = 64;
+        let [dx, dy] = [0, 1].map(|d| pos[d] - self.centre[d]);
+        let r = (dx * dx + dy * dy).sqrt();
+        let outer = r + 8.0 * self.core_radius;
+        let step = (outer - r) / STEPS as f32;
+        let inner = (0..STEPS)
+            .map(|i| {
+                let s = r + (i as f32 + 0.5) * step;
+                let u = self.tangential_velocity(s);
+                u * u / s * step
+            })
+            .sum::<f32>();
+        let irrotational = self.circulation * self.circulation / (8.0 * PI * PI * outer * outer);
+        -density * (inner + irrotational)
+    }
+}
+
+/// Superimpose [Lamb–Oseen vortices](https://en.wikipedia.org/wiki/Lamb%E2%80%93Oseen_vortex)
+/// onto the flow (e.g. for vortex–airfoil interaction or to test the accuracy of advection),
+/// adding their velocities and pressures to the fluid lattice positions.
+///
+/// note: the pressures are of each vortex alone, so vortices close to each other are not in
+/// balance (and radiate weak sound)
+pub fn lamb_oseen_vortices<const B: usize>(
+    lbgk: &mut Lbgk<2, B>,
+    vortices: &[LambOseenVortex],
+    density: f32,
+) {
+    let sound_speed_squared = lbgk.sound_speed_squared();
+    for pos in lbgk.positions() {
+        let fpos = pos.map(|val| val as f32);
+        for vortex in vortices {
+            lbgk.add_density(&pos, vortex.pressure(&fpos, density) / sound_speed_squared);
+            lbgk.add_velocity(&pos, &vortex.velocity_vector(&fpos));
+        }
+    }
+}
+
+/// Superimpose a doubly periodic pair of shear layers onto the flow, a quarter and three
+/// quarters of the way up the lattice, with a velocity along x either side of them and a
+/// sinusoidal velocity across them perturbing their interfaces (to seed the
+/// [Kelvin–Helmholtz instability](https://en.wikipedia.org/wiki/Kelvin%E2%80%93Helmholtz_instability)).
+///
+/// note: the velocity profile across each shear layer is a hyperbolic tangent of a thickness,
+/// and the perturbation is a single period across the lattice of a fraction of the velocity
+pub fn double_shear_layer<const B: usize>(
+    lbgk: &mut Lbgk<2, B>,
+    velocity: f32,
+    thickness: f32,
+    perturbation: f32,
+) {
+    let [width, height] = lbgk.size().map(|size| size as f32);
+    for pos in lbgk.positions() {
+        let [x, y] = pos.map(|val| val as f32);
+        let distance = match y <= 0.5 * height {
+            true => y - 0.25 * height,
+            false => 0.75 * height - y,
+        };
+        lbgk.add_velocity(
+            &pos,
+            &[
+                velocity * (distance / thickness).tanh(),
+                perturbation * velocity * (2.0 * PI * x / width).sin(),
+            ],
+        );
+    }
+}
+
+/// Superimpose a random divergence free velocity field onto the fluid lattice positions of a
+/// doubly periodic lattice, of a root mean square velocity and an energy spectrum
+/// E(k) ∝ k⁴ exp(-2 (k / k₀)²) peaking at a wavenumber k₀ (e.g. to seed
+/// [two dimensional turbulence](https://en.wikipedia.org/wiki/Two-dimensional_turbulence)).
+///
+/// note: the velocity is the central differences of a stream function of random phases, so
+/// free of divergence by central differences, with modes truncated at three times the peak
+/// wavenumber
+pub fn random_turbulence<const B: usize>(
+    lbgk: &mut Lbgk<2, B>,
+    velocity: f32,
+    peak_wavenumber: f32,
+    random: &mut Random,
+) {
+    let size = lbgk.size();
+    let [width, height] = size;
+
+    let mut coefficients = vec![[0.0; 2]; width * height];
+    for (coefficient, (n, m)) in izip!(&mut coefficients, iproduct!(0..height, 0..width)) {
+        // note: a phase is drawn for every mode, so the phases of a seed are independent of
+        // the peak wavenumber
+        let phase = random.uniform_range(0.0, 2.0 * PI);
+        let k = spectrum::wavenumber(&[m, n], &size);
+        if k == 0.0 || k > 3.0 * peak_wavenumber {
+            continue;
+        }
+        // note: the velocity of a mode is the wavenumber times the stream function, and the
+        // modes of a shell are proportional to the wavenumber, so |ψ|² ∝ E(k) / k³
+        let ratio = k / peak_wavenumber;
+        let amplitude = (ratio.powi(4) * (-2.0 * ratio * ratio).exp() / k.powi(3)).sqrt();
+        *coefficient = [amplitude * phase.cos(), amplitude * phase.sin()];
+    }
+    let stream_function = spectrum::discrete_fourier_transform(&coefficients, &size, true);
+
+    let psi = |x: usize, y: usize| stream_function[width * (y % height) + x % width][0];
+    let velocity_vectors = lbgk
+        .positions()
+        .map(|[x, y]| {
+            [
+                0.5 * (psi(x, y + 1) - psi(x, y + height - 1)),
+                -0.5 * (psi(x + 1, y) - psi(x + width - 1, y)),
+            ]
+        })
+        .collect::<Vec<_>>();
+
+    let mean_squared = velocity_vectors
+        .iter()
+        .map(|[u, v]| u * u + v * v)
+        .sum::<f32>()
+        / velocity_vectors.len() as f32;
+    let scale = match mean_squared > 0.0 {
+        true => velocity / mean_squared.sqrt(),
+        false => 0.0,
+    };
+    for (pos, velocity_vector) in izip!(lbgk.positions(), velocity_vectors) {
+        if !lbgk.object(&pos) {
+            lbgk.add_velocity(&pos, &velocity_vector.map(|val| scale * val));
+        }
+    }
+}
+
+/// Project velocity vectors of a lattice size (x varying fastest) onto the nearest free of
+/// divergence by central differences, removing their gradient part by the
+/// [Helmholtz decomposition](https://en.wikipedia.org/wiki/Helmholtz_decomposition) (e.g. so a
+/// user provided initial velocity field starts without a spurious pressure transient).
+///
+/// note: spectral, removing the component of each Fourier mode along its central difference
+/// gradient, so the velocity vectors are taken as doubly periodic; the mean velocity is kept
+pub fn divergence_free_projection(
+    size: &[usize; 2],
+    velocity_vectors: &[[f32; 2]],
+) -> Vec<[f32; 2]> {
+    let [width, height] = *size;
+    let mut modes = [0, 1].map(|d| {
+        let values = velocity_vectors
+            .iter()
+            .map(|velocity_vector| [velocity_vector[d], 0.0])
+            .collect::<Vec<_>>();
+        spectrum::discrete_fourier_transform(&values, size, false)
+    });
+
+    for (index, (n, m)) in iproduct!(0..height, 0..width).enumerate() {
+        // note: the central difference of a mode is i sin(k) times the mode
+        let gradient =
+            [(m, width), (n, height)].map(|(j, len)| (2.0 * PI * j as f32 / len as f32).sin());
+        let norm_squared = gradient[0] * gradient[0] + gradient[1] * gradient[1];
+        if norm_squared < 1e-12 {
+            continue;
+        }
+        // note: the (complex) potential of the gradient part of the mode
+        let potential = [0, 1].map(|part| {
+            (gradient[0] * modes[0][index][part] + gradient[1] * modes[1][index][part])
+                / norm_squared
+        });
+        for (mode, gradient) in izip!(&mut modes, gradient) {
+            for (val, potential) in izip!(&mut mode[index], potential) {
+                *val -= gradient * potential;
+            }
+        }
+    }
+
+    let [u, v] = modes.map(|mode| spectrum::discrete_fourier_transform(&mode, size, true));
+    let len = (width * height) as f32;
+    izip!(u, v).map(|(u, v)| [u[0] / len, v[0] / len]).collect()
+}
+
+/// Set the velocity at the fluid lattice positions of the lattice from velocity vectors (x
+/// varying fastest) with a density, with equilibrium distributions, optionally projected to be
+/// free of divergence first (see `divergence_free_projection`).
+pub fn velocity_field<const B: usize>(
+    lbgk: &mut Lbgk<2, B>,
+    velocity_vectors: &[[f32; 2]],
+    density: f32,
+    projected: bool,
+) {
+    let velocity_vectors = match projected {
+        true => divergence_free_projection(&lbgk.size(), velocity_vectors),
+        false => velocity_vectors.to_vec(),
+    };
+    for (pos, velocity_vector) in izip!(lbgk.positions(), velocity_vectors) {
+        lbgk.set_equilibrium(&pos, density, &velocity_vector);
+    }
+}
+
+/// Decay rate of the velocity of a Taylor–Green vortex of a lattice size, for a kinematic
+/// viscosity (the kinetic energy decays at twice the rate).
+pub fn taylor_green_decay_rate(size: [usize; 2], viscosity: f32) -> f32 {
+    let [kx, ky] = size.map(|size| 2.0 * PI / size as f32);
+    viscosity * (kx * kx + ky * ky)
+}
@@ -1,19 +1,161 @@
+pub mod boundary_condition;
+pub mod cell_view;
+pub mod collision_operator;
 pub mod parameters;
+pub mod position_iter;
+pub mod slice;
 
-use crate::linear_algebra::VectorOps;
+use crate::linear_algebra::{Matrix, VectorOps};
+use crate::profiling;
+use boundary_condition::{BoundaryCondition, Streaming};
+use cell_view::CellView;
+use collision_operator::{Bgk, CollisionOperator, CollisionValues};
+use position_iter::PositionIter;
+use slice::Slice;
 
-use itertools::izip;
+use itertools::{iproduct, izip};
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use std::sync::Arc;
 
 /// Boundary schemes.
-#[derive(Clone, Copy)]
+///
+/// note: (de)serialised in camel case (e.g. `bounceBack`), as in JavaScript
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub enum BoundaryScheme {
     Inflow,
+    /// Inflow of the source velocity (or that of the inflow profile), but of the density
+    /// extrapolated from the neighbouring fluid, so the pressure is not fixed at the inlet
+    /// ([reference](https://doi.org/10.1088/1009-1963/11/4/310)).
+    ExtrapolatedInflow,
     Outflow,
+    /// Outflow of the density and velocity extrapolated from the neighbouring fluid, with the
+    /// distributions from their equilibrium and the non-equilibrium part of the neighbouring
+    /// fluid ([reference](https://doi.org/10.1088/1009-1963/11/4/310)).
+    ExtrapolatedOutflow,
     Periodic,
     BounceBack,
+    /// Bounce-back from a wall moving with the source velocity
+    /// ([reference](https://doi.org/10.1017/S0022112094001771)).
+    MovingWall,
     SpecularReflection,
 }
 
+/// Treatments of the corner lattice positions (or edges of three dimensional lattices) where
+/// different boundary schemes meet.
+#[derive(Clone, Copy, PartialEq)]
+pub enum CornerScheme {
+    /// The combination of the boundary schemes applied in each dimension in turn.
+    Unchanged,
+    /// Equilibrium distributions for the average density and velocity of the adjacent edge
+    /// lattice positions.
+    Equilibrium,
+    /// Average of the values of the adjacent edge lattice positions.
+    Average,
+}
+
+/// Treatments of the distributions leaving the lattice through both boundaries at the corner
+/// lattice positions (or edges of three dimensional lattices) where specular reflection
+/// boundaries meet.
+#[derive(Clone, Copy, PartialEq)]
+pub enum CornerReflection {
+    /// Reflect the components of the lattice vector across each boundary.
+    Reflect,
+    /// Bounce back, i.e. reverse the lattice vector.
+    ///
+    /// note: the same as reflecting across each boundary of a two dimensional lattice
+    BounceBack,
+    /// Split equally between the reflections across either boundary (keeping the other
+    /// component).
+    Split,
+}
+
+/// Surfaces of objects.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Surface {
+    /// No-slip, by bounce-back.
+    NoSlip,
+    /// Free-slip, by specular reflection of the lattice vectors from the (staircase) surface.
+    FreeSlip,
+    /// Porous, of a [permeability](https://en.wikipedia.org/wiki/Darcy%27s_law), holding fluid
+    /// partially bounced back by a [gray lattice](https://doi.org/10.1016/j.cageo.2008.05.004).
+    Porous(f32),
+}
+
+/// Strategies of traversing the lattice to stream the distributions, independent of the
+/// physics (i.e. every scheme gives the same distributions).
+#[derive(Clone, Copy, PartialEq)]
+pub enum StreamingScheme {
+    /// Push the collision distributions of each lattice position to the neighbouring lattice
+    /// positions (scattered writes), fused with the collision in one pass.
+    Push,
+    /// Pull the collision distributions arriving at each lattice position from the
+    /// neighbouring lattice positions (scattered reads), after the collision of all.
+    Pull,
+    /// Collide and stream the distributions in place in one buffer, by the
+    /// [AA pattern](https://doi.org/10.1109/ICPP.2009.38): even iterations store the collision
+    /// distributions of each lattice position along the opposite lattice vectors of the lattice
+    /// position they stream from, and odd iterations read them from there and write the collision
+    /// distributions back to where they were read from.
+    Aa,
+}
+
+/// Fields derived from the algorithm values of a two dimensional lattice.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Field {
+    Density,
+    Pressure,
+    /// Velocity magnitude.
+    Velocity,
+    VelocityX,
+    VelocityY,
+    Vorticity,
+    Divergence,
+    /// Density gradient magnitude.
+    DensityGradient,
+    /// [Q-criterion](https://en.wikipedia.org/wiki/Vortex#Vortex_identification) (positive
+    /// where rotation dominates strain).
+    QCriterion,
+    /// [Stream function](https://en.wikipedia.org/wiki/Stream_function) (constant along
+    /// streamlines).
+    StreamFunction,
+}
+
+impl Field {
+    /// All fields.
+    pub const ALL: [Field; 10] = [
+        Field::Density,
+        Field::Pressure,
+        Field::Velocity,
+        Field::VelocityX,
+        Field::VelocityY,
+        Field::Vorticity,
+        Field::Divergence,
+        Field::DensityGradient,
+        Field::QCriterion,
+        Field::StreamFunction,
+    ];
+
+    /// Name of the field.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Field::Density => "Density",
+            Field::Pressure => "Pressure",
+            Field::Velocity => "Velocity",
+            Field::VelocityX => "Velocity x",
+            Field::VelocityY => "Velocity y",
+            Field::Vorticity => "Vorticity",
+            Field::Divergence => "Divergence",
+            Field::DensityGradient => "Density gradient",
+            Field::QCriterion => "Q-criterion",
+            Field::StreamFunction => "Stream function",
+        }
+    }
+}
+
 /// Lattice parameters,
 #[derive(Clone, Copy)]
 struct LatticeParameters<const N: usize> {
@@ -30,27 +172,160 @@ impl<const N: usize> Default for LatticeParameters<N> {
     }
 }
 
+/// Destination of a streamed collision distribution.
+struct StreamingDestination {
+    index: usize,
+    /// Index of the lattice vector.
+    i: usize,
+    distribution: f32,
+    /// ID of the object reflecting the distribution, if any.
+    object_id: Option<u16>,
+}
+
+/// Collision distributions pulled to a lattice position.
+struct PulledDistributions<const N: usize, const B: usize> {
+    /// Distribution arriving along each lattice vector, if any.
+    distributions: [Option<f32>; B],
+    /// Momentum exchanged with objects by the distributions arriving (see `exchanged_momentum`),
+    /// in the order exchanged.
+    momentum_exchanges: Vec<(u16, [f32; N], [f32; N])>,
+}
+
+/// ID of the objects set without their own ID (e.g. painted).
+pub const DEFAULT_OBJECT_ID: u16 = 1;
+
+/// Velocity vector and density of the inflow at a lattice position.
+pub type InflowProfile<const N: usize> = Box<dyn Fn(&[usize; N]) -> ([f32; N], f32) + Send + Sync>;
+
 /// Algorithm values.
 #[derive(Clone, Copy)]
 struct AlgorithmValues<const N: usize, const B: usize> {
     distributions: [f32; B],
-    collision_distributions: [f32; B],
     density: f32,
     velocity_vector: [f32; N],
+    force_density: [f32; N],
+}
+
+/// Algorithm values of all lattice positions, as separate arrays of each value (a structure of
+/// arrays), so each step only loads the values it needs.
+struct AlgorithmArrays<const N: usize, const B: usize> {
+    // note: a plane of each distribution
+    distributions: [Vec<f32>; B],
+    density: Vec<f32>,
+    velocity_vector: [Vec<f32>; N],
+    force_density: [Vec<f32>; N],
+}
+
+impl<const N: usize, const B: usize> AlgorithmArrays<N, B> {
+    /// Create a new `AlgorithmArrays` of a length, filled with algorithm values.
+    fn new(len: usize, algorithm_values: &AlgorithmValues<N, B>) -> Self {
+        Self {
+            distributions: algorithm_values.distributions.map(|f| vec![f; len]),
+            density: vec![algorithm_values.density; len],
+            velocity_vector: algorithm_values.velocity_vector.map(|u| vec![u; len]),
+            force_density: algorithm_values.force_density.map(|val| vec![val; len]),
+        }
+    }
+
+    /// Algorithm values at an index.
+    fn get(&self, index: usize) -> AlgorithmValues<N, B> {
+        AlgorithmValues {
+            distributions: self.distributions(index),
+            density: self.density[index],
+            velocity_vector: self.velocity_vector(index),
+            force_density: self.force_density(index),
+        }
+    }
+
+    /// Set the algorithm values at an index.
+    fn set(&mut self, index: usize, algorithm_values: &AlgorithmValues<N, B>) {
+        self.set_distributions(index, &algorithm_values.distributions);
+        self.density[index] = algorithm_values.density;
+        for (plane, u) in izip!(&mut self.velocity_vector, algorithm_values.velocity_vector) {
+            plane[index] = u;
+        }
+        for (plane, val) in izip!(&mut self.force_density, algorithm_values.force_density) {
+            plane[index] = val;
+        }
+    }
+
+    /// Fill all indexes with algorithm values.
+    fn fill(&mut self, algorithm_values: &AlgorithmValues<N, B>) {
+        for (plane, f) in izip!(&mut self.distributions, algorithm_values.distributions) {
+            plane.fill(f);
+        }
+        self.density.fill(algorithm_values.density);
+        for (plane, u) in izip!(&mut self.velocity_vector, algorithm_values.velocity_vector) {
+            plane.fill(u);
+        }
+        for (plane, val) in izip!(&mut self.force_density, algorithm_values.force_density) {
+            plane.fill(val);
+        }
+    }
+
+    /// Distributions at an index.
+    fn distributions(&self, index: usize) -> [f32; B] {
+        std::array::from_fn(|i| self.distributions[i][index])
+    }
+
+    /// Set the distributions at an index.
+    fn set_distributions(&mut self, index: usize, distributions: &[f32; B]) {
+        for (plane, f) in izip!(&mut self.distributions, distributions) {
+            plane[index] = *f;
+        }
+    }
+
+    /// Velocity vector at an index.
+    fn velocity_vector(&self, index: usize) -> [f32; N] {
+        std::array::from_fn(|d| self.velocity_vector[d][index])
+    }
+
+    /// Force density at an index.
+    fn force_density(&self, index: usize) -> [f32; N] {
+        std::array::from_fn(|d| self.force_density[d][index])
+    }
 }
 
 /// Implementation of the [Lattice Boltzmann method (LBM)](https://en.wikipedia.org/wiki/Lattice_Boltzmann_methods) for the
 /// [Bhatnagar–Gross–Krook (BGK) operator](https://en.wikipedia.org/wiki/Bhatnagar%E2%80%93Gross%E2%80%93Krook_operator)
-/// model for relaxation.
+/// model for relaxation, or another collision operator.
 pub struct Lbgk<const N: usize, const B: usize> {
     lattice_parameters: [LatticeParameters<N>; B],
     sound_speed_squared: f32,
     size: [usize; N],
-    boundary_schemes: [[BoundaryScheme; 2]; N],
+    boundary_conditions: [[Arc<dyn BoundaryCondition<N, B>>; 2]; N],
+    collision_operator: Arc<dyn CollisionOperator<N, B>>,
+    corner_scheme: CornerScheme,
+    corner_reflection: CornerReflection,
+    streaming_scheme: StreamingScheme,
     source_algorithm_values: AlgorithmValues<N, B>,
+    inflow_profile: Option<InflowProfile<N>>,
     // note: flat vectors reduce cache loads
-    algorithm_values: Vec<AlgorithmValues<N, B>>,
-    object: Vec<bool>,
+    algorithm_values: AlgorithmArrays<N, B>,
+    // note: the other buffer of a plane of each distribution, of those pushed to each lattice
+    // position by the fused collision and streaming (swapped with the distributions after), or
+    // of the collision distributions pulled from, so streaming never reads what it writes
+    next_distributions: [Vec<f32>; B],
+    // note: whether the distributions are stored along the opposite lattice vectors of the
+    // lattice positions they stream from (between the iterations of the AA pattern), with
+    // whether each dimension is periodic
+    reversed: Option<[bool; N]>,
+    // note: the index of the opposite of each lattice vector
+    opposite_lattice_vectors: [usize; B],
+    // note: the object ID at each lattice position, 0 for fluid
+    object: Vec<u16>,
+    // note: the lattice positions (by index) of the solid objects and of the fluid next to
+    // them, recalculated after the solid objects change
+    object_boundary: Option<Vec<usize>>,
+    force: [f32; N],
+    force_moment: [[f32; N]; N],
+    // note: the force and force moment on the objects of each ID
+    object_forces: Vec<([f32; N], [[f32; N]; N])>,
+    object_surfaces: Vec<Surface>,
+    // note: the velocity of the surface at each lattice position (empty while all are at rest)
+    wall_velocities: Vec<[f32; N]>,
+    mass_correction_period: Option<usize>,
+    iterations_since_mass_correction: usize,
 }
 
 impl Lbgk<2, 9> {
@@ -82,9 +357,9 @@ impl Lbgk<2, 9> {
 
         let source_algorithm_values = AlgorithmValues::<2, 9> {
             distributions,
-            collision_distributions: [0.0; 9],
             density,
             velocity_vector: *velocity_vector,
+            force_density: [0.0; 2],
         };
 
         let len = size.iter().product();
@@ -93,12 +368,189 @@ impl Lbgk<2, 9> {
             lattice_parameters,
             sound_speed_squared,
             size: *size,
-            boundary_schemes: *boundary_schemes,
+            boundary_conditions: boundary_schemes.map(|boundary_schemes| {
+                boundary_schemes.map(|boundary_scheme| boundary_scheme.boundary_condition())
+            }),
+            collision_operator: Arc::new(Bgk),
+            corner_scheme: CornerScheme::Unchanged,
+            corner_reflection: CornerReflection::Reflect,
+            streaming_scheme: StreamingScheme::Push,
             source_algorithm_values,
-            algorithm_values: vec![source_algorithm_values; len],
-            object: vec![false; len],
+            inflow_profile: None,
+            algorithm_values: AlgorithmArrays::new(len, &source_algorithm_values),
+            next_distributions: distributions.map(|f| vec![f; len]),
+            reversed: None,
+            opposite_lattice_vectors: lattice_parameters.map(|lattice_parameters| {
+                let c = lattice_parameters.lattice_vector.map(|val| -val);
+                parameters::d2q9::C
+                    .iter()
+                    .position(|val| *val == c)
+                    .unwrap()
+            }),
+            object: vec![0; len],
+            object_boundary: None,
+            force: [0.0; 2],
+            force_moment: [[0.0; 2]; 2],
+            object_forces: vec![([0.0; 2], [[0.0; 2]; 2])],
+            object_surfaces: vec![Surface::NoSlip],
+            wall_velocities: Vec::new(),
+            mass_correction_period: None,
+            iterations_since_mass_correction: 0,
+        }
+    }
+}
+
+/// Lattice positions around a position (clamped to a lattice size) and their weights of
+/// [bilinear interpolation](https://en.wikipedia.org/wiki/Bilinear_interpolation).
+pub(crate) fn bilinear_weights(size: &[usize; 2], pos: &[f32; 2]) -> [([usize; 2], f32); 4] {
+    let pos = [0, 1].map(|i| pos[i].clamp(0.0, (size[i] - 1) as f32));
+    let pos0 = pos.map(|val| val.floor() as usize);
+    let pos1 = [0, 1].map(|i| (pos0[i] + 1).min(size[i] - 1));
+    let [tx, ty] = [0, 1].map(|i| pos[i] - pos0[i] as f32);
+    [
+        ([pos0[0], pos0[1]], (1.0 - tx) * (1.0 - ty)),
+        ([pos1[0], pos0[1]], tx * (1.0 - ty)),
+        ([pos0[0], pos1[1]], (1.0 - tx) * ty),
+        ([pos1[0], pos1[1]], tx * ty),
+    ]
+}
+
+impl<const B: usize> Lbgk<2, B> {
+    /// Velocity vector at a position, bilinearly interpolated between lattice positions.
+    ///
+    /// Object lattice positions contribute zero velocity.
+    pub fn interpolated_velocity_vector(&self, pos: &[f32; 2]) -> [f32; 2] {
+        let mut result = [0.0; 2];
+        for (other_pos, weight) in bilinear_weights(&self.size, pos) {
+            let index = self.index(&other_pos);
+            if !self.solid(index) {
+                for (u, other_u) in izip!(&mut result, self.algorithm_values.velocity_vector(index))
+                {
+                    *u += weight * other_u;
+                }
+            }
+        }
+        result
+    }
+
+    /// Density at a position, bilinearly interpolated between fluid lattice positions (the
+    /// source density, if none).
+    pub fn interpolated_density(&self, pos: &[f32; 2]) -> f32 {
+        let (mut total, mut total_weight) = (0.0, 0.0);
+        for (other_pos, weight) in bilinear_weights(&self.size, pos) {
+            let index = self.index(&other_pos);
+            if !self.solid(index) {
+                total += weight * self.algorithm_values.density[index];
+                total_weight += weight;
+            }
+        }
+        match total_weight > 0.0 {
+            true => total / total_weight,
+            false => self.source_field(Field::Density),
+        }
+    }
+
+    /// Field value at lattice position.
+    pub fn field(&self, field: Field, pos: &[usize; 2]) -> f32 {
+        match field {
+            Field::Density => self.density(pos),
+            Field::Pressure => self.pressure(pos),
+            Field::Velocity => self.velocity(pos),
+            Field::VelocityX => self.velocity_vector(pos)[0],
+            Field::VelocityY => self.velocity_vector(pos)[1],
+            Field::Vorticity => self.vorticity(pos),
+            Field::Divergence => self.divergence(pos),
+            Field::DensityGradient => self.density_gradient(pos).norm(),
+            Field::QCriterion => self.q_criterion(pos),
+            Field::StreamFunction => self.stream_function(pos),
+        }
+    }
+
+    /// Field value for the source algorithm values, i.e. the undisturbed flow.
+    pub fn source_field(&self, field: Field) -> f32 {
+        let algorithm_values = &self.source_algorithm_values;
+        match field {
+            Field::Density => algorithm_values.density,
+            Field::Pressure => algorithm_values.density * self.sound_speed_squared,
+            Field::Velocity => algorithm_values.velocity_vector.norm(),
+            Field::VelocityX => algorithm_values.velocity_vector[0],
+            Field::VelocityY => algorithm_values.velocity_vector[1],
+            Field::Vorticity
+            | Field::Divergence
+            | Field::DensityGradient
+            | Field::QCriterion
+            | Field::StreamFunction => 0.0,
         }
     }
+
+    /// [Stream function](https://en.wikipedia.org/wiki/Stream_function) at lattice position,
+    /// integrated from the velocity.
+    ///
+    /// note: the integration is along the first column and then along the row of the position,
+    /// as for all lattice positions by `stream_functions`
+    pub fn stream_function(&self, pos: &[usize; 2]) -> f32 {
+        let mut result = 0.0;
+        for y in 1..=pos[1] {
+            result += self.stream_function_step(&[0, y], 1);
+        }
+        for x in 1..=pos[0] {
+            result += self.stream_function_step(&[x, pos[1]], 0);
+        }
+        result
+    }
+
+    /// Stream functions at all lattice positions (ordered as the lattice positions).
+    pub fn stream_functions(&self) -> Vec<f32> {
+        let [width, height] = self.size;
+        let mut result = vec![0.0; width * height];
+        for y in 1..height {
+            result[width * y] = result[width * (y - 1)] + self.stream_function_step(&[0, y], 1);
+        }
+        for (y, x) in iproduct!(0..height, 1..width) {
+            result[width * y + x] =
+                result[width * y + x - 1] + self.stream_function_step(&[x, y], 0);
+        }
+        result
+    }
+
+    /// Change of the stream function from the previous lattice position in a dimension to a
+    /// lattice position, by the trapezoidal rule (∂ψ/∂x = -v, ∂ψ/∂y = u).
+    ///
+    /// note: object lattice positions have zero velocity
+    fn stream_function_step(&self, pos: &[usize; 2], d: usize) -> f32 {
+        let mut previous_pos = *pos;
+        previous_pos[d] -= 1;
+        let velocity_vector = |pos: &[usize; 2]| match self.object(pos) {
+            true => [0.0; 2],
+            false => self.velocity_vector(pos),
+        };
+        let [u, previous_u] = [velocity_vector(pos), velocity_vector(&previous_pos)];
+        match d {
+            0 => -0.5 * (u[1] + previous_u[1]),
+            _ => 0.5 * (u[0] + previous_u[0]),
+        }
+    }
+
+    /// Momentum exchange torque on the objects about a centre during the last iteration.
+    pub fn torque(&self, centre: &[f32; 2]) -> f32 {
+        Self::torque_of(&self.force, &self.force_moment, centre)
+    }
+
+    /// Momentum exchange torque on the objects of an ID about a centre during the last
+    /// iteration.
+    pub fn object_torque(&self, id: u16, centre: &[f32; 2]) -> f32 {
+        self.object_forces
+            .get(id as usize)
+            .map_or(0.0, |(force, force_moment)| {
+                Self::torque_of(force, force_moment, centre)
+            })
+    }
+
+    /// Torque of a force and its moments about a centre.
+    fn torque_of(force: &[f32; 2], force_moment: &[[f32; 2]; 2], centre: &[f32; 2]) -> f32 {
+        // note: moments are stored per force component
+        (force_moment[1][0] - centre[0] * force[1]) - (force_moment[0][1] - centre[1] * force[0])
+    }
 }
 
 impl<const N: usize, const B: usize> Lbgk<N, B> {
@@ -112,36 +564,111 @@ impl<const N: usize, const B: usize> Lbgk<N, B> {
             .0
     }
 
-    /// Advances position in particular dimensions.
-    fn next_pos(&self, pos: &mut [usize; N], dims: &[bool; N]) -> bool {
-        let mut result = false;
-        for (pos, idx, size) in izip!(pos, *dims, self.size) {
-            if idx {
-                *pos += 1;
-                if *pos < size {
-                    result = true;
-                    break;
-                }
-                *pos = 0;
+    /// Lattice position for a flat array index.
+    fn position(&self, index: usize) -> [usize; N] {
+        let mut remainder = index;
+        self.size.map(|size| {
+            let pos = remainder % size;
+            remainder /= size;
+            pos
+        })
+    }
+
+    /// Size of the lattice.
+    pub fn size(&self) -> [usize; N] {
+        self.size
+    }
+
+    /// Squared speed of sound of the lattice.
+    pub fn sound_speed_squared(&self) -> f32 {
+        self.sound_speed_squared
+    }
+
+    /// Iterator over all lattice positions.
+    pub fn positions(&self) -> PositionIter<N> {
+        PositionIter::new(self.size)
+    }
+
+    /// Iterator over all lattice positions and read-only views of their cells.
+    pub fn iter_cells(&self) -> impl Iterator<Item = ([usize; N], CellView<N, B>)> + '_ {
+        self.positions().map(|pos| {
+            let index = self.index(&pos);
+            (
+                pos,
+                CellView {
+                    algorithm_values: self.algorithm_values_at(index),
+                    object_id: self.object[index],
+                    solid: self.solid(index),
+                },
+            )
+        })
+    }
+
+    /// Values of a function at the lattice positions of an axis-aligned plane of two
+    /// dimensions, through a lattice position (e.g. a slice of a field of a three dimensional
+    /// lattice), or `None` at object lattice positions.
+    pub fn slice(
+        &self,
+        plane_dims: [usize; 2],
+        pos: &[usize; N],
+        value: impl Fn(&[usize; N]) -> f32,
+    ) -> Slice {
+        let size = plane_dims.map(|d| self.size[d]);
+        let mut dims = [false; N];
+        for d in plane_dims {
+            dims[d] = true;
+        }
+        let mut values = vec![None; size[0] * size[1]];
+        for pos in PositionIter::new_slice(self.size, dims, *pos) {
+            if !self.object(&pos) {
+                values[size[0] * pos[plane_dims[1]] + pos[plane_dims[0]]] = Some(value(&pos));
             }
         }
-        result
+        Slice::new(size, values)
+    }
+
+    /// Parallel iterator over all lattice positions and read-only views of their cells.
+    #[cfg(feature = "rayon")]
+    pub fn par_iter_cells(
+        &self,
+    ) -> impl IndexedParallelIterator<Item = ([usize; N], CellView<N, B>)> + '_ {
+        self.object.par_iter().enumerate().map(|(index, object)| {
+            (
+                self.position(index),
+                CellView {
+                    algorithm_values: self.algorithm_values_at(index),
+                    object_id: *object,
+                    solid: self.solid(index),
+                },
+            )
+        })
+    }
+
+    /// Apply a function to all lattice positions and views of their cells in parallel, giving
+    /// the results in the order of the lattice positions.
+    #[cfg(feature = "rayon")]
+    pub fn par_apply<T: Send>(
+        &self,
+        f: impl Fn(&[usize; N], &CellView<N, B>) -> T + Sync + Send,
+    ) -> Vec<T> {
+        self.par_iter_cells()
+            .map(|(pos, cell)| f(&pos, &cell))
+            .collect()
     }
 
     /// Density at lattice position.
     pub fn density(&self, pos: &[usize; N]) -> f32 {
-        self.algorithm_values[self.index(pos)].density
+        self.algorithm_values.density[self.index(pos)]
     }
 
     /// Velocity vector at lattice position.
     pub fn velocity_vector(&self, pos: &[usize; N]) -> [f32; N] {
-        self.algorithm_values[self.index(pos)].velocity_vector
+        self.algorithm_values.velocity_vector(self.index(pos))
     }
 
     /// Velocity at lattice position.
     pub fn velocity(&self, pos: &[usize; N]) -> f32 {
-        let u = &self.algorithm_values[self.index(pos)].velocity_vector;
-        u.dot_product(u).sqrt()
+        self.velocity_vector(pos).norm()
     }
 
     /// [Vorticity](https://en.wikipedia.org/wiki/Vorticity) at lattice position.
@@ -167,15 +694,732 @@ impl<const N: usize, const B: usize> Lbgk<N, B> {
         }
     }
 
-    /// Object at lattice position.
+    /// [Pressure](https://en.wikipedia.org/wiki/Pressure) at lattice position, from the
+    /// isothermal equation of state.
+    pub fn pressure(&self, pos: &[usize; N]) -> f32 {
+        self.density(pos) * self.sound_speed_squared
+    }
+
+    /// Viscous [stress](https://en.wikipedia.org/wiki/Viscous_stress_tensor) at lattice
+    /// position, from the non-equilibrium part of the distributions (e.g. the wall shear stress
+    /// next to a wall).
+    pub fn viscous_stress(&self, pos: &[usize; N], relaxation_time: f32) -> Matrix<N, N> {
+        let algorithm_values = self.algorithm_values_at(self.index(pos));
+        let equilibrium_distributions = Self::equilibrium_distributions(
+            &self.lattice_parameters,
+            self.sound_speed_squared,
+            algorithm_values.density,
+            &algorithm_values.velocity_vector,
+        );
+        let prefactor = 0.5 / relaxation_time - 1.0;
+        let mut result = Matrix::zeros();
+        for (lattice_parameters, f, f_eq) in izip!(
+            &self.lattice_parameters,
+            algorithm_values.distributions,
+            equilibrium_distributions
+        ) {
+            let c = lattice_parameters.lattice_vector.map(|val| val as f32);
+            for (i, j) in iproduct!(0..N, 0..N) {
+                result.0[i][j] += prefactor * (f - f_eq) * c[i] * c[j];
+            }
+        }
+        result
+    }
+
+    /// Velocity [divergence](https://en.wikipedia.org/wiki/Divergence) at lattice position, by
+    /// central differences (zero on the lattice edges).
+    pub fn divergence(&self, pos: &[usize; N]) -> f32 {
+        let mut result = 0.0;
+        if izip!(pos, self.size).all(|(pos, size)| (1..size - 1).contains(pos)) {
+            for d in 0..N {
+                let mut other_pos = *pos;
+                other_pos[d] = pos[d] + 1;
+                result += 0.5 * self.velocity_vector(&other_pos)[d];
+                other_pos[d] = pos[d] - 1;
+                result -= 0.5 * self.velocity_vector(&other_pos)[d];
+            }
+        }
+        result
+    }
+
+    /// Density [gradient](https://en.wikipedia.org/wiki/Gradient) at lattice position, by central
+    /// differences (zero on the lattice edges).
+    pub fn density_gradient(&self, pos: &[usize; N]) -> [f32; N] {
+        let mut result = [0.0; N];
+        if izip!(pos, self.size).all(|(pos, size)| (1..size - 1).contains(pos)) {
+            for (d, val) in result.iter_mut().enumerate() {
+                let mut other_pos = *pos;
+                other_pos[d] = pos[d] + 1;
+                *val += 0.5 * self.density(&other_pos);
+                other_pos[d] = pos[d] - 1;
+                *val -= 0.5 * self.density(&other_pos);
+            }
+        }
+        result
+    }
+
+    /// Velocity [gradient](https://en.wikipedia.org/wiki/Gradient) at lattice position, by
+    /// central differences (zero on the lattice edges).
+    ///
+    /// note: rows are of the velocity components and columns of the derivative dimensions,
+    /// i.e. ∂u_i/∂x_j
+    pub fn velocity_gradient(&self, pos: &[usize; N]) -> Matrix<N, N> {
+        let mut result = Matrix::zeros();
+        if izip!(pos, self.size).all(|(pos, size)| (1..size - 1).contains(pos)) {
+            for j in 0..N {
+                let mut other_pos = *pos;
+                other_pos[j] = pos[j] + 1;
+                let next = self.velocity_vector(&other_pos);
+                other_pos[j] = pos[j] - 1;
+                let previous = self.velocity_vector(&other_pos);
+                for (row, next, previous) in izip!(&mut result.0, next, previous) {
+                    row[j] = 0.5 * (next - previous);
+                }
+            }
+        }
+        result
+    }
+
+    /// Strain rate (symmetric) and rotation rate (antisymmetric) parts of the velocity gradient
+    /// at lattice position.
+    fn strain_and_rotation_rates(&self, pos: &[usize; N]) -> (Matrix<N, N>, Matrix<N, N>) {
+        let gradient = self.velocity_gradient(pos);
+        let transpose = gradient.transpose();
+        let (mut strain_rate, mut rotation_rate) = (Matrix::zeros(), Matrix::zeros());
+        for (i, j) in iproduct!(0..N, 0..N) {
+            strain_rate.0[i][j] = 0.5 * (gradient.0[i][j] + transpose.0[i][j]);
+            rotation_rate.0[i][j] = 0.5 * (gradient.0[i][j] - transpose.0[i][j]);
+        }
+        (strain_rate, rotation_rate)
+    }
+
+    /// [Q-criterion](https://en.wikipedia.org/wiki/Vortex#Vortex_identification) at lattice
+    /// position, Q = (|Ω|² - |S|²) / 2 of the rotation rate Ω and the strain rate S.
+    pub fn q_criterion(&self, pos: &[usize; N]) -> f32 {
+        let (strain_rate, rotation_rate) = self.strain_and_rotation_rates(pos);
+        let norm_squared =
+            |matrix: &Matrix<N, N>| matrix.0.iter().map(|row| row.norm_squared()).sum::<f32>();
+        0.5 * (norm_squared(&rotation_rate) - norm_squared(&strain_rate))
+    }
+
+    /// λ₂ vortex criterion at lattice position
+    /// ([reference](https://doi.org/10.1017/S0022112095000462)), the middle eigenvalue of
+    /// S² + Ω² of the strain rate S and the rotation rate Ω (negative within a vortex).
+    ///
+    /// note: for two dimensional lattices the smaller of the two eigenvalues is used
+    pub fn lambda2(&self, pos: &[usize; N]) -> f32 {
+        let (strain_rate, rotation_rate) = self.strain_and_rotation_rates(pos);
+        let s2 = strain_rate.multiply(&strain_rate);
+        let o2 = rotation_rate.multiply(&rotation_rate);
+        let m = |i: usize, j: usize| s2.0[i][j] + o2.0[i][j];
+        match N {
+            2 => {
+                // note: eigenvalues of a symmetric 2x2 matrix
+                let (mean, difference) = (0.5 * (m(0, 0) + m(1, 1)), 0.5 * (m(0, 0) - m(1, 1)));
+                mean - difference.hypot(m(0, 1))
+            }
+            3 => {
+                // note: trigonometric solution for the eigenvalues of a symmetric 3x3 matrix
+                // (https://en.wikipedia.org/wiki/Eigenvalue_algorithm#3%C3%973_matrices)
+                let p1 = m(0, 1).powi(2) + m(0, 2).powi(2) + m(1, 2).powi(2);
+                let q = (m(0, 0) + m(1, 1) + m(2, 2)) / 3.0;
+                let p2 = (0..3).map(|i| (m(i, i) - q).powi(2)).sum::<f32>() + 2.0 * p1;
+                if p2 == 0.0 {
+                    return q;
+                }
+                let p = (p2 / 6.0).sqrt();
+                let b = |i: usize, j: usize| {
+                    (m(i, j)
+                        - match i == j {
+                            true => q,
+                            false => 0.0,
+                        })
+                        / p
+                };
+                let r = 0.5
+                    * (b(0, 0) * (b(1, 1) * b(2, 2) - b(1, 2) * b(2, 1))
+                        - b(0, 1) * (b(1, 0) * b(2, 2) - b(1, 2) * b(2, 0))
+                        + b(0, 2) * (b(1, 0) * b(2, 1) - b(1, 1) * b(2, 0)));
+                let phi = r.clamp(-1.0, 1.0).acos() / 3.0;
+                let largest = q + 2.0 * p * phi.cos();
+                let smallest = q + 2.0 * p * (phi + 2.0 * std::f32::consts::FRAC_PI_3).cos();
+                3.0 * q - largest - smallest
+            }
+            _ => panic!(),
+        }
+    }
+
+    /// Whether a solid object is at a lattice position (by index).
+    fn solid(&self, index: usize) -> bool {
+        match self.object[index] {
+            0 => false,
+            id => !matches!(self.object_surface(id), Surface::Porous(_)),
+        }
+    }
+
+    /// Algorithm values at a lattice position (by index), wherever its distributions are
+    /// stored.
+    fn algorithm_values_at(&self, index: usize) -> AlgorithmValues<N, B> {
+        match self.reversed {
+            None => self.algorithm_values.get(index),
+            Some(_) => AlgorithmValues {
+                distributions: self.distributions_at(index),
+                ..self.algorithm_values.get(index)
+            },
+        }
+    }
+
+    /// Set the algorithm values at a lattice position (by index), wherever its distributions
+    /// are stored.
+    fn set_algorithm_values_at(&mut self, index: usize, algorithm_values: &AlgorithmValues<N, B>) {
+        match self.reversed {
+            None => self.algorithm_values.set(index, algorithm_values),
+            Some(_) => {
+                let stored = AlgorithmValues {
+                    distributions: self.algorithm_values.distributions(index),
+                    ..*algorithm_values
+                };
+                self.algorithm_values.set(index, &stored);
+                self.set_distributions_at(index, &algorithm_values.distributions);
+            }
+        }
+    }
+
+    /// Distributions at a lattice position (by index), wherever they are stored.
+    fn distributions_at(&self, index: usize) -> [f32; B] {
+        match self.reversed {
+            None => self.algorithm_values.distributions(index),
+            Some(_) => std::array::from_fn(|i| {
+                let (i, index) = self.distribution_location(index, i, self.reversed);
+                self.algorithm_values.distributions[i][index]
+            }),
+        }
+    }
+
+    /// Set the distributions at a lattice position (by index), wherever they are stored.
+    fn set_distributions_at(&mut self, index: usize, distributions: &[f32; B]) {
+        match self.reversed {
+            None => self
+                .algorithm_values
+                .set_distributions(index, distributions),
+            Some(_) => {
+                for (i, f) in distributions.iter().enumerate() {
+                    let (i, index) = self.distribution_location(index, i, self.reversed);
+                    self.algorithm_values.distributions[i][index] = *f;
+                }
+            }
+        }
+    }
+
+    /// Where the distribution of a lattice position (by index) along a lattice vector (by
+    /// index) is stored, as the lattice vector and lattice position (by index), if reversed
+    /// with whether each dimension is periodic.
+    ///
+    /// note: reversed distributions streamed between fluid lattice positions are stored along
+    /// the opposite lattice vector of the lattice position they stream from, and the others
+    /// (e.g. reflected at a face or object) where they stream to, so the distributions of each
+    /// lattice position are read from and written to the same storage
+    fn distribution_location(
+        &self,
+        index: usize,
+        i: usize,
+        reversed: Option<[bool; N]>,
+    ) -> (usize, usize) {
+        let Some(periodic) = reversed else {
+            return (i, index);
+        };
+        if self.solid(index) {
+            return (i, index);
+        }
+
+        let mut source_pos = [0; N];
+        for (source_pos, pos, c, size, periodic) in izip!(
+            &mut source_pos,
+            self.position(index),
+            self.lattice_parameters[i].lattice_vector,
+            self.size,
+            periodic
+        ) {
+            *source_pos = match pos as isize - c {
+                val if val >= 0 && val < size as isize => val as usize,
+                val if periodic => val.rem_euclid(size as isize) as usize,
+                _ => return (i, index),
+            };
+        }
+        let source_index = self.index(&source_pos);
+        match self.solid(source_index) {
+            true => (i, index),
+            false => (self.opposite_lattice_vectors[i], source_index),
+        }
+    }
+
+    /// Store the distributions of each lattice position along its own lattice vectors, if
+    /// reversed (e.g. before the objects or boundary conditions change where they stream).
+    fn unreverse_distributions(&mut self) {
+        if self.reversed.is_none() {
+            return;
+        }
+
+        for (index, i) in iproduct!(0..self.object.len(), 0..B) {
+            // note: the distributions stored at each other's location are swapped once
+            let (other_i, other_index) = self.distribution_location(index, i, self.reversed);
+            if (other_index, other_i) > (index, i) {
+                let distributions = &mut self.algorithm_values.distributions;
+                let f = distributions[i][index];
+                distributions[i][index] = distributions[other_i][other_index];
+                distributions[other_i][other_index] = f;
+            }
+        }
+        self.reversed = None;
+    }
+
+    /// Surface of the objects of an ID.
+    pub fn object_surface(&self, id: u16) -> Surface {
+        self.object_surfaces
+            .get(id as usize)
+            .copied()
+            .unwrap_or(Surface::NoSlip)
+    }
+
+    /// Set the surface of the objects of an ID.
+    pub fn set_object_surface(&mut self, id: u16, surface: Surface) {
+        self.unreverse_distributions();
+        self.reserve_object_id(id);
+        self.object_surfaces[id as usize] = surface;
+        self.object_boundary = None;
+    }
+
+    /// Object at lattice position, i.e. a solid object (as a porous object holds fluid).
     pub fn object(&self, pos: &[usize; N]) -> bool {
+        self.solid(self.index(pos))
+    }
+
+    /// ID of the object at lattice position, 0 for fluid.
+    pub fn object_id(&self, pos: &[usize; N]) -> u16 {
         self.object[self.index(pos)]
     }
 
-    /// Set object at lattice position.
+    /// Set object (of the default object ID) at lattice position.
     pub fn set_object(&mut self, pos: &[usize; N], val: bool) {
+        self.set_object_id(
+            pos,
+            match val {
+                true => DEFAULT_OBJECT_ID,
+                false => 0,
+            },
+        );
+    }
+
+    /// Set the ID of the object at lattice position, 0 for fluid (e.g. to account for the force
+    /// on each object separately).
+    ///
+    /// A lattice position released by a solid object is refilled from the neighbouring fluid,
+    /// and the velocity of the surface is reset where the ID changes.
+    pub fn set_object_id(&mut self, pos: &[usize; N], id: u16) {
+        let index = self.index(pos);
+        let solid = self.solid(index);
+        let refill = solid && id == 0;
+        if self.object[index] != id {
+            if let Some(wall_velocity) = self.wall_velocities.get_mut(index) {
+                *wall_velocity = [0.0; N];
+            }
+            self.unreverse_distributions();
+        }
+        self.object[index] = id;
+        self.reserve_object_id(id);
+        if self.solid(index) != solid {
+            self.object_boundary = None;
+        }
+        if refill {
+            self.refill(pos);
+        }
+    }
+
+    /// Velocity of the surface of a solid object at lattice position, given by bounce-back to
+    /// the fluid (e.g. of a belt or a rotating cylinder).
+    pub fn wall_velocity(&self, pos: &[usize; N]) -> [f32; N] {
+        self.wall_velocities
+            .get(self.index(pos))
+            .copied()
+            .unwrap_or([0.0; N])
+    }
+
+    /// Set the velocity of the surface of a solid object at lattice position.
+    pub fn set_wall_velocity(&mut self, pos: &[usize; N], velocity_vector: &[f32; N]) {
+        let index = self.index(pos);
+        if self.wall_velocities.is_empty() {
+            if *velocity_vector == [0.0; N] {
+                return;
+            }
+            self.wall_velocities = vec![[0.0; N]; self.object.len()];
+        }
+        self.wall_velocities[index] = *velocity_vector;
+    }
+
+    /// Reserve the values of each object ID up to an ID.
+    fn reserve_object_id(&mut self, id: u16) {
+        let len = id as usize + 1;
+        if self.object_forces.len() < len {
+            self.object_forces.resize(len, ([0.0; N], [[0.0; N]; N]));
+            self.object_surfaces.resize(len, Surface::NoSlip);
+        }
+    }
+
+    /// Force density at lattice position.
+    pub fn force_density(&self, pos: &[usize; N]) -> [f32; N] {
+        self.algorithm_values.force_density(self.index(pos))
+    }
+
+    /// Add to the force density (e.g. a body force) at lattice position.
+    pub fn add_force_density(&mut self, pos: &[usize; N], force_density: &[f32; N]) {
+        let index = self.index(pos);
+        for (plane, force_density) in izip!(&mut self.algorithm_values.force_density, force_density)
+        {
+            plane[index] += force_density;
+        }
+    }
+
+    /// Add to the velocity at a fluid lattice position, shifting the distributions by the change
+    /// in their equilibrium (i.e. keeping their non-equilibrium part).
+    pub fn add_velocity(&mut self, pos: &[usize; N], velocity_vector: &[f32; N]) {
+        let index = self.index(pos);
+        if self.solid(index) {
+            return;
+        }
+
+        let mut algorithm_values = self.algorithm_values_at(index);
+        let mut new_velocity_vector = algorithm_values.velocity_vector;
+        for (val, velocity) in izip!(&mut new_velocity_vector, velocity_vector) {
+            *val += velocity;
+        }
+        let equilibrium_distributions = Self::equilibrium_distributions(
+            &self.lattice_parameters,
+            self.sound_speed_squared,
+            algorithm_values.density,
+            &algorithm_values.velocity_vector,
+        );
+        let new_equilibrium_distributions = Self::equilibrium_distributions(
+            &self.lattice_parameters,
+            self.sound_speed_squared,
+            algorithm_values.density,
+            &new_velocity_vector,
+        );
+        for (val, equilibrium, new_equilibrium) in izip!(
+            &mut algorithm_values.distributions,
+            equilibrium_distributions,
+            new_equilibrium_distributions
+        ) {
+            *val += new_equilibrium - equilibrium;
+        }
+        algorithm_values.velocity_vector = new_velocity_vector;
+        self.set_algorithm_values_at(index, &algorithm_values);
+    }
+
+    /// Add to the density at a fluid lattice position, shifting the distributions by the change
+    /// in their equilibrium (i.e. keeping their non-equilibrium part).
+    pub fn add_density(&mut self, pos: &[usize; N], density: f32) {
+        let index = self.index(pos);
+        if self.solid(index) {
+            return;
+        }
+
+        let mut algorithm_values = self.algorithm_values_at(index);
+        let new_density = algorithm_values.density + density;
+        let equilibrium_distributions = Self::equilibrium_distributions(
+            &self.lattice_parameters,
+            self.sound_speed_squared,
+            algorithm_values.density,
+            &algorithm_values.velocity_vector,
+        );
+        let new_equilibrium_distributions = Self::equilibrium_distributions(
+            &self.lattice_parameters,
+            self.sound_speed_squared,
+            new_density,
+            &algorithm_values.velocity_vector,
+        );
+        for (val, equilibrium, new_equilibrium) in izip!(
+            &mut algorithm_values.distributions,
+            equilibrium_distributions,
+            new_equilibrium_distributions
+        ) {
+            *val += new_equilibrium - equilibrium;
+        }
+        algorithm_values.density = new_density;
+        self.set_algorithm_values_at(index, &algorithm_values);
+    }
+
+    /// Clear the force densities at all lattice positions.
+    pub fn clear_force_densities(&mut self) {
+        for plane in &mut self.algorithm_values.force_density {
+            plane.fill(0.0);
+        }
+    }
+
+    /// Momentum exchange force on the objects during the last iteration.
+    pub fn force(&self) -> [f32; N] {
+        self.force
+    }
+
+    /// Momentum exchange force on the objects of an ID during the last iteration.
+    pub fn object_force(&self, id: u16) -> [f32; N] {
+        self.object_forces
+            .get(id as usize)
+            .map_or([0.0; N], |(force, _)| *force)
+    }
+
+    /// Refill lattice position with the equilibrium distributions for the average density and
+    /// velocity vector of the neighbouring fluid.
+    fn refill(&mut self, pos: &[usize; N]) {
         let index = self.index(pos);
-        self.object[index] = val;
+
+        let (mut density, mut velocity_vector, mut count) = (0.0, [0.0; N], 0);
+        for lattice_parameters in &self.lattice_parameters {
+            let mut other_pos = [0; N];
+            let inside = izip!(
+                &mut other_pos,
+                pos,
+                lattice_parameters.lattice_vector,
+                self.size
+            )
+            .all(|(other_pos, pos, c, size)| {
+                let val = *pos as isize + c;
+                *other_pos = val.max(0) as usize;
+                (0..size as isize).contains(&val)
+            });
+            if !inside {
+                continue;
+            }
+
+            let other_index = self.index(&other_pos);
+            if other_index == index || self.solid(other_index) {
+                continue;
+            }
+
+            density += self.algorithm_values.density[other_index];
+            for (u, other_u) in izip!(
+                &mut velocity_vector,
+                self.algorithm_values.velocity_vector(other_index)
+            ) {
+                *u += other_u;
+            }
+            count += 1;
+        }
+
+        let mut algorithm_values = self.source_algorithm_values;
+        if count > 0 {
+            algorithm_values.density = density / count as f32;
+            algorithm_values.velocity_vector = velocity_vector.map(|u| u / count as f32);
+            algorithm_values.distributions = Self::equilibrium_distributions(
+                &self.lattice_parameters,
+                self.sound_speed_squared,
+                algorithm_values.density,
+                &algorithm_values.velocity_vector,
+            );
+        }
+        self.set_algorithm_values_at(index, &algorithm_values);
+    }
+
+    /// Set the density and velocity of the source algorithm values (e.g. the inflow), with
+    /// equilibrium distributions.
+    pub fn set_source(&mut self, density: f32, velocity_vector: &[f32; N]) {
+        let source_algorithm_values = &mut self.source_algorithm_values;
+        source_algorithm_values.density = density;
+        source_algorithm_values.velocity_vector = *velocity_vector;
+        source_algorithm_values.distributions = Self::equilibrium_distributions(
+            &self.lattice_parameters,
+            self.sound_speed_squared,
+            density,
+            velocity_vector,
+        );
+    }
+
+    /// Mass of the fluid, and the number of fluid lattice positions.
+    fn fluid_mass(&self) -> (f64, usize) {
+        (0..self.object.len())
+            .filter(|index| !self.solid(*index))
+            .fold((0.0, 0), |(mass, count), index| {
+                (
+                    mass + self.algorithm_values.density[index] as f64,
+                    count + 1,
+                )
+            })
+    }
+
+    /// Drift of the mass of the fluid from that of the source density (e.g. by the boundary
+    /// schemes).
+    pub fn mass_drift(&self) -> f32 {
+        let (mass, count) = self.fluid_mass();
+        (mass - self.source_algorithm_values.density as f64 * count as f64) as f32
+    }
+
+    /// Correct the drift of the mass of the fluid, redistributing the deficit or surplus in
+    /// proportion to the density of each fluid lattice position (keeping the velocity).
+    pub fn correct_mass(&mut self) {
+        let (mass, count) = self.fluid_mass();
+        if mass <= 0.0 {
+            return;
+        }
+        let scale = (self.source_algorithm_values.density as f64 * count as f64 / mass) as f32;
+        for index in 0..self.object.len() {
+            if self.solid(index) {
+                continue;
+            }
+            self.algorithm_values.density[index] *= scale;
+            for plane in &mut self.algorithm_values.distributions {
+                plane[index] *= scale;
+            }
+        }
+    }
+
+    /// Set the period (in iterations) of correcting the drift of the mass of the fluid, if any
+    /// (e.g. to keep long runs at the source density).
+    pub fn set_mass_correction_period(&mut self, period: Option<usize>) {
+        self.mass_correction_period = period;
+        self.iterations_since_mass_correction = 0;
+    }
+
+    /// Distributions at lattice position.
+    pub fn distributions(&self, pos: &[usize; N]) -> [f32; B] {
+        self.distributions_at(self.index(pos))
+    }
+
+    /// Set the distributions at a fluid lattice position (e.g. by a custom boundary condition),
+    /// with the density and velocity from them.
+    pub fn set_distributions(&mut self, pos: &[usize; N], distributions: &[f32; B]) {
+        let index = self.index(pos);
+        if self.solid(index) {
+            return;
+        }
+
+        let mut algorithm_values = self.algorithm_values_at(index);
+        algorithm_values.distributions = *distributions;
+        algorithm_values.density = distributions.iter().sum();
+        algorithm_values.velocity_vector.fill(0.0);
+        if algorithm_values.density > 0.0 {
+            for (lattice_parameters, f) in izip!(&self.lattice_parameters, distributions) {
+                for (u, c) in izip!(
+                    &mut algorithm_values.velocity_vector,
+                    lattice_parameters.lattice_vector
+                ) {
+                    *u += c as f32 * f;
+                }
+            }
+            // note: half the force density contributes to the momentum
+            for (u, force_density) in izip!(
+                &mut algorithm_values.velocity_vector,
+                algorithm_values.force_density
+            ) {
+                *u = (*u + 0.5 * force_density) / algorithm_values.density;
+            }
+        }
+        self.set_algorithm_values_at(index, &algorithm_values);
+    }
+
+    /// Set the density and velocity at a fluid lattice position, with equilibrium distributions
+    /// (e.g. for an initial condition).
+    pub fn set_equilibrium(&mut self, pos: &[usize; N], density: f32, velocity_vector: &[f32; N]) {
+        let index = self.index(pos);
+        if self.solid(index) {
+            return;
+        }
+
+        self.algorithm_values.density[index] = density;
+        for (plane, u) in izip!(&mut self.algorithm_values.velocity_vector, velocity_vector) {
+            plane[index] = *u;
+        }
+        let distributions = Self::equilibrium_distributions(
+            &self.lattice_parameters,
+            self.sound_speed_squared,
+            density,
+            velocity_vector,
+        );
+        self.set_distributions_at(index, &distributions);
+    }
+
+    /// Set the inflow profile giving the velocity vector and density at each inflow lattice
+    /// position, or `None` for the source values at all inflow lattice positions.
+    pub fn set_inflow_profile(&mut self, inflow_profile: Option<InflowProfile<N>>) {
+        self.inflow_profile = inflow_profile;
+    }
+
+    /// Algorithm values of the inflow at a lattice position, from the inflow profile if set.
+    fn inflow_algorithm_values(&self, pos: &[usize; N]) -> AlgorithmValues<N, B> {
+        let mut result = self.source_algorithm_values;
+        if let Some(inflow_profile) = &self.inflow_profile {
+            let (velocity_vector, density) = inflow_profile(pos);
+            result.density = density;
+            result.velocity_vector = velocity_vector;
+            result.distributions = Self::equilibrium_distributions(
+                &self.lattice_parameters,
+                self.sound_speed_squared,
+                density,
+                &velocity_vector,
+            );
+        }
+        result
+    }
+
+    /// Set boundary schemes of each dimension.
+    pub fn set_boundary_schemes(&mut self, boundary_schemes: &[[BoundaryScheme; 2]; N]) {
+        self.unreverse_distributions();
+        self.boundary_conditions = boundary_schemes.map(|boundary_schemes| {
+            boundary_schemes.map(|boundary_scheme| boundary_scheme.boundary_condition())
+        });
+    }
+
+    /// Set the boundary condition of a face, the start (side 0) or end (side 1) of a dimension
+    /// (e.g. a custom boundary condition).
+    pub fn set_boundary_condition(
+        &mut self,
+        dim: usize,
+        side: usize,
+        boundary_condition: Arc<dyn BoundaryCondition<N, B>>,
+    ) {
+        self.unreverse_distributions();
+        self.boundary_conditions[dim][side] = boundary_condition;
+    }
+
+    /// Lattice positions of a face, the start (side 0) or end (side 1) of a dimension.
+    pub fn face_positions(&self, dim: usize, side: usize) -> PositionIter<N> {
+        let (mut start, mut dims) = ([0; N], [true; N]);
+        (start[dim], dims[dim]) = (side * (self.size[dim] - 1), false);
+        PositionIter::new_slice(self.size, dims, start)
+    }
+
+    /// Collision operator.
+    pub fn collision_operator(&self) -> &Arc<dyn CollisionOperator<N, B>> {
+        &self.collision_operator
+    }
+
+    /// Set the collision operator (the BGK operator by default).
+    pub fn set_collision_operator(&mut self, collision_operator: Arc<dyn CollisionOperator<N, B>>) {
+        self.collision_operator = collision_operator;
+    }
+
+    /// Set the treatment of the corners where different boundary schemes meet.
+    pub fn set_corner_scheme(&mut self, corner_scheme: CornerScheme) {
+        self.corner_scheme = corner_scheme;
+    }
+
+    /// Set the treatment of the distributions leaving through both boundaries at the corners
+    /// where specular reflection boundaries meet.
+    pub fn set_corner_reflection(&mut self, corner_reflection: CornerReflection) {
+        self.corner_reflection = corner_reflection;
+    }
+
+    /// Set the strategy of traversing the lattice to stream the distributions.
+    pub fn set_streaming_scheme(&mut self, streaming_scheme: StreamingScheme) {
+        self.unreverse_distributions();
+        self.streaming_scheme = streaming_scheme;
+    }
+
+    /// Reset the algorithm values at all lattice positions to the source algorithm values.
+    pub fn reset(&mut self) {
+        self.reversed = None;
+        self.algorithm_values.fill(&self.source_algorithm_values);
+        self.force = [0.0; N];
+        self.force_moment = [[0.0; N]; N];
+        self.object_forces.fill(([0.0; N], [[0.0; N]; N]));
     }
 
     /// Calculate relaxation time.
@@ -190,249 +1434,734 @@ impl<const N: usize, const B: usize> Lbgk<N, B> {
 
     /// Perform iteration.
     pub fn iterate(&mut self, relaxation_time: f32) {
-        self.collision_step(relaxation_time);
-        self.streaming_step();
-        self.calculate_derived();
-        self.update_inflows_and_outflows();
+        self.force = [0.0; N];
+        self.force_moment = [[0.0; N]; N];
+        self.object_forces.fill(([0.0; N], [[0.0; N]; N]));
+        profiling::measure("collision and streaming", || {
+            self.collision_and_streaming_step(relaxation_time)
+        });
+        profiling::measure("derived", || self.calculate_derived());
+        self.update_boundaries();
+        self.update_corners();
+
+        if let Some(period) = self.mass_correction_period {
+            self.iterations_since_mass_correction += 1;
+            if self.iterations_since_mass_correction >= period {
+                self.correct_mass();
+                self.iterations_since_mass_correction = 0;
+            }
+        }
     }
 
-    /// Perform collision step of iteration.
-    fn collision_step(&mut self, relaxation_time: f32) {
-        let (mut pos, dims) = ([0; N], [true; N]);
-        loop {
-            let index = self.index(&pos);
+    /// Collision distributions at a fluid lattice position, partially bounced back at a porous
+    /// object.
+    fn collide(&mut self, pos: &[usize; N], index: usize, relaxation_time: f32) -> [f32; B] {
+        let mut collision_distributions = self.collision_operator.collide(
+            &CollisionValues::new(self, self.algorithm_values_at(index)),
+            relaxation_time,
+        );
+        let id = self.object[index];
+        if let Surface::Porous(permeability) = self.object_surface(id) {
+            self.partially_bounce_back(
+                pos,
+                id,
+                permeability,
+                relaxation_time,
+                &mut collision_distributions,
+            );
+        }
+        collision_distributions
+    }
+
+    /// Partially bounce back the collision distributions at a lattice position of a porous
+    /// object, by the solid fraction n_s of its permeability K, i.e. K = ν (1 - n_s) / (2 n_s),
+    /// adding the momentum removed to the force on the object.
+    fn partially_bounce_back(
+        &mut self,
+        pos: &[usize; N],
+        id: u16,
+        permeability: f32,
+        relaxation_time: f32,
+        collision_distributions: &mut [f32; B],
+    ) {
+        let viscosity = self.sound_speed_squared * (relaxation_time - 0.5);
+        let solid_fraction = viscosity / (2.0 * permeability + viscosity);
+
+        let reflected = self
+            .opposite_lattice_vectors
+            .map(|i| collision_distributions[i]);
+        let mut momentum = [0.0; N];
+        for (f_c, reflected, lattice_parameters) in izip!(
+            collision_distributions.iter_mut(),
+            reflected,
+            &self.lattice_parameters
+        ) {
+            let val = (1.0 - solid_fraction) * *f_c + solid_fraction * reflected;
+            for (momentum, c) in izip!(&mut momentum, lattice_parameters.lattice_vector) {
+                *momentum += c as f32 * (*f_c - val);
+            }
+            *f_c = val;
+        }
+        self.add_object_force(id, &pos.map(|val| val as f32), &momentum);
+    }
 
-            if self.object[index] {
-                match self.next_pos(&mut pos, &dims) {
-                    true => continue,
-                    false => break,
+    /// Perform the collision and streaming steps of iteration.
+    fn collision_and_streaming_step(&mut self, relaxation_time: f32) {
+        let streaming = self
+            .boundary_conditions
+            .each_ref()
+            .map(|boundary_conditions| {
+                boundary_conditions
+                    .each_ref()
+                    .map(|boundary_condition| boundary_condition.streaming(self))
+            });
+
+        match self.streaming_scheme {
+            StreamingScheme::Push => self.push_streaming(relaxation_time, &streaming),
+            StreamingScheme::Pull => {
+                for pos in self.positions() {
+                    let index = self.index(&pos);
+                    if !self.solid(index) {
+                        let collision_distributions = self.collide(&pos, index, relaxation_time);
+                        for (plane, f_c) in
+                            izip!(&mut self.next_distributions, collision_distributions)
+                        {
+                            plane[index] = f_c;
+                        }
+                    }
                 }
+                self.pull_streaming(&streaming);
             }
+            StreamingScheme::Aa => self.aa_streaming(relaxation_time, &streaming),
+        }
+        self.split_corner_reflections(&streaming);
+    }
 
-            // calculate equilibrium distribution
-            let algorithm_values = self.algorithm_values[index];
-            let equilibrium_distributions = Self::equilibrium_distributions(
-                &self.lattice_parameters,
-                self.sound_speed_squared,
-                algorithm_values.density,
-                &algorithm_values.velocity_vector,
-            );
+    /// Split the distributions reflected across both boundaries at the corners where specular
+    /// reflection boundaries meet, by the corner reflection.
+    fn split_corner_reflections(&mut self, streaming: &[[Streaming<N>; 2]; N]) {
+        if self.corner_reflection != CornerReflection::Split {
+            return;
+        }
 
-            // calculate collision distribution
-            let algorithm_values = &mut self.algorithm_values[index];
-            for (f_c, f, f_eq) in izip!(
-                &mut algorithm_values.collision_distributions,
-                algorithm_values.distributions,
-                equilibrium_distributions
-            ) {
-                *f_c = f - (f - f_eq) / relaxation_time;
+        for (i, j, side_i, side_j) in iproduct!(0..N, 0..N, 0..2, 0..2) {
+            if i >= j
+                || streaming[i][side_i] != Streaming::SpecularReflection
+                || streaming[j][side_j] != Streaming::SpecularReflection
+            {
+                continue;
             }
 
-            if !self.next_pos(&mut pos, &dims) {
-                break;
+            // note: the distributions reflected across both boundaries point inwards from each
+            let inwards = [side_i, side_j].map(|side| match side {
+                0 => 1,
+                _ => -1,
+            });
+            let reflected = (0..B)
+                .filter(|k| {
+                    let c = self.lattice_parameters[*k].lattice_vector;
+                    [c[i], c[j]] == inwards
+                })
+                .map(|k| {
+                    let mirrored = [i, j].map(|d| {
+                        let mut c = self.lattice_parameters[k].lattice_vector;
+                        c[d] = -c[d];
+                        self.lattice_parameters
+                            .iter()
+                            .position(|lattice_parameters| lattice_parameters.lattice_vector == c)
+                            .unwrap()
+                    });
+                    (k, mirrored)
+                })
+                .collect::<Vec<_>>();
+
+            let (mut start, mut dims) = ([0; N], [true; N]);
+            (start[i], dims[i]) = (side_i * (self.size[i] - 1), false);
+            (start[j], dims[j]) = (side_j * (self.size[j] - 1), false);
+            for pos in PositionIter::new_slice(self.size, dims, start) {
+                let index = self.index(&pos);
+                if self.solid(index) {
+                    continue;
+                }
+                let mut distributions = self.distributions_at(index);
+                for (k, mirrored) in &reflected {
+                    let f = std::mem::take(&mut distributions[*k]);
+                    for m in mirrored {
+                        distributions[*m] += 0.5 * f;
+                    }
+                }
+                self.set_distributions_at(index, &distributions);
             }
         }
     }
 
-    /// Perform streaming step of iteration.
-    fn streaming_step(&mut self) {
-        let (mut pos, dims) = ([0; N], [true; N]);
-        loop {
+    /// Collide and stream the distributions in one pass, by pushing the collision
+    /// distributions of each lattice position directly to their destinations in the other
+    /// buffer, then swapping the buffers.
+    ///
+    /// note: distributions not streamed to (e.g. entering through a face, to be set by the
+    /// boundary condition) are unchanged
+    fn push_streaming(&mut self, relaxation_time: f32, streaming: &[[Streaming<N>; 2]; N]) {
+        // note: only the distributions which may not be pushed to are copied to the other
+        // buffer, i.e. those of the faces left through and of the solid objects, and those of
+        // the fluid next to the objects (as reflections off a staircase surface may not reach
+        // every distribution)
+        let object_boundary = self
+            .object_boundary
+            .take()
+            .unwrap_or_else(|| self.object_boundary_indices());
+        let faces = iproduct!(0..N, 0..2)
+            .filter(|(dim, side)| streaming[*dim][*side] == Streaming::Leave)
+            .flat_map(|(dim, side)| self.face_positions(dim, side))
+            .map(|pos| self.index(&pos))
+            .collect::<Vec<_>>();
+        for (next_plane, plane) in izip!(
+            &mut self.next_distributions,
+            &self.algorithm_values.distributions
+        ) {
+            for index in faces.iter().chain(&object_boundary) {
+                next_plane[*index] = plane[*index];
+            }
+        }
+        self.object_boundary = Some(object_boundary);
+
+        for pos in self.positions() {
             let index = self.index(&pos);
 
-            if self.object[index] {
-                match self.next_pos(&mut pos, &dims) {
-                    true => continue,
-                    false => break,
+            if self.solid(index) {
+                continue;
+            }
+
+            let collision_distributions = self.collide(&pos, index, relaxation_time);
+            for (i, f_c) in collision_distributions.into_iter().enumerate() {
+                if let Some(destination) =
+                    self.streaming_destination(&pos, index, i, f_c, streaming)
+                {
+                    if let Some((id, arm, momentum)) =
+                        self.exchanged_momentum(&pos, i, f_c, &destination)
+                    {
+                        self.add_object_force(id, &arm, &momentum);
+                    }
+                    self.next_distributions[destination.i][destination.index] =
+                        destination.distribution;
                 }
             }
+        }
 
-            for (i, lattice_parameters) in self.lattice_parameters.iter().enumerate() {
-                let mut new_pos = [None; N];
-                let mut new_lattice_vector = lattice_parameters.lattice_vector;
-                let mut changed_lattice_vector = false;
-                let mut bounce_back = false;
-                for (new_pos, new_c, pos, c, size, boundary_schemes) in izip!(
-                    &mut new_pos,
-                    &mut new_lattice_vector,
-                    pos,
-                    lattice_parameters.lattice_vector,
-                    self.size,
-                    &self.boundary_schemes
-                ) {
-                    match pos as isize + c {
-                        val if val < 0 => match boundary_schemes[0] {
-                            BoundaryScheme::Periodic => *new_pos = Some(size - 1),
-                            BoundaryScheme::BounceBack => bounce_back = true, // handled below
-                            BoundaryScheme::SpecularReflection => {
-                                *new_pos = Some(pos);
-                                (*new_c, changed_lattice_vector) = (-c, true);
-                            }
-                            _ => {}
-                        },
-                        val if val >= size as isize => match boundary_schemes[1] {
-                            BoundaryScheme::Periodic => *new_pos = Some(0),
-                            BoundaryScheme::BounceBack => bounce_back = true, // handled below
-                            BoundaryScheme::SpecularReflection => {
-                                *new_pos = Some(pos);
-                                (*new_c, changed_lattice_vector) = (-c, true);
-                            }
-                            _ => {}
-                        },
-                        val => *new_pos = Some(val as usize),
+        std::mem::swap(
+            &mut self.algorithm_values.distributions,
+            &mut self.next_distributions,
+        );
+    }
+
+    /// Collide and stream the distributions in one pass in place, by the AA pattern, alternately
+    /// reversing the distributions (storing them along the opposite lattice vectors of the
+    /// lattice positions they stream from) and restoring them.
+    ///
+    /// note: the storage of the distributions read by each lattice position is written to
+    /// straight away, and that of others (e.g. reflected to other lattice positions) after all
+    /// are read; distributions not streamed to are unchanged
+    fn aa_streaming(&mut self, relaxation_time: f32, streaming: &[[Streaming<N>; 2]; N]) {
+        let reversed = match self.reversed {
+            Some(_) => None,
+            None => Some(std::array::from_fn(|d| {
+                streaming[d] == [Streaming::Periodic; 2]
+            })),
+        };
+        let mut later = Vec::new();
+
+        for pos in self.positions() {
+            let index = self.index(&pos);
+
+            if self.solid(index) {
+                continue;
+            }
+
+            let read = std::array::from_fn::<_, B, _>(|i| {
+                self.distribution_location(index, i, self.reversed)
+            });
+            let collision_distributions = self.collide(&pos, index, relaxation_time);
+            for (i, f_c) in collision_distributions.into_iter().enumerate() {
+                if let Some(destination) =
+                    self.streaming_destination(&pos, index, i, f_c, streaming)
+                {
+                    if let Some((id, arm, momentum)) =
+                        self.exchanged_momentum(&pos, i, f_c, &destination)
+                    {
+                        self.add_object_force(id, &arm, &momentum);
+                    }
+                    let location =
+                        self.distribution_location(destination.index, destination.i, reversed);
+                    match read.contains(&location) {
+                        true => {
+                            self.algorithm_values.distributions[location.0][location.1] =
+                                destination.distribution
+                        }
+                        false => later.push((location, destination.distribution)),
                     }
                 }
+            }
+        }
 
-                if !new_pos.contains(&None) {
-                    let new_pos = new_pos.map(Option::unwrap);
-                    let new_index = self.index(&new_pos);
-                    if self.object[new_index] {
-                        // TODO other boundary schemes
-                        bounce_back = true;
+        for ((i, index), f) in later {
+            self.algorithm_values.distributions[i][index] = f;
+        }
+        self.reversed = reversed;
+    }
+
+    /// Lattice positions (by index) of the solid objects and of the fluid neighbouring them
+    /// (through any face).
+    fn object_boundary_indices(&self) -> Vec<usize> {
+        self.positions()
+            .filter(|pos| {
+                self.lattice_parameters.iter().any(|lattice_parameters| {
+                    let other_pos = std::array::from_fn(|d| {
+                        (pos[d] as isize + lattice_parameters.lattice_vector[d])
+                            .rem_euclid(self.size[d] as isize) as usize
+                    });
+                    self.solid(self.index(&other_pos))
+                })
+            })
+            .map(|pos| self.index(&pos))
+            .collect()
+    }
+
+    /// Stream the distributions by pulling the collision distributions arriving at each lattice
+    /// position from their sources in the other buffer.
+    ///
+    /// note: each lattice position only reads the other buffer, so the lattice positions are
+    /// pulled in parallel with the `rayon` feature
+    fn pull_streaming(&mut self, streaming: &[[Streaming<N>; 2]; N]) {
+        let any_free_slip = self.object_surfaces.contains(&Surface::FreeSlip);
+
+        #[cfg(feature = "rayon")]
+        {
+            let pulled = (0..self.object.len())
+                .into_par_iter()
+                .map(|index| {
+                    (!self.solid(index)).then(|| {
+                        self.pulled_distributions(
+                            &self.position(index),
+                            index,
+                            streaming,
+                            any_free_slip,
+                        )
+                    })
+                })
+                .collect::<Vec<_>>();
+            for (index, pulled) in pulled.into_iter().enumerate() {
+                if let Some(pulled) = pulled {
+                    self.set_pulled_distributions(index, pulled);
+                }
+            }
+        }
+        #[cfg(not(feature = "rayon"))]
+        for pos in self.positions() {
+            let index = self.index(&pos);
+            if !self.solid(index) {
+                let pulled = self.pulled_distributions(&pos, index, streaming, any_free_slip);
+                self.set_pulled_distributions(index, pulled);
+            }
+        }
+    }
+
+    /// Collision distributions pulled to a fluid lattice position (by index).
+    ///
+    /// note: the sources are the lattice positions neighbouring (or at) the lattice position,
+    /// along their lattice vector (or any lattice vector, where reflected at a face or object)
+    fn pulled_distributions(
+        &self,
+        pos: &[usize; N],
+        index: usize,
+        streaming: &[[Streaming<N>; 2]; N],
+        any_free_slip: bool,
+    ) -> PulledDistributions<N, B> {
+        let pos = *pos;
+        let mut result = PulledDistributions {
+            distributions: [None; B],
+            momentum_exchanges: Vec::new(),
+        };
+        // note: distributions reflected at a face or free-slip object may arrive along any
+        // lattice vector
+        let face = izip!(pos, self.size).any(|(pos, size)| pos == 0 || pos == size - 1);
+        let free_slip = any_free_slip
+            && self.lattice_parameters.iter().any(|lattice_parameters| {
+                let other_pos: [Option<usize>; N] = std::array::from_fn(|d| {
+                    pos[d]
+                        .checked_add_signed(lattice_parameters.lattice_vector[d])
+                        .filter(|val| *val < self.size[d])
+                });
+                !other_pos.contains(&None) && {
+                    let other_index = self.index(&other_pos.map(Option::unwrap));
+                    self.solid(other_index)
+                        && self.object_surface(self.object[other_index]) == Surface::FreeSlip
+                }
+            });
+        for k in 0..B {
+            let lattice_vector = self.lattice_parameters[k].lattice_vector;
+            let mut other_pos = [0; N];
+            let mut in_range = true;
+            for (other_pos, pos, c, size, streaming) in
+                izip!(&mut other_pos, pos, lattice_vector, self.size, streaming)
+            {
+                *other_pos = match pos as isize - c {
+                    val if val >= 0 && val < size as isize => val as usize,
+                    val => {
+                        // note: the source left through the opposite face
+                        let side = match val < 0 {
+                            true => 1,
+                            false => 0,
+                        };
+                        in_range &= streaming[side] == Streaming::Periodic;
+                        val.rem_euclid(size as isize) as usize
                     }
+                };
+            }
+            let other_index = self.index(&other_pos);
+            if !in_range || self.solid(other_index) {
+                continue;
+            }
+
+            let directions = match face || free_slip || lattice_vector == [0; N] {
+                true => 0..B,
+                false => k..k + 1,
+            };
+            for i in directions {
+                let f_c = self.next_distributions[i][other_index];
+                match self.streaming_destination(&other_pos, other_index, i, f_c, streaming) {
+                    Some(destination) if destination.index == index => {
+                        result.momentum_exchanges.extend(self.exchanged_momentum(
+                            &other_pos,
+                            i,
+                            f_c,
+                            &destination,
+                        ));
+                        result.distributions[destination.i] = Some(destination.distribution);
+                    }
+                    _ => {}
                 }
+            }
+        }
+        result
+    }
+
+    /// Set the collision distributions pulled to a lattice position (by index), adding the
+    /// momentum exchanged with objects.
+    fn set_pulled_distributions(&mut self, index: usize, pulled: PulledDistributions<N, B>) {
+        for (plane, f) in izip!(
+            &mut self.algorithm_values.distributions,
+            pulled.distributions
+        ) {
+            if let Some(f) = f {
+                plane[index] = f;
+            }
+        }
+        for (id, arm, momentum) in pulled.momentum_exchanges {
+            self.add_object_force(id, &arm, &momentum);
+        }
+    }
 
-                if bounce_back {
-                    for (pos_new, new_c, pos, c) in izip!(
-                        &mut new_pos,
-                        &mut new_lattice_vector,
-                        pos,
-                        lattice_parameters.lattice_vector
-                    ) {
-                        *pos_new = Some(pos);
-                        *new_c = -c;
+    /// Momentum exchanged with an object, if any, by streaming a collision distribution of a
+    /// lattice position along a lattice vector (by index) to its destination, with the ID of
+    /// the object and the position it acts at.
+    fn exchanged_momentum(
+        &self,
+        pos: &[usize; N],
+        i: usize,
+        f_c: f32,
+        destination: &StreamingDestination,
+    ) -> Option<(u16, [f32; N], [f32; N])> {
+        let object_id = destination.object_id?;
+        // momentum exchanged with the object (acting midway along the link), by the change of
+        // the lattice vector and of the distribution (where the surface moves)
+        let c = self.lattice_parameters[i].lattice_vector;
+        let new_c = self.lattice_parameters[destination.i].lattice_vector;
+        let mut arm = [0.0; N];
+        let mut momentum = [0.0; N];
+        for (arm, momentum, pos, c, new_c) in izip!(&mut arm, &mut momentum, pos, c, new_c) {
+            *arm = *pos as f32 + 0.5 * c as f32;
+            *momentum = c as f32 * f_c - new_c as f32 * destination.distribution;
+        }
+        Some((object_id, arm, momentum))
+    }
+
+    /// Add momentum exchanged with an object, acting at a position, to the force and force
+    /// moments on the objects.
+    fn add_object_force(&mut self, id: u16, pos: &[f32; N], momentum: &[f32; N]) {
+        let (object_force, object_force_moment) = &mut self.object_forces[id as usize];
+        for (force, force_moment, object_force, object_force_moment, val) in izip!(
+            &mut self.force,
+            &mut self.force_moment,
+            object_force,
+            object_force_moment,
+            momentum
+        ) {
+            *force += val;
+            *object_force += val;
+            for (force_moment, object_force_moment, pos) in
+                izip!(force_moment, object_force_moment, pos)
+            {
+                let moment = pos * val;
+                *force_moment += moment;
+                *object_force_moment += moment;
+            }
+        }
+    }
+
+    /// Destination of a collision distribution of a lattice position (by index) along a
+    /// lattice vector (by index), if it stays in the lattice.
+    fn streaming_destination(
+        &self,
+        pos: &[usize; N],
+        index: usize,
+        i: usize,
+        f_c: f32,
+        streaming: &[[Streaming<N>; 2]; N],
+    ) -> Option<StreamingDestination> {
+        let lattice_parameters = &self.lattice_parameters[i];
+        let mut new_pos = [None; N];
+        let mut new_lattice_vector = lattice_parameters.lattice_vector;
+        let mut changed_lattice_vector = false;
+        let mut bounce_back = false;
+        let mut wall_velocity = None;
+        let mut reflections = 0;
+        let mut object_id = None;
+        for (new_pos, new_c, pos, c, size, streaming) in izip!(
+            &mut new_pos,
+            &mut new_lattice_vector,
+            pos,
+            lattice_parameters.lattice_vector,
+            self.size,
+            streaming
+        ) {
+            let (val, side) = match *pos as isize + c {
+                val if val < 0 => (val, 0),
+                val if val >= size as isize => (val, 1),
+                val => {
+                    *new_pos = Some(val as usize);
+                    continue;
+                }
+            };
+            match streaming[side] {
+                Streaming::Leave => {}
+                Streaming::Periodic => *new_pos = Some(val.rem_euclid(size as isize) as usize),
+                Streaming::BounceBack(velocity_vector) => {
+                    bounce_back = true; // handled below
+                    if velocity_vector != [0.0; N] {
+                        wall_velocity = Some(velocity_vector);
                     }
-                    changed_lattice_vector = true;
                 }
+                Streaming::SpecularReflection => {
+                    *new_pos = Some(*pos);
+                    (*new_c, changed_lattice_vector) = (-c, true);
+                    reflections += 1;
+                }
+            }
+        }
+        if reflections > 1 && self.corner_reflection == CornerReflection::BounceBack {
+            bounce_back = true;
+        }
 
-                if !new_pos.contains(&None) {
-                    let new_pos = new_pos.map(Option::unwrap);
-                    let new_index = self.index(&new_pos);
-                    let new_i = match changed_lattice_vector {
-                        true => self
-                            .lattice_parameters
-                            .iter()
-                            .position(|lattice_parameters| {
-                                lattice_parameters.lattice_vector == new_lattice_vector
-                            })
-                            .unwrap(),
-                        false => i,
-                    };
-                    self.algorithm_values[new_index].distributions[new_i] =
-                        self.algorithm_values[index].collision_distributions[i];
+        if !new_pos.contains(&None) {
+            let destination_pos = new_pos.map(Option::unwrap);
+            let new_index = self.index(&destination_pos);
+            if self.solid(new_index) {
+                let id = self.object[new_index];
+                object_id = Some(id);
+                match self.object_surface(id) {
+                    Surface::FreeSlip => {
+                        // note: the components of the lattice vector towards the object along
+                        // each axis are reflected (all, where only diagonally adjacent)
+                        let mut reflected_pos = destination_pos;
+                        let mut reflected = false;
+                        for (d, c) in lattice_parameters.lattice_vector.into_iter().enumerate() {
+                            let mut axial_pos = *pos;
+                            axial_pos[d] = destination_pos[d];
+                            if c != 0 && self.solid(self.index(&axial_pos)) {
+                                reflected_pos[d] = pos[d];
+                                new_lattice_vector[d] = -new_lattice_vector[d];
+                                reflected = true;
+                            }
+                        }
+                        match reflected && !self.solid(self.index(&reflected_pos)) {
+                            true => {
+                                new_pos = reflected_pos.map(Some);
+                                changed_lattice_vector = true;
+                            }
+                            false => bounce_back = true,
+                        }
+                    }
+                    _ => {
+                        bounce_back = true;
+                        if let Some(&velocity_vector) = self.wall_velocities.get(new_index) {
+                            if velocity_vector != [0.0; N] {
+                                wall_velocity = Some(velocity_vector);
+                            }
+                        }
+                    }
                 }
             }
+        }
 
-            if !self.next_pos(&mut pos, &dims) {
-                break;
+        if bounce_back {
+            for (pos_new, new_c, pos, c) in izip!(
+                &mut new_pos,
+                &mut new_lattice_vector,
+                pos,
+                lattice_parameters.lattice_vector
+            ) {
+                *pos_new = Some(*pos);
+                *new_c = -c;
             }
+            changed_lattice_vector = true;
+        }
+
+        if new_pos.contains(&None) {
+            return None;
         }
+        let new_index = self.index(&new_pos.map(Option::unwrap));
+        // note: a reflection reverses only some of the components of the lattice vector
+        let new_i = match (bounce_back, changed_lattice_vector) {
+            (true, _) => self.opposite_lattice_vectors[i],
+            (false, true) => self
+                .lattice_parameters
+                .iter()
+                .position(|lattice_parameters| {
+                    lattice_parameters.lattice_vector == new_lattice_vector
+                })
+                .unwrap(),
+            (false, false) => i,
+        };
+        let mut f = f_c;
+        if let Some(wall_velocity) = wall_velocity {
+            // note: momentum is given by the wall to the reflected distribution
+            let c_dot_u = lattice_parameters
+                .lattice_vector
+                .map(|val| val as f32)
+                .dot_product(&wall_velocity);
+            f -= 2.0 * lattice_parameters.weight * self.algorithm_values.density[index] * c_dot_u
+                / self.sound_speed_squared;
+        }
+        Some(StreamingDestination {
+            index: new_index,
+            i: new_i,
+            distribution: f,
+            object_id,
+        })
     }
 
     /// Calculate derived values.
     fn calculate_derived(&mut self) {
-        let (mut pos, dims) = ([0; N], [true; N]);
-        loop {
+        for pos in self.positions() {
             let index = self.index(&pos);
 
-            if self.object[index] {
-                match self.next_pos(&mut pos, &dims) {
-                    true => continue,
-                    false => break,
-                }
+            if self.solid(index) {
+                continue;
             }
 
-            let algorithm_values = &mut self.algorithm_values[index];
+            let distributions = self.distributions_at(index);
 
             // calculate density
-            algorithm_values.density = algorithm_values.distributions.iter().sum();
+            let density = distributions.iter().sum();
+            self.algorithm_values.density[index] = density;
 
             // calculate velocity vector
-            algorithm_values.velocity_vector.fill(0.0);
-            if algorithm_values.density > 0.0 {
-                for (lattice_parameters, f) in
-                    izip!(&self.lattice_parameters, algorithm_values.distributions)
-                {
-                    for (u, c) in izip!(
-                        &mut algorithm_values.velocity_vector,
-                        lattice_parameters.lattice_vector
-                    ) {
+            let mut velocity_vector = [0.0; N];
+            if density > 0.0 {
+                for (lattice_parameters, f) in izip!(&self.lattice_parameters, distributions) {
+                    for (u, c) in izip!(&mut velocity_vector, lattice_parameters.lattice_vector) {
                         *u += c as f32 * f;
                     }
                 }
-                for u in &mut algorithm_values.velocity_vector {
-                    *u /= algorithm_values.density;
+                // note: half the force density contributes to the momentum
+                for (u, force_density) in izip!(
+                    &mut velocity_vector,
+                    self.algorithm_values.force_density(index)
+                ) {
+                    *u = (*u + 0.5 * force_density) / density;
                 }
             }
-
-            if !self.next_pos(&mut pos, &dims) {
-                break;
+            for (plane, u) in izip!(&mut self.algorithm_values.velocity_vector, velocity_vector) {
+                plane[index] = u;
             }
         }
     }
 
-    /// Update inflows and the outflows.
-    fn update_inflows_and_outflows(&mut self) {
-        for (i, boundary_schemes) in self.boundary_schemes.iter().enumerate() {
-            match boundary_schemes[0] {
-                BoundaryScheme::Inflow => {
-                    let (mut pos, mut dims) = ([0; N], [true; N]);
-                    dims[i] = false;
-                    loop {
-                        let index = self.index(&pos);
-                        self.algorithm_values[index] = self.source_algorithm_values;
+    /// Update the faces by their boundary conditions.
+    fn update_boundaries(&mut self) {
+        for (dim, side) in iproduct!(0..N, 0..2) {
+            // note: the boundary condition is shared, as it updates the lattice
+            let boundary_condition = self.boundary_conditions[dim][side].clone();
+            boundary_condition.update(self, dim, side);
+        }
+    }
 
-                        if !self.next_pos(&mut pos, &dims) {
-                            break;
-                        }
-                    }
-                }
-                BoundaryScheme::Outflow => {
-                    let (mut pos, mut dims) = ([0; N], [true; N]);
-                    dims[i] = false;
-                    loop {
-                        let mut other_pos = pos;
-                        other_pos[i] += 1;
+    /// Update the corners where different boundary schemes meet, by the corner scheme.
+    fn update_corners(&mut self) {
+        if self.corner_scheme == CornerScheme::Unchanged {
+            return;
+        }
 
-                        let (index, other_index) = (self.index(&pos), self.index(&other_pos));
-                        self.algorithm_values[index] = self.algorithm_values[other_index];
+        for (i, j, side_i, side_j) in iproduct!(0..N, 0..N, 0..2, 0..2) {
+            if i >= j
+                || self.boundary_conditions[i][side_i].name()
+                    == self.boundary_conditions[j][side_j].name()
+            {
+                continue;
+            }
 
-                        if !self.next_pos(&mut pos, &dims) {
-                            break;
-                        }
-                    }
+            let (mut start, mut dims) = ([0; N], [true; N]);
+            (start[i], dims[i]) = (side_i * (self.size[i] - 1), false);
+            (start[j], dims[j]) = (side_j * (self.size[j] - 1), false);
+            for pos in PositionIter::new_slice(self.size, dims, start) {
+                let index = self.index(&pos);
+                if self.solid(index) {
+                    continue;
                 }
-                _ => {}
-            }
-            match boundary_schemes[1] {
-                BoundaryScheme::Inflow => {
-                    let (mut pos, mut dims) = ([0; N], [true; N]);
-                    (pos[i], dims[i]) = (self.size[i] - 1, false);
-                    loop {
-                        let index = self.index(&pos);
-                        self.algorithm_values[index] = self.source_algorithm_values;
-
-                        if !self.next_pos(&mut pos, &dims) {
-                            break;
-                        }
-                    }
-                }
-                BoundaryScheme::Outflow => {
-                    let (mut pos, mut dims) = ([0; N], [true; N]);
-                    (pos[i], dims[i]) = (self.size[i] - 1, false);
-                    loop {
-                        let mut other_pos = pos;
-                        other_pos[i] -= 1;
 
-                        let (index, other_index) = (self.index(&pos), self.index(&other_pos));
-                        self.algorithm_values[index] = self.algorithm_values[other_index];
+                // note: the adjacent edge lattice positions are inwards in each dimension
+                let adjacent = [(i, side_i), (j, side_j)]
+                    .map(|(dim, side)| {
+                        let mut other_pos = pos;
+                        other_pos[dim] = match side {
+                            0 => (pos[dim] + 1).min(self.size[dim] - 1),
+                            _ => pos[dim].saturating_sub(1),
+                        };
+                        self.index(&other_pos)
+                    })
+                    .into_iter()
+                    .filter(|other_index| *other_index != index && !self.solid(*other_index))
+                    .map(|other_index| self.algorithm_values_at(other_index))
+                    .collect::<Vec<_>>();
+                if adjacent.is_empty() {
+                    continue;
+                }
 
-                        if !self.next_pos(&mut pos, &dims) {
-                            break;
-                        }
+                let count = adjacent.len() as f32;
+                let mut algorithm_values = self.algorithm_values_at(index);
+                algorithm_values.density =
+                    adjacent.iter().map(|val| val.density).sum::<f32>() / count;
+                algorithm_values.velocity_vector = std::array::from_fn(|k| {
+                    adjacent
+                        .iter()
+                        .map(|val| val.velocity_vector[k])
+                        .sum::<f32>()
+                        / count
+                });
+                algorithm_values.distributions = match self.corner_scheme {
+                    CornerScheme::Average => std::array::from_fn(|k| {
+                        adjacent.iter().map(|val| val.distributions[k]).sum::<f32>() / count
+                    }),
+                    CornerScheme::Unchanged | CornerScheme::Equilibrium => {
+                        Self::equilibrium_distributions(
+                            &self.lattice_parameters,
+                            self.sound_speed_squared,
+                            algorithm_values.density,
+                            &algorithm_values.velocity_vector,
+                        )
                     }
-                }
-                _ => {}
+                };
+                self.set_algorithm_values_at(index, &algorithm_values);
             }
         }
     }
@@ -464,4 +2193,27 @@ impl<const N: usize, const B: usize> Lbgk<N, B> {
         }
         result
     }
+
+    /// Calculate [Guo forcing](https://doi.org/10.1103/PhysRevE.65.046308) terms.
+    fn forcing_terms(
+        lattice_parameters: &[LatticeParameters<N>; B],
+        sound_speed_squared: f32,
+        prefactor: f32,
+        velocity_vector: &[f32; N],
+        force_density: &[f32; N],
+    ) -> [f32; B] {
+        let cs4 = sound_speed_squared * sound_speed_squared;
+
+        let mut result = [0.0; B];
+        for (val, lattice_parameters) in izip!(&mut result, lattice_parameters) {
+            let c = lattice_parameters.lattice_vector.map(|val| val as f32);
+            let c_dot_u = c.dot_product(velocity_vector);
+            *val = prefactor
+                * lattice_parameters.weight
+                * izip!(c, velocity_vector, force_density).fold(0.0, |acc, (c, u, f)| {
+                    acc + ((c - u) / sound_speed_squared + c_dot_u * c / cs4) * f
+                });
+        }
+        result
+    }
 }
@@ -0,0 +1,354 @@
+use super::{AlgorithmValues, BoundaryScheme, Lbgk};
+
+use itertools::izip;
+
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+
+/// Streaming of the distributions leaving the lattice through a face.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Streaming<const N: usize> {
+    /// Leave the lattice (e.g. for the entering distributions to be set by the update).
+    Leave,
+    /// Enter through the opposite face.
+    Periodic,
+    /// Return along the reversed lattice vector, from a wall of a velocity (zero for a
+    /// stationary wall).
+    BounceBack([f32; N]),
+    /// Return with the component of the lattice vector normal to the face reversed.
+    SpecularReflection,
+}
+
+/// Boundary condition of a face of the lattice, i.e. the lattice positions at the start (side 0)
+/// or end (side 1) of a dimension.
+pub trait BoundaryCondition<const N: usize, const B: usize>: Send + Sync {
+    /// Name of the boundary condition (e.g. to compare the conditions meeting at a corner).
+    fn name(&self) -> &'static str;
+
+    /// Streaming of the distributions leaving the lattice through the face, during an
+    /// iteration.
+    fn streaming(&self, _lbgk: &Lbgk<N, B>) -> Streaming<N> {
+        Streaming::Leave
+    }
+
+    /// Update the lattice positions of the face after streaming and the calculation of the
+    /// derived values.
+    fn update(&self, _lbgk: &mut Lbgk<N, B>, _dim: usize, _side: usize) {}
+}
+
+impl BoundaryScheme {
+    /// Boundary condition of the scheme.
+    pub fn boundary_condition<const N: usize, const B: usize>(
+        &self,
+    ) -> Arc<dyn BoundaryCondition<N, B>> {
+        match self {
+            BoundaryScheme::Inflow => Arc::new(Inflow),
+            BoundaryScheme::ExtrapolatedInflow => Arc::new(ExtrapolatedInflow),
+            BoundaryScheme::Outflow => Arc::new(Outflow),
+            BoundaryScheme::ExtrapolatedOutflow => Arc::new(ExtrapolatedOutflow),
+            BoundaryScheme::Periodic => Arc::new(Periodic),
+            BoundaryScheme::BounceBack => Arc::new(BounceBack),
+            BoundaryScheme::MovingWall => Arc::new(MovingWall),
+            BoundaryScheme::SpecularReflection => Arc::new(SpecularReflection),
+        }
+    }
+}
+
+/// Lattice position neighbouring a lattice position of a face inwards.
+fn inwards<const N: usize>(pos: &[usize; N], dim: usize, side: usize) -> [usize; N] {
+    let mut result = *pos;
+    match side {
+        0 => result[dim] += 1,
+        _ => result[dim] -= 1,
+    }
+    result
+}
+
+/// Distributions of a density and velocity, as the equilibrium distributions with the
+/// non-equilibrium part of the algorithm values of a neighbouring lattice position
+/// ([reference](https://doi.org/10.1088/1009-1963/11/4/310)).
+fn extrapolated_distributions<const N: usize, const B: usize>(
+    lbgk: &Lbgk<N, B>,
+    density: f32,
+    velocity_vector: &[f32; N],
+    other: &AlgorithmValues<N, B>,
+) -> [f32; B] {
+    let [equilibrium, other_equilibrium] = [
+        (density, velocity_vector),
+        (other.density, &other.velocity_vector),
+    ]
+    .map(|(density, velocity_vector)| {
+        Lbgk::<N, B>::equilibrium_distributions(
+            &lbgk.lattice_parameters,
+            lbgk.sound_speed_squared,
+            density,
+            velocity_vector,
+        )
+    });
+    let mut result = [0.0; B];
+    for (f, equilibrium, other_f, other_equilibrium) in izip!(
+        &mut result,
+        equilibrium,
+        other.distributions,
+        other_equilibrium
+    ) {
+        *f = equilibrium + other_f - other_equilibrium;
+    }
+    result
+}
+
+/// Inflow of the source values, or the inflow profile if set.
+pub struct Inflow;
+
+impl<const N: usize, const B: usize> BoundaryCondition<N, B> for Inflow {
+    fn name(&self) -> &'static str {
+        "Inflow"
+    }
+
+    fn update(&self, lbgk: &mut Lbgk<N, B>, dim: usize, side: usize) {
+        for pos in lbgk.face_positions(dim, side) {
+            let index = lbgk.index(&pos);
+            let algorithm_values = lbgk.inflow_algorithm_values(&pos);
+            lbgk.set_algorithm_values_at(index, &algorithm_values);
+        }
+    }
+}
+
+/// Inflow of the source velocity (or the inflow profile), of the density of the neighbouring
+/// lattice positions.
+pub struct ExtrapolatedInflow;
+
+impl<const N: usize, const B: usize> BoundaryCondition<N, B> for ExtrapolatedInflow {
+    fn name(&self) -> &'static str {
+        "Extrapolated inflow"
+    }
+
+    fn update(&self, lbgk: &mut Lbgk<N, B>, dim: usize, side: usize) {
+        for pos in lbgk.face_positions(dim, side) {
+            let other_pos = inwards(&pos, dim, side);
+            let (index, other_index) = (lbgk.index(&pos), lbgk.index(&other_pos));
+            if lbgk.solid(index) || lbgk.solid(other_index) {
+                continue;
+            }
+
+            let other = lbgk.algorithm_values_at(other_index);
+            let mut algorithm_values = lbgk.inflow_algorithm_values(&pos);
+            algorithm_values.density = other.density;
+            algorithm_values.distributions = extrapolated_distributions(
+                lbgk,
+                algorithm_values.density,
+                &algorithm_values.velocity_vector,
+                &other,
+            );
+            lbgk.set_algorithm_values_at(index, &algorithm_values);
+        }
+    }
+}
+
+/// Outflow copying all values of the neighbouring lattice positions.
+pub struct Outflow;
+
+impl<const N: usize, const B: usize> BoundaryCondition<N, B> for Outflow {
+    fn name(&self) -> &'static str {
+        "Outflow"
+    }
+
+    fn update(&self, lbgk: &mut Lbgk<N, B>, dim: usize, side: usize) {
+        for pos in lbgk.face_positions(dim, side) {
+            let other_pos = inwards(&pos, dim, side);
+            let (index, other_index) = (lbgk.index(&pos), lbgk.index(&other_pos));
+            let algorithm_values = lbgk.algorithm_values_at(other_index);
+            lbgk.set_algorithm_values_at(index, &algorithm_values);
+        }
+    }
+}
+
+/// Outflow of the density and velocity extrapolated from the neighbouring fluid, with the
+/// equilibrium distributions and the non-equilibrium part of the neighbouring fluid.
+pub struct ExtrapolatedOutflow;
+
+impl<const N: usize, const B: usize> BoundaryCondition<N, B> for ExtrapolatedOutflow {
+    fn name(&self) -> &'static str {
+        "Extrapolated outflow"
+    }
+
+    fn update(&self, lbgk: &mut Lbgk<N, B>, dim: usize, side: usize) {
+        for pos in lbgk.face_positions(dim, side) {
+            let other_pos = inwards(&pos, dim, side);
+            let (index, other_index) = (lbgk.index(&pos), lbgk.index(&other_pos));
+            if lbgk.solid(index) || lbgk.solid(other_index) {
+                continue;
+            }
+
+            let other = lbgk.algorithm_values_at(other_index);
+            // note: extrapolated linearly (2 x_1 - x_2) from the two neighbouring lattice
+            // positions, or of zero gradient where there is no second fluid lattice position
+            let next_index = (lbgk.size[dim] > 2)
+                .then(|| lbgk.index(&inwards(&other_pos, dim, side)))
+                .filter(|next_index| !lbgk.solid(*next_index));
+            let (density, velocity_vector) = match next_index {
+                None => (other.density, other.velocity_vector),
+                Some(next_index) => {
+                    let next = lbgk.algorithm_values_at(next_index);
+                    (
+                        2.0 * other.density - next.density,
+                        std::array::from_fn(|d| {
+                            2.0 * other.velocity_vector[d] - next.velocity_vector[d]
+                        }),
+                    )
+                }
+            };
+
+            let mut algorithm_values = other;
+            algorithm_values.density = density;
+            algorithm_values.velocity_vector = velocity_vector;
+            algorithm_values.distributions =
+                extrapolated_distributions(lbgk, density, &velocity_vector, &other);
+            lbgk.set_algorithm_values_at(index, &algorithm_values);
+        }
+    }
+}
+
+/// Setpoints of an outlet held by feedback.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Setpoint {
+    /// Mean pressure of the fluid neighbouring the face.
+    Pressure(f32),
+    /// Mass flow out through the face (summed over the fluid neighbouring it).
+    MassFlow(f32),
+}
+
+/// Outlet of an imposed density, adjusted slowly by feedback each iteration to hold a setpoint
+/// (e.g. the operating point of an internal flow), with the velocity of zero gradient.
+pub struct PressureOutlet {
+    setpoint: Setpoint,
+    gain: f32,
+    // note: the bits of the imposed density, adjusted through the shared boundary condition
+    density: AtomicU32,
+}
+
+impl PressureOutlet {
+    /// Create `PressureOutlet` of a setpoint, of a gain (the fraction of the difference from the
+    /// setpoint corrected each iteration, per lattice position for the mass flow) and an initial
+    /// imposed density.
+    pub fn new(setpoint: Setpoint, gain: f32, density: f32) -> Self {
+        Self {
+            setpoint,
+            gain,
+            density: AtomicU32::new(density.to_bits()),
+        }
+    }
+
+    /// Imposed density.
+    pub fn density(&self) -> f32 {
+        f32::from_bits(self.density.load(Ordering::Relaxed))
+    }
+}
+
+impl<const N: usize, const B: usize> BoundaryCondition<N, B> for PressureOutlet {
+    fn name(&self) -> &'static str {
+        "Pressure outlet"
+    }
+
+    fn update(&self, lbgk: &mut Lbgk<N, B>, dim: usize, side: usize) {
+        let outwards = match side {
+            0 => -1.0,
+            _ => 1.0,
+        };
+        let pairs = lbgk
+            .face_positions(dim, side)
+            .map(|pos| (lbgk.index(&pos), lbgk.index(&inwards(&pos, dim, side))))
+            .filter(|(index, other_index)| !lbgk.solid(*index) && !lbgk.solid(*other_index))
+            .collect::<Vec<_>>();
+        if pairs.is_empty() {
+            return;
+        }
+
+        let count = pairs.len() as f32;
+        let correction = match self.setpoint {
+            Setpoint::Pressure(pressure) => {
+                let mean_density = pairs
+                    .iter()
+                    .map(|(_, other_index)| lbgk.algorithm_values.density[*other_index])
+                    .sum::<f32>()
+                    / count;
+                pressure / lbgk.sound_speed_squared - mean_density
+            }
+            Setpoint::MassFlow(mass_flow) => {
+                // note: a lower density draws more flow out
+                let val = pairs
+                    .iter()
+                    .map(|(_, other_index)| {
+                        lbgk.algorithm_values.density[*other_index]
+                            * lbgk.algorithm_values.velocity_vector[dim][*other_index]
+                            * outwards
+                    })
+                    .sum::<f32>();
+                (val - mass_flow) / count
+            }
+        };
+        let density = self.density() + self.gain * correction;
+        self.density.store(density.to_bits(), Ordering::Relaxed);
+
+        for (index, other_index) in pairs {
+            let other = lbgk.algorithm_values_at(other_index);
+            let mut algorithm_values = other;
+            algorithm_values.density = density;
+            algorithm_values.distributions =
+                extrapolated_distributions(lbgk, density, &other.velocity_vector, &other);
+            lbgk.set_algorithm_values_at(index, &algorithm_values);
+        }
+    }
+}
+
+/// Periodic boundary, entering through the opposite face.
+pub struct Periodic;
+
+impl<const N: usize, const B: usize> BoundaryCondition<N, B> for Periodic {
+    fn name(&self) -> &'static str {
+        "Periodic"
+    }
+
+    fn streaming(&self, _lbgk: &Lbgk<N, B>) -> Streaming<N> {
+        Streaming::Periodic
+    }
+}
+
+/// Bounce-back from a stationary wall.
+pub struct BounceBack;
+
+impl<const N: usize, const B: usize> BoundaryCondition<N, B> for BounceBack {
+    fn name(&self) -> &'static str {
+        "Bounce-back"
+    }
+
+    fn streaming(&self, _lbgk: &Lbgk<N, B>) -> Streaming<N> {
+        Streaming::BounceBack([0.0; N])
+    }
+}
+
+/// Bounce-back from a wall moving with the source velocity.
+pub struct MovingWall;
+
+impl<const N: usize, const B: usize> BoundaryCondition<N, B> for MovingWall {
+    fn name(&self) -> &'static str {
+        "Moving wall"
+    }
+
+    fn streaming(&self, lbgk: &Lbgk<N, B>) -> Streaming<N> {
+        Streaming::BounceBack(lbgk.source_algorithm_values.velocity_vector)
+    }
+}
+
+/// Specular reflection, e.g. of a free-slip wall.
+pub struct SpecularReflection;
+
+impl<const N: usize, const B: usize> BoundaryCondition<N, B> for SpecularReflection {
+    fn name(&self) -> &'static str {
+        "Specular reflection"
+    }
+
+    fn streaming(&self, _lbgk: &Lbgk<N, B>) -> Streaming<N> {
+        Streaming::SpecularReflection
+    }
+}
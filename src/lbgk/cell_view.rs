@@ -0,0 +1,42 @@
+use super::AlgorithmValues;
+use crate::linear_algebra::VectorOps;
+
+/// Read-only view of the values of a lattice cell (a copy, as the values of each are stored in
+/// separate arrays).
+pub struct CellView<const N: usize, const B: usize> {
+    pub(super) algorithm_values: AlgorithmValues<N, B>,
+    pub(super) object_id: u16,
+    pub(super) solid: bool,
+}
+
+impl<const N: usize, const B: usize> CellView<N, B> {
+    /// Density of the cell.
+    pub fn density(&self) -> f32 {
+        self.algorithm_values.density
+    }
+
+    /// Velocity vector of the cell.
+    pub fn velocity_vector(&self) -> [f32; N] {
+        self.algorithm_values.velocity_vector
+    }
+
+    /// Velocity (magnitude) of the cell.
+    pub fn velocity(&self) -> f32 {
+        self.algorithm_values.velocity_vector.norm()
+    }
+
+    /// Whether the cell is within a solid object (as a porous object holds fluid).
+    pub fn object(&self) -> bool {
+        self.solid
+    }
+
+    /// ID of the object the cell is within, 0 for fluid.
+    pub fn object_id(&self) -> u16 {
+        self.object_id
+    }
+
+    /// Distributions of the cell, ordered as the lattice vectors.
+    pub fn distributions(&self) -> &[f32; B] {
+        &self.algorithm_values.distributions
+    }
+}
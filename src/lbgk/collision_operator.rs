@@ -0,0 +1,381 @@
+use super::{parameters, AlgorithmValues, Lbgk};
+use crate::linear_algebra::Matrix;
+
+use itertools::{iproduct, izip};
+
+/// Values of a lattice position for its collision.
+pub struct CollisionValues<'a, const N: usize, const B: usize> {
+    lbgk: &'a Lbgk<N, B>,
+    algorithm_values: AlgorithmValues<N, B>,
+}
+
+impl<'a, const N: usize, const B: usize> CollisionValues<'a, N, B> {
+    pub(super) fn new(lbgk: &'a Lbgk<N, B>, algorithm_values: AlgorithmValues<N, B>) -> Self {
+        Self {
+            lbgk,
+            algorithm_values,
+        }
+    }
+
+    /// Distributions before the collision.
+    pub fn distributions(&self) -> &[f32; B] {
+        &self.algorithm_values.distributions
+    }
+
+    /// Density.
+    pub fn density(&self) -> f32 {
+        self.algorithm_values.density
+    }
+
+    /// Velocity vector (including half the force density).
+    pub fn velocity_vector(&self) -> &[f32; N] {
+        &self.algorithm_values.velocity_vector
+    }
+
+    /// Force density.
+    pub fn force_density(&self) -> &[f32; N] {
+        &self.algorithm_values.force_density
+    }
+
+    /// Lattice vectors, in the order of the distributions.
+    pub fn lattice_vectors(&self) -> [[isize; N]; B] {
+        self.lbgk
+            .lattice_parameters
+            .map(|lattice_parameters| lattice_parameters.lattice_vector)
+    }
+
+    /// Index of the opposite of each lattice vector, in the order of the distributions.
+    pub fn opposite_lattice_vectors(&self) -> &[usize; B] {
+        &self.lbgk.opposite_lattice_vectors
+    }
+
+    /// Sound speed squared of the lattice.
+    pub fn sound_speed_squared(&self) -> f32 {
+        self.lbgk.sound_speed_squared
+    }
+
+    /// Equilibrium distributions of the density and velocity vector.
+    pub fn equilibrium_distributions(&self) -> [f32; B] {
+        Lbgk::equilibrium_distributions(
+            &self.lbgk.lattice_parameters,
+            self.lbgk.sound_speed_squared,
+            self.algorithm_values.density,
+            &self.algorithm_values.velocity_vector,
+        )
+    }
+
+    /// [Guo forcing](https://doi.org/10.1103/PhysRevE.65.046308) terms of the force density,
+    /// multiplied by a prefactor (e.g. 1 - 1 / (2 τ) for the BGK operator).
+    pub fn forcing_terms(&self, prefactor: f32) -> [f32; B] {
+        match self.algorithm_values.force_density == [0.0; N] {
+            true => [0.0; B],
+            false => Lbgk::forcing_terms(
+                &self.lbgk.lattice_parameters,
+                self.lbgk.sound_speed_squared,
+                prefactor,
+                &self.algorithm_values.velocity_vector,
+                &self.algorithm_values.force_density,
+            ),
+        }
+    }
+}
+
+/// Collision operator, relaxing the distributions of a lattice position towards equilibrium.
+pub trait CollisionOperator<const N: usize, const B: usize>: Send + Sync {
+    /// Name of the collision operator.
+    fn name(&self) -> &'static str;
+
+    /// Distributions after the collision of the values of a lattice position, for a relaxation
+    /// time (of the shear viscosity).
+    fn collide(&self, values: &CollisionValues<N, B>, relaxation_time: f32) -> [f32; B];
+}
+
+/// [Bhatnagar–Gross–Krook (BGK)](https://en.wikipedia.org/wiki/Bhatnagar%E2%80%93Gross%E2%80%93Krook_operator)
+/// operator, relaxing all distributions at a single rate.
+pub struct Bgk;
+
+impl<const N: usize, const B: usize> CollisionOperator<N, B> for Bgk {
+    fn name(&self) -> &'static str {
+        "BGK"
+    }
+
+    fn collide(&self, values: &CollisionValues<N, B>, relaxation_time: f32) -> [f32; B] {
+        let equilibrium_distributions = values.equilibrium_distributions();
+        let forcing_terms = values.forcing_terms(1.0 - 0.5 / relaxation_time);
+
+        let mut result = [0.0; B];
+        for (f_c, f, f_eq, s) in izip!(
+            &mut result,
+            values.distributions(),
+            equilibrium_distributions,
+            forcing_terms
+        ) {
+            *f_c = f - (f - f_eq) / relaxation_time + s;
+        }
+        result
+    }
+}
+
+/// [Two relaxation time (TRT)](https://doi.org/10.4208/cicp.2008.v3.p427) operator, relaxing
+/// the parts of the distributions symmetric and antisymmetric in the lattice vector at separate
+/// rates.
+///
+/// note: the rate of the antisymmetric parts is set by the magic parameter
+/// Λ = (τ⁺ - 1/2) (τ⁻ - 1/2)
+pub struct Trt {
+    magic_parameter: f32,
+}
+
+impl Trt {
+    /// Create a new `Trt` of a magic parameter.
+    pub fn new(magic_parameter: f32) -> Self {
+        Self { magic_parameter }
+    }
+}
+
+impl Default for Trt {
+    /// Magic parameter 3/16, which places bounce-back walls exactly halfway between lattice
+    /// positions.
+    fn default() -> Self {
+        Self::new(3.0 / 16.0)
+    }
+}
+
+impl<const N: usize, const B: usize> CollisionOperator<N, B> for Trt {
+    fn name(&self) -> &'static str {
+        "TRT"
+    }
+
+    fn collide(&self, values: &CollisionValues<N, B>, relaxation_time: f32) -> [f32; B] {
+        let omega_plus = 1.0 / relaxation_time;
+        let omega_minus = 1.0 / (0.5 + self.magic_parameter / (relaxation_time - 0.5));
+
+        let distributions = values.distributions();
+        let equilibrium_distributions = values.equilibrium_distributions();
+        let forcing_terms = values.forcing_terms(1.0);
+        let opposite_lattice_vectors = values.opposite_lattice_vectors();
+
+        let mut result = [0.0; B];
+        for (i, (f_c, &opposite)) in izip!(&mut result, opposite_lattice_vectors).enumerate() {
+            let symmetric = |values: &[f32; B]| 0.5 * (values[i] + values[opposite]);
+            let antisymmetric = |values: &[f32; B]| 0.5 * (values[i] - values[opposite]);
+            *f_c = distributions[i]
+                - omega_plus * (symmetric(distributions) - symmetric(&equilibrium_distributions))
+                - omega_minus
+                    * (antisymmetric(distributions) - antisymmetric(&equilibrium_distributions))
+                + (1.0 - 0.5 * omega_plus) * symmetric(&forcing_terms)
+                + (1.0 - 0.5 * omega_minus) * antisymmetric(&forcing_terms);
+        }
+        result
+    }
+}
+
+/// [Multiple relaxation time (MRT)](https://doi.org/10.1103/PhysRevE.61.6546) operator for the
+/// D2Q9 parameters, relaxing the moments of the distributions at separate rates.
+///
+/// note: the moments are the density, energy, energy squared, momentum, energy flux and stress
+/// tensor of the lattice vectors of `parameters::d2q9`
+pub struct Mrt {
+    moments: Matrix<9, 9>,
+    inverse_moments: Matrix<9, 9>,
+    energy_rate: f32,
+    energy_squared_rate: f32,
+    energy_flux_rate: f32,
+}
+
+impl Mrt {
+    /// Create a new `Mrt` of the relaxation rates of the energy (of the bulk viscosity), energy
+    /// squared and energy flux moments.
+    pub fn new(energy_rate: f32, energy_squared_rate: f32, energy_flux_rate: f32) -> Self {
+        let moments = Matrix::new(std::array::from_fn(|k| {
+            parameters::d2q9::C.map(|[x, y]| {
+                let (x, y) = (x as f32, y as f32);
+                let c2 = x * x + y * y;
+                match k {
+                    0 => 1.0,
+                    1 => -4.0 + 3.0 * c2,
+                    2 => 4.0 - 10.5 * c2 + 4.5 * c2 * c2,
+                    3 => x,
+                    4 => (-5.0 + 3.0 * c2) * x,
+                    5 => y,
+                    6 => (-5.0 + 3.0 * c2) * y,
+                    7 => x * x - y * y,
+                    _ => x * y,
+                }
+            })
+        }));
+        // note: the moments are orthogonal, so the inverse is the transpose divided by the
+        // squared norms of the moments
+        let mut inverse_moments = moments.transpose();
+        for (k, row) in moments.0.iter().enumerate() {
+            let norm_squared = row.iter().map(|val| val * val).sum::<f32>();
+            for inverse_row in &mut inverse_moments.0 {
+                inverse_row[k] /= norm_squared;
+            }
+        }
+
+        Self {
+            moments,
+            inverse_moments,
+            energy_rate,
+            energy_squared_rate,
+            energy_flux_rate,
+        }
+    }
+}
+
+impl Default for Mrt {
+    /// Relaxation rates of [Lallemand and Luo](https://doi.org/10.1103/PhysRevE.61.6546).
+    fn default() -> Self {
+        Self::new(1.64, 1.54, 1.9)
+    }
+}
+
+impl CollisionOperator<2, 9> for Mrt {
+    fn name(&self) -> &'static str {
+        "MRT"
+    }
+
+    fn collide(&self, values: &CollisionValues<2, 9>, relaxation_time: f32) -> [f32; 9] {
+        let omega = 1.0 / relaxation_time;
+        // note: the rates of the conserved moments are irrelevant but for the forcing
+        let rates = [
+            0.0,
+            self.energy_rate,
+            self.energy_squared_rate,
+            0.0,
+            self.energy_flux_rate,
+            0.0,
+            self.energy_flux_rate,
+            omega,
+            omega,
+        ];
+
+        let moments = self.moments.multiply_vector(values.distributions());
+        let equilibrium_moments = self
+            .moments
+            .multiply_vector(&values.equilibrium_distributions());
+        let forcing_moments = self.moments.multiply_vector(&values.forcing_terms(1.0));
+
+        let mut result = [0.0; 9];
+        for (val, m, m_eq, s, rate) in izip!(
+            &mut result,
+            moments,
+            equilibrium_moments,
+            forcing_moments,
+            rates
+        ) {
+            *val = m - rate * (m - m_eq) + (1.0 - 0.5 * rate) * s;
+        }
+        self.inverse_moments.multiply_vector(&result)
+    }
+}
+
+/// [Cumulant](https://doi.org/10.1016/j.camwa.2015.05.001) operator for the D2Q9 parameters,
+/// relaxing the cumulants of the distributions (central moments in the frame moving with the
+/// fluid, up to the fourth order) at separate rates.
+///
+/// note: the force density is applied by reversing the first order central moments, which are
+/// minus half the force density as the velocity vector includes half the force density
+pub struct Cumulant {
+    // note: the raw moments of exponents (a, b) of the lattice vector components, at index 3 a + b
+    moments: Matrix<9, 9>,
+    inverse_moments: Matrix<9, 9>,
+    bulk_rate: f32,
+    third_order_rate: f32,
+    fourth_order_rate: f32,
+}
+
+impl Cumulant {
+    /// Create a new `Cumulant` of the relaxation rates of the trace of the second order cumulants
+    /// (of the bulk viscosity), and of the third and fourth order cumulants.
+    pub fn new(bulk_rate: f32, third_order_rate: f32, fourth_order_rate: f32) -> Self {
+        let mut moments = Matrix::zeros();
+        for (row, (a, b)) in izip!(&mut moments.0, iproduct!(0..3, 0..3)) {
+            *row = parameters::d2q9::C.map(|[x, y]| (x as f32).powi(a) * (y as f32).powi(b));
+        }
+
+        Self {
+            moments,
+            inverse_moments: moments
+                .inverse()
+                .expect("should have invertible D2Q9 moments"),
+            bulk_rate,
+            third_order_rate,
+            fourth_order_rate,
+        }
+    }
+
+    /// Moments shifted by a velocity vector, from central to raw moments (or raw to central
+    /// moments by the negated velocity vector).
+    fn shift(moments: &[f32; 9], velocity_vector: &[f32; 2]) -> [f32; 9] {
+        // note: the shift of each dimension is by the binomial expansion of (c + u)^a
+        let shift_1d =
+            |[m0, m1, m2]: [f32; 3], u: f32| [m0, m1 + u * m0, m2 + 2.0 * u * m1 + u * u * m0];
+
+        let mut result = *moments;
+        for a in 0..3 {
+            let [m0, m1, m2] = shift_1d(
+                [result[3 * a], result[3 * a + 1], result[3 * a + 2]],
+                velocity_vector[1],
+            );
+            (result[3 * a], result[3 * a + 1], result[3 * a + 2]) = (m0, m1, m2);
+        }
+        for b in 0..3 {
+            let [m0, m1, m2] = shift_1d(
+                [result[b], result[3 + b], result[6 + b]],
+                velocity_vector[0],
+            );
+            (result[b], result[3 + b], result[6 + b]) = (m0, m1, m2);
+        }
+        result
+    }
+}
+
+impl Default for Cumulant {
+    /// Relaxation rates of one, relaxing the higher order cumulants to equilibrium.
+    fn default() -> Self {
+        Self::new(1.0, 1.0, 1.0)
+    }
+}
+
+impl CollisionOperator<2, 9> for Cumulant {
+    fn name(&self) -> &'static str {
+        "Cumulant"
+    }
+
+    fn collide(&self, values: &CollisionValues<2, 9>, relaxation_time: f32) -> [f32; 9] {
+        let omega = 1.0 / relaxation_time;
+        let velocity_vector = values.velocity_vector();
+
+        let moments = self.moments.multiply_vector(values.distributions());
+        let [k00, k01, k02, k10, k11, k12, k20, k21, k22] =
+            Self::shift(&moments, &velocity_vector.map(|val| -val));
+        let density = k00;
+        if density <= 0.0 {
+            return *values.distributions();
+        }
+
+        // note: the fourth order cumulant is the central moment less the products of the
+        // second order central moments
+        let c22 = k22 - (k20 * k02 + 2.0 * k11 * k11) / density;
+
+        // note: the trace of the second order central moments relaxes to the equilibrium
+        // pressure, and the deviatoric part (of the shear viscosity) to zero
+        let trace = k20 + k02;
+        let trace = trace - self.bulk_rate * (trace - 2.0 * density * values.sound_speed_squared());
+        let difference = (1.0 - omega) * (k20 - k02);
+        let (k20, k02) = (0.5 * (trace + difference), 0.5 * (trace - difference));
+        let k11 = (1.0 - omega) * k11;
+        let (k21, k12) = (
+            (1.0 - self.third_order_rate) * k21,
+            (1.0 - self.third_order_rate) * k12,
+        );
+        let c22 = (1.0 - self.fourth_order_rate) * c22;
+        let k22 = c22 + (k20 * k02 + 2.0 * k11 * k11) / density;
+
+        let central_moments = [k00, -k01, k02, -k10, k11, k12, k20, k21, k22];
+        self.inverse_moments
+            .multiply_vector(&Self::shift(&central_moments, velocity_vector))
+    }
+}
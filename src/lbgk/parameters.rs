@@ -1 +1,2 @@
+pub mod d2q5;
 pub mod d2q9;
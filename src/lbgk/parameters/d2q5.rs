@@ -0,0 +1,14 @@
+/// Lattice vectors for the D2Q5 model (e.g. of advection–diffusion).
+/// Index for vectors:
+///         2
+///         |
+///     3 — 0 — 1
+///         |
+///         4
+pub const C: [[isize; 2]; 5] = [[0, 0], [1, 0], [0, 1], [-1, 0], [0, -1]];
+
+/// Weights corresponding to the lattice vectors for the D2Q5 model.
+pub const W: [f32; 5] = [1.0 / 3.0, 1.0 / 6.0, 1.0 / 6.0, 1.0 / 6.0, 1.0 / 6.0];
+
+/// Sound speed squared for the D2Q5 model.
+pub const CS2: f32 = 1.0 / 3.0;
@@ -0,0 +1,63 @@
+use itertools::izip;
+
+/// Iterator over the positions of a lattice, optionally restricted to a slice of particular
+/// dimensions.
+///
+/// note: positions advance fastest in the first dimension, matching the flat array index
+pub struct PositionIter<const N: usize> {
+    size: [usize; N],
+    dims: [bool; N],
+    pos: Option<[usize; N]>,
+}
+
+impl<const N: usize> PositionIter<N> {
+    /// Create a new `PositionIter` over all positions of a lattice size.
+    pub fn new(size: [usize; N]) -> Self {
+        Self::new_slice(size, [true; N], [0; N])
+    }
+
+    /// Create a new `PositionIter` over the positions varying in particular dimensions, with
+    /// the other dimensions fixed at those of a start position.
+    pub fn new_slice(size: [usize; N], dims: [bool; N], start: [usize; N]) -> Self {
+        let pos = izip!(&start, dims, size)
+            .all(|(pos, idx, size)| match idx {
+                true => size > 0,
+                false => *pos < size,
+            })
+            .then(|| {
+                let mut pos = start;
+                for (pos, idx) in izip!(&mut pos, dims) {
+                    if idx {
+                        *pos = 0;
+                    }
+                }
+                pos
+            });
+        Self { size, dims, pos }
+    }
+}
+
+impl<const N: usize> Iterator for PositionIter<N> {
+    type Item = [usize; N];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let result = self.pos?;
+
+        // advance the position (finished when all dimensions wrap around)
+        let mut pos = result;
+        let mut advanced = false;
+        for (pos, idx, size) in izip!(&mut pos, self.dims, self.size) {
+            if idx {
+                *pos += 1;
+                if *pos < size {
+                    advanced = true;
+                    break;
+                }
+                *pos = 0;
+            }
+        }
+        self.pos = advanced.then_some(pos);
+
+        Some(result)
+    }
+}
@@ -0,0 +1,37 @@
+/// Values at the lattice positions of an axis-aligned plane, ordered with the first dimension
+/// of the plane varying fastest (as image values).
+pub struct Slice {
+    size: [usize; 2],
+    /// Values, or `None` at object lattice positions.
+    values: Vec<Option<f32>>,
+}
+
+impl Slice {
+    /// Create a new `Slice` of a size from values.
+    pub(super) fn new(size: [usize; 2], values: Vec<Option<f32>>) -> Self {
+        Self { size, values }
+    }
+
+    /// Size of the plane.
+    pub fn size(&self) -> [usize; 2] {
+        self.size
+    }
+
+    /// Value at a position in the plane.
+    pub fn value(&self, pos: &[usize; 2]) -> Option<f32> {
+        self.values[self.size[0] * pos[1] + pos[0]]
+    }
+
+    /// All values.
+    pub fn values(&self) -> &[Option<f32>] {
+        &self.values
+    }
+
+    /// Minimum and maximum values, if any.
+    pub fn range(&self) -> Option<[f32; 2]> {
+        self.values.iter().flatten().fold(None, |range, val| {
+            let [min, max] = range.unwrap_or([*val; 2]);
+            Some([min.min(*val), max.max(*val)])
+        })
+    }
+}
@@ -1,83 +1,1803 @@
+pub mod advection_diffusion;
+pub mod benchmark;
+pub mod checkpoint;
 pub mod colour;
+pub mod compression;
+pub mod embedding;
+pub mod export;
+pub mod flow_lines;
 pub mod image_values;
+pub mod immersed_boundary;
+pub mod indexed_db;
+pub mod initial_condition;
 pub mod lbgk;
 pub mod linear_algebra;
+pub mod log;
 pub mod object;
+pub mod particles;
+pub mod probes;
+pub mod profiling;
+pub mod random;
+pub mod recording;
+pub mod residual_monitor;
+pub mod resolution;
+pub mod scenario;
+pub mod scene;
+pub mod simulation;
+pub mod snapshot;
+pub mod spectrum;
+pub mod stats;
+pub mod streaming;
+pub mod time_series;
+pub mod units;
+pub mod viewport;
+pub mod volume_renderer;
+pub mod vtk;
 
-use image_values::ImageValues;
-use lbgk::Lbgk;
+use advection_diffusion::AdvectionDiffusion;
+use checkpoint::{AutoCheckpoint, Checkpoint, IndexedDbStorage};
+use colour::Colormap;
+use flow_lines::{Pathlines, Streaklines};
+use image_values::contours::Contours;
+use image_values::line_integral_convolution::LineIntegralConvolution;
+use image_values::quiver::Quiver;
+use image_values::streamlines::{self, Streamlines};
+use image_values::{ImageValues, ScaleMode};
+use indexed_db::IndexedDb;
+use lbgk::{Field, Lbgk};
 use linear_algebra::VectorOps;
-use object::Object;
+use particles::Particles;
+use random::Random;
+use recording::{IndexedDbSink, Recording};
+use residual_monitor::ResidualMonitor;
+use scenario::Scenario;
+use scene::Scene;
+use simulation::{PartialConfig, Simulation};
+use streaming::WebSocketStream;
+use time_series::TimeSeries;
+use units::Units;
+use viewport::Viewport;
 
-use itertools::iproduct;
-use js_sys::Date;
+use itertools::{iproduct, izip};
 use wasm_bindgen::prelude::*;
 
 use std::cell::RefCell;
-use std::collections::VecDeque;
 use std::rc::Rc;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 const SIZE: [usize; 2] = [401, 201];
-const BOUNDARY_SCHEMES: [[lbgk::BoundaryScheme; 2]; 2] = [
-    [lbgk::BoundaryScheme::Inflow, lbgk::BoundaryScheme::Outflow],
-    [lbgk::BoundaryScheme::SpecularReflection; 2],
-];
+const SCENARIO: Scenario = Scenario::Cylinder;
+const SCENE_KEY_PREFIX: &str = "wasm-lbm scene ";
+const SCENE_LOAD_KEY: &str = "wasm-lbm load scene";
 
-const DENSITY: f32 = 1.0;
-const VELOCITY_VECTOR: [f32; 2] = [0.1, 0.0];
+const VELOCITY: f32 = 0.1;
 
 // Reynolds number (https://en.wikipedia.org/wiki/Reynolds_number)
 const RE: f32 = 200.0;
+/// Physical characteristic length (m) of the scenarios, for the physical time.
+const PHYSICAL_CHARACTERISTIC_LENGTH: f32 = 0.01;
+/// Physical kinematic viscosity (m²/s) of the fluid (water), for the physical time.
+const PHYSICAL_VISCOSITY: f32 = 1e-6;
+/// Style properties of the overlay shown on a panic.
+const PANIC_OVERLAY_STYLE: [(&str, &str); 10] = [
+    ("position", "fixed"),
+    ("top", "1em"),
+    ("left", "1em"),
+    ("right", "1em"),
+    ("margin", "0"),
+    ("padding", "1em"),
+    ("background", "#fff0f0"),
+    ("border", "2px solid darkred"),
+    ("white-space", "pre-wrap"),
+    ("z-index", "1000"),
+];
+/// Smallest relaxation time without a warning of instability.
+const MIN_STABLE_RELAXATION_TIME: f32 = 0.505;
+/// Largest Mach number without a warning of compressibility errors.
+const MAX_MACH_NUMBER: f32 = 0.3;
+const RE_RANGE: [f32; 3] = [10.0, 1000.0, 10.0];
+// Rayleigh number (https://en.wikipedia.org/wiki/Rayleigh_number) and Prandtl number
+// (https://en.wikipedia.org/wiki/Prandtl_number) of the thermal scenarios
+const RA: f32 = 10000.0;
+const PR: f32 = 0.71;
+const RA_RANGE: [f32; 3] = [1000.0, 100000.0, 1000.0];
+const PR_RANGE: [f32; 3] = [0.1, 10.0, 0.01];
+const VELOCITY_RANGE: [f32; 3] = [0.01, 0.2, 0.01];
 
-const RATE_MOVING_AVERAGE_PERIOD_SECS: f64 = 2.0;
+/// Iteration rate (iterations per second) of an iteration each frame of a typical display.
+const TARGET_RATE: f64 = 60.0;
+const RESIDUAL_INTERVAL: usize = 100;
+const RESIDUAL_TARGET: f32 = 1e-6;
 const DRAW_ITERATION_STEPS: usize = 10;
+const BATCH_COUNT: usize = 10000;
+/// Time spent iterating a batch each frame, so the page stays responsive.
+const BATCH_FRAME_MILLIS: f64 = 100.0;
+const FORCE_HISTORY_LENGTH: usize = 4000;
+const FORCE_CANVAS_HEIGHT: usize = 100;
+const PARTICLES_NAMES: [&str; 2] = ["Tracers", "Inertial particles"];
+const PARTICLES_COLOURS: [[&str; 2]; 2] = [["black", "black"], ["red", "darkred"]];
+const PARTICLES_CAPACITY: usize = 10000;
+const PARTICLES_SEED_COUNT: usize = 100;
+const PARTICLES_SEED_ITERATION_STEPS: usize = 20;
+
+const FLOW_LINES_LENGTH: usize = 500;
+const FLOW_LINES_SEED_COUNT: usize = 20;
+const STREAKLINES_RELEASE_INTERVAL: usize = 5;
+const STREAMLINES_STEP_SIZE: f32 = 0.5;
+const STREAMLINES_MAX_STEPS: usize = 2000;
+const STREAMLINES_SEED_COUNT: usize = 20;
+const QUIVER_STRIDE: usize = 10;
+const QUIVER_SCALE: f32 = 80.0;
+const CANVAS_COUNT: usize = 4;
+/// Canvas showing the temperature, in place of its field, in thermal scenarios.
+const TEMPERATURE_CANVAS: usize = 0;
+const FIELDS: [Field; CANVAS_COUNT] = [
+    Field::Density,
+    Field::Velocity,
+    Field::Vorticity,
+    Field::DensityGradient,
+];
+const COLORMAPS: [Colormap; CANVAS_COUNT] = [
+    Colormap::TwoHue,
+    Colormap::TwoHue,
+    Colormap::TwoHue,
+    Colormap::Grayscale,
+];
+const CUSTOM_COLORMAP: &str = "0 #000000, 0.4 #b00000, 0.7 #ff8000, 1 #ffffc0";
+// note: vorticity is amplified to show the weaker structures
+const SCALE_MODES: [ScaleMode; CANVAS_COUNT] = [
+    ScaleMode::Linear,
+    ScaleMode::Linear,
+    ScaleMode::Sqrt,
+    ScaleMode::Linear,
+];
+const SCALE_EXPONENT: f32 = 0.5;
+const FIXED_RANGES: [[f32; 2]; CANVAS_COUNT] =
+    [[0.98, 1.02], [0.0, 0.2], [-0.02, 0.02], [0.0, 1.0]];
+// note: larger values darken weaker density gradients
+const SCHLIEREN_SENSITIVITY: f32 = 10.0;
+const OUTLINE_COLOUR: [u8; 3] = [0, 160, 0];
+/// Colours of the objects when coloured, of the default object ID (e.g. painted) followed by
+/// those of the objects of the scenario in turn.
+const OBJECT_COLOURS: [[u8; 3]; 6] = [
+    [208, 208, 208],
+    [255, 204, 153],
+    [153, 204, 255],
+    [204, 255, 153],
+    [255, 153, 204],
+    [204, 153, 255],
+];
+const SINGLE_CANVAS_SCALE: usize = 2;
+const DISPLAY_SCALES: [usize; 4] = [1, 2, 3, 4];
+const VIEWPORT_ZOOM_FACTOR: f64 = 1.2;
+const VIEWPORT_MAX_ZOOM: f64 = 16.0;
+const BRUSH_RADIUS: f32 = 3.0;
+const STIR_VELOCITY: f32 = 0.05;
+/// Amplitude of the density of the pulses injected by clicking.
+const PULSE_AMPLITUDE: f32 = 0.01;
+const CHECKPOINT_DATABASE: &str = "wasm-lbm checkpoints";
+const CHECKPOINT_INTERVAL: usize = 1000;
+const CHECKPOINT_RETENTION: usize = 3;
+const RECORDING_DATABASE: &str = "wasm-lbm recordings";
+const RECORDING_SNAPSHOT_INTERVAL: usize = 1000;
+/// Rows of the time series of a recording written to IndexedDB at once.
+const RECORDING_CHUNK_LENGTH: usize = 1000;
+const STREAMING_URL: &str = "ws://localhost:8080";
+const STREAMING_STATS_INTERVAL: usize = 10;
+const STREAMING_SNAPSHOT_INTERVAL: usize = 1000;
+const READOUT_FIELDS: [Field; 4] = [
+    Field::Density,
+    Field::VelocityX,
+    Field::VelocityY,
+    Field::Vorticity,
+];
+const RANGE_SMOOTHING_WEIGHT: f32 = 0.1;
+const CONTOURS_COUNT: usize = 10;
+const LINE_INTEGRAL_CONVOLUTION_LENGTH: usize = 10;
+const LINE_INTEGRAL_CONVOLUTION_STEP_SIZE: f32 = 1.0;
+
+// Stokes number (https://en.wikipedia.org/wiki/Stokes_number) of the inertial particles
+const STOKES_NUMBER: f32 = 0.5;
+const GRAVITY: [f32; 2] = [0.0, 0.0];
+
+/// Last iteration performed, shown on a panic.
+static LAST_ITERATION: AtomicUsize = AtomicUsize::new(0);
 
 fn window() -> web_sys::Window {
     web_sys::window().expect("should have window")
 }
 
+/// Install a panic hook showing an overlay of the panic message and location, and the last
+/// iteration, on the page (as well as logging it).
+fn set_panic_hook() {
+    std::panic::set_hook(Box::new(|info| {
+        let message = info
+            .payload()
+            .downcast_ref::<&str>()
+            .map(|message| message.to_string())
+            .or_else(|| info.payload().downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown panic".to_string());
+        let location = info
+            .location()
+            .map(|location| {
+                format!(
+                    "{}:{}:{}",
+                    location.file(),
+                    location.line(),
+                    location.column()
+                )
+            })
+            .unwrap_or_default();
+        let iteration = LAST_ITERATION.load(Ordering::Relaxed);
+        let text = format!(
+            "The simulation stopped at iteration {iteration} due to an error.\n\n{message}\n\nat {location}\n\nReload the page to restart."
+        );
+        log::error("panic", &text);
+
+        let _ = (|| -> Result<(), JsValue> {
+            let document = window().document().ok_or("should have document")?;
+            let body = document.body().ok_or("should have document body")?;
+            let overlay = document
+                .create_element("pre")?
+                .dyn_into::<web_sys::HtmlElement>()?;
+            for (property, value) in PANIC_OVERLAY_STYLE {
+                overlay.style().set_property(property, value)?;
+            }
+            overlay.set_text_content(Some(&text));
+            body.append_child(&overlay)?;
+            Ok(())
+        })();
+    }));
+}
+
 fn request_animation_frame(f: &Closure<dyn FnMut()>) {
     window()
         .request_animation_frame(f.as_ref().unchecked_ref())
         .expect("should register request animation frame callback");
 }
 
+/// Log the flow parameters, with warnings where the flow may be unstable or inaccurate.
+fn log_flow_parameters(
+    lbgk: &Lbgk<2, 9>,
+    velocity: f32,
+    reynolds_number: f32,
+    relaxation_time: f32,
+) {
+    log::info(
+        "parameters",
+        &format!("velocity {velocity}, Reynolds number {reynolds_number}, relaxation time {relaxation_time}"),
+    );
+    if relaxation_time < MIN_STABLE_RELAXATION_TIME {
+        log::warn(
+            "parameters",
+            &format!("relaxation time {relaxation_time} is close to 0.5, so the flow may be unstable (reduce the Reynolds number or increase the velocity)"),
+        );
+    }
+    // note: compressibility errors grow with the square of the Mach number
+    let mach_number = velocity / lbgk.sound_speed_squared().sqrt();
+    if mach_number > MAX_MACH_NUMBER {
+        log::warn(
+            "parameters",
+            &format!("Mach number {mach_number} is large, so the flow may be inaccurate or unstable (reduce the velocity)"),
+        );
+    }
+}
+
+/// Log the throughput of this device measured by a micro-benchmark, with a warning and the
+/// recommended lattice size if too slow for the target rate.
+fn log_throughput() {
+    let throughput = resolution::measure_throughput(
+        &resolution::BENCHMARK_SIZE,
+        resolution::BENCHMARK_ITERATIONS,
+    );
+    let rate = throughput * 1.0e6 / (SIZE[0] * SIZE[1]) as f64;
+    log::info(
+        "benchmark",
+        &format!("throughput {throughput:.1} MLUPS, so about {rate:.0} iterations per second"),
+    );
+    if rate < TARGET_RATE {
+        let size = resolution::recommended_size(&SIZE, 1, throughput, TARGET_RATE);
+        log::warn(
+            "benchmark",
+            &format!("iteration rate is below {TARGET_RATE} per second, so a lattice size of {} by {} is recommended", size[0], size[1]),
+        );
+    }
+}
+
+/// Paint (or erase) objects on the lattice of a simulation within a circular brush.
+fn paint_objects(simulation: &mut Simulation, centre: &[f32; 2], radius: f32, val: bool) {
+    let [x0, y0] = [0, 1].map(|d| (centre[d] - radius).floor().max(0.0) as usize);
+    let [x1, y1] = [0, 1].map(|d| ((centre[d] + radius).ceil() as usize).min(SIZE[d] - 1));
+    for pos in iproduct!(x0..=x1, y0..=y1).map(|(x, y)| [x, y]) {
+        let [dx, dy] = [0, 1].map(|d| pos[d] as f32 - centre[d]);
+        if dx * dx + dy * dy <= radius * radius {
+            simulation.paint_object(&pos, val);
+        }
+    }
+}
+
+/// Stir the fluid by adding a velocity with a
+/// [Gaussian](https://en.wikipedia.org/wiki/Gaussian_function) profile of standard deviation
+/// `radius` about a centre.
+fn stir(lbgk: &mut Lbgk<2, 9>, centre: &[f32; 2], velocity_vector: &[f32; 2], radius: f32) {
+    // note: the profile is truncated at three standard deviations
+    let extent = 3.0 * radius;
+    let [x0, y0] = [0, 1].map(|d| (centre[d] - extent).floor().max(0.0) as usize);
+    let [x1, y1] = [0, 1].map(|d| ((centre[d] + extent).ceil() as usize).min(SIZE[d] - 1));
+    for pos in iproduct!(x0..=x1, y0..=y1).map(|(x, y)| [x, y]) {
+        let [dx, dy] = [0, 1].map(|d| pos[d] as f32 - centre[d]);
+        let weight = (-(dx * dx + dy * dy) / (2.0 * radius * radius)).exp();
+        lbgk.add_velocity(&pos, &velocity_vector.map(|val| weight * val));
+    }
+}
+
+/// Name of a field canvas.
+fn canvas_name(i: usize) -> String {
+    format!("Canvas {}", i + 1)
+}
+
+/// Effects of dragging the mouse on the field canvases.
+#[derive(Clone, Copy, PartialEq)]
+enum Interaction {
+    /// Pan the viewport.
+    Pan,
+    /// Paint objects.
+    Paint,
+    /// Erase objects, converting them back to fluid.
+    Erase,
+    /// Stir the fluid in the direction of the mouse movement.
+    Stir,
+    /// Inject a pulse of pressure radiating sound.
+    Pulse,
+}
+
+impl Interaction {
+    const ALL: [Interaction; 5] = [
+        Interaction::Pan,
+        Interaction::Paint,
+        Interaction::Erase,
+        Interaction::Stir,
+        Interaction::Pulse,
+    ];
+
+    fn name(&self) -> &'static str {
+        match self {
+            Interaction::Pan => "Pan",
+            Interaction::Paint => "Paint objects",
+            Interaction::Erase => "Erase objects",
+            Interaction::Stir => "Stir",
+            Interaction::Pulse => "Pulse",
+        }
+    }
+}
+
+/// Colour filling the lattice positions of an object ID, white unless coloured.
+fn object_colour(id: u16, coloured: bool) -> [u8; 3] {
+    match (coloured, id) {
+        (false, _) | (true, 0) => [u8::MAX; 3],
+        (true, id) => {
+            OBJECT_COLOURS[(id - lbgk::DEFAULT_OBJECT_ID) as usize % OBJECT_COLOURS.len()]
+        }
+    }
+}
+
+/// Set image values to a field, with the colour scale centred on the undisturbed flow.
+///
+/// note: the density gradient is shown as a
+/// [numerical schlieren](https://en.wikipedia.org/wiki/Schlieren_photography) image,
+/// exp(-k |∇ρ| / max |∇ρ|), and the stream function is centred on the middle of its range
+fn set_field_values(lbgk: &Lbgk<2, 9>, field: Field, image_values: &mut ImageValues) {
+    // note: the stream function is integrated for all lattice positions at once
+    let stream_functions = (field == Field::StreamFunction).then(|| lbgk.stream_functions());
+    let slice = lbgk.slice([0, 1], &[0, 0], |pos| match &stream_functions {
+        Some(stream_functions) => stream_functions[SIZE[0] * pos[1] + pos[0]],
+        None => lbgk.field(field, pos),
+    });
+    image_values.set_slice(&slice);
+    let [min, max] = slice.range().unwrap_or([0.0; 2]);
+
+    match field {
+        Field::DensityGradient => {
+            let max = max.max(f32::MIN_POSITIVE);
+            for pos in lbgk.positions() {
+                if !lbgk.object(&pos) {
+                    let val = lbgk.field(field, &pos);
+                    image_values.set_value(&pos, Some((-SCHLIEREN_SENSITIVITY * val / max).exp()));
+                }
+            }
+            image_values.set_standard_value(0.5);
+            image_values.set_minimum_value(0.0);
+            image_values.set_maximum_value(1.0);
+        }
+        Field::StreamFunction => {
+            image_values.set_standard_value(0.5 * (min + max));
+            image_values.set_minimum_value(min);
+            image_values.set_maximum_value(max);
+        }
+        _ => {
+            image_values.set_standard_value(lbgk.source_field(field));
+            image_values.set_minimum_value(min);
+            image_values.set_maximum_value(max);
+        }
+    }
+}
+
+/// Set the image values to the temperatures of a thermal scenario.
+fn set_temperature_values(
+    lbgk: &Lbgk<2, 9>,
+    advection_diffusion: &AdvectionDiffusion,
+    image_values: &mut ImageValues,
+) {
+    let slice = lbgk.slice([0, 1], &[0, 0], |pos| advection_diffusion.value(pos));
+    image_values.set_slice(&slice);
+    let [min, max] = slice.range().unwrap_or([0.0; 2]);
+    image_values.set_standard_value(0.5 * (min + max));
+    image_values.set_minimum_value(min);
+    image_values.set_maximum_value(max);
+}
+
+fn create_canvas_rendering_context(
+    document: &web_sys::Document,
+    body: &web_sys::HtmlElement,
+    name: &str,
+    size: &[usize; 2],
+) -> web_sys::CanvasRenderingContext2d {
+    let div = document.create_element("div").unwrap();
+    div.set_text_content(Some(name));
+    body.append_child(&div).unwrap();
+
+    create_bare_canvas_rendering_context(document, body, size)
+}
+
+fn create_bare_canvas_rendering_context(
+    document: &web_sys::Document,
+    body: &web_sys::HtmlElement,
+    size: &[usize; 2],
+) -> web_sys::CanvasRenderingContext2d {
+    // note: the container allows overlay canvases to be positioned over the canvas
+    let container = document.create_element("div").unwrap();
+    container
+        .set_attribute("style", "position: relative; display: inline-block")
+        .unwrap();
+    body.append_child(&container).unwrap();
+
+    let canvas = document
+        .create_element("canvas")
+        .unwrap()
+        .dyn_into::<web_sys::HtmlCanvasElement>()
+        .unwrap();
+    canvas.set_width(size[0] as u32);
+    canvas.set_height(size[1] as u32);
+    container.append_child(&canvas).unwrap();
+
+    canvas
+        .get_context("2d")
+        .unwrap()
+        .expect("should have 2d context")
+        .dyn_into::<web_sys::CanvasRenderingContext2d>()
+        .unwrap()
+}
+
+fn create_field_canvas_rendering_context(
+    document: &web_sys::Document,
+    body: &web_sys::HtmlElement,
+    visualisation: Rc<Visualisation>,
+    i: usize,
+) -> web_sys::CanvasRenderingContext2d {
+    let div = document.create_element("div").unwrap();
+    div.set_text_content(Some(&format!("{} ", canvas_name(i))));
+    body.append_child(&div).unwrap();
+
+    let names = Field::ALL.map(|field| field.name());
+    create_select(
+        document,
+        &div,
+        "",
+        &names,
+        Field::ALL
+            .iter()
+            .position(|&field| field == *visualisation.fields[i].borrow())
+            .unwrap_or(0),
+        move |selected| {
+            *visualisation.fields[i].borrow_mut() = Field::ALL[selected];
+        },
+    )
+    .unwrap();
+
+    create_bare_canvas_rendering_context(document, body, &SIZE)
+}
+
+fn create_overlay_canvas_rendering_context(
+    document: &web_sys::Document,
+    canvas_rendering_context: &web_sys::CanvasRenderingContext2d,
+) -> web_sys::CanvasRenderingContext2d {
+    let canvas = canvas_rendering_context.canvas().unwrap();
+
+    let overlay_canvas = document
+        .create_element("canvas")
+        .unwrap()
+        .dyn_into::<web_sys::HtmlCanvasElement>()
+        .unwrap();
+    overlay_canvas.set_width(canvas.width());
+    overlay_canvas.set_height(canvas.height());
+    overlay_canvas
+        .set_attribute("style", "position: absolute; left: 0; top: 0")
+        .unwrap();
+    canvas
+        .parent_element()
+        .unwrap()
+        .append_child(&overlay_canvas)
+        .unwrap();
+
+    overlay_canvas
+        .get_context("2d")
+        .unwrap()
+        .expect("should have 2d context")
+        .dyn_into::<web_sys::CanvasRenderingContext2d>()
+        .unwrap()
+}
+
+fn create_button(
+    document: &web_sys::Document,
+    parent: &web_sys::Element,
+    text: &str,
+    mut f: impl FnMut() + 'static,
+) -> Result<web_sys::HtmlButtonElement, JsValue> {
+    let button = document
+        .create_element("button")?
+        .dyn_into::<web_sys::HtmlButtonElement>()?;
+    button.set_text_content(Some(text));
+    parent.append_child(&button)?;
+
+    let closure = Closure::<dyn FnMut(_)>::new(move |_: web_sys::Event| f());
+    button.add_event_listener_with_callback("click", closure.as_ref().unchecked_ref())?;
+    closure.forget();
+
+    Ok(button)
+}
+
+fn create_input(
+    document: &web_sys::Document,
+    parent: &web_sys::Element,
+    label: &str,
+    input_type: &str,
+    value: &str,
+    mut f: impl FnMut(&web_sys::HtmlInputElement) + 'static,
+) -> Result<web_sys::HtmlInputElement, JsValue> {
+    let label_element = document.create_element("label")?;
+    label_element.set_text_content(Some(label));
+    parent.append_child(&label_element)?;
+
+    let input = document
+        .create_element("input")?
+        .dyn_into::<web_sys::HtmlInputElement>()?;
+    input.set_type(input_type);
+    match input_type {
+        "checkbox" => input.set_checked(value == "true"),
+        _ => input.set_value(value),
+    }
+    label_element.append_child(&input)?;
+
+    let input_clone = input.clone();
+    let closure = Closure::<dyn FnMut(_)>::new(move |_: web_sys::Event| f(&input_clone));
+    input.add_event_listener_with_callback("change", closure.as_ref().unchecked_ref())?;
+    closure.forget();
+
+    Ok(input)
+}
+
+fn create_select(
+    document: &web_sys::Document,
+    parent: &web_sys::Element,
+    label: &str,
+    options: &[&str],
+    selected: usize,
+    mut f: impl FnMut(usize) + 'static,
+) -> Result<web_sys::HtmlSelectElement, JsValue> {
+    let label_element = document.create_element("label")?;
+    label_element.set_text_content(Some(label));
+    parent.append_child(&label_element)?;
+
+    let select = document
+        .create_element("select")?
+        .dyn_into::<web_sys::HtmlSelectElement>()?;
+    for option in options {
+        let option_element = document
+            .create_element("option")?
+            .dyn_into::<web_sys::HtmlOptionElement>()?;
+        option_element.set_text(option);
+        select.append_child(&option_element)?;
+    }
+    select.set_selected_index(selected as i32);
+    label_element.append_child(&select)?;
+
+    let select_clone = select.clone();
+    let closure = Closure::<dyn FnMut(_)>::new(move |_: web_sys::Event| {
+        f(select_clone.selected_index().max(0) as usize)
+    });
+    select.add_event_listener_with_callback("change", closure.as_ref().unchecked_ref())?;
+    closure.forget();
+
+    Ok(select)
+}
+
+fn create_quiver_controls(
+    document: &web_sys::Document,
+    body: &web_sys::HtmlElement,
+    visualisation: Rc<Visualisation>,
+) -> Result<(), JsValue> {
+    let div = document.create_element("div")?;
+    div.set_text_content(Some("Velocity arrows "));
+    body.append_child(&div)?;
+
+    let visualisation_clone = visualisation.clone();
+    create_input(document, &div, "Show ", "checkbox", "false", move |input| {
+        *visualisation_clone.quiver_shown.borrow_mut() = input.checked();
+    })?;
+    let visualisation_clone = visualisation.clone();
+    create_input(
+        document,
+        &div,
+        " Stride ",
+        "number",
+        &QUIVER_STRIDE.to_string(),
+        move |input| {
+            if let Ok(stride) = input.value().parse() {
+                visualisation_clone.quiver.borrow_mut().set_stride(stride);
+            }
+        },
+    )?;
+    create_input(
+        document,
+        &div,
+        " Scale ",
+        "number",
+        &QUIVER_SCALE.to_string(),
+        move |input| {
+            if let Ok(scale) = input.value().parse() {
+                visualisation.quiver.borrow_mut().set_scale(scale);
+            }
+        },
+    )?;
+
+    Ok(())
+}
+
+/// Lay out the field canvases (and their overlays), showing all or a single field canvas
+/// enlarged, with display pixels per lattice position.
+///
+/// note: the canvases are sized in CSS pixels, with backing stores scaled by the
+/// [device pixel ratio](https://developer.mozilla.org/en-US/docs/Web/API/Window/devicePixelRatio)
+/// to be crisp on high density displays
+fn layout_field_canvases(
+    canvas_rendering_contexts: &[web_sys::CanvasRenderingContext2d],
+    single: Option<usize>,
+    display_scale: usize,
+    viewport: &Viewport,
+) -> Result<(), JsValue> {
+    let pixel_scale = pixel_scale(display_scale);
+    let width = SIZE[0]
+        * display_scale
+        * match single {
+            Some(_) => SINGLE_CANVAS_SCALE,
+            None => 1,
+        };
+
+    for (i, canvas_rendering_context) in canvas_rendering_contexts.iter().enumerate() {
+        let canvas = canvas_rendering_context
+            .canvas()
+            .ok_or("should have canvas")?;
+        let container = canvas.parent_element().ok_or("should have container")?;
+        let title = container
+            .previous_element_sibling()
+            .ok_or("should have title")?;
+
+        let display = match single {
+            Some(single) if single != i => "none",
+            _ => "",
+        };
+        for element in [&title, &container] {
+            element
+                .dyn_ref::<web_sys::HtmlElement>()
+                .ok_or("should be HTML element")?
+                .style()
+                .set_property("display", display)?;
+        }
+
+        for canvas in container_canvases(canvas_rendering_context)? {
+            canvas
+                .style()
+                .set_property("width", &format!("{width}px"))?;
+            let size = [SIZE[0] * pixel_scale, SIZE[1] * pixel_scale].map(|val| val as u32);
+            // note: resizing resets the rendering context
+            if [canvas.width(), canvas.height()] != size {
+                canvas.set_width(size[0]);
+                canvas.set_height(size[1]);
+            }
+            let canvas_rendering_context = canvas
+                .get_context("2d")?
+                .ok_or("should have 2d context")?
+                .dyn_into::<web_sys::CanvasRenderingContext2d>()?;
+            set_lattice_transform(&canvas_rendering_context, pixel_scale, viewport)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Backing store pixels per lattice position for display pixels per lattice position.
+fn pixel_scale(display_scale: usize) -> usize {
+    ((display_scale as f64 * window().device_pixel_ratio()).round() as usize).max(1)
+}
+
+/// Canvas and overlay canvases in the container of a canvas.
+fn container_canvases(
+    canvas_rendering_context: &web_sys::CanvasRenderingContext2d,
+) -> Result<Vec<web_sys::HtmlCanvasElement>, JsValue> {
+    let canvas = canvas_rendering_context
+        .canvas()
+        .ok_or("should have canvas")?;
+    let container = canvas.parent_element().ok_or("should have container")?;
+    let children = container.children();
+    Ok((0..children.length())
+        .filter_map(|i| children.item(i))
+        .filter_map(|child| child.dyn_into::<web_sys::HtmlCanvasElement>().ok())
+        .collect())
+}
+
+/// Set the transform of a field canvas rendering context from lattice coordinates (with the
+/// lattice y-axis pointing up the canvas) through a viewport, for backing store pixels per
+/// lattice position.
+fn set_lattice_transform(
+    canvas_rendering_context: &web_sys::CanvasRenderingContext2d,
+    pixel_scale: usize,
+    viewport: &Viewport,
+) -> Result<(), JsValue> {
+    let [a, b, c, d, e, f] = viewport.transform(pixel_scale as f64);
+    canvas_rendering_context.set_transform(a, b, c, d, e, f)
+}
+
+/// Clear a canvas regardless of its transform.
+fn clear_canvas(
+    canvas_rendering_context: &web_sys::CanvasRenderingContext2d,
+) -> Result<(), JsValue> {
+    let canvas = canvas_rendering_context
+        .canvas()
+        .ok_or("should have canvas")?;
+    canvas_rendering_context.save();
+    canvas_rendering_context.set_transform(1.0, 0.0, 0.0, 1.0, 0.0, 0.0)?;
+    canvas_rendering_context.clear_rect(0.0, 0.0, canvas.width() as f64, canvas.height() as f64);
+    canvas_rendering_context.restore();
+    Ok(())
+}
+
+/// Zoom the field canvases with the mouse wheel, pan by dragging and reset by double clicking.
+fn add_viewport_listeners(
+    canvas_rendering_contexts: &[web_sys::CanvasRenderingContext2d; CANVAS_COUNT],
+    visualisation: Rc<Visualisation>,
+    layout: Rc<impl Fn() + 'static>,
+) -> Result<(), JsValue> {
+    // note: CSS pixels per lattice position of the canvas being dragged, if any
+    let dragging = Rc::new(RefCell::new(None::<f64>));
+
+    for canvas_rendering_context in canvas_rendering_contexts {
+        let canvas = canvas_rendering_context
+            .canvas()
+            .ok_or("should have canvas")?;
+        let container = canvas.parent_element().ok_or("should have container")?;
+
+        let container_clone = container.clone();
+        let visualisation_clone = visualisation.clone();
+        let layout_clone = layout.clone();
+        let closure = Closure::<dyn FnMut(_)>::new(move |event: web_sys::WheelEvent| {
+            event.prevent_default();
+            let css_scale = container_clone.client_width() as f64 / SIZE[0] as f64;
+            {
+                let mut viewport = visualisation_clone.viewport.borrow_mut();
+                let pos = viewport.lattice_position(
+                    &[event.offset_x() as f64, event.offset_y() as f64],
+                    css_scale,
+                );
+                let factor = match event.delta_y() < 0.0 {
+                    true => VIEWPORT_ZOOM_FACTOR,
+                    false => 1.0 / VIEWPORT_ZOOM_FACTOR,
+                };
+                viewport.zoom_about(&pos, factor, VIEWPORT_MAX_ZOOM);
+            }
+            layout_clone();
+        });
+        container.add_event_listener_with_callback("wheel", closure.as_ref().unchecked_ref())?;
+        closure.forget();
+
+        let container_clone = container.clone();
+        let visualisation_clone = visualisation.clone();
+        let dragging_clone = dragging.clone();
+        let closure = Closure::<dyn FnMut(_)>::new(move |_: web_sys::MouseEvent| {
+            if *visualisation_clone.interaction.borrow() == Interaction::Pan {
+                *dragging_clone.borrow_mut() =
+                    Some(container_clone.client_width() as f64 / SIZE[0] as f64);
+            }
+        });
+        container
+            .add_event_listener_with_callback("mousedown", closure.as_ref().unchecked_ref())?;
+        closure.forget();
+
+        let visualisation_clone = visualisation.clone();
+        let layout_clone = layout.clone();
+        let closure = Closure::<dyn FnMut(_)>::new(move |_: web_sys::MouseEvent| {
+            visualisation_clone.viewport.borrow_mut().reset();
+            layout_clone();
+        });
+        container.add_event_listener_with_callback("dblclick", closure.as_ref().unchecked_ref())?;
+        closure.forget();
+    }
+
+    let dragging_clone = dragging.clone();
+    let closure = Closure::<dyn FnMut(_)>::new(move |event: web_sys::MouseEvent| {
+        if let Some(css_scale) = *dragging_clone.borrow() {
+            {
+                let mut viewport = visualisation.viewport.borrow_mut();
+                let scale = css_scale * viewport.zoom();
+                viewport.pan(&[
+                    event.movement_x() as f64 / scale,
+                    -event.movement_y() as f64 / scale,
+                ]);
+            }
+            layout();
+        }
+    });
+    window().add_event_listener_with_callback("mousemove", closure.as_ref().unchecked_ref())?;
+    closure.forget();
+
+    let closure = Closure::<dyn FnMut(_)>::new(move |_: web_sys::MouseEvent| {
+        *dragging.borrow_mut() = None;
+    });
+    window().add_event_listener_with_callback("mouseup", closure.as_ref().unchecked_ref())?;
+    closure.forget();
+
+    Ok(())
+}
+
+fn create_interaction_controls(
+    document: &web_sys::Document,
+    body: &web_sys::HtmlElement,
+    visualisation: Rc<Visualisation>,
+    canvas_rendering_contexts: &[web_sys::CanvasRenderingContext2d; CANVAS_COUNT],
+) -> Result<(), JsValue> {
+    let div = document.create_element("div")?;
+    body.append_child(&div)?;
+
+    {
+        let visualisation = visualisation.clone();
+        create_select(
+            document,
+            &div,
+            "Mouse drag ",
+            &Interaction::ALL.map(|interaction| interaction.name()),
+            0,
+            move |index| *visualisation.interaction.borrow_mut() = Interaction::ALL[index],
+        )?;
+    }
+    {
+        let visualisation = visualisation.clone();
+        create_input(
+            document,
+            &div,
+            " Brush radius ",
+            "number",
+            &BRUSH_RADIUS.to_string(),
+            move |input| {
+                if let Ok(radius) = input.value().parse::<f32>() {
+                    *visualisation.brush_radius.borrow_mut() = radius.max(0.5);
+                }
+            },
+        )?;
+    }
+
+    for canvas_rendering_context in canvas_rendering_contexts {
+        let canvas = canvas_rendering_context
+            .canvas()
+            .ok_or("should have canvas")?;
+        let container = canvas.parent_element().ok_or("should have container")?;
+
+        let container_clone = container.clone();
+        let visualisation = visualisation.clone();
+        let closure = Closure::<dyn FnMut(_)>::new(move |event: web_sys::MouseEvent| {
+            // note: only while the primary button is pressed
+            if event.buttons() & 1 == 0 {
+                return;
+            }
+            interact(
+                &visualisation,
+                container_clone.client_width() as f64 / SIZE[0] as f64,
+                &[event.offset_x() as f64, event.offset_y() as f64],
+                &[event.movement_x() as f64, event.movement_y() as f64],
+            );
+        });
+        for event_type in ["mousedown", "mousemove"] {
+            container
+                .add_event_listener_with_callback(event_type, closure.as_ref().unchecked_ref())?;
+        }
+        closure.forget();
+    }
+
+    Ok(())
+}
+
+/// Paint, erase, stir or pulse at a CSS pixel position within a field canvas, moving by a CSS pixel
+/// displacement.
+fn interact(
+    visualisation: &Visualisation,
+    css_scale: f64,
+    css_pos: &[f64; 2],
+    css_movement: &[f64; 2],
+) {
+    let pos = visualisation
+        .viewport
+        .borrow()
+        .lattice_position(css_pos, css_scale)
+        .map(|val| val as f32);
+    match *visualisation.interaction.borrow() {
+        Interaction::Pan => {}
+        Interaction::Paint => visualisation.object_edits.borrow_mut().push((pos, true)),
+        Interaction::Erase => visualisation.object_edits.borrow_mut().push((pos, false)),
+        Interaction::Stir => {
+            // note: canvas y-axis points down
+            let movement = [css_movement[0] as f32, -css_movement[1] as f32];
+            let length = movement.norm();
+            if length > 0.0 {
+                visualisation
+                    .stirs
+                    .borrow_mut()
+                    .push((pos, movement.map(|val| STIR_VELOCITY * val / length)));
+            }
+        }
+        // note: once per press, not while dragging
+        Interaction::Pulse => {
+            if *css_movement == [0.0; 2] {
+                visualisation.pulses.borrow_mut().push(pos);
+            }
+        }
+    }
+}
+
+/// Track the lattice position under the mouse on the field canvases.
+fn add_readout_listeners(
+    canvas_rendering_contexts: &[web_sys::CanvasRenderingContext2d; CANVAS_COUNT],
+    visualisation: Rc<Visualisation>,
+) -> Result<(), JsValue> {
+    for canvas_rendering_context in canvas_rendering_contexts {
+        let canvas = canvas_rendering_context
+            .canvas()
+            .ok_or("should have canvas")?;
+        let container = canvas.parent_element().ok_or("should have container")?;
+
+        let container_clone = container.clone();
+        let visualisation_clone = visualisation.clone();
+        let closure = Closure::<dyn FnMut(_)>::new(move |event: web_sys::MouseEvent| {
+            let css_scale = container_clone.client_width() as f64 / SIZE[0] as f64;
+            let pos = visualisation_clone.viewport.borrow().lattice_position(
+                &[event.offset_x() as f64, event.offset_y() as f64],
+                css_scale,
+            );
+            *visualisation_clone.hover_position.borrow_mut() = Some(pos.map(|val| val as f32));
+        });
+        container
+            .add_event_listener_with_callback("mousemove", closure.as_ref().unchecked_ref())?;
+        closure.forget();
+
+        let visualisation_clone = visualisation.clone();
+        let closure = Closure::<dyn FnMut(_)>::new(move |_: web_sys::MouseEvent| {
+            *visualisation_clone.hover_position.borrow_mut() = None;
+        });
+        container
+            .add_event_listener_with_callback("mouseleave", closure.as_ref().unchecked_ref())?;
+        closure.forget();
+    }
+
+    Ok(())
+}
+
+/// Readout of the field values at the lattice position nearest a position, if on the lattice.
+fn readout(lbgk: &Lbgk<2, 9>, pos: &[f32; 2]) -> Option<String> {
+    let pos = [0, 1].map(|d| pos[d].round());
+    if (0..2).any(|d| !(0.0..SIZE[d] as f32).contains(&pos[d])) {
+        return None;
+    }
+    let pos = pos.map(|val| val as usize);
+
+    let values = match lbgk.object(&pos) {
+        true => "object".to_string(),
+        false => READOUT_FIELDS
+            .iter()
+            .map(|field| format!("{} {:.5}", field.name(), lbgk.field(*field, &pos)))
+            .collect::<Vec<_>>()
+            .join(", "),
+    };
+    Some(format!("Position ({}, {}): {values}", pos[0], pos[1]))
+}
+
+/// Create a slider for a flow parameter over a range (minimum, maximum and step).
+fn create_flow_parameter_input(
+    document: &web_sys::Document,
+    parent: &web_sys::Element,
+    visualisation: Rc<Visualisation>,
+    label: &str,
+    [min, max, step]: [f32; 3],
+    parameter: fn(&Visualisation) -> &RefCell<f32>,
+) -> Result<(), JsValue> {
+    let value = parameter(&visualisation).borrow().to_string();
+    let input = create_input(document, parent, label, "range", &value, move |input| {
+        if let Ok(val) = input.value().parse::<f32>() {
+            *parameter(&visualisation).borrow_mut() = val;
+        }
+    })?;
+    input.set_min(&min.to_string());
+    input.set_max(&max.to_string());
+    input.set_step(&step.to_string());
+
+    Ok(())
+}
+
+/// Zoom the field canvases by pinching with two touches, and pan by dragging with two touches
+/// (or one touch, when panning is the mouse drag interaction).
+fn add_touch_listeners(
+    canvas_rendering_contexts: &[web_sys::CanvasRenderingContext2d; CANVAS_COUNT],
+    visualisation: Rc<Visualisation>,
+    layout: Rc<impl Fn() + 'static>,
+) -> Result<(), JsValue> {
+    // note: CSS pixel positions of the touches within the container at the last event
+    let previous_touches = Rc::new(RefCell::new(Vec::<[f64; 2]>::new()));
+
+    for canvas_rendering_context in canvas_rendering_contexts {
+        let canvas = canvas_rendering_context
+            .canvas()
+            .ok_or("should have canvas")?;
+        let container = canvas.parent_element().ok_or("should have container")?;
+
+        let container_clone = container.clone();
+        let visualisation = visualisation.clone();
+        let layout = layout.clone();
+        let previous_touches = previous_touches.clone();
+        let closure = Closure::<dyn FnMut(_)>::new(move |event: web_sys::TouchEvent| {
+            // note: prevents scrolling and the emulated mouse events
+            event.prevent_default();
+            let rect = container_clone.get_bounding_client_rect();
+            let touch_list = event.touches();
+            let touches = (0..touch_list.length())
+                .filter_map(|i| touch_list.get(i))
+                .map(|touch| {
+                    [
+                        touch.client_x() as f64 - rect.left(),
+                        touch.client_y() as f64 - rect.top(),
+                    ]
+                })
+                .collect::<Vec<_>>();
+            let previous = previous_touches.replace(touches.clone());
+            // note: lifting a touch only updates the touches, and touches start singly
+            match event.type_().as_str() {
+                "touchend" | "touchcancel" => return,
+                "touchstart" if touches.len() != 1 => return,
+                _ => {}
+            }
+
+            let css_scale = container_clone.client_width() as f64 / SIZE[0] as f64;
+            match (&touches[..], &previous[..]) {
+                ([touch], [previous])
+                    if *visualisation.interaction.borrow() == Interaction::Pan =>
+                {
+                    {
+                        let mut viewport = visualisation.viewport.borrow_mut();
+                        let scale = css_scale * viewport.zoom();
+                        viewport.pan(&[
+                            (touch[0] - previous[0]) / scale,
+                            -(touch[1] - previous[1]) / scale,
+                        ]);
+                    }
+                    layout();
+                }
+                ([touch], _) => {
+                    let movement = match &previous[..] {
+                        [previous] => [touch[0] - previous[0], touch[1] - previous[1]],
+                        _ => [0.0; 2],
+                    };
+                    interact(&visualisation, css_scale, touch, &movement);
+                }
+                ([first, second], [previous_first, previous_second]) => {
+                    let centre = |first: &[f64; 2], second: &[f64; 2]| {
+                        [0, 1].map(|d| 0.5 * (first[d] + second[d]))
+                    };
+                    let distance = |first: &[f64; 2], second: &[f64; 2]| {
+                        (first[0] - second[0]).hypot(first[1] - second[1])
+                    };
+                    let (pos, previous_pos) = (
+                        centre(first, second),
+                        centre(previous_first, previous_second),
+                    );
+                    let previous_distance = distance(previous_first, previous_second);
+                    {
+                        let mut viewport = visualisation.viewport.borrow_mut();
+                        let scale = css_scale * viewport.zoom();
+                        viewport.pan(&[
+                            (pos[0] - previous_pos[0]) / scale,
+                            -(pos[1] - previous_pos[1]) / scale,
+                        ]);
+                        if previous_distance > 0.0 {
+                            let lattice_pos = viewport.lattice_position(&pos, css_scale);
+                            viewport.zoom_about(
+                                &lattice_pos,
+                                distance(first, second) / previous_distance,
+                                VIEWPORT_MAX_ZOOM,
+                            );
+                        }
+                    }
+                    layout();
+                }
+                _ => {}
+            }
+        });
+        for event_type in ["touchstart", "touchmove", "touchend", "touchcancel"] {
+            container
+                .add_event_listener_with_callback(event_type, closure.as_ref().unchecked_ref())?;
+        }
+        closure.forget();
+    }
+
+    Ok(())
+}
+
+fn create_layout_controls(
+    document: &web_sys::Document,
+    body: &web_sys::HtmlElement,
+    visualisation: Rc<Visualisation>,
+    canvas_rendering_contexts: &[web_sys::CanvasRenderingContext2d; CANVAS_COUNT],
+) -> Result<(), JsValue> {
+    let div = document.create_element("div")?;
+    div.set_text_content(Some("Layout (keys 0-4) "));
+    body.append_child(&div)?;
+
+    let layout = {
+        let canvas_rendering_contexts = canvas_rendering_contexts.clone();
+        let visualisation = visualisation.clone();
+        Rc::new(move || {
+            let _ = layout_field_canvases(
+                &canvas_rendering_contexts,
+                *visualisation.single_canvas.borrow(),
+                *visualisation.display_scale.borrow(),
+                &visualisation.viewport.borrow(),
+            );
+        })
+    };
+    layout();
+
+    for single in [None].into_iter().chain((0..CANVAS_COUNT).map(Some)) {
+        let visualisation = visualisation.clone();
+        let layout = layout.clone();
+        create_button(
+            document,
+            &div,
+            &match single {
+                Some(i) => canvas_name(i),
+                None => "All".to_string(),
+            },
+            move || {
+                *visualisation.single_canvas.borrow_mut() = single;
+                layout();
+            },
+        )?;
+    }
+
+    // note: 0 shows all canvases and 1 to 4 show a single canvas, except while typing in inputs
+    let visualisation_clone = visualisation.clone();
+    let layout_clone = layout.clone();
+    let closure = Closure::<dyn FnMut(_)>::new(move |event: web_sys::KeyboardEvent| {
+        if event
+            .target()
+            .is_some_and(|target| target.has_type::<web_sys::HtmlInputElement>())
+        {
+            return;
+        }
+        if let Ok(key) = event.key().parse::<usize>() {
+            if key <= CANVAS_COUNT {
+                *visualisation_clone.single_canvas.borrow_mut() = key.checked_sub(1);
+                layout_clone();
+            }
+        }
+    });
+    document.add_event_listener_with_callback("keydown", closure.as_ref().unchecked_ref())?;
+    closure.forget();
+
+    add_viewport_listeners(
+        canvas_rendering_contexts,
+        visualisation.clone(),
+        layout.clone(),
+    )?;
+    add_touch_listeners(
+        canvas_rendering_contexts,
+        visualisation.clone(),
+        layout.clone(),
+    )?;
+
+    // note: the device pixel ratio changes with browser zoom and between displays
+    let layout_clone = layout.clone();
+    let closure = Closure::<dyn FnMut(_)>::new(move |_: web_sys::Event| layout_clone());
+    window().add_event_listener_with_callback("resize", closure.as_ref().unchecked_ref())?;
+    closure.forget();
+
+    create_select(
+        document,
+        &div,
+        " Display scale ",
+        &DISPLAY_SCALES
+            .map(|display_scale| format!("{display_scale}x"))
+            .iter()
+            .map(String::as_str)
+            .collect::<Vec<_>>(),
+        DISPLAY_SCALES
+            .iter()
+            .position(|&display_scale| display_scale == *visualisation.display_scale.borrow())
+            .unwrap_or(0),
+        move |selected| {
+            *visualisation.display_scale.borrow_mut() = DISPLAY_SCALES[selected];
+            layout();
+        },
+    )?;
+
+    Ok(())
+}
+
+fn create_colormaps_controls(
+    document: &web_sys::Document,
+    body: &web_sys::HtmlElement,
+    visualisation: Rc<Visualisation>,
+) -> Result<(), JsValue> {
+    let div = document.create_element("div")?;
+    div.set_text_content(Some("Colour maps"));
+    body.append_child(&div)?;
+
+    // note: the custom colour map follows all others
+    let mut names = Colormap::ALL.map(|colormap| colormap.name()).to_vec();
+    names.push(visualisation.custom_colormap.borrow().name());
+    for (i, colormap) in visualisation.colormaps.iter().enumerate() {
+        let name = canvas_name(i);
+        let visualisation_clone = visualisation.clone();
+        create_select(
+            document,
+            &div,
+            &format!(" {name} "),
+            &names,
+            colormap_index(&colormap.borrow()),
+            move |selected| {
+                *visualisation_clone.colormaps[i].borrow_mut() = Colormap::ALL
+                    .get(selected)
+                    .cloned()
+                    .unwrap_or_else(|| visualisation_clone.custom_colormap.borrow().clone());
+            },
+        )?;
+    }
+    let custom_colormap = visualisation
+        .custom_colormap
+        .borrow()
+        .to_text()
+        .unwrap_or_default();
+    let visualisation_clone = visualisation.clone();
+    create_input(
+        document,
+        &div,
+        " Custom ",
+        "text",
+        &custom_colormap,
+        move |input| match Colormap::from_text(&input.value()) {
+            Some(custom_colormap) => {
+                for colormap in &visualisation_clone.colormaps {
+                    if let Colormap::Custom(_) = *colormap.borrow() {
+                        *colormap.borrow_mut() = custom_colormap.clone();
+                    }
+                }
+                *visualisation_clone.custom_colormap.borrow_mut() = custom_colormap;
+            }
+            None => input.set_value(
+                &visualisation_clone
+                    .custom_colormap
+                    .borrow()
+                    .to_text()
+                    .unwrap_or_default(),
+            ),
+        },
+    )?;
+    let outlines_shown = visualisation.outlines_shown.borrow().to_string();
+    let visualisation_clone = visualisation.clone();
+    create_input(
+        document,
+        &div,
+        " Object outlines ",
+        "checkbox",
+        &outlines_shown,
+        move |input| {
+            *visualisation_clone.outlines_shown.borrow_mut() = input.checked();
+        },
+    )?;
+    let objects_coloured = visualisation.objects_coloured.borrow().to_string();
+    create_input(
+        document,
+        &div,
+        " Object colours ",
+        "checkbox",
+        &objects_coloured,
+        move |input| {
+            *visualisation.objects_coloured.borrow_mut() = input.checked();
+        },
+    )?;
+
+    Ok(())
+}
+
+/// Index of a colour map in the options of the colour map controls.
+fn colormap_index(colormap: &Colormap) -> usize {
+    Colormap::ALL
+        .iter()
+        .position(|val| val == colormap)
+        .unwrap_or(Colormap::ALL.len())
+}
+
+fn create_scale_controls(
+    document: &web_sys::Document,
+    body: &web_sys::HtmlElement,
+    visualisation: Rc<Visualisation>,
+) -> Result<(), JsValue> {
+    let names = ScaleMode::ALL.map(|scale_mode| scale_mode.name());
+    for i in 0..CANVAS_COUNT {
+        let name = canvas_name(i);
+        let div = document.create_element("div")?;
+        div.set_text_content(Some(&format!("{name} scale ")));
+        body.append_child(&div)?;
+
+        let visualisation_clone = visualisation.clone();
+        create_select(
+            document,
+            &div,
+            "Mode ",
+            &names,
+            ScaleMode::ALL
+                .iter()
+                .position(|&scale_mode| scale_mode == SCALE_MODES[i])
+                .unwrap_or(0),
+            move |selected| {
+                *visualisation_clone.scale_modes[i].borrow_mut() = ScaleMode::ALL[selected];
+            },
+        )?;
+        let visualisation_clone = visualisation.clone();
+        create_input(
+            document,
+            &div,
+            " Gamma exponent ",
+            "number",
+            &SCALE_EXPONENT.to_string(),
+            move |input| {
+                if let Ok(exponent) = input.value().parse() {
+                    *visualisation_clone.exponents[i].borrow_mut() = exponent;
+                }
+            },
+        )?;
+        let visualisation_clone = visualisation.clone();
+        create_input(
+            document,
+            &div,
+            " Smooth range ",
+            "checkbox",
+            "false",
+            move |input| {
+                *visualisation_clone.ranges_smoothed[i].borrow_mut() = input.checked();
+            },
+        )?;
+        let visualisation_clone = visualisation.clone();
+        create_input(
+            document,
+            &div,
+            " Fixed range ",
+            "checkbox",
+            "false",
+            move |input| {
+                *visualisation_clone.fixed_ranges_enabled[i].borrow_mut() = input.checked();
+            },
+        )?;
+        for (j, label) in [" Minimum ", " Maximum "].iter().enumerate() {
+            let visualisation_clone = visualisation.clone();
+            create_input(
+                document,
+                &div,
+                label,
+                "number",
+                &FIXED_RANGES[i][j].to_string(),
+                move |input| {
+                    if let Ok(value) = input.value().parse() {
+                        visualisation_clone.fixed_ranges[i].borrow_mut()[j] = value;
+                    }
+                },
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+fn create_contours_controls(
+    document: &web_sys::Document,
+    body: &web_sys::HtmlElement,
+    visualisation: Rc<Visualisation>,
+) -> Result<(), JsValue> {
+    let div = document.create_element("div")?;
+    div.set_text_content(Some("Contours "));
+    body.append_child(&div)?;
+
+    for i in 0..CANVAS_COUNT {
+        let name = canvas_name(i);
+        let visualisation_clone = visualisation.clone();
+        create_input(
+            document,
+            &div,
+            &format!("{name} "),
+            "checkbox",
+            "false",
+            move |input| {
+                *visualisation_clone.contours_shown[i].borrow_mut() = input.checked();
+            },
+        )?;
+    }
+    create_input(
+        document,
+        &div,
+        " Levels ",
+        "number",
+        &CONTOURS_COUNT.to_string(),
+        move |input| {
+            if let Ok(count) = input.value().parse() {
+                visualisation.contours.borrow_mut().set_count(count);
+            }
+        },
+    )?;
+
+    Ok(())
+}
+
+fn create_line_integral_convolution_controls(
+    document: &web_sys::Document,
+    body: &web_sys::HtmlElement,
+    visualisation: Rc<Visualisation>,
+) -> Result<(), JsValue> {
+    let div = document.create_element("div")?;
+    div.set_text_content(Some("Line integral convolution "));
+    body.append_child(&div)?;
+
+    let visualisation_clone = visualisation.clone();
+    create_input(document, &div, "Show ", "checkbox", "false", move |input| {
+        *visualisation_clone
+            .line_integral_convolution_shown
+            .borrow_mut() = input.checked();
+    })?;
+    create_input(
+        document,
+        &div,
+        " Length ",
+        "number",
+        &LINE_INTEGRAL_CONVOLUTION_LENGTH.to_string(),
+        move |input| {
+            if let Ok(length) = input.value().parse() {
+                visualisation
+                    .line_integral_convolution
+                    .borrow_mut()
+                    .set_length(length);
+            }
+        },
+    )?;
+
+    Ok(())
+}
+
+fn create_streamlines_controls(
+    document: &web_sys::Document,
+    body: &web_sys::HtmlElement,
+    visualisation: Rc<Visualisation>,
+) -> Result<(), JsValue> {
+    let div = document.create_element("div")?;
+    div.set_text_content(Some("Streamlines "));
+    body.append_child(&div)?;
+
+    let visualisation_clone = visualisation.clone();
+    create_button(document, &div, "Seed line", move || {
+        visualisation_clone.streamlines.borrow_mut().seed_line(
+            [1.0, 0.0],
+            [1.0, (SIZE[1] - 1) as f32],
+            STREAMLINES_SEED_COUNT,
+        );
+    })?;
+    let visualisation_clone = visualisation.clone();
+    create_button(document, &div, "Clear", move || {
+        visualisation_clone.streamlines.borrow_mut().clear();
+    })?;
+    create_select(
+        document,
+        &div,
+        " Integrator ",
+        &["RK2", "RK4"],
+        0,
+        move |selected| {
+            visualisation
+                .streamlines
+                .borrow_mut()
+                .set_integrator(match selected {
+                    0 => streamlines::Integrator::RungeKutta2,
+                    _ => streamlines::Integrator::RungeKutta4,
+                });
+        },
+    )?;
+
+    Ok(())
+}
+
+fn create_flow_lines_controls(
+    document: &web_sys::Document,
+    body: &web_sys::HtmlElement,
+    visualisation: Rc<Visualisation>,
+) -> Result<(), JsValue> {
+    let div = document.create_element("div")?;
+    div.set_text_content(Some("Pathlines "));
+    body.append_child(&div)?;
+
+    let visualisation_clone = visualisation.clone();
+    create_button(document, &div, "Seed line", move || {
+        visualisation_clone.pathlines.borrow_mut().seed_line(
+            [1.0, 0.0],
+            [1.0, (SIZE[1] - 1) as f32],
+            FLOW_LINES_SEED_COUNT,
+        );
+    })?;
+    let visualisation_clone = visualisation.clone();
+    create_button(document, &div, "Clear", move || {
+        visualisation_clone.pathlines.borrow_mut().clear();
+    })?;
+
+    let div = document.create_element("div")?;
+    div.set_text_content(Some("Streaklines "));
+    body.append_child(&div)?;
+
+    let visualisation_clone = visualisation.clone();
+    create_button(document, &div, "Add sources", move || {
+        visualisation_clone
+            .streaklines
+            .borrow_mut()
+            .add_sources_line(
+                [1.0, 0.0],
+                [1.0, (SIZE[1] - 1) as f32],
+                FLOW_LINES_SEED_COUNT,
+            );
+    })?;
+    let visualisation_clone = visualisation.clone();
+    create_button(document, &div, "Clear", move || {
+        visualisation_clone.streaklines.borrow_mut().clear();
+    })?;
+
+    let div = document.create_element("div")?;
+    body.append_child(&div)?;
+
+    let visualisation_clone = visualisation.clone();
+    create_input(
+        document,
+        &div,
+        "Line length ",
+        "number",
+        &FLOW_LINES_LENGTH.to_string(),
+        move |input| {
+            if let Ok(length) = input.value().parse() {
+                visualisation_clone
+                    .pathlines
+                    .borrow_mut()
+                    .set_length(length);
+                visualisation_clone
+                    .streaklines
+                    .borrow_mut()
+                    .set_length(length);
+            }
+        },
+    )?;
+    create_input(document, &div, " Fade ", "checkbox", "true", move |input| {
+        *visualisation.flow_lines_fade.borrow_mut() = input.checked();
+    })?;
+
+    Ok(())
+}
+
+fn create_particles_controls(
+    document: &web_sys::Document,
+    body: &web_sys::HtmlElement,
+    visualisation: Rc<Visualisation>,
+    i: usize,
+) -> Result<(), JsValue> {
+    let div = document.create_element("div")?;
+    div.set_text_content(Some(&format!("{} ", PARTICLES_NAMES[i])));
+    body.append_child(&div)?;
+
+    let visualisation_clone = visualisation.clone();
+    create_button(document, &div, "Seed line", move || {
+        visualisation_clone.particles[i].borrow_mut().seed_line(
+            [1.0, 0.0],
+            [1.0, (SIZE[1] - 1) as f32],
+            PARTICLES_SEED_COUNT,
+        );
+    })?;
+    let visualisation_clone = visualisation.clone();
+    create_button(document, &div, "Seed random", move || {
+        let mut particles = visualisation_clone.particles[i].borrow_mut();
+        for _ in 0..PARTICLES_SEED_COUNT {
+            let mut random = visualisation_clone.random.borrow_mut();
+            particles.seed(SIZE.map(|size| random.uniform_range(0.0, size as f32)));
+        }
+    })?;
+    let button_seeding = Rc::new(RefCell::new(None::<web_sys::HtmlButtonElement>));
+    let button_seeding_clone = button_seeding.clone();
+    let visualisation_clone = visualisation.clone();
+    *button_seeding.borrow_mut() = Some(create_button(
+        document,
+        &div,
+        "Seed continuously",
+        move || {
+            let seeding = !*visualisation_clone.particles_seeding[i].borrow();
+            *visualisation_clone.particles_seeding[i].borrow_mut() = seeding;
+            if let Some(button) = button_seeding_clone.borrow().as_ref() {
+                button.set_text_content(Some(match seeding {
+                    true => "Stop seeding",
+                    false => "Seed continuously",
+                }));
+            }
+        },
+    )?);
+    create_button(document, &div, "Clear", move || {
+        visualisation.particles[i].borrow_mut().clear();
+    })?;
+
+    Ok(())
+}
+
+/// Visualisation state shared between the user interface and the animation loop.
+struct Visualisation {
+    particles: [RefCell<Particles>; 2],
+    particles_seeding: [RefCell<bool>; 2],
+    pathlines: RefCell<Pathlines>,
+    streaklines: RefCell<Streaklines>,
+    flow_lines_fade: RefCell<bool>,
+    streamlines: RefCell<Streamlines>,
+    quiver: RefCell<Quiver>,
+    quiver_shown: RefCell<bool>,
+    line_integral_convolution: RefCell<LineIntegralConvolution>,
+    line_integral_convolution_shown: RefCell<bool>,
+    contours: RefCell<Contours>,
+    contours_shown: [RefCell<bool>; CANVAS_COUNT],
+    colormaps: [RefCell<Colormap>; CANVAS_COUNT],
+    /// User-defined colour map, selectable for any canvas.
+    custom_colormap: RefCell<Colormap>,
+    scale_modes: [RefCell<ScaleMode>; CANVAS_COUNT],
+    exponents: [RefCell<f32>; CANVAS_COUNT],
+    fixed_ranges_enabled: [RefCell<bool>; CANVAS_COUNT],
+    fixed_ranges: [RefCell<[f32; 2]>; CANVAS_COUNT],
+    ranges_smoothed: [RefCell<bool>; CANVAS_COUNT],
+    fields: [RefCell<Field>; CANVAS_COUNT],
+    outlines_shown: RefCell<bool>,
+    objects_coloured: RefCell<bool>,
+    display_scale: RefCell<usize>,
+    single_canvas: RefCell<Option<usize>>,
+    viewport: RefCell<Viewport>,
+    interaction: RefCell<Interaction>,
+    brush_radius: RefCell<f32>,
+    /// Brush centres (in lattice coordinates) painted, or erased, since the last iteration.
+    object_edits: RefCell<Vec<([f32; 2], bool)>>,
+    /// Centres (in lattice coordinates) and velocity vectors stirred since the last iteration.
+    stirs: RefCell<Vec<([f32; 2], [f32; 2])>>,
+    /// Centres (in lattice coordinates) of the pulses injected since the last iteration.
+    pulses: RefCell<Vec<[f32; 2]>>,
+    /// Position (in lattice coordinates) under the mouse, if any.
+    hover_position: RefCell<Option<[f32; 2]>>,
+    /// Whether a reset of the simulation is requested.
+    reset: RefCell<bool>,
+    /// Whether a reset also clears painted (and erased) objects.
+    reset_clears_painted_objects: RefCell<bool>,
+    /// Whether the document is hidden (e.g. a background tab).
+    hidden: RefCell<bool>,
+    visibility_changed: RefCell<bool>,
+    /// Scenario set up by a reset.
+    scenario: RefCell<Scenario>,
+    /// Magnitude of the inflow velocity.
+    velocity: RefCell<f32>,
+    reynolds_number: RefCell<f32>,
+    rayleigh_number: RefCell<f32>,
+    prandtl_number: RefCell<f32>,
+    /// Seed of the random numbers, of the scenario (on reset) and of seeding particles.
+    seed: RefCell<u32>,
+    random: RefCell<Random>,
+    /// Name to save the scene to local storage under, if requested.
+    scene_save: RefCell<Option<String>>,
+    /// Field canvas to export as a PNG image, if requested.
+    png_export: RefCell<Option<usize>>,
+    /// Field canvases recorded to video, while recording.
+    video_canvases: RefCell<Option<Vec<usize>>>,
+    /// Whether an export of the time series as CSV is requested.
+    csv_export: RefCell<bool>,
+    /// Whether an export of the energy and enstrophy spectra as CSV is requested.
+    spectra_export: RefCell<bool>,
+    /// Whether an export of the fields as VTK image data is requested.
+    vtk_export: RefCell<bool>,
+    /// Whether an export of the fields as a binary snapshot is requested.
+    snapshot_export: RefCell<bool>,
+    /// Whether checkpoints are saved automatically, every interval of iterations.
+    checkpointing: RefCell<bool>,
+    checkpoint_interval: RefCell<usize>,
+    /// Whether automatic checkpointing is to be (re)started.
+    checkpointing_changed: RefCell<bool>,
+    /// Whether a restore of the latest checkpoint is requested.
+    checkpoint_restore: RefCell<bool>,
+    /// Checkpoint loaded to restore, if any.
+    restored_checkpoint: RefCell<Option<Checkpoint>>,
+    /// Whether the force coefficients and fields are recorded (to IndexedDB), with snapshots
+    /// of the fields every interval of iterations.
+    recording: RefCell<bool>,
+    recording_snapshot_interval: RefCell<usize>,
+    /// Whether the recording is to be (re)started or stopped.
+    recording_changed: RefCell<bool>,
+    /// Whether an export of all of the recording is requested.
+    recording_export: RefCell<bool>,
+    /// Whether results are streamed over a WebSocket to the URL of a collector, with snapshots
+    /// of the fields every interval of iterations.
+    streaming: RefCell<bool>,
+    streaming_url: RefCell<String>,
+    streaming_snapshot_interval: RefCell<usize>,
+    /// Whether the stream is to be (re)connected or disconnected.
+    streaming_changed: RefCell<bool>,
+    /// Iterations requested while paused.
+    steps: RefCell<usize>,
+    /// Iterations advanced by the step button.
+    step_count: RefCell<usize>,
+    /// Iterations of a batch run by the batch button.
+    batch_count: RefCell<usize>,
+    /// Iterations remaining of a requested batch, run as fast as possible without drawing.
+    batch_remaining: RefCell<usize>,
+}
+
 struct UserInterfaceElements {
-    canvas_rendering_contexts: [web_sys::CanvasRenderingContext2d; 3],
+    canvas_rendering_contexts: [web_sys::CanvasRenderingContext2d; CANVAS_COUNT],
+    force_canvas_rendering_context: web_sys::CanvasRenderingContext2d,
+    particles_canvas_rendering_context: web_sys::CanvasRenderingContext2d,
+    flow_lines_canvas_rendering_context: web_sys::CanvasRenderingContext2d,
+    line_integral_convolution_canvas_rendering_context: web_sys::CanvasRenderingContext2d,
     iteration_element: web_sys::Element,
     rate_element: web_sys::Element,
+    timings_element: web_sys::Element,
+    batch_element: web_sys::Element,
+    convergence_element: web_sys::Element,
+    force_element: web_sys::Element,
+    readout_element: web_sys::Element,
+    parameters_element: web_sys::Element,
+    benchmark_element: web_sys::Element,
 }
 
 impl UserInterfaceElements {
-    fn new(
-        paused: Rc<RefCell<bool>>,
-        velocity: f32,
-        relaxation_time: f32,
-    ) -> Result<Self, JsValue> {
+    fn new(paused: Rc<RefCell<bool>>, visualisation: Rc<Visualisation>) -> Result<Self, JsValue> {
         let document = window().document().ok_or("should have document")?;
         let body = document.body().ok_or("should have document body")?;
 
-        let canvas_rendering_contexts = ["Density", "Velocity", "Vorticity"].map(|name| {
-            let div = document.create_element("div").unwrap();
-            div.set_text_content(Some(name));
-            body.append_child(&div).unwrap();
-
-            let canvas = document
-                .create_element("canvas")
-                .unwrap()
-                .dyn_into::<web_sys::HtmlCanvasElement>()
-                .unwrap();
-            canvas.set_width(SIZE[0] as u32);
-            canvas.set_height(SIZE[1] as u32);
-            body.append_child(&canvas).unwrap();
-
-            canvas
-                .get_context("2d")
-                .unwrap()
-                .expect("should have 2d context")
-                .dyn_into::<web_sys::CanvasRenderingContext2d>()
-                .unwrap()
+        let canvas_rendering_contexts = std::array::from_fn(|i| {
+            create_field_canvas_rendering_context(&document, &body, visualisation.clone(), i)
         });
+        let particles_canvas_rendering_context =
+            create_overlay_canvas_rendering_context(&document, &canvas_rendering_contexts[1]);
+        let flow_lines_canvas_rendering_context =
+            create_overlay_canvas_rendering_context(&document, &canvas_rendering_contexts[2]);
+        let line_integral_convolution_canvas_rendering_context =
+            create_canvas_rendering_context(&document, &body, "Line integral convolution", &SIZE);
+        let force_canvas_rendering_context = create_canvas_rendering_context(
+            &document,
+            &body,
+            "Drag (red) and lift (blue) coefficients",
+            &[SIZE[0], FORCE_CANVAS_HEIGHT],
+        );
 
         let iteration_element = {
             let iteration_element = document.create_element("div")?;
@@ -89,20 +1809,91 @@ impl UserInterfaceElements {
             body.append_child(&frames_element)?;
             frames_element
         };
+        let timings_element = {
+            let timings_element = document.create_element("div")?;
+            body.append_child(&timings_element)?;
+            timings_element
+        };
+        let batch_element = {
+            let batch_element = document.create_element("div")?;
+            body.append_child(&batch_element)?;
+            batch_element
+        };
+        let convergence_element = {
+            let convergence_element = document.create_element("div")?;
+            body.append_child(&convergence_element)?;
+            convergence_element
+        };
+        let force_element = {
+            let force_element = document.create_element("div")?;
+            body.append_child(&force_element)?;
+            force_element
+        };
+        let readout_element = {
+            let readout_element = document.create_element("div")?;
+            body.append_child(&readout_element)?;
+            readout_element
+        };
+        let parameters_element = {
+            let parameters_element = document.create_element("div")?;
+            body.append_child(&parameters_element)?;
+            parameters_element
+        };
+        let benchmark_element = {
+            let benchmark_element = document.create_element("div")?;
+            body.append_child(&benchmark_element)?;
+            benchmark_element
+        };
         {
             let div = document.create_element("div")?;
-            div.set_text_content(Some(&format!("Magnitude velocity {velocity}")));
-            body.append_child(&div)?;
-        }
-        {
-            let div = document.create_element("div")?;
-            div.set_text_content(Some(&format!("Reynolds number {RE}")));
-            body.append_child(&div)?;
-        }
-        {
-            let div = document.create_element("div")?;
-            div.set_text_content(Some(&format!("=> Relaxation time {relaxation_time}")));
             body.append_child(&div)?;
+
+            create_flow_parameter_input(
+                &document,
+                &div,
+                visualisation.clone(),
+                "Magnitude velocity ",
+                VELOCITY_RANGE,
+                |visualisation| &visualisation.velocity,
+            )?;
+            create_flow_parameter_input(
+                &document,
+                &div,
+                visualisation.clone(),
+                " Reynolds number ",
+                RE_RANGE,
+                |visualisation| &visualisation.reynolds_number,
+            )?;
+            create_flow_parameter_input(
+                &document,
+                &div,
+                visualisation.clone(),
+                " Rayleigh number ",
+                RA_RANGE,
+                |visualisation| &visualisation.rayleigh_number,
+            )?;
+            create_flow_parameter_input(
+                &document,
+                &div,
+                visualisation.clone(),
+                " Prandtl number ",
+                PR_RANGE,
+                |visualisation| &visualisation.prandtl_number,
+            )?;
+            // note: applied on reset
+            let visualisation_clone = visualisation.clone();
+            create_input(
+                &document,
+                &div,
+                " Seed ",
+                "number",
+                &visualisation.seed.borrow().to_string(),
+                move |input| {
+                    if let Ok(seed) = input.value().parse::<u32>() {
+                        *visualisation_clone.seed.borrow_mut() = seed;
+                    }
+                },
+            )?;
         }
         {
             let button_pause = document
@@ -115,6 +1906,43 @@ impl UserInterfaceElements {
             div.append_child(&button_pause)?;
             body.append_child(&div)?;
 
+            let visualisation_clone = visualisation.clone();
+            create_button(&document, &div, "Step", move || {
+                let step_count = *visualisation_clone.step_count.borrow();
+                *visualisation_clone.steps.borrow_mut() += step_count;
+            })?;
+            let visualisation_clone = visualisation.clone();
+            create_input(
+                &document,
+                &div,
+                " Iterations per step ",
+                "number",
+                "1",
+                move |input| {
+                    if let Ok(step_count) = input.value().parse::<usize>() {
+                        *visualisation_clone.step_count.borrow_mut() = step_count.max(1);
+                    }
+                },
+            )?;
+            let visualisation_clone = visualisation.clone();
+            create_button(&document, &div, "Run batch", move || {
+                let batch_count = *visualisation_clone.batch_count.borrow();
+                *visualisation_clone.batch_remaining.borrow_mut() = batch_count;
+            })?;
+            let visualisation_clone = visualisation.clone();
+            create_input(
+                &document,
+                &div,
+                " Iterations per batch ",
+                "number",
+                &BATCH_COUNT.to_string(),
+                move |input| {
+                    if let Ok(batch_count) = input.value().parse::<usize>() {
+                        *visualisation_clone.batch_count.borrow_mut() = batch_count.max(1);
+                    }
+                },
+            )?;
+
             let button_pause = Rc::new(RefCell::new(button_pause));
 
             let paused_clone = paused.clone();
@@ -132,154 +1960,1302 @@ impl UserInterfaceElements {
                 .unwrap();
             closure.forget();
         };
+        {
+            let document_clone = document.clone();
+            let visualisation_clone = visualisation.clone();
+            let closure = Closure::<dyn FnMut(_)>::new(move |_: web_sys::Event| {
+                *visualisation_clone.hidden.borrow_mut() = document_clone.hidden();
+                *visualisation_clone.visibility_changed.borrow_mut() = true;
+            });
+            document.add_event_listener_with_callback(
+                "visibilitychange",
+                closure.as_ref().unchecked_ref(),
+            )?;
+            closure.forget();
+        }
+        {
+            let div = document.create_element("div")?;
+            body.append_child(&div)?;
+
+            let visualisation_clone = visualisation.clone();
+            create_select(
+                &document,
+                &div,
+                "Scenario ",
+                &Scenario::ALL
+                    .map(|scenario| scenario.name())
+                    .each_ref()
+                    .map(|name| name.as_str()),
+                Scenario::ALL
+                    .iter()
+                    .position(|scenario| *scenario == *visualisation.scenario.borrow())
+                    .unwrap_or(0),
+                move |index| {
+                    *visualisation_clone.scenario.borrow_mut() = Scenario::ALL[index];
+                    *visualisation_clone.reset.borrow_mut() = true;
+                },
+            )?;
+            let visualisation_clone = visualisation.clone();
+            create_button(&document, &div, "Reset", move || {
+                *visualisation_clone.reset.borrow_mut() = true
+            })?;
+            let visualisation_clone = visualisation.clone();
+            create_input(
+                &document,
+                &div,
+                " Clear painted objects",
+                "checkbox",
+                "false",
+                move |input| {
+                    *visualisation_clone
+                        .reset_clears_painted_objects
+                        .borrow_mut() = input.checked()
+                },
+            )?;
+        }
+        for i in 0..PARTICLES_NAMES.len() {
+            create_particles_controls(&document, &body, visualisation.clone(), i)?;
+        }
+        create_scene_controls(&document, &body, visualisation.clone())?;
+        create_export_controls(&document, &body, visualisation.clone())?;
+        create_log_controls(&document, &body)?;
+        create_flow_lines_controls(&document, &body, visualisation.clone())?;
+        create_streamlines_controls(&document, &body, visualisation.clone())?;
+        create_quiver_controls(&document, &body, visualisation.clone())?;
+        create_layout_controls(
+            &document,
+            &body,
+            visualisation.clone(),
+            &canvas_rendering_contexts,
+        )?;
+        create_interaction_controls(
+            &document,
+            &body,
+            visualisation.clone(),
+            &canvas_rendering_contexts,
+        )?;
+        add_readout_listeners(&canvas_rendering_contexts, visualisation.clone())?;
+        create_colormaps_controls(&document, &body, visualisation.clone())?;
+        create_scale_controls(&document, &body, visualisation.clone())?;
+        create_contours_controls(&document, &body, visualisation.clone())?;
+        create_line_integral_convolution_controls(&document, &body, visualisation)?;
 
         Ok(Self {
             canvas_rendering_contexts,
+            force_canvas_rendering_context,
+            particles_canvas_rendering_context,
+            flow_lines_canvas_rendering_context,
+            line_integral_convolution_canvas_rendering_context,
             iteration_element,
             rate_element,
+            timings_element,
+            batch_element,
+            convergence_element,
+            force_element,
+            readout_element,
+            parameters_element,
+            benchmark_element,
         })
     }
 }
 
-/// Entry point of the application.
-#[wasm_bindgen(start)]
-pub fn main() -> Result<(), JsValue> {
-    let objects = vec![object::circular::Circular::new(
-        [SIZE[0] as f32 / 4.0, SIZE[1] as f32 / 2.0],
-        SIZE[1] as f32 / 10.0,
-    )];
-    // let objects = vec![
-    //     object::circular::Circular::new([SIZE[0] as f32 / 3.0, 0.0], SIZE[1] as f32 / 4.0),
-    //     object::circular::Circular::new(
-    //         [SIZE[0] as f32 / 3.0, (SIZE[1] - 1) as f32],
-    //         SIZE[1] as f32 / 4.0,
-    //     ),
-    // ];
-    // NACA 2412
-    // let objects = vec![object::naca_4_digit_airfoil::Naca4DigitAirfoil::new(
-    //     [SIZE[0] as f32 / 5.0, SIZE[1] as f32 / 2.0],
-    //     SIZE[1] as f32 / 2.0,
-    //     0.02,
-    //     0.4,
-    //     0.12,
-    //     8.0f32.to_radians(),
-    // )];
-    // NACA 2415
-    // let objects = vec![object::naca_4_digit_airfoil::Naca4DigitAirfoil::new(
-    //     [SIZE[0] as f32 / 5.0, SIZE[1] as f32 / 2.0],
-    //     SIZE[1] as f32 / 2.0,
-    //     0.02,
-    //     0.4,
-    //     0.15,
-    //     8.0f32.to_radians(),
-    // )];
-    // NACA 6412
-    // let objects = vec![object::naca_4_digit_airfoil::Naca4DigitAirfoil::new(
-    //     [SIZE[0] as f32 / 5.0, SIZE[1] as f32 / 2.0],
-    //     SIZE[1] as f32 / 2.0,
-    //     0.06,
-    //     0.4,
-    //     0.12,
-    //     8.0f32.to_radians(),
-    // )];
-
-    let mut lbgk = Lbgk::new_d2q9(&SIZE, &BOUNDARY_SCHEMES, DENSITY, &VELOCITY_VECTOR);
-    for pos in iproduct!(0..SIZE[0], 0..SIZE[1]).map(|(x, y)| [x, y]) {
-        lbgk.set_object(
-            &pos,
-            objects
-                .iter()
-                .any(|object| object.contains(&[pos[0] as f32, pos[1] as f32])),
+fn create_export_controls(
+    document: &web_sys::Document,
+    body: &web_sys::HtmlElement,
+    visualisation: Rc<Visualisation>,
+) -> Result<(), JsValue> {
+    let div = document.create_element("div")?;
+    div.set_text_content(Some("Export PNG "));
+    body.append_child(&div)?;
+
+    for i in 0..CANVAS_COUNT {
+        let visualisation = visualisation.clone();
+        create_button(document, &div, &canvas_name(i), move || {
+            *visualisation.png_export.borrow_mut() = Some(i);
+        })?;
+    }
+
+    let div = document.create_element("div")?;
+    div.set_text_content(Some("Export CSV "));
+    body.append_child(&div)?;
+
+    let visualisation_clone = visualisation.clone();
+    create_button(document, &div, "Force coefficients", move || {
+        *visualisation_clone.csv_export.borrow_mut() = true;
+    })?;
+    let visualisation_clone = visualisation.clone();
+    create_button(document, &div, "Spectra", move || {
+        *visualisation_clone.spectra_export.borrow_mut() = true;
+    })?;
+
+    let div = document.create_element("div")?;
+    div.set_text_content(Some("Export fields "));
+    body.append_child(&div)?;
+
+    let visualisation_clone = visualisation.clone();
+    create_button(document, &div, "VTK image data", move || {
+        *visualisation_clone.vtk_export.borrow_mut() = true;
+    })?;
+    let visualisation_clone = visualisation.clone();
+    create_button(document, &div, "Binary snapshot", move || {
+        *visualisation_clone.snapshot_export.borrow_mut() = true;
+    })?;
+
+    // note: checkpoints are saved to IndexedDB, cleared when started (or reset)
+    let div = document.create_element("div")?;
+    div.set_text_content(Some("Checkpoints "));
+    body.append_child(&div)?;
+
+    let visualisation_clone = visualisation.clone();
+    create_input(
+        document,
+        &div,
+        "Automatic ",
+        "checkbox",
+        "false",
+        move |input| {
+            *visualisation_clone.checkpointing.borrow_mut() = input.checked();
+            *visualisation_clone.checkpointing_changed.borrow_mut() = true;
+        },
+    )?;
+    let visualisation_clone = visualisation.clone();
+    create_input(
+        document,
+        &div,
+        " every (iterations) ",
+        "number",
+        &CHECKPOINT_INTERVAL.to_string(),
+        move |input| {
+            if let Ok(interval) = input.value().parse::<usize>() {
+                *visualisation_clone.checkpoint_interval.borrow_mut() = interval.max(1);
+                *visualisation_clone.checkpointing_changed.borrow_mut() = true;
+            }
+        },
+    )?;
+    let visualisation_clone = visualisation.clone();
+    create_button(document, &div, "Restore latest", move || {
+        *visualisation_clone.checkpoint_restore.borrow_mut() = true;
+    })?;
+
+    // note: recordings are streamed to IndexedDB, cleared when started (or reset)
+    let div = document.create_element("div")?;
+    div.set_text_content(Some("Record to IndexedDB "));
+    body.append_child(&div)?;
+
+    let visualisation_clone = visualisation.clone();
+    create_input(
+        document,
+        &div,
+        "Recording ",
+        "checkbox",
+        "false",
+        move |input| {
+            *visualisation_clone.recording.borrow_mut() = input.checked();
+            *visualisation_clone.recording_changed.borrow_mut() = true;
+        },
+    )?;
+    let visualisation_clone = visualisation.clone();
+    create_input(
+        document,
+        &div,
+        " snapshot every (iterations) ",
+        "number",
+        &RECORDING_SNAPSHOT_INTERVAL.to_string(),
+        move |input| {
+            if let Ok(interval) = input.value().parse::<usize>() {
+                *visualisation_clone.recording_snapshot_interval.borrow_mut() = interval.max(1);
+                *visualisation_clone.recording_changed.borrow_mut() = true;
+            }
+        },
+    )?;
+    let visualisation_clone = visualisation.clone();
+    create_button(document, &div, "Export all", move || {
+        *visualisation_clone.recording_export.borrow_mut() = true;
+    })?;
+
+    // note: the stream is reconnected when the URL or interval is changed (or reset)
+    let div = document.create_element("div")?;
+    div.set_text_content(Some("Stream to WebSocket "));
+    body.append_child(&div)?;
+
+    let visualisation_clone = visualisation.clone();
+    create_input(
+        document,
+        &div,
+        "Streaming ",
+        "checkbox",
+        "false",
+        move |input| {
+            *visualisation_clone.streaming.borrow_mut() = input.checked();
+            *visualisation_clone.streaming_changed.borrow_mut() = true;
+        },
+    )?;
+    let visualisation_clone = visualisation.clone();
+    create_input(
+        document,
+        &div,
+        " URL ",
+        "text",
+        STREAMING_URL,
+        move |input| {
+            *visualisation_clone.streaming_url.borrow_mut() = input.value();
+            *visualisation_clone.streaming_changed.borrow_mut() = true;
+        },
+    )?;
+    let visualisation_clone = visualisation.clone();
+    create_input(
+        document,
+        &div,
+        " snapshot every (iterations) ",
+        "number",
+        &STREAMING_SNAPSHOT_INTERVAL.to_string(),
+        move |input| {
+            if let Ok(interval) = input.value().parse::<usize>() {
+                *visualisation_clone.streaming_snapshot_interval.borrow_mut() = interval.max(1);
+                *visualisation_clone.streaming_changed.borrow_mut() = true;
+            }
+        },
+    )?;
+
+    let div = document.create_element("div")?;
+    div.set_text_content(Some("Record video "));
+    body.append_child(&div)?;
+
+    // note: all canvases, or a single canvas
+    let selected = Rc::new(RefCell::new(0));
+    let selected_clone = selected.clone();
+    create_select(
+        document,
+        &div,
+        "",
+        &std::iter::once("All canvases".to_string())
+            .chain((0..CANVAS_COUNT).map(canvas_name))
+            .collect::<Vec<_>>()
+            .iter()
+            .map(String::as_str)
+            .collect::<Vec<_>>(),
+        0,
+        move |index| *selected_clone.borrow_mut() = index,
+    )?;
+    let visualisation_clone = visualisation.clone();
+    create_button(document, &div, "Start", move || {
+        *visualisation_clone.video_canvases.borrow_mut() = Some(match *selected.borrow() {
+            0 => (0..CANVAS_COUNT).collect(),
+            index => vec![index - 1],
+        });
+    })?;
+    create_button(document, &div, "Stop", move || {
+        *visualisation.video_canvases.borrow_mut() = None;
+    })?;
+
+    Ok(())
+}
+
+/// Create controls of the verbosity of the log to the browser console.
+fn create_log_controls(
+    document: &web_sys::Document,
+    body: &web_sys::HtmlElement,
+) -> Result<(), JsValue> {
+    let div = document.create_element("div")?;
+    body.append_child(&div)?;
+
+    create_select(
+        document,
+        &div,
+        "Console log level ",
+        &log::Level::ALL.map(|level| level.name()),
+        log::verbosity() as usize,
+        |index| log::set_verbosity(log::Level::ALL[index]),
+    )?;
+
+    Ok(())
+}
+
+/// Browser local storage, if available.
+fn local_storage() -> Option<web_sys::Storage> {
+    window().local_storage().ok().flatten()
+}
+
+/// Names of the scenes saved to local storage, sorted.
+fn saved_scene_names() -> Vec<String> {
+    let Some(storage) = local_storage() else {
+        return Vec::new();
+    };
+    let mut names = (0..storage.length().unwrap_or(0))
+        .filter_map(|i| storage.key(i).ok().flatten())
+        .filter_map(|key| key.strip_prefix(SCENE_KEY_PREFIX).map(str::to_string))
+        .collect::<Vec<_>>();
+    names.sort();
+    names
+}
+
+/// Scene requested to be loaded when the page was reloaded, if any.
+fn take_loaded_scene() -> Option<Scene> {
+    let storage = local_storage()?;
+    let name = storage.get_item(SCENE_LOAD_KEY).ok().flatten()?;
+    let _ = storage.remove_item(SCENE_LOAD_KEY);
+    let text = storage
+        .get_item(&format!("{SCENE_KEY_PREFIX}{name}"))
+        .ok()
+        .flatten()?;
+    let scene = Scene::from_text(&text);
+    if scene.is_none() {
+        log::warn(
+            "scene",
+            &format!("scene \"{name}\" is invalid, so not loaded"),
         );
     }
+    scene
+}
+
+/// Create controls saving the scene to local storage under a name, and loading a saved scene.
+///
+/// note: a scene is loaded by reloading the page, so all controls are created with its settings
+fn create_scene_controls(
+    document: &web_sys::Document,
+    body: &web_sys::HtmlElement,
+    visualisation: Rc<Visualisation>,
+) -> Result<(), JsValue> {
+    let div = document.create_element("div")?;
+    div.set_text_content(Some("Scenes "));
+    body.append_child(&div)?;
+
+    let names = saved_scene_names();
+    let load_select = create_select(
+        document,
+        &div,
+        "Load ",
+        &std::iter::once("-")
+            .chain(names.iter().map(String::as_str))
+            .collect::<Vec<_>>(),
+        0,
+        |_| {},
+    )?;
+    let load_select_clone = load_select.clone();
+    let closure = Closure::<dyn FnMut(_)>::new(move |_: web_sys::Event| {
+        if load_select_clone.selected_index() <= 0 {
+            return;
+        }
+        if let Some(storage) = local_storage() {
+            let _ = storage.set_item(SCENE_LOAD_KEY, &load_select_clone.value());
+            let _ = window().location().reload();
+        }
+    });
+    load_select.add_event_listener_with_callback("change", closure.as_ref().unchecked_ref())?;
+    closure.forget();
 
-    let velocity = VELOCITY_VECTOR.dot_product(&VELOCITY_VECTOR).sqrt();
-    let relaxation_time = lbgk.relaxation_time(velocity, objects[0].characteristic_length(), RE);
+    let name_input = create_input(document, &div, " Name ", "text", "", |_| {})?;
+    let document_clone = document.clone();
+    create_button(document, &div, "Save", move || {
+        let name = name_input.value().trim().to_string();
+        if name.is_empty() {
+            return;
+        }
+        if !saved_scene_names().contains(&name) {
+            if let Ok(option) = document_clone.create_element("option") {
+                option.set_text_content(Some(&name));
+                let _ = load_select.append_child(&option);
+            }
+        }
+        *visualisation.scene_save.borrow_mut() = Some(name);
+    })?;
+
+    Ok(())
+}
+
+/// Entry point of the application.
+#[cfg_attr(feature = "ui", wasm_bindgen(start))]
+pub fn main() -> Result<(), JsValue> {
+    set_panic_hook();
+    log_throughput();
+
+    let scene = take_loaded_scene();
+
+    let scenario = scene
+        .as_ref()
+        .and_then(|scene| Scenario::ALL.get(scene.scenario).copied())
+        .unwrap_or(SCENARIO);
+    let seed = scene
+        .as_ref()
+        .map_or(random::DEFAULT_SEED, |scene| scene.seed);
+    let mut characteristic_length = scenario.characteristic_length(&SIZE);
+    // note: set from the flow parameters in the animation loop
+    let mut flow_parameters = None;
+    let mut units = Units::new(1.0, 1.0, 1.0, 1.0);
+
+    let custom_colormap = scene
+        .as_ref()
+        .and_then(|scene| Colormap::from_text(&scene.custom_colormap))
+        .or_else(|| Colormap::from_text(CUSTOM_COLORMAP))
+        .expect("should have valid custom colour map");
 
     let paused = Rc::new(RefCell::new(false));
-    let ui = UserInterfaceElements::new(paused.clone(), velocity, relaxation_time)?;
+    let visualisation = Rc::new(Visualisation {
+        particles: [
+            Particles::new(PARTICLES_CAPACITY),
+            Particles::new_inertial(
+                PARTICLES_CAPACITY,
+                Particles::response_time(STOKES_NUMBER, characteristic_length, VELOCITY),
+                GRAVITY,
+            ),
+        ]
+        .map(RefCell::new),
+        particles_seeding: [(); 2].map(|_| RefCell::new(false)),
+        pathlines: RefCell::new(Pathlines::new(FLOW_LINES_LENGTH)),
+        streaklines: RefCell::new(Streaklines::new(
+            FLOW_LINES_LENGTH,
+            STREAKLINES_RELEASE_INTERVAL,
+        )),
+        flow_lines_fade: RefCell::new(true),
+        streamlines: RefCell::new(Streamlines::new(
+            streamlines::Integrator::RungeKutta2,
+            STREAMLINES_STEP_SIZE,
+            STREAMLINES_MAX_STEPS,
+        )),
+        quiver: RefCell::new(Quiver::new(QUIVER_STRIDE, QUIVER_SCALE)),
+        quiver_shown: RefCell::new(false),
+        line_integral_convolution: RefCell::new(LineIntegralConvolution::new(
+            &SIZE,
+            LINE_INTEGRAL_CONVOLUTION_LENGTH,
+            LINE_INTEGRAL_CONVOLUTION_STEP_SIZE,
+        )),
+        line_integral_convolution_shown: RefCell::new(false),
+        contours: RefCell::new(Contours::new(CONTOURS_COUNT)),
+        contours_shown: [(); CANVAS_COUNT].map(|_| RefCell::new(false)),
+        colormaps: std::array::from_fn(|i| {
+            RefCell::new(
+                scene
+                    .as_ref()
+                    .and_then(|scene| {
+                        let index = *scene.colormaps.get(i)?;
+                        match index == Colormap::ALL.len() {
+                            true => Some(custom_colormap.clone()),
+                            false => Colormap::ALL.get(index).cloned(),
+                        }
+                    })
+                    .unwrap_or(COLORMAPS[i].clone()),
+            )
+        }),
+        custom_colormap: RefCell::new(custom_colormap),
+        scale_modes: SCALE_MODES.map(RefCell::new),
+        exponents: [(); CANVAS_COUNT].map(|_| RefCell::new(SCALE_EXPONENT)),
+        fixed_ranges_enabled: [(); CANVAS_COUNT].map(|_| RefCell::new(false)),
+        fixed_ranges: FIXED_RANGES.map(RefCell::new),
+        ranges_smoothed: [(); CANVAS_COUNT].map(|_| RefCell::new(false)),
+        fields: std::array::from_fn(|i| {
+            RefCell::new(
+                scene
+                    .as_ref()
+                    .and_then(|scene| Field::ALL.get(*scene.fields.get(i)?))
+                    .copied()
+                    .unwrap_or(FIELDS[i]),
+            )
+        }),
+        outlines_shown: RefCell::new(scene.as_ref().is_none_or(|scene| scene.outlines_shown)),
+        objects_coloured: RefCell::new(scene.as_ref().is_some_and(|scene| scene.objects_coloured)),
+        display_scale: RefCell::new(
+            scene
+                .as_ref()
+                .map(|scene| scene.display_scale)
+                .filter(|display_scale| DISPLAY_SCALES.contains(display_scale))
+                .unwrap_or(DISPLAY_SCALES[0]),
+        ),
+        single_canvas: RefCell::new(None),
+        viewport: RefCell::new(Viewport::new(&SIZE)),
+        interaction: RefCell::new(Interaction::Pan),
+        brush_radius: RefCell::new(BRUSH_RADIUS),
+        object_edits: RefCell::new(Vec::new()),
+        stirs: RefCell::new(Vec::new()),
+        pulses: RefCell::new(Vec::new()),
+        hover_position: RefCell::new(None),
+        reset: RefCell::new(false),
+        reset_clears_painted_objects: RefCell::new(false),
+        scenario: RefCell::new(scenario),
+        hidden: RefCell::new(false),
+        visibility_changed: RefCell::new(false),
+        velocity: RefCell::new(scene.as_ref().map_or(VELOCITY, |scene| scene.velocity)),
+        reynolds_number: RefCell::new(scene.as_ref().map_or(RE, |scene| scene.reynolds_number)),
+        rayleigh_number: RefCell::new(RA),
+        prandtl_number: RefCell::new(PR),
+        seed: RefCell::new(seed),
+        random: RefCell::new(Random::new(seed)),
+        scene_save: RefCell::new(None),
+        png_export: RefCell::new(None),
+        video_canvases: RefCell::new(None),
+        csv_export: RefCell::new(false),
+        spectra_export: RefCell::new(false),
+        vtk_export: RefCell::new(false),
+        snapshot_export: RefCell::new(false),
+        checkpointing: RefCell::new(false),
+        checkpoint_interval: RefCell::new(CHECKPOINT_INTERVAL),
+        checkpointing_changed: RefCell::new(false),
+        checkpoint_restore: RefCell::new(false),
+        restored_checkpoint: RefCell::new(None),
+        recording: RefCell::new(false),
+        recording_snapshot_interval: RefCell::new(RECORDING_SNAPSHOT_INTERVAL),
+        recording_changed: RefCell::new(false),
+        recording_export: RefCell::new(false),
+        streaming: RefCell::new(false),
+        streaming_url: RefCell::new(STREAMING_URL.to_string()),
+        streaming_snapshot_interval: RefCell::new(STREAMING_SNAPSHOT_INTERVAL),
+        streaming_changed: RefCell::new(false),
+        steps: RefCell::new(0),
+        batch_count: RefCell::new(BATCH_COUNT),
+        batch_remaining: RefCell::new(0),
+        step_count: RefCell::new(1),
+    });
+    // note: the flow parameters are set in the animation loop
+    let mut simulation = Simulation::with_scenario(
+        &SIZE,
+        scenario,
+        *visualisation.velocity.borrow(),
+        *visualisation.reynolds_number.borrow(),
+        seed,
+    );
+    if let Some(scene) = &scene {
+        // note: painted objects of a different lattice size are ignored
+        simulation.set_painted_objects(&scene.painted_objects);
+    }
+    let ui = UserInterfaceElements::new(paused.clone(), visualisation.clone())?;
 
-    let mut iteration = 0usize;
-    let mut rate_dates = VecDeque::new();
-    let mut image_values = ImageValues::new(&SIZE);
+    // note: the iteration and time at the start of a running batch
+    let mut batch_start = None;
+    // note: divergence is logged once, until reset
+    let mut diverged = false;
+    let mut image_values = FIELDS.map(|_| ImageValues::new(&SIZE));
+    let mut force_coefficients = TimeSeries::<2>::new(FORCE_HISTORY_LENGTH);
+    let mut residual_monitor = ResidualMonitor::new(RESIDUAL_INTERVAL, RESIDUAL_TARGET);
+    let mut video_recorder: Option<(export::VideoRecorder, Vec<usize>)> = None;
+    let checkpoint_indexed_db = IndexedDb::open(CHECKPOINT_DATABASE)
+        .inspect_err(|_| log::warn("checkpoint", "IndexedDB unavailable, so no checkpoints"))
+        .ok();
+    let mut auto_checkpoint: Option<AutoCheckpoint<IndexedDbStorage>> = None;
+    let recording_indexed_db = IndexedDb::open(RECORDING_DATABASE)
+        .inspect_err(|_| log::warn("recording", "IndexedDB unavailable, so no recordings"))
+        .ok();
+    let mut recording: Option<Recording<IndexedDbSink>> = None;
+    let mut web_socket_stream: Option<WebSocketStream> = None;
 
     let ff = Rc::new(RefCell::new(None));
     let ff_cloned = ff.clone();
     *ff.borrow_mut() = Some(Closure::new(move || {
-        if !*paused.borrow() {
-            iteration += 1;
-            ui.iteration_element
-                .set_text_content(Some(&format!("Iteration {iteration}")));
-
-            let now = Date::now();
-            rate_dates.push_back(now);
-            while let Some(front) = rate_dates.front() {
-                if front + (RATE_MOVING_AVERAGE_PERIOD_SECS * 1.0e3) > now {
-                    break;
+        // reset the simulation (also while paused)
+        if visualisation.reset.replace(false) {
+            // note: painted objects are specific to a scenario
+            let scenario = *visualisation.scenario.borrow();
+            let painted_objects = (scenario == simulation.scenario()
+                && !*visualisation.reset_clears_painted_objects.borrow())
+            .then(|| simulation.painted_objects().to_vec());
+            log::info("scenario", &format!("reset to {}", scenario.name()));
+            let seed = *visualisation.seed.borrow();
+            simulation = Simulation::with_scenario(
+                &SIZE,
+                scenario,
+                *visualisation.velocity.borrow(),
+                *visualisation.reynolds_number.borrow(),
+                seed,
+            );
+            if let Some(painted_objects) = painted_objects {
+                simulation.set_painted_objects(&painted_objects);
+            }
+            characteristic_length = scenario.characteristic_length(&SIZE);
+            flow_parameters = None;
+            *visualisation.random.borrow_mut() = Random::new(seed);
+
+            profiling::clear_phase_timings();
+            diverged = false;
+            *visualisation.batch_remaining.borrow_mut() = 0;
+            batch_start = None;
+            ui.batch_element.set_text_content(None);
+            force_coefficients.clear();
+            residual_monitor.clear();
+            for particles in &visualisation.particles {
+                particles.borrow_mut().clear();
+            }
+            visualisation.pathlines.borrow_mut().clear();
+            visualisation.streaklines.borrow_mut().clear();
+            *visualisation.checkpointing_changed.borrow_mut() = true;
+            *visualisation.recording_changed.borrow_mut() = true;
+            *visualisation.streaming_changed.borrow_mut() = true;
+        }
+
+        // (re)start automatic checkpointing, clearing the checkpoints of a previous run (also
+        // while paused)
+        if visualisation.checkpointing_changed.replace(false) {
+            auto_checkpoint = None;
+            if let (true, Some(indexed_db)) = (
+                *visualisation.checkpointing.borrow(),
+                &checkpoint_indexed_db,
+            ) {
+                let storage = IndexedDbStorage::new(indexed_db.clone());
+                if let Err(err) = storage.clear() {
+                    log::warn("checkpoint", &format!("checkpoints not cleared: {err}"));
                 }
-                rate_dates.pop_front();
-            }
-            let rate = rate_dates.len() as f64 / RATE_MOVING_AVERAGE_PERIOD_SECS;
-            ui.rate_element
-                .set_text_content(Some(&format!("Iteration rate {rate}")));
-
-            // iterate the algorithm
-            lbgk.iterate(relaxation_time);
-
-            if iteration % DRAW_ITERATION_STEPS == 0 {
-                // draw density image
-                let (mut min, mut max) = (f32::MAX, f32::MIN);
-                for pos in iproduct!(0..SIZE[0], 0..SIZE[1]).map(|(x, y)| [x, y]) {
-                    match lbgk.object(&pos) {
-                        true => image_values.set_value(&pos, None),
-                        false => {
-                            let val = lbgk.density(&pos);
-                            image_values.set_value(&pos, Some(val));
-                            (min, max) = (min.min(val), max.max(val));
-                        }
+                auto_checkpoint = Some(AutoCheckpoint::new(
+                    *visualisation.checkpoint_interval.borrow(),
+                    CHECKPOINT_RETENTION,
+                    storage,
+                ));
+            }
+        }
+
+        // (re)start or stop recording, clearing a previous recording when started (also while
+        // paused)
+        if visualisation.recording_changed.replace(false) {
+            if let Some(Err(err)) = recording.take().map(|mut recording| recording.flush()) {
+                log::warn("recording", &format!("time series not written: {err}"));
+            }
+            if let (true, Some(indexed_db)) =
+                (*visualisation.recording.borrow(), &recording_indexed_db)
+            {
+                let sink = IndexedDbSink::new(indexed_db.clone());
+                if let Err(err) = sink.clear() {
+                    log::warn("recording", &format!("recording not cleared: {err}"));
+                }
+                recording = Some(Recording::new(
+                    *visualisation.recording_snapshot_interval.borrow(),
+                    &Field::ALL,
+                    &["drag coefficient", "lift coefficient"],
+                    RECORDING_CHUNK_LENGTH,
+                    sink,
+                ));
+            }
+        }
+
+        // (re)connect or disconnect the stream (also while paused)
+        if visualisation.streaming_changed.replace(false) {
+            // note: the previous stream is closed when dropped
+            web_socket_stream = None;
+            if *visualisation.streaming.borrow() {
+                let url = visualisation.streaming_url.borrow();
+                web_socket_stream = WebSocketStream::connect(
+                    &url,
+                    STREAMING_STATS_INTERVAL,
+                    *visualisation.streaming_snapshot_interval.borrow(),
+                    &Field::ALL,
+                )
+                .inspect_err(|_| {
+                    log::warn("streaming", &format!("could not connect to {url}"));
+                })
+                .ok();
+            }
+        }
+
+        // restore the latest checkpoint, once loaded (also while paused)
+        if visualisation.checkpoint_restore.replace(false) {
+            if let Some(indexed_db) = &checkpoint_indexed_db {
+                let visualisation = visualisation.clone();
+                let _ = IndexedDbStorage::new(indexed_db.clone()).latest(move |checkpoint| {
+                    if checkpoint.is_none() {
+                        log::warn("checkpoint", "no checkpoint to restore");
                     }
+                    *visualisation.restored_checkpoint.borrow_mut() = checkpoint;
+                });
+            }
+        }
+        if let Some(checkpoint) = visualisation.restored_checkpoint.take() {
+            match simulation.restore(&checkpoint) {
+                true => log::info(
+                    "checkpoint",
+                    &format!("restored iteration {}", simulation.iteration()),
+                ),
+                false => log::warn(
+                    "checkpoint",
+                    "checkpoint of a different lattice size or thermal model not restored",
+                ),
+            }
+        }
+
+        // set the flow parameters (also while paused)
+        let parameters = [
+            *visualisation.velocity.borrow(),
+            *visualisation.reynolds_number.borrow(),
+            *visualisation.rayleigh_number.borrow(),
+            *visualisation.prandtl_number.borrow(),
+        ];
+        if Some(parameters) != flow_parameters {
+            flow_parameters = Some(parameters);
+            let [velocity, reynolds_number, rayleigh_number, prandtl_number] = parameters;
+            // note: the Reynolds number of the free-fall velocity in thermal scenarios
+            let reynolds_number = match simulation.scenario().thermal() {
+                true => (rayleigh_number / prandtl_number).sqrt(),
+                false => reynolds_number,
+            };
+            let partial_config = PartialConfig {
+                velocity: Some(velocity),
+                reynolds_number: Some(reynolds_number),
+                prandtl_number: Some(prandtl_number),
+                ..Default::default()
+            };
+            if let Err(err) = simulation.update_config(&partial_config) {
+                log::warn("parameters", &format!("not set: {err}"));
+            }
+            let relaxation_time = simulation.relaxation_time();
+
+            units = Units::from_reynolds_number(
+                characteristic_length,
+                velocity,
+                PHYSICAL_CHARACTERISTIC_LENGTH,
+                PHYSICAL_VISCOSITY,
+                reynolds_number,
+            );
+
+            log_flow_parameters(
+                simulation.lbgk(),
+                velocity,
+                reynolds_number,
+                relaxation_time,
+            );
+            let text = match simulation.thermal_relaxation_time() {
+                Some(thermal_relaxation_time) => format!(
+                    "Free-fall velocity {velocity}, Rayleigh number {rayleigh_number}, Prandtl number {prandtl_number} \
+                     => Relaxation times {relaxation_time} (flow), {thermal_relaxation_time} (temperature)"
+                ),
+                None => format!(
+                    "Magnitude velocity {velocity}, Reynolds number {reynolds_number} \
+                     => Relaxation time {relaxation_time}"
+                ),
+            };
+            ui.parameters_element.set_text_content(Some(&text));
+        }
+
+        // start or stop recording video (also while paused)
+        {
+            let video_canvases = visualisation.video_canvases.borrow();
+            match (&video_recorder, &*video_canvases) {
+                (None, Some(canvases)) => {
+                    video_recorder = canvases
+                        .first()
+                        .and_then(|i| ui.canvas_rendering_contexts[*i].canvas())
+                        .and_then(|canvas| {
+                            export::VideoRecorder::new(
+                                [canvas.width(), canvas.height()],
+                                canvases.len() as u32,
+                                &format!("iteration-{}.webm", simulation.iteration()),
+                            )
+                            .ok()
+                        })
+                        .map(|video_recorder| (video_recorder, canvases.clone()));
                 }
-                image_values.set_standard_value(DENSITY);
-                image_values.set_minimum_value(min);
-                image_values.set_maximum_value(max);
-                let _ = image_values.draw(false, &ui.canvas_rendering_contexts[0]);
-
-                // draw velocity image
-                let (mut min, mut max) = (f32::MAX, f32::MIN);
-                for pos in iproduct!(0..SIZE[0], 0..SIZE[1]).map(|(x, y)| [x, y]) {
-                    match lbgk.object(&pos) {
-                        true => image_values.set_value(&pos, None),
-                        false => {
-                            let val = lbgk.velocity(&pos);
-                            image_values.set_value(&pos, Some(val));
-                            (min, max) = (min.min(val), max.max(val));
-                        }
+                (Some((recorder, _)), None) => {
+                    let _ = recorder.stop();
+                    video_recorder = None;
+                }
+                _ => {}
+            }
+        }
+
+        // save the scene (also while paused)
+        if let Some(name) = visualisation.scene_save.take() {
+            let scene = Scene {
+                scenario: Scenario::ALL
+                    .iter()
+                    .position(|val| *val == simulation.scenario())
+                    .unwrap_or(0),
+                velocity: *visualisation.velocity.borrow(),
+                reynolds_number: *visualisation.reynolds_number.borrow(),
+                seed: *visualisation.seed.borrow(),
+                fields: visualisation
+                    .fields
+                    .iter()
+                    .map(|field| {
+                        Field::ALL
+                            .iter()
+                            .position(|val| *val == *field.borrow())
+                            .unwrap_or(0)
+                    })
+                    .collect(),
+                colormaps: visualisation
+                    .colormaps
+                    .iter()
+                    .map(|colormap| colormap_index(&colormap.borrow()))
+                    .collect(),
+                custom_colormap: visualisation
+                    .custom_colormap
+                    .borrow()
+                    .to_text()
+                    .unwrap_or_default(),
+                display_scale: *visualisation.display_scale.borrow(),
+                outlines_shown: *visualisation.outlines_shown.borrow(),
+                objects_coloured: *visualisation.objects_coloured.borrow(),
+                painted_objects: simulation.painted_objects().to_vec(),
+            };
+            if let Some(storage) = local_storage() {
+                let _ = storage.set_item(&format!("{SCENE_KEY_PREFIX}{name}"), &scene.to_text());
+            }
+        }
+
+        // paint objects, stir and pulse (also while paused)
+        let brush_radius = *visualisation.brush_radius.borrow();
+        for (centre, val) in visualisation.object_edits.borrow_mut().drain(..) {
+            paint_objects(&mut simulation, &centre, brush_radius, val);
+        }
+        for (centre, velocity_vector) in visualisation.stirs.borrow_mut().drain(..) {
+            stir(
+                simulation.lbgk_mut(),
+                &centre,
+                &velocity_vector,
+                brush_radius,
+            );
+        }
+        for centre in visualisation.pulses.borrow_mut().drain(..) {
+            initial_condition::gaussian_pulse(
+                simulation.lbgk_mut(),
+                &centre,
+                PULSE_AMPLITUDE,
+                brush_radius,
+            );
+        }
+
+        // note: the iteration rate moving average restarts when the document is shown or hidden
+        if visualisation.visibility_changed.replace(false) {
+            simulation.clear_iteration_rate();
+        }
+
+        // note: while paused (or hidden), only requested steps are iterated, and a batch is
+        // iterated (also while paused) for a time each frame until finished
+        profiling::start_frame();
+        let paused = *paused.borrow() || *visualisation.hidden.borrow();
+        let requested_steps = visualisation.steps.replace(0);
+        let batch_remaining = *visualisation.batch_remaining.borrow();
+        if batch_remaining > 0 && batch_start.is_none() {
+            batch_start = Some((simulation.iteration(), profiling::now()));
+        }
+        let steps = match (batch_remaining > 0, paused) {
+            (true, _) => batch_remaining,
+            (false, true) => requested_steps,
+            (false, false) => 1,
+        };
+        let frame_start = profiling::now();
+        let mut performed_steps = 0;
+        for _ in 0..steps {
+            if batch_remaining > 0
+                && performed_steps > 0
+                && profiling::now() - frame_start > BATCH_FRAME_MILLIS
+            {
+                break;
+            }
+            performed_steps += 1;
+            simulation.run_for(1);
+            let iteration = simulation.iteration();
+            LAST_ITERATION.store(iteration, Ordering::Relaxed);
+            ui.iteration_element.set_text_content(Some(&format!(
+                "Iteration {iteration}, physical time {:.4} s, convective time {:.2}",
+                units.time(iteration as f32),
+                iteration as f32 * parameters[0] / characteristic_length
+            )));
+            ui.rate_element.set_text_content(Some(&format!(
+                "Iteration rate {}",
+                simulation.iteration_rate()
+            )));
+            let lbgk = simulation.lbgk();
+
+            // advect particles
+            for (particles, particles_seeding) in
+                izip!(&visualisation.particles, &visualisation.particles_seeding)
+            {
+                let mut particles = particles.borrow_mut();
+                if *particles_seeding.borrow()
+                    && iteration.is_multiple_of(PARTICLES_SEED_ITERATION_STEPS)
+                {
+                    particles.seed_line(
+                        [1.0, 0.0],
+                        [1.0, (SIZE[1] - 1) as f32],
+                        PARTICLES_SEED_COUNT,
+                    );
+                }
+                particles.advect(lbgk);
+            }
+
+            // advect flow lines
+            visualisation.pathlines.borrow_mut().advect(lbgk);
+            visualisation.streaklines.borrow_mut().advect(lbgk);
+
+            // note: the force coefficients are of the total over all objects
+            force_coefficients.push(
+                iteration,
+                simulation
+                    .force_coefficients()
+                    .try_into()
+                    .expect("should have drag and lift coefficients"),
+            );
+            residual_monitor.update(iteration, lbgk);
+
+            if let Some(auto_checkpoint) = &mut auto_checkpoint {
+                if auto_checkpoint.due(iteration) {
+                    if let Err(err) = auto_checkpoint.save(&simulation.to_checkpoint()) {
+                        log::warn("checkpoint", &format!("checkpoint not saved: {err}"));
                     }
                 }
-                image_values.set_standard_value(velocity);
-                image_values.set_minimum_value(min);
-                image_values.set_maximum_value(max);
-                let _ = image_values.draw(false, &ui.canvas_rendering_contexts[1]);
-
-                // draw vorticity image
-                let (mut min, mut max) = (f32::MAX, f32::MIN);
-                for pos in iproduct!(0..SIZE[0], 0..SIZE[1]).map(|(x, y)| [x, y]) {
-                    match lbgk.object(&pos) {
-                        true => image_values.set_value(&pos, None),
-                        false => {
-                            let val = lbgk.vorticity(&pos);
-                            image_values.set_value(&pos, Some(val));
-                            (min, max) = (min.min(val), max.max(val));
-                        }
+            }
+
+            if let (Some(recording), Some(force_coefficients)) =
+                (&mut recording, force_coefficients.last())
+            {
+                if let Err(err) = recording.record(iteration, lbgk, force_coefficients) {
+                    log::warn("recording", &format!("not recorded: {err}"));
+                }
+            }
+
+            if let Some(web_socket_stream) = &mut web_socket_stream {
+                let [drag_coefficient, lift_coefficient] =
+                    force_coefficients.last().copied().unwrap_or_default();
+                let mut stats = vec![
+                    ("drag coefficient", drag_coefficient),
+                    ("lift coefficient", lift_coefficient),
+                ];
+                if let Some(residual) = residual_monitor.residual() {
+                    stats.push(("residual", residual));
+                }
+                if let Err(err) =
+                    web_socket_stream.stream(iteration, lbgk, &stats, simulation.probes())
+                {
+                    log::warn("streaming", &format!("not sent: {err:?}"));
+                }
+            }
+        }
+
+        let iteration = simulation.iteration();
+        let lbgk = simulation.lbgk();
+
+        // note: a batch is drawn only when finished
+        let mut batch_finished = false;
+        if batch_remaining > 0 {
+            let remaining = batch_remaining - performed_steps;
+            *visualisation.batch_remaining.borrow_mut() = remaining;
+            let text = match (remaining, batch_start) {
+                (0, Some((start_iteration, start))) => {
+                    batch_finished = true;
+                    batch_start = None;
+                    let text = format!(
+                        "Batch of {} iterations finished in {:.1} s",
+                        iteration - start_iteration,
+                        1.0e-3 * (profiling::now() - start)
+                    );
+                    log::info("batch", &text);
+                    text
+                }
+                _ => format!("Batch running, {remaining} iterations remaining"),
+            };
+            ui.batch_element.set_text_content(Some(&text));
+        }
+
+        // note: stepped iterations are always drawn
+        if performed_steps > 0
+            && match batch_remaining > 0 {
+                true => batch_finished,
+                false => paused || iteration.is_multiple_of(DRAW_ITERATION_STEPS),
+            }
+        {
+            let measure = profiling::Measure::start("draw");
+
+            if !diverged && lbgk.positions().any(|pos| !lbgk.density(&pos).is_finite()) {
+                diverged = true;
+                log::error(
+                    "lbgk",
+                    &format!("diverged by iteration {iteration} (try a larger relaxation time)"),
+                );
+            }
+
+            for (image_values, colormap, scale_mode, exponent) in izip!(
+                &mut image_values,
+                &visualisation.colormaps,
+                &visualisation.scale_modes,
+                &visualisation.exponents
+            ) {
+                image_values.set_colormap(colormap.borrow().clone());
+                image_values.set_scale_mode(*scale_mode.borrow());
+                image_values.set_exponent(*exponent.borrow());
+            }
+            for image_values in &mut image_values {
+                image_values.set_display_scale(pixel_scale(*visualisation.display_scale.borrow()));
+                image_values.set_viewport(*visualisation.viewport.borrow());
+                image_values.set_outline_colour(match *visualisation.outlines_shown.borrow() {
+                    true => Some(OUTLINE_COLOUR),
+                    false => None,
+                });
+            }
+            for (image_values, range_smoothed) in
+                izip!(&mut image_values, &visualisation.ranges_smoothed)
+            {
+                image_values.set_range_smoothing(match *range_smoothed.borrow() {
+                    true => Some(RANGE_SMOOTHING_WEIGHT),
+                    false => None,
+                });
+            }
+            for (image_values, fixed_range_enabled, fixed_range) in izip!(
+                &mut image_values,
+                &visualisation.fixed_ranges_enabled,
+                &visualisation.fixed_ranges
+            ) {
+                image_values.set_fixed_range(match *fixed_range_enabled.borrow() {
+                    true => Some(*fixed_range.borrow()),
+                    false => None,
+                });
+            }
+
+            // draw force coefficients
+            if let Some([drag, lift]) = force_coefficients.last() {
+                let mut text = format!("Drag coefficient {drag:.3}, lift coefficient {lift:.3}");
+                if simulation.object_count() > 1 {
+                    let object_coefficients = (0..simulation.object_count())
+                        .filter_map(|i| {
+                            let coefficients = simulation.object_force_coefficients(i)?;
+                            Some(format!(
+                                "object {}: {:.3}, {:.3}",
+                                i + 1,
+                                coefficients[0],
+                                coefficients[1]
+                            ))
+                        })
+                        .collect::<Vec<_>>();
+                    text.push_str(&format!(" ({})", object_coefficients.join("; ")));
+                }
+                ui.force_element.set_text_content(Some(&text));
+            }
+            let _ = force_coefficients.draw(&["red", "blue"], &ui.force_canvas_rendering_context);
+
+            // draw convergence
+            let convergence = match (residual_monitor.residual(), residual_monitor.convergence()) {
+                (Some(residual), Some(convergence)) => format!(
+                    "Residual {residual:.2e} (target {:.0e}): {}",
+                    residual_monitor.target(),
+                    convergence.name()
+                ),
+                _ => "Residual -".to_string(),
+            };
+            ui.convergence_element.set_text_content(Some(&convergence));
+
+            // compare with the benchmark
+            let benchmark = match simulation.scenario() {
+                Scenario::LidDrivenCavity => {
+                    let [velocity, reynolds_number, ..] = parameters;
+                    match benchmark::lid_driven_cavity_errors(
+                        lbgk,
+                        characteristic_length,
+                        velocity,
+                        reynolds_number,
+                    ) {
+                        Some([u, v]) => format!(
+                            "Centreline velocity RMS errors relative to Ghia et al.: u {u:.4}, v {v:.4}"
+                        ),
+                        None => format!(
+                            "No reference data of Ghia et al. at Reynolds number {reynolds_number} (only at {:?})",
+                            benchmark::lid_driven_cavity_reynolds_numbers()
+                        ),
                     }
                 }
-                image_values.set_standard_value(0.0);
-                image_values.set_minimum_value(min);
-                image_values.set_maximum_value(max);
-                let _ = image_values.draw(true, &ui.canvas_rendering_contexts[2]);
+                Scenario::RayleighBenard => simulation
+                    .nusselt_number()
+                    .map(|nusselt_number| {
+                        format!(
+                            "Nusselt number {nusselt_number:.3} (temperature shown on canvas {})",
+                            TEMPERATURE_CANVAS + 1
+                        )
+                    })
+                    .unwrap_or_default(),
+                Scenario::AcousticPulse => match simulation
+                    .probe_speed(0, simulation.probe_count() - 1)
+                {
+                    Some(speed) => format!(
+                        "Speed of sound between the first and last probes {speed:.4} (lattice {:.4})",
+                        lbgk.sound_speed_squared().sqrt()
+                    ),
+                    None => "Speed of sound - (pulse not yet past the probes)".to_string(),
+                },
+                _ => String::new(),
+            };
+            ui.benchmark_element.set_text_content(Some(&benchmark));
+
+            // draw field images
+            for (i, image_values) in image_values.iter_mut().enumerate() {
+                match (simulation.advection_diffusion(), i == TEMPERATURE_CANVAS) {
+                    (Some(advection_diffusion), true) => {
+                        set_temperature_values(lbgk, advection_diffusion, image_values)
+                    }
+                    _ => set_field_values(lbgk, *visualisation.fields[i].borrow(), image_values),
+                }
+                for pos in lbgk.positions() {
+                    image_values.set_fill_colour(
+                        &pos,
+                        object_colour(
+                            lbgk.object_id(&pos),
+                            *visualisation.objects_coloured.borrow(),
+                        ),
+                    );
+                }
+                let _ = image_values.draw(&ui.canvas_rendering_contexts[i]);
+                if *visualisation.contours_shown[i].borrow() {
+                    let _ = visualisation.contours.borrow().draw(
+                        image_values,
+                        "black",
+                        &ui.canvas_rendering_contexts[i],
+                    );
+                }
+                for immersed_boundary in simulation.immersed_boundaries() {
+                    let _ = immersed_boundary.draw(&ui.canvas_rendering_contexts[i]);
+                }
+                let _ = simulation
+                    .probes()
+                    .draw("black", &ui.canvas_rendering_contexts[i]);
+                if *visualisation.quiver_shown.borrow() {
+                    let _ = visualisation.quiver.borrow().draw(
+                        lbgk,
+                        "black",
+                        &ui.canvas_rendering_contexts[i],
+                    );
+                }
+            }
+
+            // draw streamlines
+            {
+                let mut streamlines = visualisation.streamlines.borrow_mut();
+                if !streamlines.is_empty() {
+                    streamlines.calculate(lbgk);
+                    let _ = streamlines.draw("black", &ui.canvas_rendering_contexts[1]);
+                }
+            }
+
+            // draw particles
+            let _ = clear_canvas(&ui.particles_canvas_rendering_context);
+            for (particles, [colour, deposit_colour]) in
+                izip!(&visualisation.particles, PARTICLES_COLOURS)
+            {
+                let _ = particles.borrow().draw(
+                    colour,
+                    deposit_colour,
+                    &ui.particles_canvas_rendering_context,
+                );
             }
+
+            // draw flow lines
+            let _ = clear_canvas(&ui.flow_lines_canvas_rendering_context);
+            let fade = *visualisation.flow_lines_fade.borrow();
+            let _ = visualisation.pathlines.borrow().draw(
+                "black",
+                fade,
+                &ui.flow_lines_canvas_rendering_context,
+            );
+            let _ = visualisation.streaklines.borrow().draw(
+                "white",
+                fade,
+                &ui.flow_lines_canvas_rendering_context,
+            );
+
+            // draw line integral convolution image
+            if *visualisation.line_integral_convolution_shown.borrow() {
+                let _ = visualisation
+                    .line_integral_convolution
+                    .borrow_mut()
+                    .draw(lbgk, &ui.line_integral_convolution_canvas_rendering_context);
+            }
+
+            // record video frame
+            if let Some((recorder, canvases)) = &video_recorder {
+                let _ = canvases
+                    .iter()
+                    .map(|i| container_canvases(&ui.canvas_rendering_contexts[*i]))
+                    .collect::<Result<Vec<_>, _>>()
+                    .and_then(|groups| recorder.draw(&groups));
+            }
+            drop(measure);
+
+            // draw phase timings
+            let phase_timings = profiling::phase_timings()
+                .iter()
+                .map(|(name, timings)| {
+                    format!(
+                        "{name} {:.2} ms (total {:.1} s)",
+                        timings.frame,
+                        1.0e-3 * timings.total
+                    )
+                })
+                .collect::<Vec<_>>();
+            ui.timings_element.set_text_content(Some(&format!(
+                "Phase timings of the frame: {}",
+                phase_timings.join(", ")
+            )));
         }
 
+        // export a field canvas (also while paused)
+        if let Some(i) = visualisation.png_export.take() {
+            let annotation = format!(
+                "{}, iteration {iteration}, magnitude velocity {}, Reynolds number {}",
+                visualisation.fields[i].borrow().name(),
+                visualisation.velocity.borrow(),
+                visualisation.reynolds_number.borrow(),
+            );
+            let _ = container_canvases(&ui.canvas_rendering_contexts[i]).and_then(|canvases| {
+                export::download_png(
+                    &canvases,
+                    &image_values[i],
+                    &annotation,
+                    &format!("canvas-{}-iteration-{iteration}.png", i + 1),
+                )
+            });
+        }
+
+        // export the time series (also while paused)
+        if visualisation.csv_export.replace(false) {
+            // note: time in convective time units
+            let [velocity, ..] = parameters;
+            let _ = export::download_csv(
+                &force_coefficients.to_csv(
+                    "convective time",
+                    velocity / characteristic_length,
+                    &["drag coefficient", "lift coefficient"],
+                ),
+                &format!("force-coefficients-iteration-{iteration}.csv"),
+            );
+        }
+        if visualisation.spectra_export.replace(false) {
+            let _ = export::download_csv(
+                &spectrum::Spectra::new(lbgk).to_csv(),
+                &format!("spectra-iteration-{iteration}.csv"),
+            );
+        }
+
+        // export the fields (also while paused)
+        if visualisation.vtk_export.replace(false) {
+            let _ = export::download_text(
+                &vtk::image_data(lbgk),
+                "application/xml",
+                &format!("fields-iteration-{iteration}.vti"),
+            );
+        }
+        if visualisation.snapshot_export.replace(false) {
+            let _ = export::download_bytes(
+                &snapshot::Snapshot::new(lbgk, &Field::ALL).to_bytes(),
+                "application/octet-stream",
+                &format!("fields-iteration-{iteration}.lbm"),
+            );
+        }
+
+        // export all of the recording, once loaded (also while paused)
+        if visualisation.recording_export.replace(false) {
+            if let Some(Err(err)) = recording.as_mut().map(Recording::flush) {
+                log::warn("recording", &format!("time series not written: {err}"));
+            }
+            if let Some(indexed_db) = &recording_indexed_db {
+                let _ = IndexedDbSink::new(indexed_db.clone()).export(|time_series, snapshots| {
+                    let _ = export::download_csv(&time_series, "recording-time-series.csv");
+                    let _ = export::download_bytes(
+                        &snapshots,
+                        "application/octet-stream",
+                        "recording-snapshots.lbmr",
+                    );
+                });
+            }
+        }
+
+        // draw readout (also while paused)
+        let readout = visualisation
+            .hover_position
+            .borrow()
+            .and_then(|pos| readout(lbgk, &pos));
+        ui.readout_element
+            .set_text_content(Some(readout.as_deref().unwrap_or("Position -")));
+
         request_animation_frame(ff_cloned.borrow().as_ref().unwrap());
     }));
     request_animation_frame(ff.borrow().as_ref().unwrap());
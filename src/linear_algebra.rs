@@ -3,6 +3,14 @@ use itertools::izip;
 pub trait VectorOps<const D: usize> {
     /// Vector [dot product](https://en.wikipedia.org/wiki/Dot_product).
     fn dot_product(&self, other: &[f32; D]) -> f32;
+    /// Squared [Euclidean norm](https://en.wikipedia.org/wiki/Norm_(mathematics)#Euclidean_norm).
+    fn norm_squared(&self) -> f32;
+    /// [Euclidean norm](https://en.wikipedia.org/wiki/Norm_(mathematics)#Euclidean_norm).
+    fn norm(&self) -> f32;
+    /// [Unit vector](https://en.wikipedia.org/wiki/Unit_vector) of the same direction.
+    ///
+    /// note: the zero vector is returned unchanged
+    fn normalize(&self) -> [f32; D];
 }
 
 impl<const D: usize> VectorOps<D> for [f32; D] {
@@ -10,13 +18,56 @@ impl<const D: usize> VectorOps<D> for [f32; D] {
     fn dot_product(&self, other: &[f32; D]) -> f32 {
         izip!(self, other).fold(0.0, |acc, (first, second)| acc + first * second)
     }
+
+    /// Squared [Euclidean norm](https://en.wikipedia.org/wiki/Norm_(mathematics)#Euclidean_norm).
+    fn norm_squared(&self) -> f32 {
+        self.dot_product(self)
+    }
+
+    /// [Euclidean norm](https://en.wikipedia.org/wiki/Norm_(mathematics)#Euclidean_norm).
+    fn norm(&self) -> f32 {
+        self.norm_squared().sqrt()
+    }
+
+    /// [Unit vector](https://en.wikipedia.org/wiki/Unit_vector) of the same direction.
+    ///
+    /// note: the zero vector is returned unchanged
+    fn normalize(&self) -> [f32; D] {
+        let norm = self.norm();
+        match norm > 0.0 {
+            true => self.map(|val| val / norm),
+            false => *self,
+        }
+    }
+}
+
+pub trait VectorCrossProduct<const D: usize> {
+    /// Vector [cross product](https://en.wikipedia.org/wiki/Cross_product).
+    fn cross_product(&self, other: &[f32; D]) -> [f32; D];
+}
+
+impl VectorCrossProduct<3> for [f32; 3] {
+    /// Vector [cross product](https://en.wikipedia.org/wiki/Cross_product).
+    fn cross_product(&self, other: &[f32; 3]) -> [f32; 3] {
+        [
+            self[1] * other[2] - self[2] * other[1],
+            self[2] * other[0] - self[0] * other[2],
+            self[0] * other[1] - self[1] * other[0],
+        ]
+    }
 }
 
 pub trait VectorRotate<const D: usize> {
-    fn rotate(&self, angle: f32) -> [f32; D];
+    /// Representation of a rotation.
+    type Rotation;
+
+    fn rotate(&self, rotation: Self::Rotation) -> [f32; D];
 }
 
 impl VectorRotate<2> for [f32; 2] {
+    /// Angle (radians, counterclockwise).
+    type Rotation = f32;
+
     fn rotate(&self, angle: f32) -> [f32; 2] {
         let (sin_a, cos_a) = angle.sin_cos();
         [
@@ -25,3 +76,108 @@ impl VectorRotate<2> for [f32; 2] {
         ]
     }
 }
+
+impl VectorRotate<3> for [f32; 3] {
+    /// [Axis and angle](https://en.wikipedia.org/wiki/Axis%E2%80%93angle_representation)
+    /// (radians, counterclockwise looking against the axis).
+    type Rotation = ([f32; 3], f32);
+
+    /// Rotate by
+    /// [Rodrigues' rotation formula](https://en.wikipedia.org/wiki/Rodrigues%27_rotation_formula).
+    ///
+    /// note: the axis need not be normalized
+    fn rotate(&self, (axis, angle): ([f32; 3], f32)) -> [f32; 3] {
+        let k = axis.normalize();
+        let (sin_a, cos_a) = angle.sin_cos();
+        let k_cross_v = k.cross_product(self);
+        let k_dot_v = k.dot_product(self);
+        std::array::from_fn(|d| {
+            self[d] * cos_a + k_cross_v[d] * sin_a + k[d] * k_dot_v * (1.0 - cos_a)
+        })
+    }
+}
+
+/// Small fixed-size [matrix](https://en.wikipedia.org/wiki/Matrix_(mathematics)) of `R` rows
+/// and `C` columns.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Matrix<const R: usize, const C: usize>(pub [[f32; C]; R]);
+
+impl<const R: usize, const C: usize> Matrix<R, C> {
+    /// Create a new `Matrix` of rows.
+    pub fn new(rows: [[f32; C]; R]) -> Self {
+        Self(rows)
+    }
+
+    /// Matrix of zeros.
+    pub fn zeros() -> Self {
+        Self([[0.0; C]; R])
+    }
+
+    /// [Transpose](https://en.wikipedia.org/wiki/Transpose) of the matrix.
+    pub fn transpose(&self) -> Matrix<C, R> {
+        Matrix(std::array::from_fn(|c| {
+            std::array::from_fn(|r| self.0[r][c])
+        }))
+    }
+
+    /// [Matrix product](https://en.wikipedia.org/wiki/Matrix_multiplication) with another
+    /// matrix.
+    pub fn multiply<const K: usize>(&self, other: &Matrix<C, K>) -> Matrix<R, K> {
+        let other = other.transpose();
+        Matrix(std::array::from_fn(|r| {
+            std::array::from_fn(|k| self.0[r].dot_product(&other.0[k]))
+        }))
+    }
+
+    /// Product with a (column) vector.
+    pub fn multiply_vector(&self, vector: &[f32; C]) -> [f32; R] {
+        std::array::from_fn(|r| self.0[r].dot_product(vector))
+    }
+}
+
+impl<const D: usize> Matrix<D, D> {
+    /// [Identity matrix](https://en.wikipedia.org/wiki/Identity_matrix).
+    pub fn identity() -> Self {
+        Self(std::array::from_fn(|r| {
+            std::array::from_fn(|c| match r == c {
+                true => 1.0,
+                false => 0.0,
+            })
+        }))
+    }
+
+    /// [Inverse](https://en.wikipedia.org/wiki/Invertible_matrix) of the matrix, if not
+    /// singular.
+    ///
+    /// note: computed by
+    /// [Gauss-Jordan elimination](https://en.wikipedia.org/wiki/Gaussian_elimination) with
+    /// partial pivoting, which is sufficient for small sizes
+    pub fn inverse(&self) -> Option<Self> {
+        let (mut values, mut inverse) = (self.0, Self::identity().0);
+        for c in 0..D {
+            let pivot = (c..D).max_by(|&first, &second| {
+                values[first][c].abs().total_cmp(&values[second][c].abs())
+            })?;
+            if values[pivot][c] == 0.0 {
+                return None;
+            }
+            values.swap(c, pivot);
+            inverse.swap(c, pivot);
+
+            let divisor = values[c][c];
+            values[c] = values[c].map(|val| val / divisor);
+            inverse[c] = inverse[c].map(|val| val / divisor);
+            for r in (0..D).filter(|&r| r != c) {
+                let factor = values[r][c];
+                let (row, inverse_row) = (values[c], inverse[c]);
+                for (val, pivot_val) in izip!(&mut values[r], row) {
+                    *val -= factor * pivot_val;
+                }
+                for (val, pivot_val) in izip!(&mut inverse[r], inverse_row) {
+                    *val -= factor * pivot_val;
+                }
+            }
+        }
+        Some(Self(inverse))
+    }
+}
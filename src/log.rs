@@ -0,0 +1,80 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Verbosity, as the index of the most verbose level logged.
+static VERBOSITY: AtomicUsize = AtomicUsize::new(Level::Warn as usize);
+
+/// Log levels, from the least to the most verbose.
+#[derive(Clone, Copy, PartialEq, PartialOrd)]
+pub enum Level {
+    Error,
+    Warn,
+    Info,
+    Debug,
+}
+
+impl Level {
+    /// All levels.
+    pub const ALL: [Level; 4] = [Level::Error, Level::Warn, Level::Info, Level::Debug];
+
+    /// Name of the level.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Level::Error => "Error",
+            Level::Warn => "Warning",
+            Level::Info => "Information",
+            Level::Debug => "Debug",
+        }
+    }
+}
+
+/// Most verbose level logged.
+pub fn verbosity() -> Level {
+    Level::ALL[VERBOSITY.load(Ordering::Relaxed)]
+}
+
+/// Set the most verbose level logged.
+pub fn set_verbosity(level: Level) {
+    VERBOSITY.store(level as usize, Ordering::Relaxed);
+}
+
+/// Log a message of a level, tagged by the module (e.g. "lbgk") it concerns, to the browser
+/// console (or standard error on native targets), if within the verbosity.
+pub fn log(level: Level, tag: &str, message: &str) {
+    if level > verbosity() {
+        return;
+    }
+    let text = format!("[{tag}] {message}");
+
+    #[cfg(target_arch = "wasm32")]
+    {
+        let text = wasm_bindgen::JsValue::from_str(&text);
+        match level {
+            Level::Error => web_sys::console::error_1(&text),
+            Level::Warn => web_sys::console::warn_1(&text),
+            Level::Info => web_sys::console::log_1(&text),
+            Level::Debug => web_sys::console::debug_1(&text),
+        }
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    eprintln!("{}: {text}", level.name());
+}
+
+/// Log an error message.
+pub fn error(tag: &str, message: &str) {
+    log(Level::Error, tag, message);
+}
+
+/// Log a warning message.
+pub fn warn(tag: &str, message: &str) {
+    log(Level::Warn, tag, message);
+}
+
+/// Log an information message.
+pub fn info(tag: &str, message: &str) {
+    log(Level::Info, tag, message);
+}
+
+/// Log a debug message.
+pub fn debug(tag: &str, message: &str) {
+    log(Level::Debug, tag, message);
+}
@@ -1,5 +1,11 @@
 pub mod circular;
 pub mod naca_4_digit_airfoil;
+pub mod oscillating;
+pub mod pitching;
+pub mod rectangular;
+pub mod rigid_body;
+
+use crate::lbgk::{Lbgk, DEFAULT_OBJECT_ID};
 
 pub trait Object<const D: usize> {
     /// The [characteristic length](https://en.wikipedia.org/wiki/Characteristic_length) of the object.
@@ -7,4 +13,51 @@ pub trait Object<const D: usize> {
 
     /// Calculate whether the object contains a position.
     fn contains(&self, pos: &[f32; D]) -> bool;
+
+    /// Whether the object moves over time.
+    fn moving(&self) -> bool {
+        false
+    }
+
+    /// Set the time (in iterations) used to position the object.
+    fn set_time(&mut self, _time: f32) {}
+
+    /// Velocity of the object at a position.
+    fn velocity(&self, _pos: &[f32; D]) -> [f32; D] {
+        [0.0; D]
+    }
+
+    /// Centre about which torque on the object is calculated.
+    fn centre_of_rotation(&self) -> [f32; D] {
+        [0.0; D]
+    }
+
+    /// Respond to the force and torque (about the centre of rotation) exerted on the object
+    /// during an iteration.
+    fn respond(&mut self, _force: &[f32; D], _torque: f32) {}
+}
+
+/// ID of the lattice positions of an object (by index into the objects of a scenario), to
+/// account for the force on each object separately.
+///
+/// note: the IDs follow the default object ID of objects without their own ID (e.g. painted)
+pub fn object_id(object: usize) -> u16 {
+    DEFAULT_OBJECT_ID + 1 + object as u16
+}
+
+/// ID of the first of the objects containing a lattice position (see `object_id`), 0 for
+/// fluid.
+pub fn containing_object_id(objects: &[Box<dyn Object<2>>], pos: &[usize; 2]) -> u16 {
+    objects
+        .iter()
+        .position(|object| object.contains(&pos.map(|val| val as f32)))
+        .map_or(0, object_id)
+}
+
+/// Set objects on a lattice, of the ID of the first containing each lattice position (see
+/// `object_id`), with fluid elsewhere.
+pub fn set_objects<const B: usize>(lbgk: &mut Lbgk<2, B>, objects: &[Box<dyn Object<2>>]) {
+    for pos in lbgk.positions() {
+        lbgk.set_object_id(&pos, containing_object_id(objects, &pos));
+    }
 }
@@ -3,6 +3,7 @@ use itertools::izip;
 use super::Object;
 
 /// A type describing an object that is circular in each dimension (e.g. circle, sphere).
+#[derive(Clone)]
 pub struct Circular<const D: usize> {
     /// Position
     pos: [f32; D],
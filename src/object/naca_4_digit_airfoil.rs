@@ -2,6 +2,7 @@ use super::Object;
 use crate::linear_algebra::VectorRotate;
 
 /// A type describing a [4-digit NACA airfoil](https://en.wikipedia.org/wiki/NACA_airfoil).
+#[derive(Clone)]
 pub struct Naca4DigitAirfoil {
     /// Position.
     pos: [f32; 2],
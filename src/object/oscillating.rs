@@ -0,0 +1,74 @@
+use itertools::izip;
+
+use super::Object;
+
+use std::f32::consts::TAU;
+
+/// A type describing an object displaced sinusoidally about its position.
+#[derive(Clone)]
+pub struct Oscillating<O, const D: usize> {
+    /// Object being oscillated.
+    object: O,
+    /// Amplitude of the displacement in each dimension.
+    amplitude: [f32; D],
+    /// Frequency (cycles per iteration).
+    frequency: f32,
+    /// Current displacement.
+    displacement: [f32; D],
+    /// Current velocity.
+    velocity: [f32; D],
+}
+
+impl<O: Object<D>, const D: usize> Oscillating<O, D> {
+    /// Create a new `Oscillating`.
+    pub fn new(object: O, amplitude: [f32; D], frequency: f32) -> Self {
+        let mut result = Self {
+            object,
+            amplitude,
+            frequency,
+            displacement: [0.0; D],
+            velocity: [0.0; D],
+        };
+        result.set_time(0.0);
+        result
+    }
+
+    /// Position relative to the undisplaced object.
+    fn undisplaced(&self, pos: &[f32; D]) -> [f32; D] {
+        let mut result = *pos;
+        for (result, displacement) in izip!(&mut result, self.displacement) {
+            *result -= displacement;
+        }
+        result
+    }
+}
+
+impl<O: Object<D>, const D: usize> Object<D> for Oscillating<O, D> {
+    fn characteristic_length(&self) -> f32 {
+        self.object.characteristic_length()
+    }
+
+    fn contains(&self, pos: &[f32; D]) -> bool {
+        self.object.contains(&self.undisplaced(pos))
+    }
+
+    fn moving(&self) -> bool {
+        true
+    }
+
+    fn set_time(&mut self, time: f32) {
+        self.object.set_time(time);
+        let omega = TAU * self.frequency;
+        let (sin, cos) = (omega * time).sin_cos();
+        self.displacement = self.amplitude.map(|amplitude| amplitude * sin);
+        self.velocity = self.amplitude.map(|amplitude| amplitude * omega * cos);
+    }
+
+    fn velocity(&self, pos: &[f32; D]) -> [f32; D] {
+        let mut result = self.object.velocity(&self.undisplaced(pos));
+        for (result, velocity) in izip!(&mut result, self.velocity) {
+            *result += velocity;
+        }
+        result
+    }
+}
@@ -0,0 +1,94 @@
+use super::Object;
+use crate::linear_algebra::VectorRotate;
+
+use std::f32::consts::TAU;
+
+/// A type describing a two dimensional object rotated sinusoidally about a pivot.
+///
+/// A positive angle rotates the object clockwise, increasing the angle of attack for a flow in
+/// the positive x-direction.
+#[derive(Clone)]
+pub struct Pitching<O> {
+    /// Object being pitched.
+    object: O,
+    /// Pivot position.
+    pivot: [f32; 2],
+    /// Mean angle (radians).
+    mean: f32,
+    /// Amplitude of the angle (radians).
+    amplitude: f32,
+    /// Frequency (cycles per iteration).
+    frequency: f32,
+    /// Phase (radians).
+    phase: f32,
+    /// Current angle (radians).
+    angle: f32,
+    /// Current angular velocity (radians per iteration).
+    angular_velocity: f32,
+}
+
+impl<O: Object<2>> Pitching<O> {
+    /// Create a new `Pitching`.
+    pub fn new(
+        object: O,
+        pivot: [f32; 2],
+        mean: f32,
+        amplitude: f32,
+        frequency: f32,
+        phase: f32,
+    ) -> Self {
+        let mut result = Self {
+            object,
+            pivot,
+            mean,
+            amplitude,
+            frequency,
+            phase,
+            angle: 0.0,
+            angular_velocity: 0.0,
+        };
+        result.set_time(0.0);
+        result
+    }
+
+    /// Position relative to the unrotated object.
+    fn unrotated(&self, pos: &[f32; 2]) -> [f32; 2] {
+        let [x, y] = [pos[0] - self.pivot[0], pos[1] - self.pivot[1]].rotate(self.angle);
+        [x + self.pivot[0], y + self.pivot[1]]
+    }
+}
+
+impl<O: Object<2>> Object<2> for Pitching<O> {
+    fn characteristic_length(&self) -> f32 {
+        self.object.characteristic_length()
+    }
+
+    fn contains(&self, pos: &[f32; 2]) -> bool {
+        self.object.contains(&self.unrotated(pos))
+    }
+
+    fn moving(&self) -> bool {
+        true
+    }
+
+    fn set_time(&mut self, time: f32) {
+        self.object.set_time(time);
+        let omega = TAU * self.frequency;
+        let (sin, cos) = (omega * time + self.phase).sin_cos();
+        self.angle = self.mean + self.amplitude * sin;
+        self.angular_velocity = self.amplitude * omega * cos;
+    }
+
+    fn velocity(&self, pos: &[f32; 2]) -> [f32; 2] {
+        // note: clockwise rotation for increasing angle
+        let [rx, ry] = [pos[0] - self.pivot[0], pos[1] - self.pivot[1]];
+        let [u, v] = self
+            .object
+            .velocity(&self.unrotated(pos))
+            .rotate(-self.angle);
+        [
+            u + self.angular_velocity * ry,
+            v - self.angular_velocity * rx,
+        ]
+    }
+}
@@ -0,0 +1,33 @@
+use itertools::izip;
+
+use super::Object;
+
+/// A type describing an object that is rectangular and axis aligned (e.g. rectangle, cuboid).
+#[derive(Clone)]
+pub struct Rectangular<const D: usize> {
+    /// Minimum position (inclusive).
+    min: [f32; D],
+    /// Maximum position (inclusive).
+    max: [f32; D],
+}
+
+impl<const D: usize> Rectangular<D> {
+    /// Create a new `Rectangular` between a minimum and maximum position.
+    pub fn new(min: [f32; D], max: [f32; D]) -> Self {
+        Self { min, max }
+    }
+}
+
+impl<const D: usize> Object<D> for Rectangular<D> {
+    /// Extent across the flow, i.e. in the second dimension.
+    fn characteristic_length(&self) -> f32 {
+        match D {
+            1 => self.max[0] - self.min[0],
+            _ => self.max[1] - self.min[1],
+        }
+    }
+
+    fn contains(&self, pos: &[f32; D]) -> bool {
+        izip!(pos, self.min, self.max).all(|(pos, min, max)| (min..=max).contains(pos))
+    }
+}
@@ -0,0 +1,109 @@
+use itertools::izip;
+
+use super::Object;
+use crate::linear_algebra::VectorRotate;
+
+/// A type describing a two dimensional object moved as a
+/// [rigid body](https://en.wikipedia.org/wiki/Rigid_body) by the forces and torque exerted on it,
+/// optionally mounted on springs and dampers.
+///
+/// Degrees of freedom are ordered as x-displacement, y-displacement and (counterclockwise)
+/// rotation. A degree of freedom with infinite inertia is fixed.
+///
+/// note: the coupling is explicit, so light objects (relative to the displaced fluid) can be
+/// unstable.
+#[derive(Clone)]
+pub struct RigidBody<O> {
+    /// Object being moved.
+    object: O,
+    /// Centre of rotation when undisplaced.
+    centre: [f32; 2],
+    /// Mass for each displacement and moment of inertia for the rotation.
+    inertia: [f32; 3],
+    /// Spring stiffness for each degree of freedom.
+    stiffness: [f32; 3],
+    /// Damping coefficient for each degree of freedom.
+    damping: [f32; 3],
+    /// Current displacements and angle.
+    displacement: [f32; 3],
+    /// Current velocities and angular velocity.
+    velocity: [f32; 3],
+}
+
+impl<O: Object<2>> RigidBody<O> {
+    /// Create a new `RigidBody`.
+    pub fn new(
+        object: O,
+        centre: [f32; 2],
+        inertia: [f32; 3],
+        stiffness: [f32; 3],
+        damping: [f32; 3],
+    ) -> Self {
+        Self {
+            object,
+            centre,
+            inertia,
+            stiffness,
+            damping,
+            displacement: [0.0; 3],
+            velocity: [0.0; 3],
+        }
+    }
+
+    /// Current displacements and angle.
+    pub fn displacement(&self) -> [f32; 3] {
+        self.displacement
+    }
+
+    /// Position relative to the centre of rotation.
+    fn relative(&self, pos: &[f32; 2]) -> [f32; 2] {
+        let centre = self.centre_of_rotation();
+        [pos[0] - centre[0], pos[1] - centre[1]]
+    }
+}
+
+impl<O: Object<2>> Object<2> for RigidBody<O> {
+    fn characteristic_length(&self) -> f32 {
+        self.object.characteristic_length()
+    }
+
+    fn contains(&self, pos: &[f32; 2]) -> bool {
+        let [x, y] = self.relative(pos).rotate(-self.displacement[2]);
+        self.object
+            .contains(&[x + self.centre[0], y + self.centre[1]])
+    }
+
+    fn moving(&self) -> bool {
+        true
+    }
+
+    fn velocity(&self, pos: &[f32; 2]) -> [f32; 2] {
+        let [rx, ry] = self.relative(pos);
+        [
+            self.velocity[0] - self.velocity[2] * ry,
+            self.velocity[1] + self.velocity[2] * rx,
+        ]
+    }
+
+    fn centre_of_rotation(&self) -> [f32; 2] {
+        [
+            self.centre[0] + self.displacement[0],
+            self.centre[1] + self.displacement[1],
+        ]
+    }
+
+    fn respond(&mut self, force: &[f32; 2], torque: f32) {
+        // semi-implicit Euler integration over an iteration
+        for (displacement, velocity, load, inertia, stiffness, damping) in izip!(
+            &mut self.displacement,
+            &mut self.velocity,
+            [force[0], force[1], torque],
+            self.inertia,
+            self.stiffness,
+            self.damping
+        ) {
+            *velocity += (load - stiffness * *displacement - damping * *velocity) / inertia;
+            *displacement += *velocity;
+        }
+    }
+}
@@ -0,0 +1,195 @@
+use crate::lbgk::Lbgk;
+
+use wasm_bindgen::prelude::*;
+
+use std::collections::VecDeque;
+
+/// Particle state.
+#[derive(Clone, Copy)]
+struct Particle {
+    pos: [f32; 2],
+    // note: initialised from the fluid on the first advection
+    velocity: Option<[f32; 2]>,
+}
+
+/// Particles advected by the fluid, either massless
+/// [tracer particles](https://en.wikipedia.org/wiki/Flow_tracer) or finite inertia particles
+/// subject to [Stokes drag](https://en.wikipedia.org/wiki/Stokes%27_law) and gravity.
+pub struct Particles {
+    capacity: usize,
+    // note: the oldest particles are removed first when at capacity
+    particles: VecDeque<Particle>,
+    /// Particle response time (iterations), zero for tracers.
+    response_time: f32,
+    /// Gravitational acceleration.
+    gravity: [f32; 2],
+    /// Positions at which particles were deposited on objects.
+    deposits: VecDeque<[f32; 2]>,
+}
+
+impl Particles {
+    /// Create a new `Particles` of tracers retaining at most `capacity` particles.
+    pub fn new(capacity: usize) -> Self {
+        Self::new_inertial(capacity, 0.0, [0.0; 2])
+    }
+
+    /// Create a new `Particles` of finite inertia retaining at most `capacity` particles.
+    pub fn new_inertial(capacity: usize, response_time: f32, gravity: [f32; 2]) -> Self {
+        Self {
+            capacity,
+            particles: VecDeque::with_capacity(capacity),
+            response_time,
+            gravity,
+            deposits: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    /// Calculate the particle response time for a
+    /// [Stokes number](https://en.wikipedia.org/wiki/Stokes_number).
+    pub fn response_time(stokes_number: f32, characteristic_length: f32, velocity: f32) -> f32 {
+        stokes_number * characteristic_length / velocity
+    }
+
+    /// Number of particles.
+    pub fn len(&self) -> usize {
+        self.particles.len()
+    }
+
+    /// Whether there are no particles.
+    pub fn is_empty(&self) -> bool {
+        self.particles.is_empty()
+    }
+
+    /// Particle positions.
+    pub fn positions(&self) -> impl Iterator<Item = &[f32; 2]> {
+        self.particles.iter().map(|particle| &particle.pos)
+    }
+
+    /// Positions at which particles were deposited on objects.
+    pub fn deposits(&self) -> impl Iterator<Item = &[f32; 2]> {
+        self.deposits.iter()
+    }
+
+    /// Add a particle at a position.
+    pub fn seed(&mut self, pos: [f32; 2]) {
+        if self.particles.len() == self.capacity {
+            self.particles.pop_front();
+        }
+        self.particles.push_back(Particle {
+            pos,
+            velocity: None,
+        });
+    }
+
+    /// Add particles evenly spaced along a line.
+    pub fn seed_line(&mut self, start: [f32; 2], end: [f32; 2], count: usize) {
+        for pos in line_positions(start, end, count) {
+            self.seed(pos);
+        }
+    }
+
+    /// Remove all particles and deposits.
+    pub fn clear(&mut self) {
+        self.particles.clear();
+        self.deposits.clear();
+    }
+
+    /// Advect the particles over an iteration, removing those leaving the lattice and depositing
+    /// those entering objects.
+    pub fn advect<const B: usize>(&mut self, lbgk: &Lbgk<2, B>) {
+        let (response_time, gravity) = (self.response_time, self.gravity);
+        let mut deposits = Vec::new();
+
+        self.particles.retain_mut(|particle| {
+            let pos = particle.pos;
+            let u = lbgk.interpolated_velocity_vector(&pos);
+            match response_time > 0.0 {
+                true => {
+                    // exact solution for the fluid velocity held constant over the iteration
+                    let v = particle.velocity.unwrap_or(u);
+                    let decay = (-1.0 / response_time).exp();
+                    let mut new_v = [0.0; 2];
+                    for d in 0..2 {
+                        let terminal = u[d] + gravity[d] * response_time;
+                        new_v[d] = terminal + (v[d] - terminal) * decay;
+                        particle.pos[d] +=
+                            terminal + (v[d] - terminal) * response_time * (1.0 - decay);
+                    }
+                    particle.velocity = Some(new_v);
+                }
+                false => {
+                    let new_pos = advect_tracer(lbgk, &pos);
+                    particle.velocity = Some([new_pos[0] - pos[0], new_pos[1] - pos[1]]);
+                    particle.pos = new_pos;
+                }
+            }
+
+            let pos = particle.pos;
+            if !inside(lbgk, &pos) {
+                return false;
+            }
+            if lbgk.object(&pos.map(|val| val.round() as usize)) {
+                deposits.push(pos);
+                return false;
+            }
+            true
+        });
+
+        for pos in deposits {
+            if self.deposits.len() == self.capacity {
+                self.deposits.pop_front();
+            }
+            self.deposits.push_back(pos);
+        }
+    }
+
+    /// Draw the particles and deposits (in lattice coordinates).
+    pub fn draw(
+        &self,
+        colour: &str,
+        deposit_colour: &str,
+        canvas_rendering_context: &web_sys::CanvasRenderingContext2d,
+    ) -> Result<(), JsValue> {
+        let fill = |pos: &[f32; 2]| {
+            canvas_rendering_context.fill_rect(pos[0] as f64 - 1.0, pos[1] as f64 - 1.0, 2.0, 2.0)
+        };
+        canvas_rendering_context.set_fill_style_str(colour);
+        self.positions().for_each(fill);
+        canvas_rendering_context.set_fill_style_str(deposit_colour);
+        self.deposits().for_each(fill);
+
+        Ok(())
+    }
+}
+
+/// Advect a tracer position over an iteration with the (midpoint method) integrated velocity.
+pub fn advect_tracer<const B: usize>(lbgk: &Lbgk<2, B>, pos: &[f32; 2]) -> [f32; 2] {
+    let u = lbgk.interpolated_velocity_vector(pos);
+    let mid_pos = [pos[0] + 0.5 * u[0], pos[1] + 0.5 * u[1]];
+    let u = lbgk.interpolated_velocity_vector(&mid_pos);
+    [pos[0] + u[0], pos[1] + u[1]]
+}
+
+/// Positions evenly spaced along a line, at the centres of as many equal segments of it.
+pub fn line_positions(
+    start: [f32; 2],
+    end: [f32; 2],
+    count: usize,
+) -> impl Iterator<Item = [f32; 2]> {
+    (0..count).map(move |i| {
+        let s = (i as f32 + 0.5) / count as f32;
+        [0, 1].map(|d| start[d] + s * (end[d] - start[d]))
+    })
+}
+
+/// Whether a position is inside the lattice.
+pub fn inside<const B: usize>(lbgk: &Lbgk<2, B>, pos: &[f32; 2]) -> bool {
+    let size = lbgk.size();
+    (0..2).all(|d| (0.0..=(size[d] - 1) as f32).contains(&pos[d]))
+}
+
+/// Whether a position is inside the lattice and in the fluid (i.e. its nearest lattice position
+/// is not an object).
+pub fn fluid<const B: usize>(lbgk: &Lbgk<2, B>, pos: &[f32; 2]) -> bool {
+    inside(lbgk, pos) && !lbgk.object(&pos.map(|val| val.round() as usize))
+}
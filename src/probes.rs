@@ -0,0 +1,132 @@
+use crate::lbgk::{Field, Lbgk};
+
+use wasm_bindgen::prelude::*;
+
+use std::collections::VecDeque;
+
+/// Probes recording the values of a field at lattice positions each iteration (e.g. the
+/// pressure of passing sound).
+pub struct Probes {
+    field: Field,
+    positions: Vec<[usize; 2]>,
+    capacity: usize,
+    // note: the values of all probes are kept with the iteration they were recorded at
+    values: VecDeque<(usize, Vec<f32>)>,
+}
+
+impl Probes {
+    /// Create a new `Probes` of a field at lattice positions, retaining at most `capacity`
+    /// values of each.
+    pub fn new(field: Field, positions: Vec<[usize; 2]>, capacity: usize) -> Self {
+        Self {
+            field,
+            positions,
+            capacity,
+            values: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    /// Lattice positions of the probes.
+    pub fn positions(&self) -> &[[usize; 2]] {
+        &self.positions
+    }
+
+    /// Whether there are no probes.
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    /// Remove all recorded values.
+    pub fn clear(&mut self) {
+        self.values.clear();
+    }
+
+    /// Record the values of the field at the probes at an iteration.
+    pub fn record<const B: usize>(&mut self, iteration: usize, lbgk: &Lbgk<2, B>) {
+        if self.values.len() == self.capacity {
+            self.values.pop_front();
+        }
+        let values = self
+            .positions
+            .iter()
+            .map(|pos| lbgk.field(self.field, pos))
+            .collect();
+        self.values.push_back((iteration, values));
+    }
+
+    /// Recorded values of a probe, with the iteration they were recorded at.
+    pub fn values(&self, probe: usize) -> impl Iterator<Item = (usize, f32)> + '_ {
+        self.values
+            .iter()
+            .map(move |(iteration, values)| (*iteration, values[probe]))
+    }
+
+    /// Values of all probes recorded after an iteration (or all, if none), with the iteration
+    /// they were recorded at.
+    pub fn recorded_after(
+        &self,
+        iteration: Option<usize>,
+    ) -> impl Iterator<Item = (usize, &[f32])> + '_ {
+        self.values
+            .iter()
+            .filter(move |(recorded, _)| iteration.is_none_or(|iteration| *recorded > iteration))
+            .map(|(recorded, values)| (*recorded, values.as_slice()))
+    }
+
+    /// Time (in iterations) of the peak of the recorded values of a probe, between iterations
+    /// by fitting a parabola through the largest value and its neighbours, if passed (i.e. not
+    /// the last value).
+    ///
+    /// note: assumes values are recorded each iteration
+    pub fn peak_time(&self, probe: usize) -> Option<f32> {
+        let values = self.values(probe).collect::<Vec<_>>();
+        let (i, &(iteration, peak)) = values
+            .iter()
+            .enumerate()
+            .max_by(|(_, (_, first)), (_, (_, second))| first.total_cmp(second))?;
+        let &(_, next) = values.get(i + 1)?;
+        let offset = match i.checked_sub(1) {
+            Some(previous) => {
+                let previous = values[previous].1;
+                let curvature = previous - 2.0 * peak + next;
+                match curvature < 0.0 {
+                    true => 0.5 * (previous - next) / curvature,
+                    false => 0.0,
+                }
+            }
+            None => 0.0,
+        };
+        Some(iteration as f32 + offset)
+    }
+
+    /// Speed of propagation from a probe to another, from the distance between them and the
+    /// times of their peaks, if passed (e.g. the speed of sound of a pressure pulse).
+    pub fn speed(&self, first: usize, second: usize) -> Option<f32> {
+        let duration = self.peak_time(second)? - self.peak_time(first)?;
+        let [dx, dy] =
+            [0, 1].map(|d| self.positions[second][d] as f32 - self.positions[first][d] as f32);
+        (duration > 0.0).then(|| (dx * dx + dy * dy).sqrt() / duration)
+    }
+
+    /// Draw the probes as circles (in lattice coordinates).
+    pub fn draw(
+        &self,
+        colour: &str,
+        canvas_rendering_context: &web_sys::CanvasRenderingContext2d,
+    ) -> Result<(), JsValue> {
+        canvas_rendering_context.set_stroke_style_str(colour);
+        for pos in &self.positions {
+            canvas_rendering_context.begin_path();
+            canvas_rendering_context.arc(
+                pos[0] as f64,
+                pos[1] as f64,
+                2.0,
+                0.0,
+                std::f64::consts::TAU,
+            )?;
+            canvas_rendering_context.stroke();
+        }
+
+        Ok(())
+    }
+}
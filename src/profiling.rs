@@ -0,0 +1,172 @@
+use std::cell::RefCell;
+use std::collections::VecDeque;
+
+thread_local! {
+    static PHASE_TIMINGS: RefCell<Vec<(&'static str, PhaseTimings)>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Durations of a phase of work (milliseconds).
+#[derive(Clone, Copy, Default)]
+pub struct PhaseTimings {
+    /// Duration during the current frame.
+    pub frame: f64,
+    /// Cumulative duration since the timings were cleared.
+    pub total: f64,
+}
+
+/// Timings of the phases of work measured, in the order first measured.
+pub fn phase_timings() -> Vec<(&'static str, PhaseTimings)> {
+    PHASE_TIMINGS.with_borrow(|phase_timings| phase_timings.clone())
+}
+
+/// Start a new frame, clearing the durations of the phases during the current frame.
+pub fn start_frame() {
+    PHASE_TIMINGS.with_borrow_mut(|phase_timings| {
+        for (_, timings) in phase_timings {
+            timings.frame = 0.0;
+        }
+    });
+}
+
+/// Clear the timings of all phases.
+pub fn clear_phase_timings() {
+    PHASE_TIMINGS.with_borrow_mut(|phase_timings| phase_timings.clear());
+}
+
+/// High resolution time (milliseconds), from the browser
+/// [performance](https://developer.mozilla.org/en-US/docs/Web/API/Performance) interface.
+///
+/// note: on native targets the time is from the first call
+pub fn now() -> f64 {
+    #[cfg(target_arch = "wasm32")]
+    {
+        performance()
+            .map(|performance| performance.now())
+            .unwrap_or(0.0)
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        static START: std::sync::OnceLock<std::time::Instant> = std::sync::OnceLock::new();
+        START
+            .get_or_init(std::time::Instant::now)
+            .elapsed()
+            .as_secs_f64()
+            * 1.0e3
+    }
+}
+
+/// Moving average of the iteration rate (iterations per second) over a period.
+pub struct IterationRate {
+    /// Period (milliseconds).
+    period: f64,
+    /// Time of the first iteration recorded since cleared, if any.
+    start: Option<f64>,
+    /// Times of the iterations recorded within the period.
+    dates: VecDeque<f64>,
+}
+
+impl IterationRate {
+    /// Create a new `IterationRate` of a period (seconds).
+    pub fn new(period_secs: f64) -> Self {
+        Self {
+            period: period_secs * 1.0e3,
+            start: None,
+            dates: VecDeque::new(),
+        }
+    }
+
+    /// Record an iteration at a time (milliseconds, see `now`).
+    pub fn record(&mut self, now: f64) {
+        self.start.get_or_insert(now);
+        self.dates.push_back(now);
+        while let Some(front) = self.dates.front() {
+            if front + self.period > now {
+                break;
+            }
+            self.dates.pop_front();
+        }
+    }
+
+    /// Iterations per second over the period.
+    pub fn rate(&self) -> f64 {
+        self.dates.len() as f64 / (self.period * 1.0e-3)
+    }
+
+    /// Whether iterations have been recorded for at least the period, as the rate is
+    /// underestimated until then.
+    pub fn settled(&self) -> bool {
+        match (self.start, self.dates.back()) {
+            (Some(start), Some(back)) => back - start >= self.period,
+            _ => false,
+        }
+    }
+
+    /// Clear the iterations recorded (e.g. when the work of an iteration changes).
+    pub fn clear(&mut self) {
+        self.start = None;
+        self.dates.clear();
+    }
+}
+
+/// Perform a phase of work, measured from its start to its end.
+pub fn measure<T>(name: &'static str, f: impl FnOnce() -> T) -> T {
+    let _measure = Measure::start(name);
+    f()
+}
+
+/// Measure of a phase of work, from its start until dropped, added to the phase timings.
+///
+/// note: in the browser, performance marks and measures are also made for developer tools
+pub struct Measure {
+    name: &'static str,
+    start: f64,
+}
+
+impl Measure {
+    /// Start a new `Measure` of a phase of work.
+    pub fn start(name: &'static str) -> Self {
+        #[cfg(target_arch = "wasm32")]
+        if let Some(performance) = performance() {
+            let _ = performance.mark(&format!("{name} start"));
+        }
+        Self { name, start: now() }
+    }
+}
+
+impl Drop for Measure {
+    fn drop(&mut self) {
+        let duration = now() - self.start;
+        PHASE_TIMINGS.with_borrow_mut(|phase_timings| {
+            let index = match phase_timings
+                .iter()
+                .position(|(name, _)| *name == self.name)
+            {
+                Some(index) => index,
+                None => {
+                    phase_timings.push((self.name, PhaseTimings::default()));
+                    phase_timings.len() - 1
+                }
+            };
+            let timings = &mut phase_timings[index].1;
+            timings.frame += duration;
+            timings.total += duration;
+        });
+
+        #[cfg(target_arch = "wasm32")]
+        if let Some(performance) = performance() {
+            let (start, end) = (format!("{} start", self.name), format!("{} end", self.name));
+            let _ = performance.mark(&end);
+            let _ = performance.measure_with_start_mark_and_end_mark(self.name, &start, &end);
+            // note: developer tools record the entries as they are made, so the buffer is
+            // cleared rather than growing every iteration
+            performance.clear_marks_with_mark_name(&start);
+            performance.clear_marks_with_mark_name(&end);
+            performance.clear_measures_with_measure_name(self.name);
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn performance() -> Option<web_sys::Performance> {
+    web_sys::window().and_then(|window| window.performance())
+}
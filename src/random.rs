@@ -0,0 +1,53 @@
+/// Seed of the random numbers, unless given.
+pub const DEFAULT_SEED: u32 = 0x9e37_79b9;
+
+/// Deterministic [pseudorandom number generator](https://en.wikipedia.org/wiki/Pseudorandom_number_generator)
+/// of a seed, by [xoshiro128++](https://prng.di.unimi.it/), so stochastic runs are reproducible
+/// (and identical in native and WebAssembly builds).
+#[derive(Clone)]
+pub struct Random {
+    state: [u32; 4],
+}
+
+impl Random {
+    /// Create a new `Random` of a seed.
+    ///
+    /// note: the state is expanded from the seed as by
+    /// [SplitMix](https://doi.org/10.1145/2714064.2660195), mixing successive values by a
+    /// bijection, so its values are distinct and never all zero
+    pub fn new(seed: u32) -> Self {
+        let mut val = seed;
+        let state = [(); 4].map(|_| {
+            val = val.wrapping_add(0x9e37_79b9);
+            let mut z = val;
+            z = (z ^ (z >> 16)).wrapping_mul(0x85eb_ca6b);
+            z = (z ^ (z >> 13)).wrapping_mul(0xc2b2_ae35);
+            z ^ (z >> 16)
+        });
+        Self { state }
+    }
+
+    /// Next random integer, uniformly distributed over all values.
+    pub fn next_u32(&mut self) -> u32 {
+        let [s0, s1, s2, s3] = self.state;
+        let result = s0.wrapping_add(s3).rotate_left(7).wrapping_add(s0);
+        let t = s1 << 9;
+        let s2 = s2 ^ s0;
+        let s3 = s3 ^ s1;
+        let s1 = s1 ^ s2;
+        let s0 = s0 ^ s3;
+        self.state = [s0, s1, s2 ^ t, s3.rotate_left(11)];
+        result
+    }
+
+    /// Next random number, uniformly distributed in [0, 1).
+    pub fn uniform(&mut self) -> f32 {
+        // note: the upper 24 bits fill the significand exactly
+        (self.next_u32() >> 8) as f32 / (1 << 24) as f32
+    }
+
+    /// Next random number, uniformly distributed in [min, max).
+    pub fn uniform_range(&mut self, min: f32, max: f32) -> f32 {
+        min + (max - min) * self.uniform()
+    }
+}
@@ -0,0 +1,234 @@
+use crate::indexed_db::IndexedDb;
+use crate::lbgk::{Field, Lbgk};
+use crate::snapshot::Snapshot;
+
+const MAGIC: &[u8; 8] = b"LBMRECD1";
+const SNAPSHOT_PREFIX: &str = "snapshot ";
+const TIME_SERIES_PREFIX: &str = "time series ";
+
+/// Key of the snapshot chunk of an iteration.
+///
+/// note: zero padded, so the keys sort by iteration
+fn snapshot_key(iteration: usize) -> String {
+    format!("{SNAPSHOT_PREFIX}{iteration:012}")
+}
+
+/// Key of a time series chunk.
+fn time_series_key(chunk: usize) -> String {
+    format!("{TIME_SERIES_PREFIX}{chunk:06}")
+}
+
+/// Sink of the chunks of a recording, by key.
+pub trait OutputSink {
+    /// Name of the sink.
+    fn name(&self) -> &'static str;
+
+    /// Write a chunk, replacing any of its key.
+    fn write(&mut self, key: &str, bytes: &[u8]) -> Result<(), String>;
+}
+
+/// Sink of the chunks of a recording in memory.
+#[derive(Default)]
+pub struct MemorySink {
+    chunks: Vec<(String, Vec<u8>)>,
+}
+
+impl MemorySink {
+    /// Chunks, in the order written.
+    pub fn chunks(&self) -> &[(String, Vec<u8>)] {
+        &self.chunks
+    }
+}
+
+impl OutputSink for MemorySink {
+    fn name(&self) -> &'static str {
+        "Memory"
+    }
+
+    fn write(&mut self, key: &str, bytes: &[u8]) -> Result<(), String> {
+        self.chunks.retain(|(chunk_key, _)| chunk_key != key);
+        self.chunks.push((key.to_string(), bytes.to_vec()));
+        Ok(())
+    }
+}
+
+/// Sink of the chunks of a recording in an IndexedDB database of the browser dedicated to them,
+/// so a long recording is not kept in memory.
+pub struct IndexedDbSink {
+    indexed_db: IndexedDb,
+}
+
+impl IndexedDbSink {
+    /// Create a new `IndexedDbSink` of an IndexedDB database.
+    pub fn new(indexed_db: IndexedDb) -> Self {
+        Self { indexed_db }
+    }
+
+    /// Remove all chunks (e.g. of a previous recording).
+    pub fn clear(&self) -> Result<(), String> {
+        self.indexed_db.clear().map_err(|err| format!("{err:?}"))
+    }
+
+    /// Load all chunks and export them (see `export`), passing the time series and snapshots
+    /// to a callback.
+    pub fn export(&self, f: impl FnOnce(String, Vec<u8>) + 'static) -> Result<(), String> {
+        self.indexed_db
+            .entries(move |chunks| {
+                let (time_series, snapshots) = export(chunks);
+                f(time_series, snapshots);
+            })
+            .map_err(|err| format!("{err:?}"))
+    }
+}
+
+impl OutputSink for IndexedDbSink {
+    fn name(&self) -> &'static str {
+        "IndexedDB"
+    }
+
+    fn write(&mut self, key: &str, bytes: &[u8]) -> Result<(), String> {
+        self.indexed_db
+            .put(key, bytes)
+            .map_err(|err| format!("{err:?}"))
+    }
+}
+
+/// Recording of a simulation streamed in chunks to a sink, i.e. snapshots of fields every
+/// interval of iterations and rows of a time series each iteration, written in chunks of a
+/// number of rows (e.g. for long runs too large to keep in memory).
+pub struct Recording<S: OutputSink> {
+    snapshot_interval: usize,
+    fields: Vec<Field>,
+    headers: Vec<String>,
+    chunk_length: usize,
+    sink: S,
+    /// Rows of the time series not yet written, as CSV.
+    rows: String,
+    row_count: usize,
+    /// Index of the next time series chunk.
+    chunk: usize,
+}
+
+impl<S: OutputSink> Recording<S> {
+    /// Create a new `Recording` of fields every snapshot interval (iterations), and a time
+    /// series of headers in chunks of a length (rows), to a sink.
+    pub fn new(
+        snapshot_interval: usize,
+        fields: &[Field],
+        headers: &[&str],
+        chunk_length: usize,
+        sink: S,
+    ) -> Self {
+        Self {
+            snapshot_interval: snapshot_interval.max(1),
+            fields: fields.to_vec(),
+            headers: std::iter::once("iteration")
+                .chain(headers.iter().copied())
+                .map(str::to_string)
+                .collect(),
+            chunk_length: chunk_length.max(1),
+            sink,
+            rows: String::new(),
+            row_count: 0,
+            chunk: 0,
+        }
+    }
+
+    /// Sink of the chunks.
+    pub fn sink(&self) -> &S {
+        &self.sink
+    }
+
+    /// Record the values of the time series at an iteration, and a snapshot of the fields of a
+    /// lattice if due.
+    pub fn record<const B: usize>(
+        &mut self,
+        iteration: usize,
+        lbgk: &Lbgk<2, B>,
+        values: &[f32],
+    ) -> Result<(), String> {
+        // note: the header is the first row of the first chunk
+        if self.chunk == 0 && self.row_count == 0 {
+            self.rows.push_str(&format!("{}\n", self.headers.join(",")));
+        }
+        let row = std::iter::once(iteration.to_string())
+            .chain(values.iter().map(f32::to_string))
+            .collect::<Vec<_>>();
+        self.rows.push_str(&format!("{}\n", row.join(",")));
+        self.row_count += 1;
+        if self.row_count == self.chunk_length {
+            self.flush()?;
+        }
+
+        if iteration.is_multiple_of(self.snapshot_interval) {
+            let snapshot = Snapshot::new(lbgk, &self.fields);
+            self.sink
+                .write(&snapshot_key(iteration), &snapshot.to_bytes())?;
+        }
+        Ok(())
+    }
+
+    /// Write the rows of the time series not yet written as a chunk, if any (e.g. before an
+    /// export).
+    pub fn flush(&mut self) -> Result<(), String> {
+        if self.row_count == 0 {
+            return Ok(());
+        }
+        self.sink
+            .write(&time_series_key(self.chunk), self.rows.as_bytes())?;
+        self.rows.clear();
+        self.row_count = 0;
+        self.chunk += 1;
+        Ok(())
+    }
+}
+
+/// Export the chunks of a recording as the time series in
+/// [CSV](https://en.wikipedia.org/wiki/Comma-separated_values) and the snapshots in a binary
+/// format, ignoring other chunks.
+///
+/// The binary format is the magic bytes `LBMRECD1`, then for each snapshot in order of
+/// iteration, its iteration and the length of its binary representation (u64, little endian)
+/// followed by its binary representation (see `Snapshot`).
+pub fn export(chunks: impl IntoIterator<Item = (String, Vec<u8>)>) -> (String, Vec<u8>) {
+    let mut chunks = chunks.into_iter().collect::<Vec<_>>();
+    chunks.sort_by(|(first, _), (second, _)| first.cmp(second));
+
+    let mut time_series = String::new();
+    let mut snapshots = MAGIC.to_vec();
+    for (key, bytes) in chunks {
+        if key.starts_with(TIME_SERIES_PREFIX) {
+            time_series.push_str(&String::from_utf8_lossy(&bytes));
+        } else if let Some(iteration) = key
+            .strip_prefix(SNAPSHOT_PREFIX)
+            .and_then(|iteration| iteration.parse::<u64>().ok())
+        {
+            snapshots.extend_from_slice(&iteration.to_le_bytes());
+            snapshots.extend_from_slice(&(bytes.len() as u64).to_le_bytes());
+            snapshots.extend_from_slice(&bytes);
+        }
+    }
+    (time_series, snapshots)
+}
+
+/// Snapshots, with their iteration, from their exported binary representation (see `export`),
+/// if valid.
+pub fn snapshots_from_bytes(bytes: &[u8]) -> Option<Vec<(usize, Snapshot)>> {
+    let (magic, mut bytes) = bytes.split_at_checked(MAGIC.len())?;
+    if magic != MAGIC {
+        return None;
+    }
+    let mut result = Vec::new();
+    while !bytes.is_empty() {
+        let (iteration, rest) = bytes.split_at_checked(8)?;
+        let (len, rest) = rest.split_at_checked(8)?;
+        let len = u64::from_le_bytes(len.try_into().ok()?) as usize;
+        let (snapshot, rest) = rest.split_at_checked(len)?;
+        result.push((
+            u64::from_le_bytes(iteration.try_into().ok()?) as usize,
+            Snapshot::from_bytes(snapshot)?,
+        ));
+        bytes = rest;
+    }
+    Some(result)
+}
@@ -0,0 +1,135 @@
+use crate::lbgk::Lbgk;
+use crate::linear_algebra::VectorOps;
+
+use itertools::izip;
+
+use std::collections::VecDeque;
+
+/// Number of residuals averaged to judge whether the flow is statistically steady.
+const WINDOW_LENGTH: usize = 10;
+/// Largest relative change of the average residual between windows of a statistically steady
+/// flow.
+const STATISTICALLY_STEADY_TOLERANCE: f32 = 0.1;
+
+/// Convergence of the flow to a steady state.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Convergence {
+    /// The residual is changing.
+    Developing,
+    /// The residual is above the target, but no longer changing on average (e.g. periodic
+    /// vortex shedding).
+    StatisticallySteady,
+    /// The residual is below the target.
+    Steady,
+}
+
+impl Convergence {
+    /// Name of the convergence.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Convergence::Developing => "Developing",
+            Convergence::StatisticallySteady => "Statistically steady",
+            Convergence::Steady => "Steady",
+        }
+    }
+}
+
+/// Monitor of the residual of the flow, the change of the velocity per iteration relative to the
+/// velocity (over the fluid lattice positions), calculated at an interval of iterations.
+pub struct ResidualMonitor<const N: usize> {
+    interval: usize,
+    target: f32,
+    // note: the velocity vectors at the last calculation, with the iteration
+    previous: Option<(usize, Vec<[f32; N]>)>,
+    residuals: VecDeque<f32>,
+}
+
+impl<const N: usize> ResidualMonitor<N> {
+    /// Create a new `ResidualMonitor` of an interval of iterations, and a target residual of a
+    /// steady flow.
+    pub fn new(interval: usize, target: f32) -> Self {
+        Self {
+            interval: interval.max(1),
+            target,
+            previous: None,
+            residuals: VecDeque::with_capacity(2 * WINDOW_LENGTH),
+        }
+    }
+
+    /// Target residual of a steady flow.
+    pub fn target(&self) -> f32 {
+        self.target
+    }
+
+    /// Remove the residuals (e.g. when the flow is reset).
+    pub fn clear(&mut self) {
+        self.previous = None;
+        self.residuals.clear();
+    }
+
+    /// Update the residual at an iteration, if at the interval.
+    pub fn update<const B: usize>(&mut self, iteration: usize, lbgk: &Lbgk<N, B>) {
+        if !iteration.is_multiple_of(self.interval) {
+            return;
+        }
+
+        let velocity_vectors = lbgk
+            .iter_cells()
+            .map(|(_, cell)| match cell.object() {
+                true => [0.0; N],
+                false => cell.velocity_vector(),
+            })
+            .collect::<Vec<_>>();
+        if let Some((previous_iteration, previous)) = &self.previous {
+            let iterations = iteration.saturating_sub(*previous_iteration).max(1);
+            let (mut change, mut magnitude) = (0.0, 0.0);
+            for (val, previous) in izip!(&velocity_vectors, previous) {
+                let mut difference = *val;
+                for (difference, previous) in izip!(&mut difference, previous) {
+                    *difference -= previous;
+                }
+                change += difference.norm_squared();
+                magnitude += val.norm_squared();
+            }
+            let residual = match magnitude > 0.0 {
+                true => (change / magnitude).sqrt() / iterations as f32,
+                false => 0.0,
+            };
+            if self.residuals.len() == 2 * WINDOW_LENGTH {
+                self.residuals.pop_front();
+            }
+            self.residuals.push_back(residual);
+        }
+        self.previous = Some((iteration, velocity_vectors));
+    }
+
+    /// Latest residual, if calculated.
+    pub fn residual(&self) -> Option<f32> {
+        self.residuals.back().copied()
+    }
+
+    /// Convergence of the flow, if a residual is calculated.
+    ///
+    /// note: the flow is statistically steady when the average residual of the latest window of
+    /// residuals is close to that of the window before
+    pub fn convergence(&self) -> Option<Convergence> {
+        let residual = self.residual()?;
+        if residual <= self.target {
+            return Some(Convergence::Steady);
+        }
+        if self.residuals.len() < 2 * WINDOW_LENGTH {
+            return Some(Convergence::Developing);
+        }
+        let (earlier, later) = self.residuals.iter().enumerate().fold(
+            (0.0, 0.0),
+            |(earlier, later), (i, val)| match i < WINDOW_LENGTH {
+                true => (earlier + val, later),
+                false => (earlier, later + val),
+            },
+        );
+        match (later - earlier).abs() <= STATISTICALLY_STEADY_TOLERANCE * earlier {
+            true => Some(Convergence::StatisticallySteady),
+            false => Some(Convergence::Developing),
+        }
+    }
+}
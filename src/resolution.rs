@@ -0,0 +1,37 @@
+use crate::profiling;
+use crate::simulation::Simulation;
+
+/// Lattice size of the micro-benchmark.
+pub const BENCHMARK_SIZE: [usize; 2] = [64, 32];
+/// Iterations of the micro-benchmark.
+pub const BENCHMARK_ITERATIONS: usize = 200;
+
+/// Throughput in MLUPS (millions of lattice updates per second) of the first scenario on a
+/// lattice size, measured by a micro-benchmark of a number of iterations.
+///
+/// note: an iteration is performed before measuring, so the warm-up is excluded
+pub fn measure_throughput(size: &[usize; 2], iterations: usize) -> f64 {
+    let mut simulation = Simulation::new(size[0], size[1], 0, 0.1, 100.0);
+    simulation.run_for(1);
+    let start = profiling::now();
+    simulation.run_for(iterations);
+    // note: a duration below the resolution of the time is taken as the resolution
+    let duration = (profiling::now() - start).max(1.0e-3);
+    (size[0] * size[1] * iterations) as f64 / (duration * 1.0e3)
+}
+
+/// Largest lattice size, of the aspect ratio of a largest size (and no smaller than a smallest
+/// size in each dimension), which iterates at a target rate (iterations per second) at a
+/// throughput (MLUPS).
+pub fn recommended_size(
+    max_size: &[usize; 2],
+    min_size: usize,
+    throughput: f64,
+    target_rate: f64,
+) -> [usize; 2] {
+    let lattice_updates = throughput * 1.0e6 / target_rate;
+    let scale = (lattice_updates / (max_size[0] * max_size[1]) as f64)
+        .sqrt()
+        .min(1.0);
+    max_size.map(|val| ((val as f64 * scale) as usize).max(min_size.min(val)))
+}
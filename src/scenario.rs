@@ -0,0 +1,389 @@
+use crate::advection_diffusion::{AdvectionDiffusion, ScalarBoundaryScheme};
+use crate::immersed_boundary::{self, ImmersedBoundary};
+use crate::initial_condition;
+use crate::lbgk::{BoundaryScheme, Lbgk};
+use crate::object::{
+    self, circular::Circular, naca_4_digit_airfoil::Naca4DigitAirfoil, oscillating::Oscillating,
+    pitching::Pitching, rectangular::Rectangular, rigid_body::RigidBody, Object,
+};
+use crate::random::Random;
+
+use itertools::iproduct;
+
+const CHANNEL_BOUNDARY_SCHEMES: [[BoundaryScheme; 2]; 2] = [
+    [BoundaryScheme::Inflow, BoundaryScheme::Outflow],
+    [BoundaryScheme::SpecularReflection; 2],
+];
+// note: the lid is the wall along the top moving with the source velocity
+const CAVITY_BOUNDARY_SCHEMES: [[BoundaryScheme; 2]; 2] = [
+    [BoundaryScheme::BounceBack; 2],
+    [BoundaryScheme::BounceBack, BoundaryScheme::MovingWall],
+];
+const CONVECTION_BOUNDARY_SCHEMES: [[BoundaryScheme; 2]; 2] = [
+    [BoundaryScheme::Periodic; 2],
+    [BoundaryScheme::BounceBack; 2],
+];
+const ACOUSTIC_BOUNDARY_SCHEMES: [[BoundaryScheme; 2]; 2] = [[BoundaryScheme::Periodic; 2]; 2];
+const SHEAR_LAYER_BOUNDARY_SCHEMES: [[BoundaryScheme; 2]; 2] = [[BoundaryScheme::Periodic; 2]; 2];
+const TURBULENCE_BOUNDARY_SCHEMES: [[BoundaryScheme; 2]; 2] = [[BoundaryScheme::Periodic; 2]; 2];
+/// Perturbation of the velocity across the shear layers, relative to the velocity.
+const SHEAR_LAYER_PERTURBATION: f32 = 0.05;
+/// Amplitude of the density of the acoustic pulse.
+const ACOUSTIC_PULSE_AMPLITUDE: f32 = 0.01;
+/// Temperatures of the hot bottom and cold top walls of Rayleigh–Bénard convection.
+const CONVECTION_TEMPERATURES: [f32; 2] = [1.0, 0.0];
+/// Amplitude of the perturbation of the conduction temperature profile seeding the convection
+/// cells, relative to the temperature difference.
+const CONVECTION_PERTURBATION: f32 = 0.01;
+
+/// Predefined configurations of objects and immersed boundaries in a channel.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Scenario {
+    Cylinder,
+    /// Transversely oscillating cylinder.
+    OscillatingCylinder,
+    /// Elastically mounted cylinder free to move transversely (mass ratio 10).
+    ElasticallyMountedCylinder,
+    /// Flexible filament tethered in the wake of a cylinder.
+    CylinderWithFilament,
+    /// Cylinders side by side across the channel.
+    TwinCylinders,
+    /// Semicircular constrictions of the channel walls.
+    ChannelConstriction,
+    /// [4-digit NACA airfoil](https://en.wikipedia.org/wiki/NACA_airfoil) with a designation
+    /// and an angle of attack (degrees).
+    Airfoil(&'static str, f32),
+    /// NACA 0012 pitching about the quarter chord (dynamic stall).
+    PitchingAirfoil,
+    /// NACA 0012 pitching and heaving (flapping propulsion).
+    FlappingAirfoil,
+    /// Square cavity driven by a lid moving along its top, in the left of the channel (closed
+    /// off by a block).
+    LidDrivenCavity,
+    /// [Rayleigh–Bénard convection](https://en.wikipedia.org/wiki/Rayleigh%E2%80%93B%C3%A9nard_convection)
+    /// between a hot bottom wall and a cold top wall, periodic across the sides.
+    RayleighBenard,
+    /// Pulse of pressure radiating sound past a row of probes, at rest in a periodic domain.
+    AcousticPulse,
+    /// Doubly periodic pair of shear layers rolling up by the
+    /// [Kelvin–Helmholtz instability](https://en.wikipedia.org/wiki/Kelvin%E2%80%93Helmholtz_instability).
+    KelvinHelmholtz,
+    /// Doubly periodic random velocity field of a peak wavelength, decaying as
+    /// [two dimensional turbulence](https://en.wikipedia.org/wiki/Two-dimensional_turbulence).
+    DecayingTurbulence,
+}
+
+impl Scenario {
+    /// All scenarios.
+    pub const ALL: [Scenario; 20] = [
+        Scenario::Cylinder,
+        Scenario::OscillatingCylinder,
+        Scenario::ElasticallyMountedCylinder,
+        Scenario::CylinderWithFilament,
+        Scenario::TwinCylinders,
+        Scenario::ChannelConstriction,
+        Scenario::Airfoil("0012", 0.0),
+        Scenario::Airfoil("0012", 8.0),
+        Scenario::Airfoil("2412", 4.0),
+        Scenario::Airfoil("2412", 8.0),
+        Scenario::Airfoil("2412", 12.0),
+        Scenario::Airfoil("2415", 8.0),
+        Scenario::Airfoil("6412", 8.0),
+        Scenario::PitchingAirfoil,
+        Scenario::FlappingAirfoil,
+        Scenario::LidDrivenCavity,
+        Scenario::RayleighBenard,
+        Scenario::AcousticPulse,
+        Scenario::KelvinHelmholtz,
+        Scenario::DecayingTurbulence,
+    ];
+
+    /// Scenario of a name (see `name`), if any.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|scenario| scenario.name() == name)
+    }
+
+    /// Name of the scenario.
+    pub fn name(&self) -> String {
+        match self {
+            Scenario::Cylinder => "Cylinder".to_string(),
+            Scenario::OscillatingCylinder => "Oscillating cylinder".to_string(),
+            Scenario::ElasticallyMountedCylinder => "Elastically mounted cylinder".to_string(),
+            Scenario::CylinderWithFilament => "Cylinder with filament".to_string(),
+            Scenario::TwinCylinders => "Twin cylinders".to_string(),
+            Scenario::ChannelConstriction => "Channel constriction".to_string(),
+            Scenario::Airfoil(designation, angle_of_attack) => {
+                format!("NACA {designation} at {angle_of_attack}°")
+            }
+            Scenario::PitchingAirfoil => "Pitching NACA 0012".to_string(),
+            Scenario::FlappingAirfoil => "Flapping NACA 0012".to_string(),
+            Scenario::LidDrivenCavity => "Lid-driven cavity".to_string(),
+            Scenario::RayleighBenard => "Rayleigh–Bénard convection".to_string(),
+            Scenario::AcousticPulse => "Acoustic pulse".to_string(),
+            Scenario::KelvinHelmholtz => "Kelvin–Helmholtz shear layers".to_string(),
+            Scenario::DecayingTurbulence => "Decaying turbulence".to_string(),
+        }
+    }
+
+    /// Boundary schemes of the lattice for the scenario.
+    pub fn boundary_schemes(&self) -> [[BoundaryScheme; 2]; 2] {
+        match self {
+            Scenario::LidDrivenCavity => CAVITY_BOUNDARY_SCHEMES,
+            Scenario::RayleighBenard => CONVECTION_BOUNDARY_SCHEMES,
+            Scenario::AcousticPulse => ACOUSTIC_BOUNDARY_SCHEMES,
+            Scenario::KelvinHelmholtz => SHEAR_LAYER_BOUNDARY_SCHEMES,
+            Scenario::DecayingTurbulence => TURBULENCE_BOUNDARY_SCHEMES,
+            _ => CHANNEL_BOUNDARY_SCHEMES,
+        }
+    }
+
+    /// Whether the flow is enclosed, i.e. driven by a moving wall (or buoyancy, or an initial
+    /// condition) rather than an inflow, and so starts at rest.
+    pub fn enclosed(&self) -> bool {
+        matches!(
+            self,
+            Scenario::LidDrivenCavity
+                | Scenario::RayleighBenard
+                | Scenario::AcousticPulse
+                | Scenario::KelvinHelmholtz
+                | Scenario::DecayingTurbulence
+        )
+    }
+
+    /// Whether the flow is driven by the buoyancy of its temperature.
+    pub fn thermal(&self) -> bool {
+        matches!(self, Scenario::RayleighBenard)
+    }
+
+    /// [Characteristic length](https://en.wikipedia.org/wiki/Characteristic_length) of the
+    /// scenario for a lattice size.
+    ///
+    /// note: that of the first object, except for the cavity whose size is used
+    pub fn characteristic_length(&self, size: &[usize; 2]) -> f32 {
+        match self {
+            // note: between the bottom wall midway below the first row and the lid midway above
+            // the last row
+            Scenario::LidDrivenCavity => size[1] as f32,
+            // note: between the walls midway below the first row and above the last row
+            Scenario::RayleighBenard => size[1] as f32,
+            Scenario::AcousticPulse => size[1] as f32,
+            // note: the thickness of the shear layers
+            Scenario::KelvinHelmholtz => size[1] as f32 / 40.0,
+            // note: the peak wavelength of the energy spectrum
+            Scenario::DecayingTurbulence => size[1] as f32 / 8.0,
+            _ => self.objects(size)[0].characteristic_length(),
+        }
+    }
+
+    /// Objects of the scenario for a lattice size.
+    pub fn objects(&self, size: &[usize; 2]) -> Vec<Box<dyn Object<2>>> {
+        let [width, height] = size.map(|val| val as f32);
+        let cylinder = || Circular::new([width / 4.0, height / 2.0], height / 10.0);
+        match self {
+            Scenario::Cylinder | Scenario::CylinderWithFilament => vec![Box::new(cylinder())],
+            Scenario::OscillatingCylinder => vec![Box::new(Oscillating::new(
+                cylinder(),
+                [0.0, height / 40.0],
+                1.0 / 2000.0,
+            ))],
+            Scenario::ElasticallyMountedCylinder => vec![Box::new(RigidBody::new(
+                cylinder(),
+                [width / 4.0, height / 2.0],
+                [f32::INFINITY, 12500.0, f32::INFINITY],
+                [0.0, 0.12, 0.0],
+                [0.0, 0.0, 0.0],
+            ))],
+            Scenario::TwinCylinders => [-1.0, 1.0]
+                .map(|side| -> Box<dyn Object<2>> {
+                    Box::new(Circular::new(
+                        [width / 4.0, height / 2.0 + side * height / 8.0],
+                        height / 20.0,
+                    ))
+                })
+                .into(),
+            Scenario::ChannelConstriction => [0.0, height - 1.0]
+                .map(|y| -> Box<dyn Object<2>> {
+                    Box::new(Circular::new([width / 3.0, y], height / 4.0))
+                })
+                .into(),
+            Scenario::Airfoil(designation, angle_of_attack) => {
+                let digits = designation
+                    .chars()
+                    .map(|c| c.to_digit(10).unwrap_or(0) as f32)
+                    .collect::<Vec<_>>();
+                vec![Box::new(Naca4DigitAirfoil::new(
+                    [width / 5.0, height / 2.0],
+                    height / 2.0,
+                    digits[0] / 100.0,
+                    digits[1] / 10.0,
+                    (10.0 * digits[2] + digits[3]) / 100.0,
+                    angle_of_attack.to_radians(),
+                ))]
+            }
+            Scenario::PitchingAirfoil => vec![Box::new(Pitching::new(
+                Naca4DigitAirfoil::new(
+                    [width / 5.0, height / 2.0],
+                    height / 2.0,
+                    0.0,
+                    0.0,
+                    0.12,
+                    0.0,
+                ),
+                [width / 5.0 + height / 8.0, height / 2.0],
+                10.0f32.to_radians(),
+                15.0f32.to_radians(),
+                1.0 / 4000.0,
+                0.0,
+            ))],
+            Scenario::FlappingAirfoil => vec![Box::new(Oscillating::new(
+                Pitching::new(
+                    Naca4DigitAirfoil::new(
+                        [width / 5.0, height / 2.0],
+                        height / 4.0,
+                        0.0,
+                        0.0,
+                        0.12,
+                        0.0,
+                    ),
+                    [width / 5.0 + height / 16.0, height / 2.0],
+                    0.0,
+                    20.0f32.to_radians(),
+                    1.0 / 1000.0,
+                    std::f32::consts::FRAC_PI_2,
+                ),
+                [0.0, height / 10.0],
+                1.0 / 1000.0,
+            ))],
+            // note: the right wall is midway between the lattice positions either side of it, so
+            // that the cavity is as wide as it is high
+            Scenario::LidDrivenCavity => vec![Box::new(Rectangular::new(
+                [height - 0.5, 0.0],
+                [width, height],
+            ))],
+            Scenario::RayleighBenard
+            | Scenario::AcousticPulse
+            | Scenario::KelvinHelmholtz
+            | Scenario::DecayingTurbulence => Vec::new(),
+        }
+    }
+
+    /// Set the objects of the scenario for the size of a lattice on it (see
+    /// `object::set_objects`), with fluid elsewhere.
+    pub fn set_objects<const B: usize>(&self, lbgk: &mut Lbgk<2, B>) {
+        object::set_objects(lbgk, &self.objects(&lbgk.size()));
+    }
+
+    /// Set the initial condition of the scenario on a lattice for a velocity of the flow and a
+    /// seed of its random numbers, over the flow at rest or of the inflow.
+    pub fn set_initial_condition<const B: usize>(
+        &self,
+        lbgk: &mut Lbgk<2, B>,
+        velocity: f32,
+        seed: u32,
+    ) {
+        let size = lbgk.size();
+        let [width, height] = size.map(|val| val as f32);
+        match self {
+            Scenario::AcousticPulse => initial_condition::gaussian_pulse(
+                lbgk,
+                &[width / 4.0, height / 2.0],
+                ACOUSTIC_PULSE_AMPLITUDE,
+                height / 40.0,
+            ),
+            Scenario::KelvinHelmholtz => initial_condition::double_shear_layer(
+                lbgk,
+                velocity,
+                self.characteristic_length(&size),
+                SHEAR_LAYER_PERTURBATION,
+            ),
+            // note: the velocity is the root mean square velocity
+            Scenario::DecayingTurbulence => initial_condition::random_turbulence(
+                lbgk,
+                velocity,
+                2.0 * std::f32::consts::PI / self.characteristic_length(&size),
+                &mut Random::new(seed),
+            ),
+            _ => {}
+        }
+    }
+
+    /// Lattice positions of the probes of the scenario for a lattice size.
+    pub fn probe_positions(&self, size: &[usize; 2]) -> Vec<[usize; 2]> {
+        match self {
+            // note: along the centreline from the pulse
+            Scenario::AcousticPulse => [1, 2, 3, 4]
+                .map(|i| [size[0] / 4 + i * size[1] / 8, size[1] / 2])
+                .into(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Temperature transport of a thermal scenario for a lattice size, with its initial
+    /// temperatures.
+    pub fn advection_diffusion(&self, size: &[usize; 2]) -> Option<AdvectionDiffusion> {
+        match self {
+            Scenario::RayleighBenard => {
+                let [hot, cold] = CONVECTION_TEMPERATURES;
+                let mut advection_diffusion = AdvectionDiffusion::new(
+                    size,
+                    &[
+                        [ScalarBoundaryScheme::Periodic; 2],
+                        CONVECTION_TEMPERATURES.map(ScalarBoundaryScheme::Value),
+                    ],
+                    0.5 * (hot + cold),
+                );
+                // note: the conduction profile, perturbed periodically across the width by
+                // pairs of convection cells about as wide as they are high
+                let [width, height] = size.map(|val| val as f32);
+                let pairs = (0.5 * width / height).round().max(1.0);
+                for (x, y) in iproduct!(0..size[0], 0..size[1]) {
+                    let s = (y as f32 + 0.5) / height;
+                    let perturbation = CONVECTION_PERTURBATION
+                        * (2.0 * std::f32::consts::PI * pairs * x as f32 / width).cos()
+                        * (std::f32::consts::PI * s).sin();
+                    advection_diffusion.set_value(&[x, y], hot + (cold - hot) * (s - perturbation));
+                }
+                Some(advection_diffusion)
+            }
+            _ => None,
+        }
+    }
+
+    /// Buoyancy (per unit density and temperature difference) of a thermal scenario for a
+    /// lattice size, such that the [free-fall velocity](https://en.wikipedia.org/wiki/Rayleigh%E2%80%93B%C3%A9nard_convection)
+    /// across the characteristic length is a velocity.
+    pub fn buoyancy(&self, size: &[usize; 2], velocity: f32) -> [f32; 2] {
+        match self {
+            Scenario::RayleighBenard => {
+                let [hot, cold] = CONVECTION_TEMPERATURES;
+                [
+                    0.0,
+                    velocity * velocity / ((hot - cold) * self.characteristic_length(size)),
+                ]
+            }
+            _ => [0.0; 2],
+        }
+    }
+
+    /// Immersed boundaries of the scenario for a lattice size.
+    pub fn immersed_boundaries(&self, size: &[usize; 2]) -> Vec<ImmersedBoundary> {
+        let [width, height] = size.map(|val| val as f32);
+        match self {
+            Scenario::CylinderWithFilament => vec![{
+                let mut immersed_boundary = ImmersedBoundary::new_line(
+                    immersed_boundary::Kernel::FourPoint,
+                    [width / 4.0 + height * 0.4, height / 2.0],
+                    [width / 4.0 + height * 0.7, height / 2.0],
+                    61,
+                    0.5,
+                    0.01,
+                );
+                immersed_boundary.set_tethers(&[0], 1.0);
+                immersed_boundary
+            }],
+            _ => Vec::new(),
+        }
+    }
+}
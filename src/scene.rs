@@ -0,0 +1,134 @@
+use crate::random;
+
+/// Scene configuration, persisted as lines of a key and its values separated by spaces.
+///
+/// Options (e.g. scenarios and colour maps) are stored as indexes into the lists of all
+/// options.
+#[derive(Clone, PartialEq)]
+pub struct Scene {
+    pub scenario: usize,
+    /// Magnitude of the inflow velocity.
+    pub velocity: f32,
+    pub reynolds_number: f32,
+    /// Seed of the random numbers.
+    pub seed: u32,
+    /// Field shown on each canvas.
+    pub fields: Vec<usize>,
+    /// Colour map of each canvas.
+    pub colormaps: Vec<usize>,
+    /// Text representation of the custom colour map.
+    pub custom_colormap: String,
+    pub display_scale: usize,
+    pub outlines_shown: bool,
+    /// Whether objects are filled with a colour of their object ID.
+    pub objects_coloured: bool,
+    /// Painted (or erased) lattice positions overriding the objects of the scenario.
+    pub painted_objects: Vec<Option<bool>>,
+}
+
+impl Scene {
+    /// Text representation of the scene.
+    ///
+    /// note: painted objects are run-length encoded with `.` unpainted, `#` painted and `~`
+    /// erased
+    pub fn to_text(&self) -> String {
+        let join = |values: &[usize]| {
+            values
+                .iter()
+                .map(|val| val.to_string())
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+
+        let mut runs: Vec<(usize, char)> = Vec::new();
+        for painted in &self.painted_objects {
+            let symbol = match painted {
+                None => '.',
+                Some(true) => '#',
+                Some(false) => '~',
+            };
+            match runs.last_mut() {
+                Some((count, last)) if *last == symbol => *count += 1,
+                _ => runs.push((1, symbol)),
+            }
+        }
+        let runs = runs
+            .iter()
+            .map(|(count, symbol)| format!("{count}{symbol}"))
+            .collect::<String>();
+
+        [
+            format!("scenario {}", self.scenario),
+            format!("velocity {}", self.velocity),
+            format!("reynolds_number {}", self.reynolds_number),
+            format!("seed {}", self.seed),
+            format!("fields {}", join(&self.fields)),
+            format!("colormaps {}", join(&self.colormaps)),
+            format!("custom_colormap {}", self.custom_colormap),
+            format!("display_scale {}", self.display_scale),
+            format!("outlines_shown {}", self.outlines_shown),
+            format!("objects_coloured {}", self.objects_coloured),
+            format!("painted_objects {runs}"),
+        ]
+        .join("\n")
+    }
+
+    /// Scene from its text representation, if valid.
+    pub fn from_text(text: &str) -> Option<Self> {
+        let mut scene = Self {
+            scenario: 0,
+            velocity: 0.0,
+            reynolds_number: 0.0,
+            // note: scenes saved before the seed was added use the default seed
+            seed: random::DEFAULT_SEED,
+            fields: Vec::new(),
+            colormaps: Vec::new(),
+            custom_colormap: String::new(),
+            display_scale: 1,
+            outlines_shown: true,
+            objects_coloured: false,
+            painted_objects: Vec::new(),
+        };
+        for line in text.lines() {
+            let (key, values) = line.split_once(' ').unwrap_or((line, ""));
+            let list = || {
+                values
+                    .split_whitespace()
+                    .map(|val| val.parse().ok())
+                    .collect::<Option<Vec<_>>>()
+            };
+            match key {
+                "scenario" => scene.scenario = values.parse().ok()?,
+                "velocity" => scene.velocity = values.parse().ok()?,
+                "reynolds_number" => scene.reynolds_number = values.parse().ok()?,
+                "seed" => scene.seed = values.parse().ok()?,
+                "fields" => scene.fields = list()?,
+                "colormaps" => scene.colormaps = list()?,
+                "custom_colormap" => scene.custom_colormap = values.to_string(),
+                "display_scale" => scene.display_scale = values.parse().ok()?,
+                "outlines_shown" => scene.outlines_shown = values.parse().ok()?,
+                "objects_coloured" => scene.objects_coloured = values.parse().ok()?,
+                "painted_objects" => {
+                    let mut count = String::new();
+                    for c in values.chars() {
+                        let painted = match c {
+                            '.' => None,
+                            '#' => Some(true),
+                            '~' => Some(false),
+                            _ => {
+                                count.push(c);
+                                continue;
+                            }
+                        };
+                        let count = std::mem::take(&mut count).parse::<usize>().ok()?;
+                        scene
+                            .painted_objects
+                            .extend(std::iter::repeat_n(painted, count));
+                    }
+                }
+                _ => return None,
+            }
+        }
+        Some(scene)
+    }
+}
@@ -0,0 +1,869 @@
+use crate::advection_diffusion::{AdvectionDiffusion, Subcycling};
+use crate::checkpoint::{AutoCheckpoint, Checkpoint, MemoryStorage};
+use crate::immersed_boundary::ImmersedBoundary;
+use crate::initial_condition;
+use crate::lbgk::{BoundaryScheme, Field, Lbgk, DEFAULT_OBJECT_ID};
+use crate::linear_algebra::VectorOps;
+use crate::log;
+use crate::object::{self, Object};
+use crate::probes::Probes;
+use crate::profiling::{self, IterationRate};
+use crate::random;
+use crate::scenario::Scenario;
+use crate::snapshot::Snapshot;
+use crate::spectrum::Spectra;
+use crate::stats::Stats;
+
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+const DENSITY: f32 = 1.0;
+/// [Prandtl number](https://en.wikipedia.org/wiki/Prandtl_number) of the thermal scenarios
+/// (air), unless given.
+const PRANDTL_NUMBER: f32 = 0.71;
+/// Values of each probe retained.
+const PROBES_CAPACITY: usize = 10000;
+/// Factor by which the lattice size is reduced in each dimension in the preview mode.
+const PREVIEW_SCALE: usize = 2;
+/// Period of the moving average of the iteration rate (seconds).
+const RATE_MOVING_AVERAGE_PERIOD_SECS: f64 = 2.0;
+
+/// Partial configuration of a `Simulation`, of the changes which are applied in place without
+/// resetting the flow, accepted from JavaScript as an object with camel case keys (e.g.
+/// `reynoldsNumber`), of which any omitted are unchanged.
+///
+/// note: the lattice size (see `resize`) and scenario are not included, as changing the
+/// scenario needs a new simulation
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct PartialConfig {
+    /// Magnitude of the velocity of the inflow or moving wall (or the free-fall velocity, if
+    /// thermal).
+    #[serde(default)]
+    pub velocity: Option<f32>,
+    #[serde(default)]
+    pub reynolds_number: Option<f32>,
+    /// Prandtl number of a thermal scenario.
+    #[serde(default)]
+    pub prandtl_number: Option<f32>,
+    /// Boundary schemes of the start and end of each dimension.
+    #[serde(default)]
+    pub boundary_schemes: Option<[[BoundaryScheme; 2]; 2]>,
+}
+
+impl PartialConfig {
+    /// Error of the partial configuration, if not physically sensible (i.e. of parameters not
+    /// positive and finite, or a dimension periodic at only one side).
+    pub(crate) fn validate(&self) -> Result<(), String> {
+        let parameters = [
+            ("velocity", self.velocity),
+            ("Reynolds number", self.reynolds_number),
+            ("Prandtl number", self.prandtl_number),
+        ];
+        for (name, val) in parameters {
+            if let Some(val) = val.filter(|val| !val.is_finite() || *val <= 0.0) {
+                return Err(format!("{name} {val} should be positive and finite"));
+            }
+        }
+        for (dim, boundary_schemes) in self.boundary_schemes.iter().flatten().enumerate() {
+            let periodic = boundary_schemes.map(|scheme| scheme == BoundaryScheme::Periodic);
+            if periodic[0] != periodic[1] {
+                return Err(format!(
+                    "dimension {dim} should be periodic at both sides or neither"
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Headless simulation of the flow of a scenario on a two dimensional lattice, without drawing
+/// (e.g. for scripted experiments from JavaScript).
+#[wasm_bindgen]
+pub struct Simulation {
+    scenario: Scenario,
+    boundary_schemes: [[BoundaryScheme; 2]; 2],
+    lbgk: Lbgk<2, 9>,
+    objects: Vec<Box<dyn Object<2>>>,
+    /// Painted (or erased) objects at the lattice positions, with x varying fastest, overriding
+    /// the objects of the scenario.
+    painted_objects: Vec<Option<bool>>,
+    immersed_boundaries: Vec<ImmersedBoundary>,
+    /// Temperature transport of a thermal scenario.
+    advection_diffusion: Option<AdvectionDiffusion>,
+    probes: Probes,
+    iteration: usize,
+    velocity: f32,
+    reynolds_number: f32,
+    relaxation_time: f32,
+    prandtl_number: f32,
+    /// Relaxation time of the temperature transport.
+    thermal_relaxation_time: f32,
+    /// Dynamic pressure multiplied by the characteristic length.
+    force_divisor: f32,
+    auto_checkpoint: Option<AutoCheckpoint<MemoryStorage>>,
+    /// Lattice size outside of the preview mode.
+    full_size: [usize; 2],
+    /// Whether in the coarse preview mode.
+    preview: bool,
+    /// Iteration rate below which the preview mode is fallen back to, if any.
+    preview_threshold: Option<f64>,
+    iteration_rate: IterationRate,
+}
+
+#[wasm_bindgen]
+impl Simulation {
+    /// Create a new `Simulation` of a lattice size, for a scenario (an index into the
+    /// scenarios, the first if out of range), a velocity of the flow and a Reynolds number.
+    ///
+    /// note: thermal scenarios are of air, with the velocity the free-fall velocity
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        width: usize,
+        height: usize,
+        scenario: usize,
+        velocity: f32,
+        reynolds_number: f32,
+    ) -> Self {
+        Self::new_seeded(
+            width,
+            height,
+            scenario,
+            velocity,
+            reynolds_number,
+            random::DEFAULT_SEED,
+        )
+    }
+
+    /// Create a new `Simulation` as `new`, with a seed of the random numbers of the scenario
+    /// (e.g. of the phases of decaying turbulence).
+    #[wasm_bindgen(js_name = newSeeded)]
+    pub fn new_seeded(
+        width: usize,
+        height: usize,
+        scenario: usize,
+        velocity: f32,
+        reynolds_number: f32,
+        seed: u32,
+    ) -> Self {
+        let scenario = Scenario::ALL.get(scenario).copied().unwrap_or_else(|| {
+            log::warn(
+                "simulation",
+                &format!("scenario {scenario} is out of range, so the first is used"),
+            );
+            Scenario::ALL[0]
+        });
+        Self::with_scenario(&[width, height], scenario, velocity, reynolds_number, seed)
+    }
+
+    /// Create a new `Simulation` of Rayleigh–Bénard convection on a lattice size, for a
+    /// free-fall velocity, a [Rayleigh number](https://en.wikipedia.org/wiki/Rayleigh_number)
+    /// and a [Prandtl number](https://en.wikipedia.org/wiki/Prandtl_number).
+    #[wasm_bindgen(js_name = newRayleighBenard)]
+    pub fn new_rayleigh_benard(
+        width: usize,
+        height: usize,
+        velocity: f32,
+        rayleigh_number: f32,
+        prandtl_number: f32,
+    ) -> Self {
+        // note: the Reynolds number of the free-fall velocity
+        Self::new_scenario(
+            &[width, height],
+            Scenario::RayleighBenard,
+            velocity,
+            (rayleigh_number / prandtl_number).sqrt(),
+            prandtl_number,
+            random::DEFAULT_SEED,
+        )
+    }
+
+    /// Width of the lattice.
+    pub fn width(&self) -> usize {
+        self.lbgk.size()[0]
+    }
+
+    /// Height of the lattice.
+    pub fn height(&self) -> usize {
+        self.lbgk.size()[1]
+    }
+
+    /// Resize the lattice without resetting the flow (e.g. to a resolution of the browser
+    /// window), returning whether resized (i.e. of a width and height of at least one).
+    ///
+    /// The density, velocity and temperatures are bilinearly interpolated from the old lattice
+    /// (mapped corner to corner) with equilibrium distributions, the objects, immersed
+    /// boundaries and probes are those of the scenario at the new size, and the relaxation
+    /// times are of the new characteristic length (keeping the Reynolds number).
+    ///
+    /// note: objects responding to the flow and immersed boundaries are reset, and painted
+    /// objects and the values of the probes are cleared
+    ///
+    /// note: the preview mode is left
+    pub fn resize(&mut self, width: usize, height: usize) -> bool {
+        let size = [width, height];
+        if width == 0 || height == 0 {
+            log::warn(
+                "simulation",
+                &format!("lattice size {width} by {height} is empty, so is ignored"),
+            );
+            return false;
+        }
+        self.full_size = size;
+        self.preview = false;
+        self.resize_lattice(&size);
+        true
+    }
+
+    /// Whether in the coarse preview mode.
+    pub fn preview(&self) -> bool {
+        self.preview
+    }
+
+    /// Enter or leave the coarse preview mode, of the lattice size reduced by a factor in each
+    /// dimension, resizing without resetting the flow (see `resize`) to keep interactivity on
+    /// slow devices.
+    #[wasm_bindgen(js_name = setPreview)]
+    pub fn set_preview(&mut self, preview: bool) {
+        if preview == self.preview {
+            return;
+        }
+        let size = match preview {
+            true => self.full_size.map(|val| val.div_ceil(PREVIEW_SCALE)),
+            false => self.full_size,
+        };
+        self.preview = preview;
+        self.resize_lattice(&size);
+        self.iteration_rate.clear();
+    }
+
+    /// Set the iteration rate (iterations per second), measured as a moving average, below
+    /// which the preview mode is entered automatically (none if not positive).
+    ///
+    /// note: the preview mode is left by `setPreview`
+    #[wasm_bindgen(js_name = setPreviewThreshold)]
+    pub fn set_preview_threshold(&mut self, threshold: f64) {
+        self.preview_threshold = (threshold > 0.0).then_some(threshold);
+    }
+
+    /// Iteration rate (iterations per second), as a moving average.
+    #[wasm_bindgen(js_name = iterationRate)]
+    pub fn iteration_rate(&self) -> f64 {
+        self.iteration_rate.rate()
+    }
+
+    /// Number of iterations performed.
+    pub fn iteration(&self) -> usize {
+        self.iteration
+    }
+
+    /// Relaxation time of the flow parameters.
+    #[wasm_bindgen(js_name = relaxationTime)]
+    pub fn relaxation_time(&self) -> f32 {
+        self.relaxation_time
+    }
+
+    /// Set the velocity of the flow and the Reynolds number, without resetting the flow (e.g.
+    /// to ramp up the Reynolds number).
+    ///
+    /// note: thermal scenarios are of air, with the velocity the free-fall velocity
+    #[wasm_bindgen(js_name = setFlowParameters)]
+    pub fn set_flow_parameters(&mut self, velocity: f32, reynolds_number: f32) {
+        let size = self.lbgk.size();
+        let velocity_vector = [velocity, 0.0];
+        // note: the source velocity is that of the inflow or the moving wall
+        self.lbgk.set_source(DENSITY, &velocity_vector);
+
+        let characteristic_length = self.scenario.characteristic_length(&size);
+        if let Some(advection_diffusion) = &mut self.advection_diffusion {
+            advection_diffusion.set_buoyancy(&self.scenario.buoyancy(&size, velocity));
+            self.thermal_relaxation_time = advection_diffusion.relaxation_time(
+                velocity,
+                characteristic_length,
+                reynolds_number * self.prandtl_number,
+            );
+        }
+        self.relaxation_time = self.lbgk.relaxation_time(
+            velocity_vector.norm(),
+            characteristic_length,
+            reynolds_number,
+        );
+        self.force_divisor = 0.5 * DENSITY * velocity * velocity * characteristic_length;
+        self.velocity = velocity;
+        self.reynolds_number = reynolds_number;
+    }
+
+    /// Apply a partial configuration object (see `PartialConfig`) in place without resetting
+    /// the flow, throwing an error (and applying none of it) if invalid.
+    #[wasm_bindgen(js_name = updateConfig)]
+    pub fn update_config_object(&mut self, partial_config: JsValue) -> Result<(), String> {
+        let partial_config =
+            serde_wasm_bindgen::from_value(partial_config).map_err(|err| err.to_string())?;
+        self.update_config(&partial_config)
+    }
+
+    /// Magnitude of the velocity of the flow.
+    pub fn velocity(&self) -> f32 {
+        self.velocity
+    }
+
+    /// Reynolds number of the flow parameters.
+    #[wasm_bindgen(js_name = reynoldsNumber)]
+    pub fn reynolds_number(&self) -> f32 {
+        self.reynolds_number
+    }
+
+    /// Perform a number of iterations.
+    #[wasm_bindgen(js_name = runFor)]
+    pub fn run_for(&mut self, iterations: usize) {
+        for _ in 0..iterations {
+            self.iterate();
+        }
+    }
+
+    /// Perform iterations for (at least) a duration (milliseconds), returning the number of
+    /// iterations performed.
+    ///
+    /// note: at least one iteration is performed
+    #[wasm_bindgen(js_name = runForDuration)]
+    pub fn run_for_duration(&mut self, duration: f64) -> usize {
+        let start = profiling::now();
+        let mut iterations = 0;
+        loop {
+            self.iterate();
+            iterations += 1;
+            if profiling::now() - start >= duration {
+                return iterations;
+            }
+        }
+    }
+
+    /// Set the velocity at the fluid lattice positions from velocity components (interleaved x
+    /// and y, with x varying fastest), at rest density with equilibrium distributions,
+    /// optionally projected to be free of divergence first (assuming a doubly periodic lattice),
+    /// returning whether the number of components matches the lattice.
+    #[wasm_bindgen(js_name = setVelocities)]
+    pub fn set_velocities(&mut self, velocities: &[f32], projected: bool) -> bool {
+        let [width, height] = self.lbgk.size();
+        if velocities.len() != 2 * width * height {
+            log::warn(
+                "simulation",
+                &format!(
+                    "{} velocity components do not match the lattice, so are ignored",
+                    velocities.len()
+                ),
+            );
+            return false;
+        }
+        let velocity_vectors = velocities
+            .chunks_exact(2)
+            .map(|val| [val[0], val[1]])
+            .collect::<Vec<_>>();
+        initial_condition::velocity_field(&mut self.lbgk, &velocity_vectors, DENSITY, projected);
+        true
+    }
+
+    /// Names of the fields.
+    #[wasm_bindgen(js_name = fieldNames)]
+    pub fn field_names(&self) -> Vec<String> {
+        Field::ALL
+            .iter()
+            .map(|field| field.name().to_string())
+            .collect()
+    }
+
+    /// Values of a field (an index into the field names) at the lattice positions, with x
+    /// varying fastest, if in range.
+    #[wasm_bindgen(js_name = fieldValues)]
+    pub fn field_values(&self, field: usize) -> Option<Vec<f32>> {
+        let field = Field::ALL.get(field)?;
+        Snapshot::new(&self.lbgk, &[*field])
+            .values(field.name())
+            .map(|values| values.to_vec())
+    }
+
+    /// Whether there is an object at a lattice position (false if out of range).
+    pub fn object(&self, x: usize, y: usize) -> bool {
+        x < self.width() && y < self.height() && self.lbgk.object(&[x, y])
+    }
+
+    /// Drag and lift coefficients of the objects during the last iteration.
+    #[wasm_bindgen(js_name = forceCoefficients)]
+    pub fn force_coefficients(&self) -> Vec<f32> {
+        self.lbgk
+            .force()
+            .map(|val| val / self.force_divisor)
+            .to_vec()
+    }
+
+    /// Statistics of the current iteration, as an object (see `Stats`).
+    #[wasm_bindgen(js_name = stats)]
+    pub fn stats_object(&self) -> Result<JsValue, String> {
+        serde_wasm_bindgen::to_value(&self.stats()).map_err(|err| err.to_string())
+    }
+
+    /// Number of objects.
+    #[wasm_bindgen(js_name = objectCount)]
+    pub fn object_count(&self) -> usize {
+        self.objects.len()
+    }
+
+    /// Drag and lift coefficients of an object (an index into the objects) during the last
+    /// iteration, if in range.
+    #[wasm_bindgen(js_name = objectForceCoefficients)]
+    pub fn object_force_coefficients(&self, object: usize) -> Option<Vec<f32>> {
+        (object < self.objects.len()).then(|| {
+            self.lbgk
+                .object_force(object::object_id(object))
+                .map(|val| val / self.force_divisor)
+                .to_vec()
+        })
+    }
+
+    /// [Nusselt number](https://en.wikipedia.org/wiki/Nusselt_number) of the heat transfer
+    /// between the walls of a thermal scenario, if any.
+    #[wasm_bindgen(js_name = nusseltNumber)]
+    pub fn nusselt_number(&self) -> Option<f32> {
+        self.advection_diffusion.as_ref()?.nusselt_number(
+            &self.lbgk,
+            self.thermal_relaxation_time,
+            1,
+        )
+    }
+
+    /// Temperatures of a thermal scenario at the lattice positions, with x varying fastest, if
+    /// any.
+    pub fn temperatures(&self) -> Option<Vec<f32>> {
+        self.advection_diffusion
+            .as_ref()
+            .map(|advection_diffusion| advection_diffusion.values().to_vec())
+    }
+
+    /// Number of probes of the pressure of the scenario.
+    #[wasm_bindgen(js_name = probeCount)]
+    pub fn probe_count(&self) -> usize {
+        self.probes.positions().len()
+    }
+
+    /// Pressures recorded by a probe (an index into the probes) each iteration, if in range.
+    #[wasm_bindgen(js_name = probeValues)]
+    pub fn probe_values(&self, probe: usize) -> Option<Vec<f32>> {
+        (probe < self.probe_count())
+            .then(|| self.probes.values(probe).map(|(_, val)| val).collect())
+    }
+
+    /// Speed of sound measured from the peak pressures of a probe to another (indexes into the
+    /// probes), if in range and passed.
+    #[wasm_bindgen(js_name = probeSpeed)]
+    pub fn probe_speed(&self, first: usize, second: usize) -> Option<f32> {
+        let count = self.probe_count();
+        (first < count && second < count)
+            .then(|| self.probes.speed(first, second))
+            .flatten()
+    }
+
+    /// Energy and enstrophy spectra of the velocity in shells of wavenumber magnitude, as CSV
+    /// (e.g. of decaying turbulence, assuming a doubly periodic lattice).
+    pub fn spectra(&self) -> String {
+        Spectra::new(&self.lbgk).to_csv()
+    }
+
+    /// Set automatic checkpointing to memory every interval of iterations (none if zero),
+    /// retaining a number of the latest checkpoints.
+    #[wasm_bindgen(js_name = setAutoCheckpoint)]
+    pub fn set_auto_checkpoint(&mut self, interval: usize, retention: usize) {
+        self.auto_checkpoint = (interval > 0)
+            .then(|| AutoCheckpoint::new(interval, retention, MemoryStorage::default()));
+    }
+
+    /// Binary checkpoint of the state at the current iteration, to resume from (see
+    /// `restoreCheckpoint`).
+    pub fn checkpoint(&self) -> Vec<u8> {
+        self.to_checkpoint().to_bytes()
+    }
+
+    /// Binary checkpoint of the latest iteration saved automatically, if any.
+    #[wasm_bindgen(js_name = latestCheckpoint)]
+    pub fn latest_checkpoint(&self) -> Option<Vec<u8>> {
+        self.auto_checkpoint
+            .as_ref()?
+            .storage()
+            .latest()
+            .map(Checkpoint::to_bytes)
+    }
+
+    /// Restore the state (and the iteration) from a binary checkpoint, returning whether valid
+    /// and restored (i.e. of the same lattice size and thermal model).
+    ///
+    /// note: moving objects are moved to the iteration, but objects responding to the flow and
+    /// other state beyond the distributions are not restored (see `Checkpoint`)
+    #[wasm_bindgen(js_name = restoreCheckpoint)]
+    pub fn restore_checkpoint(&mut self, bytes: &[u8]) -> bool {
+        Checkpoint::from_bytes(bytes).is_some_and(|checkpoint| self.restore(&checkpoint))
+    }
+
+    /// Binary snapshot of all fields (see `snapshotValues`).
+    pub fn snapshot(&self) -> Vec<u8> {
+        Snapshot::new(&self.lbgk, &Field::ALL).to_bytes()
+    }
+}
+
+impl Simulation {
+    /// Lattice of the simulation.
+    pub fn lbgk(&self) -> &Lbgk<2, 9> {
+        &self.lbgk
+    }
+
+    /// Lattice of the simulation, to change in place (e.g. to stir the fluid).
+    ///
+    /// note: objects should be painted by `paint_object`, or they are lost when objects move
+    pub fn lbgk_mut(&mut self) -> &mut Lbgk<2, 9> {
+        &mut self.lbgk
+    }
+
+    /// Scenario of the simulation.
+    pub fn scenario(&self) -> Scenario {
+        self.scenario
+    }
+
+    /// Immersed boundaries of the scenario.
+    pub fn immersed_boundaries(&self) -> &[ImmersedBoundary] {
+        &self.immersed_boundaries
+    }
+
+    /// Temperature transport of a thermal scenario, if any.
+    pub fn advection_diffusion(&self) -> Option<&AdvectionDiffusion> {
+        self.advection_diffusion.as_ref()
+    }
+
+    /// Relaxation time of the temperature transport of a thermal scenario, if any.
+    pub fn thermal_relaxation_time(&self) -> Option<f32> {
+        self.advection_diffusion
+            .as_ref()
+            .map(|_| self.thermal_relaxation_time)
+    }
+
+    /// Probes of the pressure of the scenario.
+    pub fn probes(&self) -> &Probes {
+        &self.probes
+    }
+
+    /// Restart the moving average of the iteration rate (e.g. when the document is hidden).
+    pub fn clear_iteration_rate(&mut self) {
+        self.iteration_rate.clear();
+    }
+
+    /// Painted (or erased) objects at the lattice positions, with x varying fastest.
+    pub fn painted_objects(&self) -> &[Option<bool>] {
+        &self.painted_objects
+    }
+
+    /// Set the painted (or erased) objects at the lattice positions, with x varying fastest,
+    /// returning whether their number matches the lattice.
+    pub fn set_painted_objects(&mut self, painted_objects: &[Option<bool>]) -> bool {
+        if painted_objects.len() != self.painted_objects.len() {
+            return false;
+        }
+        self.painted_objects.copy_from_slice(painted_objects);
+        self.set_objects();
+        true
+    }
+
+    /// Paint (or erase) an object at a lattice position, overriding the objects of the
+    /// scenario.
+    ///
+    /// note: erased lattice positions are refilled from the neighbouring fluid by `Lbgk`
+    pub fn paint_object(&mut self, pos: &[usize; 2], val: bool) {
+        let size = self.lbgk.size();
+        self.painted_objects[size[0] * pos[1] + pos[0]] = Some(val);
+        self.lbgk.set_object(pos, val);
+    }
+
+    /// Checkpoint of the state at the current iteration.
+    pub fn to_checkpoint(&self) -> Checkpoint {
+        Checkpoint::new(
+            self.iteration,
+            &self.lbgk,
+            self.advection_diffusion.as_ref(),
+        )
+    }
+
+    /// Restore the state (and the iteration) from a checkpoint, returning whether restored
+    /// (see `restoreCheckpoint`).
+    pub fn restore(&mut self, checkpoint: &Checkpoint) -> bool {
+        if !checkpoint.restore(&mut self.lbgk, self.advection_diffusion.as_mut()) {
+            return false;
+        }
+        self.iteration = checkpoint.iteration();
+        for object in &mut self.objects {
+            object.set_time(self.iteration as f32);
+        }
+        self.probes.clear();
+        true
+    }
+
+    /// Apply a partial configuration in place without resetting the flow (e.g. to ramp up the
+    /// Reynolds number, or swap an outflow boundary scheme), if physically sensible.
+    pub fn update_config(&mut self, partial_config: &PartialConfig) -> Result<(), String> {
+        partial_config.validate()?;
+        if let Some(boundary_schemes) = partial_config.boundary_schemes {
+            self.lbgk.set_boundary_schemes(&boundary_schemes);
+            self.boundary_schemes = boundary_schemes;
+        }
+        if let Some(prandtl_number) = partial_config.prandtl_number {
+            self.prandtl_number = prandtl_number;
+        }
+        // note: the relaxation times also depend on the Prandtl number
+        self.set_flow_parameters(
+            partial_config.velocity.unwrap_or(self.velocity),
+            partial_config
+                .reynolds_number
+                .unwrap_or(self.reynolds_number),
+        );
+        Ok(())
+    }
+
+    /// Set the subcycling of the temperature transport of a thermal scenario per iteration of
+    /// the flow, if any (e.g. for a Prandtl number far from one).
+    pub fn set_thermal_subcycling(&mut self, subcycling: Subcycling) -> bool {
+        match &mut self.advection_diffusion {
+            Some(advection_diffusion) => {
+                advection_diffusion.set_subcycling(subcycling);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Set the conductivity of the solid objects relative to that of the fluid of a thermal
+    /// scenario, if the temperature diffuses inside them (insulating by default), unless not
+    /// thermal or the conductivity is not positive and finite.
+    pub fn set_solid_conductivity(
+        &mut self,
+        solid_conductivity: Option<f32>,
+    ) -> Result<(), String> {
+        self.advection_diffusion
+            .as_mut()
+            .ok_or_else(|| "the scenario should be thermal".to_string())?
+            .set_solid_conductivity(solid_conductivity)
+    }
+
+    /// Statistics of the current iteration.
+    pub fn stats(&self) -> Stats {
+        Stats::new(
+            self.iteration,
+            &self.lbgk,
+            self.relaxation_time,
+            self.lbgk.force().map(|val| val / self.force_divisor),
+            self.nusselt_number(),
+        )
+    }
+
+    /// Create a new `Simulation` of a lattice size, for a scenario, a velocity of the flow, a
+    /// Reynolds number and a seed of random numbers.
+    ///
+    /// note: thermal scenarios are of air, with the velocity the free-fall velocity
+    pub fn with_scenario(
+        size: &[usize; 2],
+        scenario: Scenario,
+        velocity: f32,
+        reynolds_number: f32,
+        seed: u32,
+    ) -> Self {
+        Self::new_scenario(
+            size,
+            scenario,
+            velocity,
+            reynolds_number,
+            PRANDTL_NUMBER,
+            seed,
+        )
+    }
+
+    /// Create a new `Simulation` of a lattice size, for a scenario, a velocity of the flow, a
+    /// Reynolds number, a Prandtl number (of thermal scenarios) and a seed of random numbers.
+    fn new_scenario(
+        size: &[usize; 2],
+        scenario: Scenario,
+        velocity: f32,
+        reynolds_number: f32,
+        prandtl_number: f32,
+        seed: u32,
+    ) -> Self {
+        let boundary_schemes = scenario.boundary_schemes();
+        let lbgk = Lbgk::new_d2q9(
+            size,
+            &boundary_schemes,
+            DENSITY,
+            &match scenario.enclosed() {
+                true => [0.0; 2],
+                false => [velocity, 0.0],
+            },
+        );
+        let mut result = Self {
+            scenario,
+            boundary_schemes,
+            lbgk,
+            objects: scenario.objects(size),
+            painted_objects: vec![None; size[0] * size[1]],
+            immersed_boundaries: scenario.immersed_boundaries(size),
+            advection_diffusion: scenario.advection_diffusion(size),
+            probes: Probes::new(
+                Field::Pressure,
+                scenario.probe_positions(size),
+                PROBES_CAPACITY,
+            ),
+            iteration: 0,
+            velocity,
+            reynolds_number,
+            relaxation_time: 0.0,
+            prandtl_number,
+            thermal_relaxation_time: 0.0,
+            force_divisor: 0.0,
+            auto_checkpoint: None,
+            full_size: *size,
+            preview: false,
+            preview_threshold: None,
+            iteration_rate: IterationRate::new(RATE_MOVING_AVERAGE_PERIOD_SECS),
+        };
+        result.set_flow_parameters(velocity, reynolds_number);
+        result.set_objects();
+        scenario.set_initial_condition(&mut result.lbgk, velocity, seed);
+        result
+    }
+    /// Perform an iteration, moving the objects, immersed boundaries and temperatures (and
+    /// entering the preview mode if slow).
+    fn iterate(&mut self) {
+        self.iteration += 1;
+
+        if self.objects.iter().any(|object| object.moving()) {
+            for object in &mut self.objects {
+                object.set_time(self.iteration as f32);
+            }
+            self.set_objects();
+        }
+
+        if !self.immersed_boundaries.is_empty() || self.advection_diffusion.is_some() {
+            self.lbgk.clear_force_densities();
+            for immersed_boundary in &self.immersed_boundaries {
+                immersed_boundary.spread(&mut self.lbgk);
+            }
+            if let Some(advection_diffusion) = &self.advection_diffusion {
+                advection_diffusion.add_buoyancy(&mut self.lbgk);
+            }
+        }
+
+        self.lbgk.iterate(self.relaxation_time);
+
+        if let Some(advection_diffusion) = &mut self.advection_diffusion {
+            advection_diffusion.iterate(&self.lbgk, self.thermal_relaxation_time);
+        }
+
+        if !self.probes.is_empty() {
+            self.probes.record(self.iteration, &self.lbgk);
+        }
+
+        for immersed_boundary in &mut self.immersed_boundaries {
+            immersed_boundary.advect(&self.lbgk);
+        }
+
+        for (i, object) in self.objects.iter_mut().enumerate() {
+            let force = self.lbgk.object_force(object::object_id(i));
+            let torque = self
+                .lbgk
+                .object_torque(object::object_id(i), &object.centre_of_rotation());
+            object.respond(&force, torque);
+        }
+
+        if let Some(auto_checkpoint) = &mut self.auto_checkpoint {
+            if auto_checkpoint.due(self.iteration) {
+                let checkpoint = Checkpoint::new(
+                    self.iteration,
+                    &self.lbgk,
+                    self.advection_diffusion.as_ref(),
+                );
+                if let Err(err) = auto_checkpoint.save(&checkpoint) {
+                    log::warn("simulation", &format!("checkpoint not saved: {err}"));
+                }
+            }
+        }
+
+        self.iteration_rate.record(profiling::now());
+        if let Some(threshold) = self.preview_threshold {
+            let rate = self.iteration_rate.rate();
+            if !self.preview && self.iteration_rate.settled() && rate < threshold {
+                log::info(
+                    "simulation",
+                    &format!("iteration rate {rate} is below {threshold}, so previewing"),
+                );
+                self.set_preview(true);
+            }
+        }
+    }
+
+    /// Resize the lattice, interpolating the fields (see `resize`).
+    fn resize_lattice(&mut self, size: &[usize; 2]) {
+        let old_size = self.lbgk.size();
+        if *size == old_size {
+            return;
+        }
+        // note: lattice positions are the centres of cells, so the edges of the lattices
+        // coincide
+        let old_pos = |pos: &[usize; 2]| {
+            [0, 1].map(|i| (pos[i] as f32 + 0.5) * old_size[i] as f32 / size[i] as f32 - 0.5)
+        };
+
+        let mut lbgk = Lbgk::new_d2q9(size, &self.boundary_schemes, DENSITY, &[0.0; 2]);
+        std::mem::swap(&mut self.lbgk, &mut lbgk);
+        self.objects = self.scenario.objects(size);
+        self.painted_objects = vec![None; size[0] * size[1]];
+        for object in &mut self.objects {
+            object.set_time(self.iteration as f32);
+        }
+        self.set_objects();
+        for pos in self.lbgk.positions() {
+            let old_pos = old_pos(&pos);
+            self.lbgk.set_equilibrium(
+                &pos,
+                lbgk.interpolated_density(&old_pos),
+                &lbgk.interpolated_velocity_vector(&old_pos),
+            );
+        }
+
+        self.advection_diffusion = self.advection_diffusion.take().and_then(|old| {
+            let mut advection_diffusion = self.scenario.advection_diffusion(size)?;
+            advection_diffusion.set_subcycling(old.subcycling());
+            advection_diffusion
+                .set_solid_conductivity(old.solid_conductivity())
+                .expect("should have validated the solid conductivity");
+            for pos in self.lbgk.positions() {
+                advection_diffusion.set_value(&pos, old.interpolated_value(&old_pos(&pos)));
+            }
+            Some(advection_diffusion)
+        });
+        self.immersed_boundaries = self.scenario.immersed_boundaries(size);
+        self.probes = Probes::new(
+            Field::Pressure,
+            self.scenario.probe_positions(size),
+            PROBES_CAPACITY,
+        );
+        self.set_flow_parameters(self.velocity, self.reynolds_number);
+    }
+
+    /// Set the objects on the lattice, with painted (or erased) lattice positions overriding
+    /// them.
+    ///
+    /// note: the ID of each lattice position is set once, as a change refills it from the
+    /// neighbouring fluid or resets its wall velocity
+    fn set_objects(&mut self) {
+        let width = self.lbgk.size()[0];
+        for pos in self.lbgk.positions() {
+            let id = match self.painted_objects[width * pos[1] + pos[0]] {
+                Some(true) => DEFAULT_OBJECT_ID,
+                Some(false) => 0,
+                None => object::containing_object_id(&self.objects, &pos),
+            };
+            self.lbgk.set_object_id(&pos, id);
+        }
+    }
+}
@@ -0,0 +1,154 @@
+use crate::lbgk::{Field, Lbgk};
+
+use wasm_bindgen::prelude::*;
+
+const MAGIC: &[u8; 8] = b"LBMSNAP1";
+
+/// Snapshot of fields of a two dimensional lattice, with a compact self-describing binary
+/// format.
+///
+/// The format is little endian:
+/// - the magic bytes `LBMSNAP1`
+/// - the number of dimensions and the size in each dimension (u32)
+/// - the number of fields, and the length (u32) and UTF-8 bytes of each field name
+/// - zero padding to a multiple of four bytes, so the data can be viewed as a `Float32Array`
+/// - the values (f32) of each field in turn, with x varying fastest
+#[derive(Clone, PartialEq)]
+pub struct Snapshot {
+    size: [usize; 2],
+    fields: Vec<(String, Vec<f32>)>,
+}
+
+impl Snapshot {
+    /// Create a new `Snapshot` of fields of a lattice.
+    pub fn new<const B: usize>(lbgk: &Lbgk<2, B>, fields: &[Field]) -> Self {
+        let size = lbgk.size();
+        Self {
+            size,
+            fields: fields
+                .iter()
+                .map(|field| {
+                    // note: the stream function is integrated for all lattice positions at once
+                    let values = match field {
+                        Field::StreamFunction => lbgk.stream_functions(),
+                        _ => lbgk
+                            .positions()
+                            .map(|pos| lbgk.field(*field, &pos))
+                            .collect(),
+                    };
+                    (field.name().to_string(), values)
+                })
+                .collect(),
+        }
+    }
+
+    /// Create a new `Snapshot` of named values of a lattice size (e.g. of distributions).
+    pub(crate) fn from_values(size: &[usize; 2], fields: Vec<(String, Vec<f32>)>) -> Self {
+        Self {
+            size: *size,
+            fields,
+        }
+    }
+
+    /// Size of the lattice.
+    pub fn size(&self) -> [usize; 2] {
+        self.size
+    }
+
+    /// Names of the fields.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.fields.iter().map(|(name, _)| name.as_str())
+    }
+
+    /// Values of a field, if in the snapshot.
+    pub fn values(&self, name: &str) -> Option<&[f32]> {
+        self.fields
+            .iter()
+            .find(|(field_name, _)| field_name == name)
+            .map(|(_, values)| values.as_slice())
+    }
+
+    /// Binary representation of the snapshot.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut result = MAGIC.to_vec();
+        let push_u32 = |result: &mut Vec<u8>, val: usize| {
+            result.extend_from_slice(&(val as u32).to_le_bytes());
+        };
+        push_u32(&mut result, self.size.len());
+        for size in self.size {
+            push_u32(&mut result, size);
+        }
+        push_u32(&mut result, self.fields.len());
+        for (name, _) in &self.fields {
+            push_u32(&mut result, name.len());
+            result.extend_from_slice(name.as_bytes());
+        }
+        result.resize(result.len().next_multiple_of(4), 0);
+        for (_, values) in &self.fields {
+            for val in values {
+                result.extend_from_slice(&val.to_le_bytes());
+            }
+        }
+        result
+    }
+
+    /// Snapshot from its binary representation, if valid.
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let mut reader = Reader { bytes, offset: 0 };
+        if reader.take(MAGIC.len())? != MAGIC || reader.u32()? != 2 {
+            return None;
+        }
+        let size = [reader.u32()?, reader.u32()?];
+        let count = reader.u32()?;
+        let names = (0..count)
+            .map(|_| {
+                let len = reader.u32()?;
+                String::from_utf8(reader.take(len)?.to_vec()).ok()
+            })
+            .collect::<Option<Vec<_>>>()?;
+        reader.take(reader.offset.next_multiple_of(4) - reader.offset)?;
+
+        let len = size[0] * size[1];
+        let fields = names
+            .into_iter()
+            .map(|name| {
+                let values = reader
+                    .take(4 * len)?
+                    .chunks_exact(4)
+                    .map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+                    .collect();
+                Some((name, values))
+            })
+            .collect::<Option<Vec<_>>>()?;
+        Some(Self { size, fields })
+    }
+}
+
+/// Values of a field from the binary representation of a snapshot (as a `Float32Array` in
+/// JavaScript), if valid.
+#[wasm_bindgen(js_name = snapshotValues)]
+pub fn snapshot_values(bytes: &[u8], name: &str) -> Option<Vec<f32>> {
+    Snapshot::from_bytes(bytes)?
+        .values(name)
+        .map(<[f32]>::to_vec)
+}
+
+/// Reader of consecutive values from bytes.
+struct Reader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    /// Next bytes, if available.
+    fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+        let result = self.bytes.get(self.offset..self.offset + len)?;
+        self.offset += len;
+        Some(result)
+    }
+
+    /// Next little endian u32, if available.
+    fn u32(&mut self) -> Option<usize> {
+        Some(u32::from_le_bytes(self.take(4)?.try_into().ok()?) as usize)
+    }
+}
@@ -0,0 +1,168 @@
+use crate::lbgk::Lbgk;
+
+use itertools::{iproduct, izip};
+
+use std::f32::consts::PI;
+
+/// Energy and enstrophy [spectra](https://en.wikipedia.org/wiki/Energy_cascade) of the velocity
+/// of a doubly periodic two dimensional lattice, in shells of wavenumber magnitude (e.g. to
+/// follow the inverse energy cascade of two dimensional turbulence).
+pub struct Spectra {
+    /// Width of the shells of wavenumber magnitude.
+    wavenumber_step: f32,
+    energy: Vec<f32>,
+    enstrophy: Vec<f32>,
+}
+
+impl Spectra {
+    /// Create a new `Spectra` of the velocity of a lattice, by a discrete Fourier transform.
+    ///
+    /// note: normalised so the energy and enstrophy sum to the mean kinetic energy and half the
+    /// mean squared vorticity (per unit density), with object lattice positions at rest
+    pub fn new<const B: usize>(lbgk: &Lbgk<2, B>) -> Self {
+        let size = lbgk.size();
+        let wavenumber_step = wavenumber_step(&size);
+        let velocities = [0, 1].map(|d| {
+            let values = lbgk
+                .positions()
+                .map(|pos| match lbgk.object(&pos) {
+                    true => [0.0; 2],
+                    false => [lbgk.velocity_vector(&pos)[d], 0.0],
+                })
+                .collect::<Vec<_>>();
+            discrete_fourier_transform(&values, &size, false)
+        });
+
+        let len = size[0] * size[1];
+        let mut energy = Vec::new();
+        let mut enstrophy = Vec::new();
+        for (index, u, v) in izip!(0.., &velocities[0], &velocities[1]) {
+            let k = wavenumber(&[index % size[0], index / size[0]], &size);
+            let shell = (k / wavenumber_step).round() as usize;
+            if energy.len() <= shell {
+                energy.resize(shell + 1, 0.0);
+                enstrophy.resize(shell + 1, 0.0);
+            }
+            let val = 0.5 * (norm_squared(u) + norm_squared(v)) / (len * len) as f32;
+            energy[shell] += val;
+            enstrophy[shell] += k * k * val;
+        }
+
+        Self {
+            wavenumber_step,
+            energy,
+            enstrophy,
+        }
+    }
+
+    /// Wavenumber magnitude at the centre of a shell.
+    pub fn wavenumber(&self, shell: usize) -> f32 {
+        shell as f32 * self.wavenumber_step
+    }
+
+    /// Energy of each shell.
+    pub fn energy(&self) -> &[f32] {
+        &self.energy
+    }
+
+    /// Enstrophy of each shell.
+    pub fn enstrophy(&self) -> &[f32] {
+        &self.enstrophy
+    }
+
+    /// Spectra as [CSV](https://en.wikipedia.org/wiki/Comma-separated_values), with columns of
+    /// the wavenumber, energy and enstrophy of each shell.
+    pub fn to_csv(&self) -> String {
+        let mut result = "wavenumber,energy,enstrophy\n".to_string();
+        for (shell, energy, enstrophy) in izip!(0.., &self.energy, &self.enstrophy) {
+            result.push_str(&format!(
+                "{},{energy},{enstrophy}\n",
+                self.wavenumber(shell)
+            ));
+        }
+        result
+    }
+}
+
+/// Width of the shells of wavenumber magnitude of a lattice size, the smallest wavenumber of
+/// the longer dimension.
+pub(crate) fn wavenumber_step(size: &[usize; 2]) -> f32 {
+    2.0 * PI / size[0].max(size[1]) as f32
+}
+
+/// Wavenumber magnitude of the discrete Fourier transform index of a lattice size, with the
+/// upper half of the indexes of each dimension the negative wavenumbers.
+pub(crate) fn wavenumber(index: &[usize; 2], size: &[usize; 2]) -> f32 {
+    let [kx, ky] = [0, 1].map(|d| {
+        let m = match index[d] <= size[d] / 2 {
+            true => index[d] as f32,
+            false => index[d] as f32 - size[d] as f32,
+        };
+        2.0 * PI * m / size[d] as f32
+    });
+    (kx * kx + ky * ky).sqrt()
+}
+
+/// Squared magnitude of a complex number.
+fn norm_squared(val: &[f32; 2]) -> f32 {
+    val[0] * val[0] + val[1] * val[1]
+}
+
+/// Product of complex numbers.
+fn multiply(first: &[f32; 2], second: &[f32; 2]) -> [f32; 2] {
+    [
+        first[0] * second[0] - first[1] * second[1],
+        first[0] * second[1] + first[1] * second[0],
+    ]
+}
+
+/// [Discrete Fourier transform](https://en.wikipedia.org/wiki/Discrete_Fourier_transform) of
+/// complex values of a lattice size (x varying fastest), or its inverse (without the
+/// normalisation), as complex values of the same layout.
+///
+/// note: separable, transforming the rows and then the columns directly (so of any size, but
+/// quadratic in the length of each dimension)
+pub(crate) fn discrete_fourier_transform(
+    values: &[[f32; 2]],
+    size: &[usize; 2],
+    inverse: bool,
+) -> Vec<[f32; 2]> {
+    let [width, height] = *size;
+    let sign = match inverse {
+        true => 1.0,
+        false => -1.0,
+    };
+    // note: the roots of unity exp(sign 2πi j / n) of each dimension
+    let roots = [width, height].map(|n| {
+        (0..n)
+            .map(|j| {
+                let angle = sign * 2.0 * PI * j as f32 / n as f32;
+                [angle.cos(), angle.sin()]
+            })
+            .collect::<Vec<_>>()
+    });
+
+    let mut rows = vec![[0.0; 2]; width * height];
+    for (y, m) in iproduct!(0..height, 0..width) {
+        let row = &values[width * y..width * (y + 1)];
+        let mut sum = [0.0; 2];
+        for (x, val) in row.iter().enumerate() {
+            let val = multiply(val, &roots[0][(m * x) % width]);
+            sum[0] += val[0];
+            sum[1] += val[1];
+        }
+        rows[width * y + m] = sum;
+    }
+
+    let mut result = vec![[0.0; 2]; width * height];
+    for (n, m) in iproduct!(0..height, 0..width) {
+        let mut sum = [0.0; 2];
+        for y in 0..height {
+            let val = multiply(&rows[width * y + m], &roots[1][(n * y) % height]);
+            sum[0] += val[0];
+            sum[1] += val[1];
+        }
+        result[width * n + m] = sum;
+    }
+    result
+}
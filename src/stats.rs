@@ -0,0 +1,58 @@
+use crate::lbgk::Lbgk;
+
+use serde::Serialize;
+
+/// Statistics of a simulation at an iteration, returned to JavaScript as an object with camel
+/// case keys (e.g. `dragCoefficient`), so host applications can consume the numbers.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Stats {
+    /// Number of iterations performed.
+    pub iteration: usize,
+    /// Relaxation time of the flow.
+    pub relaxation_time: f32,
+    /// Drag coefficient of the objects during the last iteration.
+    pub drag_coefficient: f32,
+    /// Lift coefficient of the objects during the last iteration.
+    pub lift_coefficient: f32,
+    /// Mean density of the fluid (e.g. to check the conservation of mass).
+    pub mean_density: f32,
+    /// Largest magnitude of the velocity of the fluid.
+    pub max_velocity: f32,
+    /// Largest [Mach number](https://en.wikipedia.org/wiki/Mach_number) of the fluid, i.e. of
+    /// the largest velocity (e.g. to check the flow is nearly incompressible).
+    pub max_mach_number: f32,
+    /// [Nusselt number](https://en.wikipedia.org/wiki/Nusselt_number) of a thermal scenario,
+    /// if any.
+    pub nusselt_number: Option<f32>,
+}
+
+impl Stats {
+    /// Create a new `Stats` of a lattice at an iteration, of its relaxation time, force
+    /// coefficients and Nusselt number (if thermal).
+    pub fn new<const B: usize>(
+        iteration: usize,
+        lbgk: &Lbgk<2, B>,
+        relaxation_time: f32,
+        force_coefficients: [f32; 2],
+        nusselt_number: Option<f32>,
+    ) -> Self {
+        let (mut count, mut total_density, mut max_velocity) = (0, 0.0, 0.0f32);
+        for pos in lbgk.positions().filter(|pos| !lbgk.object(pos)) {
+            count += 1;
+            total_density += lbgk.density(&pos);
+            max_velocity = max_velocity.max(lbgk.velocity(&pos));
+        }
+        let [drag_coefficient, lift_coefficient] = force_coefficients;
+        Self {
+            iteration,
+            relaxation_time,
+            drag_coefficient,
+            lift_coefficient,
+            mean_density: total_density / count.max(1) as f32,
+            max_velocity,
+            max_mach_number: max_velocity / lbgk.sound_speed_squared().sqrt(),
+            nusselt_number,
+        }
+    }
+}
@@ -0,0 +1,186 @@
+use crate::compression;
+use crate::lbgk::{Field, Lbgk};
+use crate::log;
+use crate::probes::Probes;
+use crate::snapshot::Snapshot;
+
+use wasm_bindgen::prelude::*;
+
+const SNAPSHOT_MAGIC: &[u8; 8] = b"LBMSTRM1";
+/// Most bytes queued to send, beyond which snapshots are skipped (e.g. of a slow collector).
+const MAX_BUFFERED_AMOUNT: u32 = 16 * 1024 * 1024;
+
+/// Value as a JSON number, or null if not finite (as not representable).
+fn json_number(val: f32) -> String {
+    match val.is_finite() {
+        true => val.to_string(),
+        false => "null".to_string(),
+    }
+}
+
+/// [JSON](https://en.wikipedia.org/wiki/JSON) text message of named statistics (e.g. force
+/// coefficients) at an iteration.
+///
+/// note: names are assumed not to need escaping
+pub fn stats_message(iteration: usize, stats: &[(&str, f32)]) -> String {
+    let stats = stats
+        .iter()
+        .map(|(name, val)| format!("\"{name}\":{}", json_number(*val)))
+        .collect::<Vec<_>>();
+    format!(
+        "{{\"type\":\"stats\",\"iteration\":{iteration},\"stats\":{{{}}}}}",
+        stats.join(",")
+    )
+}
+
+/// JSON text message of the values of probes at lattice positions, with the iterations they
+/// were recorded at.
+pub fn probes_message<'a>(
+    positions: &[[usize; 2]],
+    values: impl IntoIterator<Item = (usize, &'a [f32])>,
+) -> String {
+    let positions = positions
+        .iter()
+        .map(|[x, y]| format!("[{x},{y}]"))
+        .collect::<Vec<_>>();
+    let (iterations, values): (Vec<_>, Vec<_>) = values
+        .into_iter()
+        .map(|(iteration, values)| {
+            let values = values
+                .iter()
+                .map(|val| json_number(*val))
+                .collect::<Vec<_>>();
+            (iteration.to_string(), format!("[{}]", values.join(",")))
+        })
+        .unzip();
+    format!(
+        "{{\"type\":\"probes\",\"positions\":[{}],\"iterations\":[{}],\"values\":[{}]}}",
+        positions.join(","),
+        iterations.join(","),
+        values.join(",")
+    )
+}
+
+/// Binary message of a snapshot of fields at an iteration, compressed.
+///
+/// The format is the magic bytes `LBMSTRM1`, the iteration (u64, little endian), then the
+/// binary representation of the snapshot (see `Snapshot`) compressed (see `compression`).
+pub fn snapshot_message(iteration: usize, snapshot: &Snapshot) -> Vec<u8> {
+    let mut result = SNAPSHOT_MAGIC.to_vec();
+    result.extend_from_slice(&(iteration as u64).to_le_bytes());
+    result.extend_from_slice(&compression::compress(&snapshot.to_bytes()));
+    result
+}
+
+/// Snapshot, with its iteration, from its binary message (see `snapshot_message`), if valid.
+pub fn snapshot_from_message(bytes: &[u8]) -> Option<(usize, Snapshot)> {
+    let (magic, bytes) = bytes.split_at_checked(SNAPSHOT_MAGIC.len())?;
+    let (iteration, bytes) = bytes.split_at_checked(8)?;
+    if magic != SNAPSHOT_MAGIC {
+        return None;
+    }
+    Some((
+        u64::from_le_bytes(iteration.try_into().ok()?) as usize,
+        Snapshot::from_bytes(&compression::decompress(bytes)?)?,
+    ))
+}
+
+/// Stream of the results of a simulation over a
+/// [WebSocket](https://en.wikipedia.org/wiki/WebSocket) to an external collector (e.g. a
+/// server-side dashboard or database), i.e. statistics and the values of probes every stats
+/// interval of iterations as JSON text messages, and compressed snapshots of fields every
+/// snapshot interval as binary messages.
+///
+/// note: results are not sent until the WebSocket is open, and the WebSocket is closed when
+/// dropped
+pub struct WebSocketStream {
+    web_socket: web_sys::WebSocket,
+    stats_interval: usize,
+    snapshot_interval: usize,
+    fields: Vec<Field>,
+    /// Iteration of the last values of probes sent, if any.
+    probes_iteration: Option<usize>,
+}
+
+impl WebSocketStream {
+    /// Connect a new `WebSocketStream` to the URL of a collector, of a stats interval and of
+    /// fields every snapshot interval (iterations).
+    pub fn connect(
+        url: &str,
+        stats_interval: usize,
+        snapshot_interval: usize,
+        fields: &[Field],
+    ) -> Result<Self, JsValue> {
+        let web_socket = web_sys::WebSocket::new(url)?;
+
+        let url_clone = url.to_string();
+        let on_open = Closure::once_into_js(move |_: web_sys::Event| {
+            log::info("streaming", &format!("connected to {url_clone}"));
+        });
+        web_socket.set_onopen(Some(on_open.unchecked_ref()));
+        let url_clone = url.to_string();
+        let on_close = Closure::once_into_js(move |_: web_sys::Event| {
+            log::info("streaming", &format!("disconnected from {url_clone}"));
+        });
+        web_socket.set_onclose(Some(on_close.unchecked_ref()));
+
+        Ok(Self {
+            web_socket,
+            stats_interval: stats_interval.max(1),
+            snapshot_interval: snapshot_interval.max(1),
+            fields: fields.to_vec(),
+            probes_iteration: None,
+        })
+    }
+
+    /// Whether the WebSocket is open.
+    pub fn is_open(&self) -> bool {
+        self.web_socket.ready_state() == web_sys::WebSocket::OPEN
+    }
+
+    /// Send the results of a lattice at an iteration which are due, i.e. statistics and the
+    /// values of probes recorded since last sent, and a snapshot of the fields.
+    pub fn stream<const B: usize>(
+        &mut self,
+        iteration: usize,
+        lbgk: &Lbgk<2, B>,
+        stats: &[(&str, f32)],
+        probes: &Probes,
+    ) -> Result<(), JsValue> {
+        if !self.is_open() {
+            return Ok(());
+        }
+
+        if iteration.is_multiple_of(self.stats_interval) {
+            self.web_socket
+                .send_with_str(&stats_message(iteration, stats))?;
+            if !probes.is_empty() {
+                let values = probes.recorded_after(self.probes_iteration);
+                self.web_socket
+                    .send_with_str(&probes_message(probes.positions(), values))?;
+                self.probes_iteration = Some(iteration);
+            }
+        }
+
+        if iteration.is_multiple_of(self.snapshot_interval) {
+            match self.web_socket.buffered_amount() > MAX_BUFFERED_AMOUNT {
+                true => {
+                    let text = format!("snapshot of iteration {iteration} skipped, as slow");
+                    log::warn("streaming", &text);
+                }
+                false => {
+                    let snapshot = Snapshot::new(lbgk, &self.fields);
+                    self.web_socket
+                        .send_with_u8_array(&snapshot_message(iteration, &snapshot))?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Drop for WebSocketStream {
+    fn drop(&mut self) {
+        let _ = self.web_socket.close();
+    }
+}
@@ -0,0 +1,107 @@
+use wasm_bindgen::prelude::*;
+
+use std::collections::VecDeque;
+
+/// Time series data.
+pub struct TimeSeries<const S: usize> {
+    capacity: usize,
+    // note: values are kept with the iteration they were recorded at
+    values: VecDeque<(usize, [f32; S])>,
+}
+
+impl<const S: usize> TimeSeries<S> {
+    /// Create a new `TimeSeries` retaining at most `capacity` values.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            values: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    /// Remove all recorded values.
+    pub fn clear(&mut self) {
+        self.values.clear();
+    }
+
+    /// Record values at an iteration.
+    pub fn push(&mut self, iteration: usize, values: [f32; S]) {
+        if self.values.len() == self.capacity {
+            self.values.pop_front();
+        }
+        self.values.push_back((iteration, values));
+    }
+
+    /// Recorded values.
+    pub fn values(&self) -> impl Iterator<Item = &(usize, [f32; S])> {
+        self.values.iter()
+    }
+
+    /// Most recently recorded values.
+    pub fn last(&self) -> Option<&[f32; S]> {
+        self.values.back().map(|(_, values)| values)
+    }
+
+    /// Recorded values as [CSV](https://en.wikipedia.org/wiki/Comma-separated_values), with
+    /// columns of the iteration, a time scaled from the iteration and the values.
+    pub fn to_csv(
+        &self,
+        time_header: &str,
+        time_per_iteration: f32,
+        headers: &[&str; S],
+    ) -> String {
+        let mut result = format!("iteration,{time_header},{}\n", headers.join(","));
+        for (iteration, values) in &self.values {
+            let time = *iteration as f32 * time_per_iteration;
+            let values = values.map(|val| val.to_string()).join(",");
+            result.push_str(&format!("{iteration},{time},{values}\n"));
+        }
+        result
+    }
+
+    /// Draw values as lines of the given colours.
+    pub fn draw(
+        &self,
+        colours: &[&str; S],
+        canvas_rendering_context: &web_sys::CanvasRenderingContext2d,
+    ) -> Result<(), JsValue> {
+        let canvas = canvas_rendering_context
+            .canvas()
+            .ok_or("should have canvas")?;
+        let (width, height) = (canvas.width() as f64, canvas.height() as f64);
+        canvas_rendering_context.clear_rect(0.0, 0.0, width, height);
+
+        let (mut min, mut max) = (0.0f32, 0.0f32);
+        for (_, values) in &self.values {
+            for val in values {
+                (min, max) = (min.min(*val), max.max(*val));
+            }
+        }
+        if max <= min {
+            return Ok(());
+        }
+
+        let x = |n: usize| width * n as f64 / (self.capacity - 1).max(1) as f64;
+        let y = |val: f32| height * ((max - val) / (max - min)) as f64;
+
+        // zero line
+        canvas_rendering_context.set_stroke_style_str("lightgrey");
+        canvas_rendering_context.begin_path();
+        canvas_rendering_context.move_to(0.0, y(0.0));
+        canvas_rendering_context.line_to(width, y(0.0));
+        canvas_rendering_context.stroke();
+
+        for (s, colour) in colours.iter().enumerate() {
+            canvas_rendering_context.set_stroke_style_str(colour);
+            canvas_rendering_context.begin_path();
+            for (n, (_, values)) in self.values.iter().enumerate() {
+                match n {
+                    0 => canvas_rendering_context.move_to(x(n), y(values[s])),
+                    _ => canvas_rendering_context.line_to(x(n), y(values[s])),
+                }
+            }
+            canvas_rendering_context.stroke();
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,59 @@
+/// Conversion between lattice units and physical ([SI](https://en.wikipedia.org/wiki/International_System_of_Units))
+/// units, by matching a characteristic length and velocity.
+#[derive(Clone, Copy, PartialEq)]
+pub struct Units {
+    /// Length of a lattice spacing (m).
+    length: f32,
+    /// Duration of an iteration (s).
+    time: f32,
+}
+
+impl Units {
+    /// Create a new `Units` matching a characteristic length and velocity in lattice units to
+    /// those in physical units (m and m/s).
+    pub fn new(
+        characteristic_length: f32,
+        velocity: f32,
+        physical_characteristic_length: f32,
+        physical_velocity: f32,
+    ) -> Self {
+        let length = physical_characteristic_length / characteristic_length;
+        Self {
+            length,
+            time: length * velocity / physical_velocity,
+        }
+    }
+
+    /// Create a new `Units` matching a characteristic length and velocity in lattice units to a
+    /// physical characteristic length (m), for a fluid of a physical kinematic viscosity (m²/s)
+    /// at a [Reynolds number](https://en.wikipedia.org/wiki/Reynolds_number).
+    pub fn from_reynolds_number(
+        characteristic_length: f32,
+        velocity: f32,
+        physical_characteristic_length: f32,
+        physical_viscosity: f32,
+        reynolds_number: f32,
+    ) -> Self {
+        Self::new(
+            characteristic_length,
+            velocity,
+            physical_characteristic_length,
+            reynolds_number * physical_viscosity / physical_characteristic_length,
+        )
+    }
+
+    /// Physical length (m) of a length in lattice units.
+    pub fn length(&self, length: f32) -> f32 {
+        length * self.length
+    }
+
+    /// Physical time (s) of a number of iterations.
+    pub fn time(&self, iterations: f32) -> f32 {
+        iterations * self.time
+    }
+
+    /// Physical velocity (m/s) of a velocity in lattice units.
+    pub fn velocity(&self, velocity: f32) -> f32 {
+        velocity * self.length / self.time
+    }
+}
@@ -0,0 +1,94 @@
+/// Viewport onto a two dimensional lattice, zoomed and panned.
+///
+/// Lattice positions map to display pixels with the lattice y-axis pointing up the display, and
+/// lattice positions at the centres of their display pixels.
+#[derive(Clone, Copy)]
+pub struct Viewport {
+    size: [usize; 2],
+    /// Magnification relative to the whole lattice.
+    zoom: f64,
+    /// Lattice position shown at the bottom left of the display.
+    origin: [f64; 2],
+}
+
+impl Viewport {
+    /// Create a new `Viewport` showing the whole lattice.
+    pub fn new(size: &[usize; 2]) -> Self {
+        Self {
+            size: *size,
+            zoom: 1.0,
+            origin: [0.0; 2],
+        }
+    }
+
+    /// Magnification relative to the whole lattice.
+    pub fn zoom(&self) -> f64 {
+        self.zoom
+    }
+
+    /// Whether the whole lattice is shown.
+    pub fn is_identity(&self) -> bool {
+        self.zoom == 1.0
+    }
+
+    /// Show the whole lattice.
+    pub fn reset(&mut self) {
+        self.zoom = 1.0;
+        self.origin = [0.0; 2];
+    }
+
+    /// Zoom by a factor about a lattice position, which remains at the same display position.
+    pub fn zoom_about(&mut self, pos: &[f64; 2], factor: f64, max_zoom: f64) {
+        let zoom = (self.zoom * factor).clamp(1.0, max_zoom);
+        let factor = zoom / self.zoom;
+        self.zoom = zoom;
+        for (origin, pos) in self.origin.iter_mut().zip(pos) {
+            *origin = pos - (pos - *origin) / factor;
+        }
+        self.clamp();
+    }
+
+    /// Pan by a lattice displacement of the display.
+    pub fn pan(&mut self, displacement: &[f64; 2]) {
+        for (origin, displacement) in self.origin.iter_mut().zip(displacement) {
+            *origin -= displacement;
+        }
+        self.clamp();
+    }
+
+    /// Keep the lattice filling the display.
+    fn clamp(&mut self) {
+        for (origin, size) in self.origin.iter_mut().zip(self.size) {
+            *origin = origin.clamp(0.0, size as f64 * (1.0 - 1.0 / self.zoom));
+        }
+    }
+
+    /// Display pixels per lattice position.
+    fn display_scale(&self, pixel_scale: f64) -> f64 {
+        pixel_scale * self.zoom
+    }
+
+    /// Transform (a, b, c, d, e, f) from lattice positions to display pixel coordinates, for
+    /// pixels per lattice position when the whole lattice is shown.
+    pub fn transform(&self, pixel_scale: f64) -> [f64; 6] {
+        let scale = self.display_scale(pixel_scale);
+        [
+            scale,
+            0.0,
+            0.0,
+            -scale,
+            scale * (0.5 - self.origin[0]) - 0.5,
+            scale * (self.size[1] as f64 - 0.5 + self.origin[1]) - 0.5,
+        ]
+    }
+
+    /// Lattice position at display pixel coordinates, for pixels per lattice position when the
+    /// whole lattice is shown.
+    pub fn lattice_position(&self, display_pos: &[f64; 2], pixel_scale: f64) -> [f64; 2] {
+        let scale = self.display_scale(pixel_scale);
+        [
+            (display_pos[0] + 0.5) / scale - 0.5 + self.origin[0],
+            self.size[1] as f64 - 0.5 - (display_pos[1] + 0.5) / scale + self.origin[1],
+        ]
+    }
+}
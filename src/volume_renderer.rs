@@ -0,0 +1,250 @@
+use crate::colour::{self, Colormap};
+use crate::linear_algebra::VectorRotate;
+
+use wasm_bindgen::prelude::*;
+use web_sys::WebGl2RenderingContext as Gl;
+
+const VERTEX_SHADER: &str = r#"#version 300 es
+in vec2 position;
+out vec2 v_position;
+void main() {
+    v_position = position;
+    gl_Position = vec4(position, 0.0, 1.0);
+}
+"#;
+
+// note: orthographic rays are marched front to back through the volume box, compositing the
+// colour mapped values with an opacity proportional to the value
+const FRAGMENT_SHADER: &str = r#"#version 300 es
+precision highp float;
+precision highp sampler3D;
+uniform sampler3D u_volume;
+uniform sampler2D u_colormap;
+uniform mat3 u_rotation;
+uniform vec3 u_extent;
+uniform float u_opacity;
+uniform int u_steps;
+in vec2 v_position;
+out vec4 colour;
+void main() {
+    vec3 origin = u_rotation * vec3(v_position, -2.0);
+    vec3 direction = u_rotation * vec3(0.0, 0.0, 1.0);
+    vec3 t0 = (-u_extent - origin) / direction;
+    vec3 t1 = (u_extent - origin) / direction;
+    vec3 t_min = min(t0, t1);
+    vec3 t_max = max(t0, t1);
+    float t_near = max(max(t_min.x, t_min.y), t_min.z);
+    float t_far = min(min(t_max.x, t_max.y), t_max.z);
+    vec4 result = vec4(0.0);
+    if (t_near < t_far) {
+        float step_length = (t_far - t_near) / float(u_steps);
+        for (int i = 0; i < u_steps && result.a < 0.99; i++) {
+            vec3 p = origin + direction * (t_near + (float(i) + 0.5) * step_length);
+            float value = texture(u_volume, (p + u_extent) / (2.0 * u_extent)).r;
+            float alpha = clamp(value * u_opacity * step_length, 0.0, 1.0);
+            vec3 rgb = texture(u_colormap, vec2(value, 0.5)).rgb;
+            result.rgb += (1.0 - result.a) * alpha * rgb;
+            result.a += (1.0 - result.a) * alpha;
+        }
+    }
+    colour = vec4(result.rgb + (1.0 - result.a) * vec3(1.0), 1.0);
+}
+"#;
+
+/// Number of colours sampled from the colour map.
+const COLORMAP_SIZE: usize = 256;
+/// Opacity per unit length of the largest value, relative to the largest extent of the volume.
+const OPACITY: f32 = 4.0;
+
+/// [Volume renderer](https://en.wikipedia.org/wiki/Volume_ray_casting) of values on a three
+/// dimensional lattice (e.g. the velocity magnitude or Q-criterion) to a canvas by
+/// [WebGL 2](https://en.wikipedia.org/wiki/WebGL).
+pub struct VolumeRenderer {
+    gl: Gl,
+    size: [usize; 3],
+    rotation_location: Option<web_sys::WebGlUniformLocation>,
+}
+
+impl VolumeRenderer {
+    /// Create a new `VolumeRenderer` drawing to a canvas, for a lattice size.
+    pub fn new(canvas: &web_sys::HtmlCanvasElement, size: [usize; 3]) -> Result<Self, JsValue> {
+        let gl = canvas
+            .get_context("webgl2")?
+            .ok_or("should have webgl2 context")?
+            .dyn_into::<Gl>()?;
+
+        let program = gl.create_program().ok_or("should create program")?;
+        for (shader_type, source) in [
+            (Gl::VERTEX_SHADER, VERTEX_SHADER),
+            (Gl::FRAGMENT_SHADER, FRAGMENT_SHADER),
+        ] {
+            let shader = gl
+                .create_shader(shader_type)
+                .ok_or("should create shader")?;
+            gl.shader_source(&shader, source);
+            gl.compile_shader(&shader);
+            if !gl
+                .get_shader_parameter(&shader, Gl::COMPILE_STATUS)
+                .as_bool()
+                .unwrap_or(false)
+            {
+                return Err(gl.get_shader_info_log(&shader).unwrap_or_default().into());
+            }
+            gl.attach_shader(&program, &shader);
+        }
+        gl.link_program(&program);
+        if !gl
+            .get_program_parameter(&program, Gl::LINK_STATUS)
+            .as_bool()
+            .unwrap_or(false)
+        {
+            return Err(gl.get_program_info_log(&program).unwrap_or_default().into());
+        }
+        gl.use_program(Some(&program));
+
+        // full canvas quad
+        let vertex_array = gl
+            .create_vertex_array()
+            .ok_or("should create vertex array")?;
+        gl.bind_vertex_array(Some(&vertex_array));
+        let buffer = gl.create_buffer().ok_or("should create buffer")?;
+        gl.bind_buffer(Gl::ARRAY_BUFFER, Some(&buffer));
+        gl.buffer_data_with_array_buffer_view(
+            Gl::ARRAY_BUFFER,
+            &js_sys::Float32Array::from(&[-1.0, -1.0, 1.0, -1.0, -1.0, 1.0, 1.0, 1.0][..]),
+            Gl::STATIC_DRAW,
+        );
+        let position = gl.get_attrib_location(&program, "position") as u32;
+        gl.enable_vertex_attrib_array(position);
+        gl.vertex_attrib_pointer_with_i32(position, 2, Gl::FLOAT, false, 0, 0);
+
+        // note: values and colours are given as bytes, without row padding
+        gl.pixel_storei(Gl::UNPACK_ALIGNMENT, 1);
+        for (unit, target, name) in [
+            (0, Gl::TEXTURE_3D, "u_volume"),
+            (1, Gl::TEXTURE_2D, "u_colormap"),
+        ] {
+            let texture = gl.create_texture().ok_or("should create texture")?;
+            gl.active_texture(Gl::TEXTURE0 + unit);
+            gl.bind_texture(target, Some(&texture));
+            for (parameter, value) in [
+                (Gl::TEXTURE_MIN_FILTER, Gl::LINEAR),
+                (Gl::TEXTURE_MAG_FILTER, Gl::LINEAR),
+                (Gl::TEXTURE_WRAP_S, Gl::CLAMP_TO_EDGE),
+                (Gl::TEXTURE_WRAP_T, Gl::CLAMP_TO_EDGE),
+                (Gl::TEXTURE_WRAP_R, Gl::CLAMP_TO_EDGE),
+            ] {
+                gl.tex_parameteri(target, parameter, value as i32);
+            }
+            gl.uniform1i(
+                gl.get_uniform_location(&program, name).as_ref(),
+                unit as i32,
+            );
+        }
+
+        // note: the largest extent of the volume box is one
+        let largest = size.iter().max().copied().unwrap_or(1).max(1) as f32;
+        let [x, y, z] = size.map(|val| 0.5 * val as f32 / largest);
+        gl.uniform3f(
+            gl.get_uniform_location(&program, "u_extent").as_ref(),
+            x,
+            y,
+            z,
+        );
+
+        gl.uniform1f(
+            gl.get_uniform_location(&program, "u_opacity").as_ref(),
+            OPACITY,
+        );
+        // note: about two ray marching steps per lattice position
+        gl.uniform1i(
+            gl.get_uniform_location(&program, "u_steps").as_ref(),
+            2 * largest as i32,
+        );
+
+        let mut result = Self {
+            rotation_location: gl.get_uniform_location(&program, "u_rotation"),
+            size,
+            gl,
+        };
+        result.set_colormap(&Colormap::Viridis)?;
+        result.set_values(&vec![0.0; size.iter().product()], [0.0, 1.0])?;
+        Ok(result)
+    }
+
+    /// Set values at the lattice positions (ordered with the first dimension varying fastest),
+    /// mapped from a minimum and maximum value to the colour map and opacity.
+    pub fn set_values(&mut self, values: &[f32], range: [f32; 2]) -> Result<(), JsValue> {
+        let [min, max] = range;
+        let divisor = (max - min).max(f32::MIN_POSITIVE);
+        let data = values
+            .iter()
+            .map(|val| ((val - min) / divisor).clamp(0.0, 1.0) * u8::MAX as f32)
+            .map(|val| val as u8)
+            .collect::<Vec<_>>();
+        let [width, height, depth] = self.size.map(|val| val as i32);
+        self.gl.active_texture(Gl::TEXTURE0);
+        self.gl.tex_image_3d_with_opt_u8_array(
+            Gl::TEXTURE_3D,
+            0,
+            Gl::R8 as i32,
+            width,
+            height,
+            depth,
+            0,
+            Gl::RED,
+            Gl::UNSIGNED_BYTE,
+            Some(&data),
+        )
+    }
+
+    /// Set colour map.
+    pub fn set_colormap(&mut self, colormap: &Colormap) -> Result<(), JsValue> {
+        let data = (0..COLORMAP_SIZE)
+            .flat_map(|i| {
+                let (r, g, b) =
+                    colour::rgb_to_u8(colormap.rgb(i as f32 / (COLORMAP_SIZE - 1) as f32));
+                [r, g, b]
+            })
+            .collect::<Vec<_>>();
+        self.gl.active_texture(Gl::TEXTURE1);
+        self.gl
+            .tex_image_2d_with_i32_and_i32_and_i32_and_format_and_type_and_opt_u8_array(
+                Gl::TEXTURE_2D,
+                0,
+                Gl::RGB8 as i32,
+                COLORMAP_SIZE as i32,
+                1,
+                0,
+                Gl::RGB,
+                Gl::UNSIGNED_BYTE,
+                Some(&data),
+            )
+    }
+
+    /// Draw the volume viewed from a direction, of a yaw about the y-axis and then a pitch
+    /// about the x-axis (radians).
+    pub fn draw(&self, yaw: f32, pitch: f32) {
+        // note: columns of the rotation are the rotated axes (column-major for WebGL)
+        let rotate = |axis: [f32; 3]| {
+            axis.rotate(([1.0, 0.0, 0.0], pitch))
+                .rotate(([0.0, 1.0, 0.0], yaw))
+        };
+        let rotation = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]].map(rotate);
+        self.gl.uniform_matrix3fv_with_f32_array(
+            self.rotation_location.as_ref(),
+            false,
+            rotation.as_flattened(),
+        );
+
+        let canvas = self
+            .gl
+            .canvas()
+            .and_then(|canvas| canvas.dyn_into::<web_sys::HtmlCanvasElement>().ok());
+        if let Some(canvas) = canvas {
+            self.gl
+                .viewport(0, 0, canvas.width() as i32, canvas.height() as i32);
+        }
+        self.gl.draw_arrays(Gl::TRIANGLE_STRIP, 0, 4);
+    }
+}
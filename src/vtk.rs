@@ -0,0 +1,75 @@
+use crate::lbgk::Lbgk;
+
+use std::fmt::Write;
+
+/// Fields of a two dimensional lattice as a [VTK](https://vtk.org) XML image data (.vti) file,
+/// with the density, velocity, vorticity and object mask at each lattice position.
+pub fn image_data<const B: usize>(lbgk: &Lbgk<2, B>) -> String {
+    let size = lbgk.size();
+    // note: VTK orders points with x varying fastest, as do lattice positions
+    let extent = format!("0 {} 0 {} 0 0", size[0] - 1, size[1] - 1);
+
+    let mut result = String::new();
+    let _ = writeln!(result, r#"<?xml version="1.0"?>"#);
+    let _ = writeln!(
+        result,
+        r#"<VTKFile type="ImageData" version="1.0" byte_order="LittleEndian">"#
+    );
+    let _ = writeln!(
+        result,
+        r#"<ImageData WholeExtent="{extent}" Origin="0 0 0" Spacing="1 1 1">"#
+    );
+    let _ = writeln!(result, r#"<Piece Extent="{extent}">"#);
+    let _ = writeln!(
+        result,
+        r#"<PointData Scalars="density" Vectors="velocity">"#
+    );
+
+    let mut data_array = |name: &str, data_type: &str, components: usize, values: String| {
+        let _ = writeln!(
+            result,
+            r#"<DataArray type="{data_type}" Name="{name}" NumberOfComponents="{components}" format="ascii">"#
+        );
+        let _ = writeln!(result, "{values}");
+        let _ = writeln!(result, "</DataArray>");
+    };
+    let join = |values: &mut dyn Iterator<Item = String>| values.collect::<Vec<_>>().join(" ");
+    data_array(
+        "density",
+        "Float32",
+        1,
+        join(&mut lbgk.positions().map(|pos| lbgk.density(&pos).to_string())),
+    );
+    // note: vectors have three components
+    data_array(
+        "velocity",
+        "Float32",
+        3,
+        join(&mut lbgk.positions().map(|pos| {
+            let [u, v] = lbgk.velocity_vector(&pos);
+            format!("{u} {v} 0")
+        })),
+    );
+    data_array(
+        "vorticity",
+        "Float32",
+        1,
+        join(&mut lbgk.positions().map(|pos| lbgk.vorticity(&pos).to_string())),
+    );
+    data_array(
+        "object",
+        "UInt8",
+        1,
+        join(
+            &mut lbgk
+                .positions()
+                .map(|pos| u8::from(lbgk.object(&pos)).to_string()),
+        ),
+    );
+
+    let _ = writeln!(result, "</PointData>");
+    let _ = writeln!(result, "</Piece>");
+    let _ = writeln!(result, "</ImageData>");
+    let _ = writeln!(result, "</VTKFile>");
+    result
+}
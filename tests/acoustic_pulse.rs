@@ -0,0 +1,53 @@
+//! Propagation of an acoustic pulse, compared with the speed of sound of the lattice.
+
+use wasm_lbm::initial_condition;
+use wasm_lbm::lbgk::{BoundaryScheme, Field, Lbgk};
+use wasm_lbm::probes::Probes;
+use wasm_lbm::scenario::Scenario;
+use wasm_lbm::simulation::Simulation;
+
+const SIZE: [usize; 2] = [96, 48];
+const DENSITY: f32 = 1.0;
+const RELAXATION_TIME: f32 = 0.55;
+/// Largest error of the measured speed of sound, relative to that of the lattice.
+const TOLERANCE: f32 = 0.02;
+
+/// Assert a measured speed of sound is that of the lattice.
+fn assert_sound_speed(speed: Option<f32>) {
+    let expected = (1.0f32 / 3.0).sqrt();
+    let speed = speed.unwrap();
+    assert!(
+        (speed - expected).abs() < TOLERANCE * expected,
+        "speed {speed}, expected {expected}"
+    );
+}
+
+#[test]
+fn sound_speed() {
+    let mut lbgk = Lbgk::new_d2q9(
+        &SIZE,
+        &[[BoundaryScheme::Periodic; 2]; 2],
+        DENSITY,
+        &[0.0; 2],
+    );
+    initial_condition::gaussian_pulse(&mut lbgk, &[24.0, 24.0], 0.001, 2.0);
+    let mut probes = Probes::new(Field::Pressure, vec![[34, 24], [64, 24]], 1000);
+    for iteration in 1..=100 {
+        lbgk.iterate(RELAXATION_TIME);
+        probes.record(iteration, &lbgk);
+    }
+    assert_sound_speed(probes.speed(0, 1));
+}
+
+#[test]
+fn scenario() {
+    let scenario = Scenario::ALL
+        .iter()
+        .position(|scenario| *scenario == Scenario::AcousticPulse)
+        .unwrap();
+    let mut simulation = Simulation::new(SIZE[0], SIZE[1], scenario, 0.1, 1000.0);
+    simulation.run_for(80);
+    assert_eq!(simulation.probe_count(), 4);
+    assert_eq!(simulation.probe_values(0).unwrap().len(), 80);
+    assert_sound_speed(simulation.probe_speed(0, 3));
+}
@@ -0,0 +1,323 @@
+use wasm_lbm::advection_diffusion::{AdvectionDiffusion, ScalarBoundaryScheme, Subcycling};
+use wasm_lbm::lbgk::{BoundaryScheme, Lbgk, DEFAULT_OBJECT_ID};
+use wasm_lbm::simulation::Simulation;
+
+use std::f32::consts::TAU;
+
+const VELOCITY: f32 = 0.1;
+const PRANDTL_NUMBER: f32 = 0.71;
+
+#[test]
+fn conduction() {
+    let size = [4, 16];
+    let lbgk = Lbgk::new_d2q9(
+        &size,
+        &[
+            [BoundaryScheme::Periodic; 2],
+            [BoundaryScheme::BounceBack; 2],
+        ],
+        1.0,
+        &[0.0; 2],
+    );
+    let mut advection_diffusion = AdvectionDiffusion::new(
+        &size,
+        &[
+            [ScalarBoundaryScheme::Periodic; 2],
+            [
+                ScalarBoundaryScheme::Value(1.0),
+                ScalarBoundaryScheme::Value(0.0),
+            ],
+        ],
+        0.5,
+    );
+    for _ in 0..5000 {
+        advection_diffusion.iterate(&lbgk, 0.8);
+    }
+
+    // note: linear between the walls midway beyond the faces
+    for y in 0..size[1] {
+        let expected = 1.0 - (y as f32 + 0.5) / size[1] as f32;
+        let value = advection_diffusion.value(&[1, y]);
+        assert!(
+            (value - expected).abs() < 1e-4,
+            "value {value} at {y}, expected {expected}"
+        );
+    }
+    let nusselt_number = advection_diffusion.nusselt_number(&lbgk, 0.8, 1).unwrap();
+    assert!((nusselt_number - 1.0).abs() < 1e-4);
+}
+
+#[test]
+fn conjugate_conduction() {
+    // note: a solid layer over the hot wall, of a lower conductivity than the fluid above
+    let (size, thickness, solid_conductivity) = ([4, 16], 8, 0.25);
+    let mut lbgk = Lbgk::new_d2q9(
+        &size,
+        &[
+            [BoundaryScheme::Periodic; 2],
+            [BoundaryScheme::BounceBack; 2],
+        ],
+        1.0,
+        &[0.0; 2],
+    );
+    for pos in lbgk.positions().filter(|pos| pos[1] < thickness) {
+        lbgk.set_object(&pos, true);
+    }
+    let mut advection_diffusion = AdvectionDiffusion::new(
+        &size,
+        &[
+            [ScalarBoundaryScheme::Periodic; 2],
+            [
+                ScalarBoundaryScheme::Value(1.0),
+                ScalarBoundaryScheme::Value(0.0),
+            ],
+        ],
+        0.5,
+    );
+    for invalid in [0.0, -1.0, f32::NAN, f32::INFINITY] {
+        assert!(advection_diffusion
+            .set_solid_conductivity(Some(invalid))
+            .is_err());
+    }
+    advection_diffusion
+        .set_solid_conductivity(Some(solid_conductivity))
+        .unwrap();
+    for _ in 0..20000 {
+        advection_diffusion.iterate(&lbgk, 0.8);
+    }
+
+    // note: linear in each layer, with the flux continuous across the surface between them,
+    // so the differences are in proportion to the thermal resistances
+    let resistances = [
+        thickness as f32 / solid_conductivity,
+        (size[1] - thickness) as f32,
+    ];
+    let surface_value = 1.0 - resistances[0] / (resistances[0] + resistances[1]);
+    for y in 0..size[1] {
+        let s = y as f32 + 0.5;
+        let expected = match y < thickness {
+            true => 1.0 - (1.0 - surface_value) * s / thickness as f32,
+            false => surface_value * (size[1] as f32 - s) / (size[1] - thickness) as f32,
+        };
+        let value = advection_diffusion.value(&[1, y]);
+        assert!(
+            (value - expected).abs() < 1e-3,
+            "value {value} at {y}, expected {expected}"
+        );
+    }
+
+    // note: insulating by default
+    advection_diffusion.set_solid_conductivity(None).unwrap();
+    for _ in 0..5000 {
+        advection_diffusion.iterate(&lbgk, 0.8);
+    }
+    let value = advection_diffusion.value(&[1, thickness]);
+    assert!(value < 0.01, "value {value} at the surface");
+}
+
+/// Conduction across a lattice at rest periodic across x (with objects on lattice positions),
+/// between boundary schemes at the start and end of y, after a number of iterations.
+fn conduction_between(
+    size: &[usize; 2],
+    objects: &[[usize; 2]],
+    boundary_schemes: [ScalarBoundaryScheme; 2],
+    object_boundary_scheme: ScalarBoundaryScheme,
+    iterations: usize,
+) -> (Lbgk<2, 9>, AdvectionDiffusion) {
+    let mut lbgk = Lbgk::new_d2q9(
+        size,
+        &[
+            [BoundaryScheme::Periodic; 2],
+            [BoundaryScheme::BounceBack; 2],
+        ],
+        1.0,
+        &[0.0; 2],
+    );
+    for pos in objects {
+        lbgk.set_object(pos, true);
+    }
+    let mut advection_diffusion = AdvectionDiffusion::new(
+        size,
+        &[[ScalarBoundaryScheme::Periodic; 2], boundary_schemes],
+        0.0,
+    );
+    advection_diffusion.set_object_boundary_scheme(DEFAULT_OBJECT_ID, object_boundary_scheme);
+    for _ in 0..iterations {
+        advection_diffusion.iterate(&lbgk, 0.8);
+    }
+    (lbgk, advection_diffusion)
+}
+
+#[test]
+fn fixed_flux() {
+    let (size, flux) = ([4, 16], 1e-3);
+    let (lbgk, advection_diffusion) = conduction_between(
+        &size,
+        &[],
+        [
+            ScalarBoundaryScheme::Flux(flux),
+            ScalarBoundaryScheme::Value(0.0),
+        ],
+        ScalarBoundaryScheme::ZeroFlux,
+        20000,
+    );
+
+    // note: linear of the gradient of the flux from the wall at the end
+    let diffusivity = advection_diffusion.diffusivity(0.8);
+    for y in 0..size[1] {
+        let expected = flux * (size[1] as f32 - 0.5 - y as f32) / diffusivity;
+        let value = advection_diffusion.value(&[1, y]);
+        assert!(
+            (value - expected).abs() < 1e-4,
+            "value {value} at {y}, expected {expected}"
+        );
+    }
+
+    // note: the heat flowing in through one wall flows out through the other
+    let (length, difference) = (size[1] as f32, flux * size[1] as f32 / diffusivity);
+    for (side, expected) in [(0, 1.0), (1, -1.0)] {
+        let nusselt_numbers = advection_diffusion
+            .wall_nusselt_numbers(&lbgk, 0.8, 1, side, length, difference)
+            .unwrap();
+        assert_eq!(nusselt_numbers.len(), size[0]);
+        for val in nusselt_numbers {
+            assert!((val - expected).abs() < 1e-2, "Nusselt number {val}");
+        }
+    }
+    assert!(advection_diffusion
+        .wall_nusselt_numbers(&lbgk, 0.8, 0, 0, length, difference)
+        .is_none());
+}
+
+#[test]
+fn object_surfaces() {
+    // note: a row of objects at the start of y, of a surface midway to the fluid
+    let size = [4, 16];
+    let objects = (0..size[0]).map(|x| [x, 0]).collect::<Vec<_>>();
+    let boundary_schemes = [
+        ScalarBoundaryScheme::ZeroFlux,
+        ScalarBoundaryScheme::Value(0.0),
+    ];
+    let height = (size[1] - 1) as f32;
+
+    let (_, advection_diffusion) = conduction_between(
+        &size,
+        &objects,
+        boundary_schemes,
+        ScalarBoundaryScheme::Value(1.0),
+        5000,
+    );
+    for y in 1..size[1] {
+        let expected = 1.0 - (y as f32 - 0.5) / height;
+        let value = advection_diffusion.value(&[1, y]);
+        assert!(
+            (value - expected).abs() < 1e-4,
+            "value {value} at {y}, expected {expected}"
+        );
+    }
+
+    let flux = 1e-3;
+    let (_, advection_diffusion) = conduction_between(
+        &size,
+        &objects,
+        boundary_schemes,
+        ScalarBoundaryScheme::Flux(flux),
+        20000,
+    );
+    let diffusivity = advection_diffusion.diffusivity(0.8);
+    for y in 1..size[1] {
+        let expected = flux * (size[1] as f32 - 0.5 - y as f32) / diffusivity;
+        let value = advection_diffusion.value(&[1, y]);
+        assert!(
+            (value - expected).abs() < 1e-4,
+            "value {value} at {y}, expected {expected}"
+        );
+    }
+}
+
+/// Nusselt number of Rayleigh–Bénard convection after a number of iterations.
+fn nusselt_number(size: &[usize; 2], rayleigh_number: f32, iterations: usize) -> f32 {
+    let mut simulation = Simulation::new_rayleigh_benard(
+        size[0],
+        size[1],
+        VELOCITY,
+        rayleigh_number,
+        PRANDTL_NUMBER,
+    );
+    simulation.run_for(iterations);
+    simulation.nusselt_number().unwrap()
+}
+
+#[test]
+fn rayleigh_benard_below_onset() {
+    // note: the perturbation decays below the onset of convection at a Rayleigh number of
+    // about 1708, leaving conduction alone
+    let nusselt_number = nusselt_number(&[16, 8], 1500.0, 1000);
+    assert!(
+        (nusselt_number - 1.0).abs() < 0.01,
+        "Nusselt number {nusselt_number}"
+    );
+}
+
+#[test]
+fn rayleigh_benard_above_onset() {
+    // note: the reference Nusselt number of Clever and Busse
+    let nusselt_number = nusselt_number(&[32, 16], 10000.0, 4000);
+    assert!(
+        (nusselt_number - 2.661).abs() < 0.05 * 2.661,
+        "Nusselt number {nusselt_number}"
+    );
+}
+
+#[test]
+fn subcycling() {
+    // note: a sine wave advected by a uniform flow half a wavelength while decaying, on a
+    // lattice periodic in both dimensions
+    let (size, velocity, relaxation_time, iterations) = ([32, 4], 0.02, 0.6, 800);
+    let lbgk = Lbgk::new_d2q9(
+        &size,
+        &[[BoundaryScheme::Periodic; 2]; 2],
+        1.0,
+        &[velocity, 0.0],
+    );
+    let wavenumber = TAU / size[0] as f32;
+
+    for subcycling in [
+        Subcycling::Substeps(1),
+        Subcycling::Substeps(4),
+        Subcycling::Interval(4),
+    ] {
+        let mut advection_diffusion =
+            AdvectionDiffusion::new(&size, &[[ScalarBoundaryScheme::Periodic; 2]; 2], 0.0);
+        for pos in lbgk.positions() {
+            advection_diffusion.set_value(&pos, (wavenumber * pos[0] as f32).sin());
+        }
+        advection_diffusion.set_subcycling(subcycling);
+        for _ in 0..iterations {
+            advection_diffusion.iterate(&lbgk, relaxation_time);
+        }
+
+        let time = iterations as f32;
+        let diffusivity = advection_diffusion.diffusivity(relaxation_time);
+        let amplitude = (-diffusivity * wavenumber * wavenumber * time).exp();
+        for pos in lbgk.positions() {
+            let expected = amplitude * (wavenumber * (pos[0] as f32 - velocity * time)).sin();
+            let value = advection_diffusion.value(&pos);
+            assert!(
+                (value - expected).abs() < 1e-2,
+                "value {value} at {pos:?}, expected {expected}"
+            );
+        }
+    }
+
+    // note: only a thermal scenario transports the temperature
+    let mut simulation = Simulation::new(32, 16, 0, VELOCITY, 100.0);
+    assert!(!simulation.set_thermal_subcycling(Subcycling::Substeps(2)));
+    assert!(simulation.set_solid_conductivity(Some(2.0)).is_err());
+    let mut simulation = Simulation::new_rayleigh_benard(32, 16, VELOCITY, 1e4, PRANDTL_NUMBER);
+    assert!(simulation.set_thermal_subcycling(Subcycling::Interval(2)));
+    assert!(simulation.set_solid_conductivity(Some(0.0)).is_err());
+    assert!(simulation.set_solid_conductivity(Some(2.0)).is_ok());
+    simulation.run_for(10);
+    assert!(simulation.nusselt_number().unwrap().is_finite());
+}
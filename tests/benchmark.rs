@@ -0,0 +1,97 @@
+//! [Lid-driven cavity](https://en.wikipedia.org/wiki/Lid-driven_cavity_problem) run to steady
+//! state, compared with the reference data of Ghia, Ghia and Shin.
+
+use wasm_lbm::benchmark;
+use wasm_lbm::lbgk::Lbgk;
+use wasm_lbm::linear_algebra::VectorOps;
+use wasm_lbm::scenario::Scenario;
+
+const SIZE: [usize; 2] = [24, 20];
+const DENSITY: f32 = 1.0;
+const LID_VELOCITY: f32 = 0.1;
+const REYNOLDS_NUMBER: f32 = 100.0;
+const MAX_ITERATIONS: usize = 100_000;
+/// Largest change of the velocity over `CHECK_ITERATIONS`, relative to the lid velocity, at
+/// steady state.
+const STEADY_TOLERANCE: f32 = 1e-4;
+const CHECK_ITERATIONS: usize = 100;
+/// Largest root mean square error of the centreline velocities, relative to the lid velocity.
+const TOLERANCE: f32 = 0.02;
+
+/// Lattice of the cavity at rest, with the lid moving and its walls.
+fn cavity() -> Lbgk<2, 9> {
+    let scenario = Scenario::LidDrivenCavity;
+    let mut lbgk = Lbgk::new_d2q9(&SIZE, &scenario.boundary_schemes(), DENSITY, &[0.0; 2]);
+    lbgk.set_source(DENSITY, &[LID_VELOCITY, 0.0]);
+    scenario.set_objects(&mut lbgk);
+    lbgk
+}
+
+/// Run to steady state.
+fn run_to_steady_state(lbgk: &mut Lbgk<2, 9>, relaxation_time: f32) {
+    let velocities = |lbgk: &Lbgk<2, 9>| {
+        lbgk.positions()
+            .map(|pos| lbgk.velocity_vector(&pos))
+            .collect::<Vec<_>>()
+    };
+    let mut previous = velocities(lbgk);
+    for _ in 0..MAX_ITERATIONS / CHECK_ITERATIONS {
+        for _ in 0..CHECK_ITERATIONS {
+            lbgk.iterate(relaxation_time);
+        }
+        let current = velocities(lbgk);
+        let change = current
+            .iter()
+            .zip(&previous)
+            .fold(0.0f32, |acc, (first, second)| {
+                acc.max([first[0] - second[0], first[1] - second[1]].norm())
+            });
+        if change <= STEADY_TOLERANCE * LID_VELOCITY {
+            return;
+        }
+        previous = current;
+    }
+    panic!("should reach steady state");
+}
+
+#[test]
+fn lid_driven_cavity() {
+    let characteristic_length = Scenario::LidDrivenCavity.characteristic_length(&SIZE);
+    assert_eq!(characteristic_length, SIZE[1] as f32);
+
+    let mut lbgk = cavity();
+    let relaxation_time =
+        lbgk.relaxation_time(LID_VELOCITY, characteristic_length, REYNOLDS_NUMBER);
+    run_to_steady_state(&mut lbgk, relaxation_time);
+
+    let errors = benchmark::lid_driven_cavity_errors(
+        &lbgk,
+        characteristic_length,
+        LID_VELOCITY,
+        REYNOLDS_NUMBER,
+    )
+    .expect("should have reference data");
+    for (name, val) in ["u", "v"].iter().zip(errors) {
+        assert!(val < TOLERANCE, "{name}: RMS error {val}");
+    }
+}
+
+#[test]
+fn reynolds_number_tolerance() {
+    let lbgk = cavity();
+    for reynolds_number in benchmark::lid_driven_cavity_reynolds_numbers() {
+        for val in [reynolds_number, reynolds_number * (1.0 + 1e-4)] {
+            assert!(
+                benchmark::lid_driven_cavity_errors(&lbgk, SIZE[1] as f32, LID_VELOCITY, val)
+                    .is_some(),
+                "Reynolds number {val}"
+            );
+        }
+    }
+    for val in [50.0, 101.0, 399.0, 1010.0] {
+        assert!(
+            benchmark::lid_driven_cavity_errors(&lbgk, SIZE[1] as f32, LID_VELOCITY, val).is_none(),
+            "Reynolds number {val}"
+        );
+    }
+}
@@ -0,0 +1,81 @@
+//! Custom boundary conditions, supplied through the `BoundaryCondition` trait.
+
+use wasm_lbm::lbgk::boundary_condition::{BoundaryCondition, Streaming};
+use wasm_lbm::lbgk::{BoundaryScheme, Lbgk};
+
+use std::sync::Arc;
+
+const SIZE: [usize; 2] = [16, 9];
+const DENSITY: f32 = 1.0;
+const VELOCITY: f32 = 0.05;
+
+/// Inflow of a fixed velocity, by equilibrium distributions.
+struct EquilibriumInflow;
+
+impl BoundaryCondition<2, 9> for EquilibriumInflow {
+    fn name(&self) -> &'static str {
+        "Equilibrium inflow"
+    }
+
+    fn update(&self, lbgk: &mut Lbgk<2, 9>, dim: usize, side: usize) {
+        for pos in lbgk.face_positions(dim, side) {
+            lbgk.set_equilibrium(&pos, DENSITY, &[VELOCITY, 0.0]);
+        }
+    }
+}
+
+/// Free-slip wall, by specular reflection.
+struct FreeSlip;
+
+impl BoundaryCondition<2, 9> for FreeSlip {
+    fn name(&self) -> &'static str {
+        "Free slip"
+    }
+
+    fn streaming(&self, _lbgk: &Lbgk<2, 9>) -> Streaming<2> {
+        Streaming::SpecularReflection
+    }
+}
+
+#[test]
+fn custom_boundary_conditions() {
+    let mut lbgk = Lbgk::new_d2q9(
+        &SIZE,
+        &[
+            [BoundaryScheme::Inflow, BoundaryScheme::Outflow],
+            [BoundaryScheme::SpecularReflection; 2],
+        ],
+        DENSITY,
+        &[VELOCITY, 0.0],
+    );
+    let mut custom = Lbgk::new_d2q9(
+        &SIZE,
+        &[
+            [BoundaryScheme::Inflow, BoundaryScheme::Outflow],
+            [BoundaryScheme::SpecularReflection; 2],
+        ],
+        DENSITY,
+        &[VELOCITY, 0.0],
+    );
+    custom.set_boundary_condition(0, 0, Arc::new(EquilibriumInflow));
+    for side in 0..2 {
+        custom.set_boundary_condition(1, side, Arc::new(FreeSlip));
+    }
+
+    // note: the custom boundary conditions are equivalent to the boundary schemes
+    for y in 0..SIZE[1] {
+        lbgk.set_object(&[SIZE[0] / 2, y], y < SIZE[1] / 3);
+        custom.set_object(&[SIZE[0] / 2, y], y < SIZE[1] / 3);
+    }
+    for _ in 0..100 {
+        lbgk.iterate(0.8);
+        custom.iterate(0.8);
+    }
+    for pos in lbgk.positions() {
+        assert_eq!(
+            lbgk.distributions(&pos),
+            custom.distributions(&pos),
+            "{pos:?}"
+        );
+    }
+}
@@ -0,0 +1,112 @@
+//! Checkpoints of the state of a simulation, resumed exactly and saved automatically with a
+//! retention.
+
+use wasm_lbm::checkpoint::{AutoCheckpoint, Checkpoint, DiskStorage, MemoryStorage};
+use wasm_lbm::lbgk::{BoundaryScheme, Lbgk};
+use wasm_lbm::simulation::Simulation;
+
+const SIZE: [usize; 2] = [48, 24];
+const ITERATIONS: usize = 20;
+
+/// Simulation of the first scenario (a cylinder in a channel).
+fn cylinder() -> Simulation {
+    Simulation::new(SIZE[0], SIZE[1], 0, 0.1, 100.0)
+}
+
+#[test]
+fn resume() {
+    let mut simulation = cylinder();
+    simulation.run_for(ITERATIONS);
+    let checkpoint = simulation.checkpoint();
+    simulation.run_for(ITERATIONS);
+
+    let mut resumed = cylinder();
+    assert!(resumed.restore_checkpoint(&checkpoint));
+    assert_eq!(resumed.iteration(), ITERATIONS);
+    resumed.run_for(ITERATIONS);
+    assert_eq!(resumed.iteration(), simulation.iteration());
+    assert!(resumed.snapshot() == simulation.snapshot());
+}
+
+#[test]
+fn resume_thermal() {
+    let rayleigh_benard = || Simulation::new_rayleigh_benard(16, 8, 0.1, 10000.0, 0.71);
+    let mut simulation = rayleigh_benard();
+    simulation.run_for(ITERATIONS);
+    let checkpoint = simulation.checkpoint();
+    simulation.run_for(ITERATIONS);
+
+    let mut resumed = rayleigh_benard();
+    assert!(resumed.restore_checkpoint(&checkpoint));
+    resumed.run_for(ITERATIONS);
+    assert_eq!(resumed.temperatures(), simulation.temperatures());
+
+    // note: a checkpoint without temperatures is not restored to a thermal simulation, nor
+    // vice versa
+    let mut isothermal = Simulation::new(16, 8, 0, 0.1, 100.0);
+    assert!(!resumed.restore_checkpoint(&isothermal.checkpoint()));
+    assert!(!isothermal.restore_checkpoint(&checkpoint));
+}
+
+#[test]
+fn invalid() {
+    let checkpoint = cylinder().checkpoint();
+    assert!(Checkpoint::from_bytes(&checkpoint[..checkpoint.len() - 1]).is_none());
+    assert!(Checkpoint::from_bytes(b"LBMSNAP1").is_none());
+
+    // note: of a different lattice size
+    let mut lbgk = Lbgk::new_d2q9(
+        &[SIZE[0], SIZE[1] + 1],
+        &[[BoundaryScheme::Periodic; 2]; 2],
+        1.0,
+        &[0.0; 2],
+    );
+    let checkpoint = Checkpoint::from_bytes(&checkpoint).unwrap();
+    assert!(!checkpoint.restore(&mut lbgk, None));
+}
+
+#[test]
+fn auto_checkpoint() {
+    let mut simulation = cylinder();
+    assert!(simulation.latest_checkpoint().is_none());
+    simulation.set_auto_checkpoint(5, 2);
+    simulation.run_for(23);
+    let latest = Checkpoint::from_bytes(&simulation.latest_checkpoint().unwrap()).unwrap();
+    assert_eq!(latest.iteration(), 20);
+
+    let lbgk = Lbgk::new_d2q9(&SIZE, &[[BoundaryScheme::Periodic; 2]; 2], 1.0, &[0.0; 2]);
+    let mut auto_checkpoint = AutoCheckpoint::new(5, 2, MemoryStorage::default());
+    for iteration in 0..=23 {
+        if auto_checkpoint.due(iteration) {
+            auto_checkpoint
+                .save(&Checkpoint::new(iteration, &lbgk, None))
+                .unwrap();
+        }
+    }
+    let iterations = auto_checkpoint
+        .storage()
+        .checkpoints()
+        .iter()
+        .map(Checkpoint::iteration)
+        .collect::<Vec<_>>();
+    assert_eq!(iterations, [15, 20]);
+}
+
+#[test]
+fn disk_storage() {
+    let directory =
+        std::env::temp_dir().join(format!("wasm-lbm-checkpoint-{}", std::process::id()));
+    let lbgk = Lbgk::new_d2q9(&SIZE, &[[BoundaryScheme::Periodic; 2]; 2], 1.0, &[0.0; 2]);
+    let mut auto_checkpoint = AutoCheckpoint::new(1, 2, DiskStorage::new(&directory).unwrap());
+    for iteration in 1..=3 {
+        auto_checkpoint
+            .save(&Checkpoint::new(iteration, &lbgk, None))
+            .unwrap();
+    }
+
+    let files = std::fs::read_dir(&directory).unwrap().count();
+    let latest = auto_checkpoint.storage().latest().unwrap();
+    std::fs::remove_dir_all(&directory).unwrap();
+    assert_eq!(files, 2);
+    assert!(latest == Checkpoint::new(3, &lbgk, None));
+}
@@ -0,0 +1,178 @@
+//! Collision operators, supplied through the `CollisionOperator` trait.
+
+use wasm_lbm::initial_condition;
+use wasm_lbm::lbgk::collision_operator::{
+    Bgk, CollisionOperator, CollisionValues, Cumulant, Mrt, Trt,
+};
+use wasm_lbm::lbgk::{BoundaryScheme, Lbgk};
+
+use std::sync::Arc;
+
+const SIZE: [usize; 2] = [32, 32];
+const DENSITY: f32 = 1.0;
+const VELOCITY: f32 = 0.01;
+const RELAXATION_TIME: f32 = 0.8;
+/// Iterations before measuring the decay, for the initial transient to settle.
+const SETTLE_ITERATIONS: usize = 20;
+const ITERATIONS: usize = 100;
+/// Largest error of the decay rate, relative to the analytical decay rate.
+const TOLERANCE: f32 = 0.02;
+const FORCE_DENSITY: [f32; 2] = [1.0e-5, 0.0];
+/// Largest error of the mass and momentum, relative to their expected values.
+const CONSERVATION_TOLERANCE: f32 = 1.0e-3;
+
+/// Lattice of a Taylor–Green vortex, periodic in both dimensions.
+fn taylor_green_vortex(collision_operator: Arc<dyn CollisionOperator<2, 9>>) -> Lbgk<2, 9> {
+    let mut lbgk = Lbgk::new_d2q9(
+        &SIZE,
+        &[[BoundaryScheme::Periodic; 2]; 2],
+        DENSITY,
+        &[0.0; 2],
+    );
+    lbgk.set_collision_operator(collision_operator);
+    initial_condition::taylor_green_vortex(&mut lbgk, DENSITY, VELOCITY);
+    lbgk
+}
+
+/// Mass and momentum of the fluid.
+fn mass_and_momentum(lbgk: &Lbgk<2, 9>) -> (f32, [f32; 2]) {
+    lbgk.iter_cells()
+        .fold((0.0, [0.0; 2]), |(mass, [x, y]), (_, cell)| {
+            let [u, v] = cell.velocity_vector();
+            let density = cell.density();
+            (mass + density, [x + density * u, y + density * v])
+        })
+}
+
+/// Assert the kinetic energy of a Taylor–Green vortex decays at the analytical rate.
+fn assert_decay_rate(collision_operator: Arc<dyn CollisionOperator<2, 9>>) {
+    let name = collision_operator.name();
+    let mut lbgk = taylor_green_vortex(collision_operator);
+    let kinetic_energy = |lbgk: &Lbgk<2, 9>| {
+        lbgk.iter_cells()
+            .map(|(_, cell)| {
+                let [u, v] = cell.velocity_vector();
+                0.5 * cell.density() * (u * u + v * v)
+            })
+            .sum::<f32>()
+    };
+
+    for _ in 0..SETTLE_ITERATIONS {
+        lbgk.iterate(RELAXATION_TIME);
+    }
+    let initial = kinetic_energy(&lbgk);
+    for _ in 0..ITERATIONS {
+        lbgk.iterate(RELAXATION_TIME);
+    }
+    let decay_rate = (initial / kinetic_energy(&lbgk)).ln() / ITERATIONS as f32;
+
+    let viscosity = (RELAXATION_TIME - 0.5) / 3.0;
+    let analytical = 2.0 * initial_condition::taylor_green_decay_rate(SIZE, viscosity);
+    let error = (decay_rate - analytical).abs() / analytical;
+    assert!(
+        error < TOLERANCE,
+        "{name}: decay rate {decay_rate}, analytical {analytical}, relative error {error}"
+    );
+}
+
+/// Assert the mass is conserved, and the momentum increases by the force density, under a
+/// uniform force density.
+fn assert_conservation(collision_operator: Arc<dyn CollisionOperator<2, 9>>) {
+    let name = collision_operator.name();
+    let mut lbgk = taylor_green_vortex(collision_operator);
+    for pos in lbgk.positions() {
+        lbgk.add_force_density(&pos, &FORCE_DENSITY);
+    }
+
+    lbgk.iterate(RELAXATION_TIME);
+    let (initial_mass, initial_momentum) = mass_and_momentum(&lbgk);
+    for _ in 0..ITERATIONS {
+        lbgk.iterate(RELAXATION_TIME);
+    }
+    let (mass, momentum) = mass_and_momentum(&lbgk);
+
+    let mass_error = (mass - initial_mass).abs() / initial_mass;
+    assert!(
+        mass_error < CONSERVATION_TOLERANCE,
+        "{name}: mass {mass}, initial {initial_mass}"
+    );
+    let cells = (SIZE[0] * SIZE[1]) as f32;
+    let expected = FORCE_DENSITY[0] * cells * ITERATIONS as f32;
+    let momentum_error = (momentum[0] - initial_momentum[0] - expected).abs() / expected;
+    assert!(
+        momentum_error < CONSERVATION_TOLERANCE,
+        "{name}: momentum {momentum:?}, initial {initial_momentum:?}, expected increase {expected}"
+    );
+}
+
+/// BGK operator of a fixed relaxation time, whatever the relaxation time of the iteration.
+struct FixedRelaxationTime(f32);
+
+impl CollisionOperator<2, 9> for FixedRelaxationTime {
+    fn name(&self) -> &'static str {
+        "Fixed relaxation time"
+    }
+
+    fn collide(&self, values: &CollisionValues<2, 9>, _relaxation_time: f32) -> [f32; 9] {
+        CollisionOperator::<2, 9>::collide(&Bgk, values, self.0)
+    }
+}
+
+#[test]
+fn decay_rate_bgk() {
+    assert_decay_rate(Arc::new(Bgk));
+}
+
+#[test]
+fn decay_rate_trt() {
+    assert_decay_rate(Arc::new(Trt::default()));
+}
+
+#[test]
+fn decay_rate_mrt() {
+    assert_decay_rate(Arc::new(Mrt::default()));
+}
+
+#[test]
+fn decay_rate_cumulant() {
+    assert_decay_rate(Arc::new(Cumulant::default()));
+}
+
+#[test]
+fn conservation_bgk() {
+    assert_conservation(Arc::new(Bgk));
+}
+
+#[test]
+fn conservation_trt() {
+    assert_conservation(Arc::new(Trt::default()));
+}
+
+#[test]
+fn conservation_mrt() {
+    assert_conservation(Arc::new(Mrt::default()));
+}
+
+#[test]
+fn conservation_cumulant() {
+    assert_conservation(Arc::new(Cumulant::default()));
+}
+
+#[test]
+fn custom_collision_operator() {
+    let mut lbgk = taylor_green_vortex(Arc::new(Bgk));
+    let mut custom = taylor_green_vortex(Arc::new(FixedRelaxationTime(RELAXATION_TIME)));
+
+    for _ in 0..ITERATIONS {
+        lbgk.iterate(RELAXATION_TIME);
+        custom.iterate(1.0);
+    }
+
+    for pos in lbgk.positions() {
+        assert_eq!(
+            lbgk.distributions(&pos),
+            custom.distributions(&pos),
+            "at {pos:?}"
+        );
+    }
+}
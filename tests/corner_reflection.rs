@@ -0,0 +1,113 @@
+//! Treatments of the distributions reflected at the corners where specular reflection
+//! boundaries meet.
+
+use wasm_lbm::lbgk::{BoundaryScheme, CornerReflection, Lbgk, StreamingScheme};
+
+const SIZE: [usize; 2] = [16, 9];
+const DENSITY: f32 = 1.0;
+const VELOCITY: f32 = 0.05;
+const RELAXATION_TIME: f32 = 0.8;
+const ITERATIONS: usize = 50;
+/// Largest difference of the mass from the initial mass, relative to it.
+const TOLERANCE: f32 = 1.0e-5;
+
+/// Closed box of specular reflection boundaries, of a uniform flow towards a corner.
+fn closed_box(
+    corner_reflection: CornerReflection,
+    streaming_scheme: StreamingScheme,
+) -> Lbgk<2, 9> {
+    let mut lbgk = Lbgk::new_d2q9(
+        &SIZE,
+        &[[BoundaryScheme::SpecularReflection; 2]; 2],
+        DENSITY,
+        &[VELOCITY; 2],
+    );
+    lbgk.set_corner_reflection(corner_reflection);
+    lbgk.set_streaming_scheme(streaming_scheme);
+    lbgk
+}
+
+/// Total mass of the lattice.
+fn mass(lbgk: &Lbgk<2, 9>) -> f32 {
+    lbgk.positions().map(|pos| lbgk.density(&pos)).sum()
+}
+
+#[test]
+fn mass_conserved() {
+    for corner_reflection in [
+        CornerReflection::Reflect,
+        CornerReflection::BounceBack,
+        CornerReflection::Split,
+    ] {
+        let mut lbgk = closed_box(corner_reflection, StreamingScheme::Push);
+        let initial = mass(&lbgk);
+        for _ in 0..ITERATIONS {
+            lbgk.iterate(RELAXATION_TIME);
+        }
+        let val = mass(&lbgk);
+        assert!((val - initial).abs() <= TOLERANCE * initial, "mass {val}");
+    }
+}
+
+#[test]
+fn policies() {
+    let [mut reflect, mut bounce_back, mut split] = [
+        CornerReflection::Reflect,
+        CornerReflection::BounceBack,
+        CornerReflection::Split,
+    ]
+    .map(|corner_reflection| closed_box(corner_reflection, StreamingScheme::Push));
+    for lbgk in [&mut reflect, &mut bounce_back, &mut split] {
+        lbgk.iterate(RELAXATION_TIME);
+    }
+
+    // note: reflecting across both boundaries reverses the lattice vector in two dimensions
+    for pos in reflect.positions() {
+        assert_eq!(reflect.distributions(&pos), bounce_back.distributions(&pos));
+    }
+
+    // note: only the corners differ, where the distribution reflected across both boundaries
+    // is split between the others
+    let corners = [
+        [0, 0],
+        [SIZE[0] - 1, 0],
+        [0, SIZE[1] - 1],
+        [SIZE[0] - 1, SIZE[1] - 1],
+    ];
+    for pos in reflect.positions() {
+        let [f_reflect, f_split] = [&reflect, &split].map(|lbgk| lbgk.distributions(&pos));
+        match corners.contains(&pos) {
+            true => {
+                assert!(f_split.contains(&0.0), "{pos:?}");
+                assert!(!f_reflect.contains(&0.0), "{pos:?}");
+                let [sum_reflect, sum_split] = [f_reflect, f_split].map(|f| f.iter().sum::<f32>());
+                assert!((sum_reflect - sum_split).abs() < 1e-6, "{pos:?}");
+            }
+            false => assert_eq!(f_reflect, f_split, "{pos:?}"),
+        }
+    }
+}
+
+#[test]
+fn streaming_schemes() {
+    for corner_reflection in [CornerReflection::BounceBack, CornerReflection::Split] {
+        let mut push = closed_box(corner_reflection, StreamingScheme::Push);
+        let mut others = [StreamingScheme::Pull, StreamingScheme::Aa]
+            .map(|streaming_scheme| closed_box(corner_reflection, streaming_scheme));
+        for _ in 0..ITERATIONS {
+            push.iterate(RELAXATION_TIME);
+            for other in &mut others {
+                other.iterate(RELAXATION_TIME);
+            }
+        }
+        for other in &others {
+            for pos in push.positions() {
+                assert_eq!(
+                    push.distributions(&pos),
+                    other.distributions(&pos),
+                    "{pos:?}"
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,97 @@
+//! [Couette flow](https://en.wikipedia.org/wiki/Couette_flow) between a stationary wall and a
+//! moving wall, compared with the analytical linear velocity profile and shear stress.
+
+use wasm_lbm::lbgk::{BoundaryScheme, Lbgk};
+
+const SIZE: [usize; 2] = [4, 21];
+const DENSITY: f32 = 1.0;
+const WALL_VELOCITY: f32 = 0.01;
+const MAX_ITERATIONS: usize = 100_000;
+/// Largest change of the velocity over `CHECK_ITERATIONS`, relative to the wall velocity, at
+/// steady state.
+const STEADY_TOLERANCE: f32 = 1e-5;
+const CHECK_ITERATIONS: usize = 100;
+/// Largest error of the velocity and the shear stress, relative to the wall velocity and the
+/// analytical shear stress.
+const TOLERANCE: f32 = 0.02;
+
+/// Run to steady state, with the wall at the end of the second dimension moving.
+fn steady_lbgk(relaxation_time: f32) -> Lbgk<2, 9> {
+    let mut lbgk = Lbgk::new_d2q9(
+        &SIZE,
+        &[
+            [BoundaryScheme::Periodic; 2],
+            [BoundaryScheme::BounceBack, BoundaryScheme::MovingWall],
+        ],
+        DENSITY,
+        &[0.0; 2],
+    );
+    // note: the source velocity is the wall velocity, with the fluid initially at rest
+    lbgk.set_source(DENSITY, &[WALL_VELOCITY, 0.0]);
+
+    let profile = |lbgk: &Lbgk<2, 9>| {
+        (0..SIZE[1])
+            .map(|y| lbgk.velocity_vector(&[0, y])[0])
+            .collect::<Vec<_>>()
+    };
+    let mut previous = profile(&lbgk);
+    for _ in 0..MAX_ITERATIONS / CHECK_ITERATIONS {
+        for _ in 0..CHECK_ITERATIONS {
+            lbgk.iterate(relaxation_time);
+        }
+        let current = profile(&lbgk);
+        let change = current
+            .iter()
+            .zip(&previous)
+            .fold(0.0f32, |acc, (first, second)| {
+                acc.max((first - second).abs())
+            });
+        if change <= STEADY_TOLERANCE * WALL_VELOCITY {
+            return lbgk;
+        }
+        previous = current;
+    }
+    panic!("should reach steady state for relaxation time {relaxation_time}");
+}
+
+/// Assert the steady velocity profile is linear, and the shear stress is uniform.
+fn assert_linear_profile(relaxation_time: f32) {
+    let viscosity = (relaxation_time - 0.5) / 3.0;
+    // note: bounce-back walls are midway between lattice positions
+    let width = SIZE[1] as f32;
+    let analytical = |y: usize| WALL_VELOCITY * (y as f32 + 0.5) / width;
+    let shear_stress = DENSITY * viscosity * WALL_VELOCITY / width;
+
+    let lbgk = steady_lbgk(relaxation_time);
+    for y in 0..SIZE[1] {
+        let val = lbgk.velocity_vector(&[0, y])[0];
+        let error = (val - analytical(y)).abs() / WALL_VELOCITY;
+        assert!(
+            error < TOLERANCE,
+            "relaxation time {relaxation_time}, y {y}: velocity {val}, analytical {}, relative error {error}",
+            analytical(y)
+        );
+
+        let val = lbgk.viscous_stress(&[0, y], relaxation_time).0[0][1];
+        let error = (val - shear_stress).abs() / shear_stress;
+        assert!(
+            error < TOLERANCE,
+            "relaxation time {relaxation_time}, y {y}: shear stress {val}, analytical {shear_stress}, relative error {error}"
+        );
+    }
+}
+
+#[test]
+fn linear_profile_relaxation_time_0_6() {
+    assert_linear_profile(0.6);
+}
+
+#[test]
+fn linear_profile_relaxation_time_1_0() {
+    assert_linear_profile(1.0);
+}
+
+#[test]
+fn linear_profile_relaxation_time_1_5() {
+    assert_linear_profile(1.5);
+}
@@ -0,0 +1,104 @@
+//! Projection of initial velocity fields onto fields free of divergence, removing the spurious
+//! pressure transient of a velocity field with divergence.
+
+use wasm_lbm::initial_condition;
+use wasm_lbm::lbgk::{BoundaryScheme, Lbgk};
+
+use itertools::iproduct;
+
+use std::f32::consts::PI;
+
+const SIZE: [usize; 2] = [32, 16];
+const DENSITY: f32 = 1.0;
+const VELOCITY: f32 = 0.05;
+const RELAXATION_TIME: f32 = 0.6;
+const ITERATIONS: usize = 20;
+
+/// Velocity vectors of the central differences of a stream function (so free of divergence by
+/// central differences) and, optionally, the central difference gradient of a potential.
+fn velocity_vectors(gradient: bool) -> Vec<[f32; 2]> {
+    let [width, height] = SIZE;
+    let [kx, ky] = SIZE.map(|size| 2.0 * PI / size as f32);
+    let stream_function = |x: usize, y: usize| {
+        let [x, y] = [x % width, y % height].map(|val| val as f32);
+        VELOCITY * (kx * x).cos() * (ky * y).sin() / ky
+    };
+    let potential = |x: usize, y: usize| {
+        let [x, y] = [x % width, y % height].map(|val| val as f32);
+        VELOCITY * (2.0 * kx * x).cos() * (ky * y).sin() / kx
+    };
+    iproduct!(0..height, 0..width)
+        .map(|(y, x)| {
+            let mut result = [
+                0.5 * (stream_function(x, y + 1) - stream_function(x, y + height - 1)),
+                -0.5 * (stream_function(x + 1, y) - stream_function(x + width - 1, y)),
+            ];
+            if gradient {
+                result[0] += 0.5 * (potential(x + 1, y) - potential(x + width - 1, y));
+                result[1] += 0.5 * (potential(x, y + 1) - potential(x, y + height - 1));
+            }
+            result
+        })
+        .collect()
+}
+
+#[test]
+fn projection() {
+    let expected = velocity_vectors(false);
+    let projected = initial_condition::divergence_free_projection(&SIZE, &velocity_vectors(true));
+    for (expected, projected) in expected.iter().zip(&projected) {
+        for (expected, projected) in expected.iter().zip(projected) {
+            assert!(
+                (expected - projected).abs() < 1e-5,
+                "velocity {projected}, expected {expected}"
+            );
+        }
+    }
+
+    // note: a field free of divergence is unchanged
+    let unchanged = initial_condition::divergence_free_projection(&SIZE, &expected);
+    for (expected, unchanged) in expected.iter().zip(&unchanged) {
+        assert!((expected[0] - unchanged[0]).abs() < 1e-5);
+        assert!((expected[1] - unchanged[1]).abs() < 1e-5);
+    }
+}
+
+#[test]
+fn pressure_transient() {
+    let densities = |gradient: bool, projected: bool| {
+        let mut lbgk = Lbgk::new_d2q9(
+            &SIZE,
+            &[[BoundaryScheme::Periodic; 2]; 2],
+            DENSITY,
+            &[0.0; 2],
+        );
+        initial_condition::velocity_field(
+            &mut lbgk,
+            &velocity_vectors(gradient),
+            DENSITY,
+            projected,
+        );
+        for _ in 0..ITERATIONS {
+            lbgk.iterate(RELAXATION_TIME);
+        }
+        lbgk.positions()
+            .map(|pos| lbgk.density(&pos))
+            .collect::<Vec<_>>()
+    };
+    // note: the densities of the flow free of divergence, without the sound radiated by the
+    // gradient part
+    let expected = densities(false, false);
+    let deviation = |densities: Vec<f32>| {
+        expected
+            .iter()
+            .zip(densities)
+            .map(|(expected, density)| (density - expected).abs())
+            .fold(0.0, f32::max)
+    };
+    let unprojected = deviation(densities(true, false));
+    let projected = deviation(densities(true, true));
+    assert!(
+        projected < 0.01 * unprojected,
+        "density deviation {projected}, {unprojected} unprojected"
+    );
+}
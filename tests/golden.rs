@@ -0,0 +1,138 @@
+//! Golden snapshot regression tests, running canonical setups for a fixed number of iterations
+//! and comparing quantized fields with stored golden files (e.g. to verify a refactor of the
+//! algorithm leaves the results unchanged).
+//!
+//! Golden files are written, rather than compared, when the `UPDATE_GOLDEN` environment
+//! variable is set, i.e. `UPDATE_GOLDEN=1 cargo test --test golden`.
+
+use wasm_lbm::initial_condition;
+use wasm_lbm::lbgk::{BoundaryScheme, Field, Lbgk};
+use wasm_lbm::scenario::Scenario;
+use wasm_lbm::snapshot::Snapshot;
+
+use std::fs;
+use std::path::PathBuf;
+
+const DENSITY: f32 = 1.0;
+const VELOCITY: f32 = 0.05;
+const RELAXATION_TIME: f32 = 0.6;
+const ITERATIONS: usize = 200;
+const FIELDS: [Field; 3] = [Field::Density, Field::VelocityX, Field::VelocityY];
+/// Quantum of the quantized field values.
+const QUANTUM: f32 = 1e-6;
+/// Largest difference of the quantized field values from the golden values, in quanta.
+///
+/// note: zero compares bit-for-bit up to the quantum, but transcendental functions (e.g. of
+/// the initial conditions) may differ in their last bit between platforms
+const TOLERANCE: i64 = 2;
+
+/// Path of a golden file.
+fn golden_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("golden")
+        .join(format!("{name}.txt"))
+}
+
+/// Quantized fields of a lattice as text, with a line of the name of each field followed by a
+/// line of values for each row of the lattice.
+fn quantized_text(lbgk: &Lbgk<2, 9>) -> String {
+    let snapshot = Snapshot::new(lbgk, &FIELDS);
+    let [width, _] = snapshot.size();
+    let mut result = String::new();
+    for name in snapshot.names() {
+        result.push_str(&format!("{name}\n"));
+        for row in snapshot.values(name).unwrap_or_default().chunks(width) {
+            let row = row
+                .iter()
+                .map(|val| ((val / QUANTUM).round() as i64).to_string())
+                .collect::<Vec<_>>();
+            result.push_str(&format!("{}\n", row.join(" ")));
+        }
+    }
+    result
+}
+
+/// Iterate a lattice, and compare its quantized fields with a golden file (or write it).
+fn assert_golden(name: &str, mut lbgk: Lbgk<2, 9>) {
+    for _ in 0..ITERATIONS {
+        lbgk.iterate(RELAXATION_TIME);
+    }
+    let text = quantized_text(&lbgk);
+
+    let path = golden_path(name);
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, text).unwrap();
+        return;
+    }
+
+    let golden = fs::read_to_string(&path).unwrap_or_else(|_| {
+        panic!(
+            "should have golden file {} (written with UPDATE_GOLDEN=1)",
+            path.display()
+        )
+    });
+    assert_eq!(
+        golden.lines().count(),
+        text.lines().count(),
+        "{name}: number of lines"
+    );
+    for (line_number, (golden, line)) in golden.lines().zip(text.lines()).enumerate() {
+        let golden = golden.split(' ').collect::<Vec<_>>();
+        let line = line.split(' ').collect::<Vec<_>>();
+        assert_eq!(golden.len(), line.len(), "{name}, line {}", line_number + 1);
+        for (golden, val) in golden.iter().zip(&line) {
+            match (golden.parse::<i64>(), val.parse::<i64>()) {
+                (Ok(golden), Ok(val)) => assert!(
+                    (golden - val).abs() <= TOLERANCE,
+                    "{name}, line {}: value {val}, golden {golden} (quanta of {QUANTUM})",
+                    line_number + 1
+                ),
+                _ => assert_eq!(golden, val, "{name}, line {}", line_number + 1),
+            }
+        }
+    }
+}
+
+/// Lattice of a scenario, with its objects and the inflow velocity.
+fn scenario_lbgk(scenario: Scenario, size: [usize; 2]) -> Lbgk<2, 9> {
+    let velocity_vector = match scenario.enclosed() {
+        true => [0.0; 2],
+        false => [VELOCITY, 0.0],
+    };
+    let mut lbgk = Lbgk::new_d2q9(
+        &size,
+        &scenario.boundary_schemes(),
+        DENSITY,
+        &velocity_vector,
+    );
+    lbgk.set_source(DENSITY, &[VELOCITY, 0.0]);
+    scenario.set_objects(&mut lbgk);
+    lbgk
+}
+
+#[test]
+fn cylinder() {
+    assert_golden("cylinder", scenario_lbgk(Scenario::Cylinder, [64, 32]));
+}
+
+#[test]
+fn lid_driven_cavity() {
+    assert_golden(
+        "lid_driven_cavity",
+        scenario_lbgk(Scenario::LidDrivenCavity, [64, 32]),
+    );
+}
+
+#[test]
+fn taylor_green_vortex() {
+    let mut lbgk = Lbgk::new_d2q9(
+        &[32, 32],
+        &[[BoundaryScheme::Periodic; 2]; 2],
+        DENSITY,
+        &[0.0; 2],
+    );
+    initial_condition::taylor_green_vortex(&mut lbgk, DENSITY, VELOCITY);
+    assert_golden("taylor_green_vortex", lbgk);
+}
@@ -0,0 +1,90 @@
+//! Inflow of the source values, or of the density extrapolated from the neighbouring fluid, and
+//! the extrapolated outflow.
+
+use wasm_lbm::lbgk::parameters::d2q9;
+use wasm_lbm::lbgk::{BoundaryScheme, Lbgk};
+
+const SIZE: [usize; 2] = [32, 9];
+const DENSITY: f32 = 1.0;
+const VELOCITY: f32 = 0.05;
+const RELAXATION_TIME: f32 = 0.8;
+const ITERATIONS: usize = 200;
+
+/// Channel of an inflow scheme, from a fluid at rest.
+fn channel(inflow: BoundaryScheme) -> Lbgk<2, 9> {
+    let mut lbgk = Lbgk::new_d2q9(
+        &SIZE,
+        &[
+            [inflow, BoundaryScheme::ExtrapolatedOutflow],
+            [BoundaryScheme::BounceBack; 2],
+        ],
+        DENSITY,
+        &[0.0; 2],
+    );
+    lbgk.set_source(DENSITY, &[VELOCITY, 0.0]);
+    for _ in 0..ITERATIONS {
+        lbgk.iterate(RELAXATION_TIME);
+    }
+    lbgk
+}
+
+#[test]
+fn source_density() {
+    let lbgk = channel(BoundaryScheme::Inflow);
+    for y in 0..SIZE[1] {
+        assert_eq!(lbgk.density(&[0, y]), DENSITY);
+        assert_eq!(lbgk.velocity_vector(&[0, y]), [VELOCITY, 0.0]);
+    }
+}
+
+#[test]
+fn extrapolated_density() {
+    let lbgk = channel(BoundaryScheme::ExtrapolatedInflow);
+    for y in 0..SIZE[1] {
+        // note: the velocity is imposed, but the density follows the neighbouring fluid
+        assert_eq!(lbgk.velocity_vector(&[0, y]), [VELOCITY, 0.0]);
+        assert_eq!(lbgk.density(&[0, y]), lbgk.density(&[1, y]));
+        assert_ne!(lbgk.density(&[0, y]), DENSITY);
+
+        // note: the non-equilibrium part of the neighbouring fluid holds no mass
+        let distributions = lbgk.distributions(&[0, y]);
+        let density = distributions.iter().sum::<f32>();
+        assert!(
+            (density - lbgk.density(&[0, y])).abs() < 1e-5,
+            "y {y}: {density}"
+        );
+    }
+    assert!(lbgk
+        .positions()
+        .all(|pos| lbgk.velocity_vector(&pos).iter().all(|val| val.is_finite())));
+}
+
+#[test]
+fn extrapolated_outflow() {
+    let lbgk = channel(BoundaryScheme::Inflow);
+    let x = SIZE[0] - 1;
+    for y in 0..SIZE[1] {
+        // note: extrapolated linearly from the two neighbouring lattice positions
+        let [density, other_density, next_density] =
+            [x, x - 1, x - 2].map(|x| lbgk.density(&[x, y]));
+        let expected = 2.0 * other_density - next_density;
+        assert!(
+            (density - expected).abs() < 1e-6,
+            "y {y}: density {density}, extrapolated {expected}"
+        );
+
+        // note: the stored density and velocity are those of the stored distributions
+        let distributions = lbgk.distributions(&[x, y]);
+        let sum = distributions.iter().sum::<f32>();
+        assert!((sum - density).abs() < 1e-5, "y {y}: {sum}");
+        let velocity_vector = lbgk.velocity_vector(&[x, y]);
+        for (d, u) in velocity_vector.iter().enumerate() {
+            let momentum = distributions
+                .iter()
+                .zip(d2q9::C)
+                .map(|(f, c)| f * c[d] as f32)
+                .sum::<f32>();
+            assert!((momentum / sum - u).abs() < 1e-5, "y {y}: {momentum}");
+        }
+    }
+}
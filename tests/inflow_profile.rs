@@ -0,0 +1,47 @@
+//! Inflow of a per lattice position profile, rather than the source values.
+
+use wasm_lbm::lbgk::{BoundaryScheme, Lbgk};
+
+const SIZE: [usize; 2] = [16, 9];
+const DENSITY: f32 = 1.0;
+const VELOCITY: f32 = 0.05;
+
+/// Parabolic velocity across the channel.
+fn parabolic_velocity(y: usize) -> f32 {
+    let (y, height) = (y as f32 + 0.5, SIZE[1] as f32);
+    4.0 * VELOCITY * y * (height - y) / (height * height)
+}
+
+#[test]
+fn parabolic_inflow() {
+    let mut lbgk = Lbgk::new_d2q9(
+        &SIZE,
+        &[
+            [BoundaryScheme::Inflow, BoundaryScheme::Outflow],
+            [BoundaryScheme::BounceBack; 2],
+        ],
+        DENSITY,
+        &[VELOCITY, 0.0],
+    );
+    lbgk.set_inflow_profile(Some(Box::new(|pos| {
+        ([parabolic_velocity(pos[1]), 0.0], DENSITY)
+    })));
+    lbgk.iterate(0.8);
+
+    for y in 0..SIZE[1] {
+        let [u, v] = lbgk.velocity_vector(&[0, y]);
+        assert!(
+            (u - parabolic_velocity(y)).abs() < 1e-6,
+            "y {y}: velocity {u}"
+        );
+        assert!(v.abs() < 1e-6, "y {y}: velocity {v}");
+        assert!((lbgk.density(&[0, y]) - DENSITY).abs() < 1e-6);
+    }
+
+    // note: without the profile the source values are used
+    lbgk.set_inflow_profile(None);
+    lbgk.iterate(0.8);
+    for y in 0..SIZE[1] {
+        assert!((lbgk.velocity_vector(&[0, y])[0] - VELOCITY).abs() < 1e-6);
+    }
+}
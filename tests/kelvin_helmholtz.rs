@@ -0,0 +1,46 @@
+//! [Kelvin–Helmholtz instability](https://en.wikipedia.org/wiki/Kelvin%E2%80%93Helmholtz_instability)
+//! of a doubly periodic pair of shear layers, growing from a perturbation of their interfaces.
+
+use wasm_lbm::initial_condition;
+use wasm_lbm::lbgk::{BoundaryScheme, Lbgk};
+
+const SIZE: [usize; 2] = [48, 48];
+const DENSITY: f32 = 1.0;
+const VELOCITY: f32 = 0.1;
+const THICKNESS: f32 = 1.5;
+const PERTURBATION: f32 = 0.01;
+const RELAXATION_TIME: f32 = 0.51;
+const ITERATIONS: usize = 300;
+
+/// Largest magnitude of the velocity across the shear layers.
+fn largest_cross_velocity(lbgk: &Lbgk<2, 9>) -> f32 {
+    lbgk.positions()
+        .map(|pos| lbgk.velocity_vector(&pos)[1].abs())
+        .fold(0.0, f32::max)
+}
+
+#[test]
+fn roll_up() {
+    let mut lbgk = Lbgk::new_d2q9(
+        &SIZE,
+        &[[BoundaryScheme::Periodic; 2]; 2],
+        DENSITY,
+        &[0.0; 2],
+    );
+    initial_condition::double_shear_layer(&mut lbgk, VELOCITY, THICKNESS, PERTURBATION);
+
+    // note: opposite velocities either side of each shear layer
+    assert!((lbgk.velocity_vector(&[0, 0])[0] + VELOCITY).abs() < 1e-3);
+    assert!((lbgk.velocity_vector(&[0, 24])[0] - VELOCITY).abs() < 1e-3);
+    let initial = largest_cross_velocity(&lbgk);
+    assert!((initial - PERTURBATION * VELOCITY).abs() < 1e-5);
+
+    for _ in 0..ITERATIONS {
+        lbgk.iterate(RELAXATION_TIME);
+    }
+    let cross_velocity = largest_cross_velocity(&lbgk);
+    assert!(
+        cross_velocity > 4.0 * initial,
+        "cross velocity {cross_velocity}, initially {initial}"
+    );
+}
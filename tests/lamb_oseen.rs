@@ -0,0 +1,71 @@
+//! [Lamb–Oseen vortex](https://en.wikipedia.org/wiki/Lamb%E2%80%93Oseen_vortex) initial
+//! condition, and its advection by a uniform flow.
+
+use wasm_lbm::initial_condition::{self, LambOseenVortex};
+use wasm_lbm::lbgk::{BoundaryScheme, Lbgk};
+
+const SIZE: [usize; 2] = [64, 64];
+const DENSITY: f32 = 1.0;
+const VELOCITY: f32 = 0.05;
+const RELAXATION_TIME: f32 = 0.52;
+const ITERATIONS: usize = 200;
+const VORTEX: LambOseenVortex = LambOseenVortex {
+    centre: [24.0, 32.0],
+    circulation: 1.0,
+    core_radius: 4.0,
+};
+
+/// Centre of the vortex, as the centroid of the vorticity.
+fn vortex_centre(lbgk: &Lbgk<2, 9>) -> [f32; 2] {
+    let (mut centre, mut total) = ([0.0; 2], 0.0);
+    for pos in lbgk.positions() {
+        let vorticity = lbgk.vorticity(&pos).max(0.0);
+        for (centre, pos) in centre.iter_mut().zip(pos) {
+            *centre += vorticity * pos as f32;
+        }
+        total += vorticity;
+    }
+    centre.map(|val| val / total)
+}
+
+#[test]
+fn velocity() {
+    // note: the tangential velocity peaks at about 1.12 core radii
+    let peak = (1..100)
+        .map(|i| 0.05 * i as f32)
+        .max_by(|first, second| {
+            let velocity = |r: f32| VORTEX.velocity_vector(&[24.0 + r * 4.0, 32.0])[1];
+            velocity(*first).total_cmp(&velocity(*second))
+        })
+        .unwrap();
+    assert!((peak - 1.12).abs() < 0.05, "peak {peak}");
+
+    // note: the pressure is lowest at the centre, rising to zero far from it
+    let pressure = |r: f32| VORTEX.pressure(&[24.0 + r, 32.0], DENSITY);
+    assert!(pressure(0.0) < pressure(4.0));
+    assert!(pressure(4.0) < pressure(40.0));
+    assert!(pressure(40.0) < 0.0 && pressure(40.0) > -1e-4);
+}
+
+#[test]
+fn advection() {
+    let mut lbgk = Lbgk::new_d2q9(
+        &SIZE,
+        &[[BoundaryScheme::Periodic; 2]; 2],
+        DENSITY,
+        &[VELOCITY, 0.0],
+    );
+    initial_condition::lamb_oseen_vortices(&mut lbgk, &[VORTEX], DENSITY);
+    let initial = vortex_centre(&lbgk);
+    assert!((initial[0] - 24.0).abs() < 0.1 && (initial[1] - 32.0).abs() < 0.1);
+
+    for _ in 0..ITERATIONS {
+        lbgk.iterate(RELAXATION_TIME);
+    }
+    let [x, y] = vortex_centre(&lbgk);
+    let expected = 24.0 + VELOCITY * ITERATIONS as f32;
+    assert!(
+        (x - expected).abs() < 0.2 && (y - 32.0).abs() < 0.2,
+        "centre {x}, {y}, expected {expected}, 32"
+    );
+}
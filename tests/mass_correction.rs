@@ -0,0 +1,56 @@
+//! Correction of the drift of the mass of the fluid from that of the source density.
+
+use wasm_lbm::lbgk::{BoundaryScheme, Lbgk};
+
+const SIZE: [usize; 2] = [32, 9];
+const DENSITY: f32 = 1.0;
+const VELOCITY: f32 = 0.05;
+const RELAXATION_TIME: f32 = 0.8;
+const ITERATIONS: usize = 200;
+const PERIOD: usize = 10;
+
+/// Channel of an extrapolated inflow, which lets the mass drift.
+fn channel(period: Option<usize>) -> Lbgk<2, 9> {
+    let mut lbgk = Lbgk::new_d2q9(
+        &SIZE,
+        &[
+            [BoundaryScheme::ExtrapolatedInflow, BoundaryScheme::Outflow],
+            [BoundaryScheme::BounceBack; 2],
+        ],
+        DENSITY,
+        &[0.0; 2],
+    );
+    lbgk.set_source(DENSITY, &[VELOCITY, 0.0]);
+    lbgk.set_object(&[SIZE[0] / 2, SIZE[1] / 2], true);
+    lbgk.set_mass_correction_period(period);
+    for _ in 0..ITERATIONS {
+        lbgk.iterate(RELAXATION_TIME);
+    }
+    lbgk
+}
+
+#[test]
+fn drift() {
+    let mass = (SIZE[0] * SIZE[1] - 1) as f32 * DENSITY;
+    let uncorrected = channel(None).mass_drift();
+    assert!(uncorrected.abs() > 1e-3 * mass, "drift {uncorrected}");
+
+    // note: corrected after the last iteration, as the iterations are a multiple of the period
+    let corrected = channel(Some(PERIOD)).mass_drift();
+    assert!(corrected.abs() < 1e-5 * mass, "drift {corrected}");
+}
+
+#[test]
+fn velocity_kept() {
+    let mut lbgk = channel(None);
+    let velocities = lbgk
+        .positions()
+        .map(|pos| lbgk.velocity_vector(&pos))
+        .collect::<Vec<_>>();
+    lbgk.correct_mass();
+    for (pos, velocity_vector) in lbgk.positions().zip(velocities) {
+        assert_eq!(lbgk.velocity_vector(&pos), velocity_vector);
+        let density = lbgk.distributions(&pos).iter().sum::<f32>();
+        assert!((density - lbgk.density(&pos)).abs() < 1e-5, "{pos:?}");
+    }
+}
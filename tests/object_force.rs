@@ -0,0 +1,91 @@
+//! Object IDs of the lattice positions, and the momentum exchange force and torque accounted
+//! for each object separately by object ID.
+
+use wasm_lbm::lbgk::{BoundaryScheme, Lbgk, DEFAULT_OBJECT_ID};
+use wasm_lbm::object;
+use wasm_lbm::scenario::Scenario;
+
+const SIZE: [usize; 2] = [96, 48];
+const DENSITY: f32 = 1.0;
+const VELOCITY: f32 = 0.05;
+const RELAXATION_TIME: f32 = 0.6;
+const ITERATIONS: usize = 200;
+/// Largest difference of the total of the forces on the objects from the force, relative to
+/// its magnitude.
+const TOLERANCE: f32 = 1.0e-4;
+
+#[test]
+fn twin_cylinders() {
+    let scenario = Scenario::TwinCylinders;
+    let mut lbgk = Lbgk::new_d2q9(
+        &SIZE,
+        &scenario.boundary_schemes(),
+        DENSITY,
+        &[VELOCITY, 0.0],
+    );
+    scenario.set_objects(&mut lbgk);
+
+    for _ in 0..ITERATIONS {
+        lbgk.iterate(RELAXATION_TIME);
+    }
+
+    let [lower, upper] = [0, 1].map(|i| lbgk.object_force(object::object_id(i)));
+    let force = lbgk.force();
+    for (force, lower, upper) in itertools::izip!(force, lower, upper) {
+        assert!(
+            (lower + upper - force).abs() <= TOLERANCE * force.abs().max(lower.abs()),
+            "force {force}, on the objects {lower} and {upper}"
+        );
+    }
+    // note: the cylinders are placed symmetrically about the centre line of the channel
+    assert!(
+        lower[0] > 0.0 && upper[0] > 0.0,
+        "drag {} and {}",
+        lower[0],
+        upper[0]
+    );
+    assert!(
+        lower[1] * upper[1] < 0.0,
+        "lift {} and {} of opposite signs",
+        lower[1],
+        upper[1]
+    );
+
+    let centre = [SIZE[0] as f32 / 4.0, SIZE[1] as f32 / 2.0];
+    let [lower_torque, upper_torque] =
+        [0, 1].map(|i| lbgk.object_torque(object::object_id(i), &centre));
+    let torque = lbgk.torque(&centre);
+    assert!(
+        (lower_torque + upper_torque - torque).abs() <= TOLERANCE * lower_torque.abs(),
+        "torque {torque}, on the objects {lower_torque} and {upper_torque}"
+    );
+    assert_eq!(lbgk.object_force(object::object_id(2)), [0.0; 2]);
+}
+
+#[test]
+fn object_ids() {
+    let mut lbgk = Lbgk::new_d2q9(
+        &[8, 8],
+        &[[BoundaryScheme::Periodic; 2]; 2],
+        DENSITY,
+        &[VELOCITY, 0.0],
+    );
+    let (first, second) = ([2, 2], [5, 5]);
+    lbgk.set_object(&first, true);
+    lbgk.set_object_id(&second, object::object_id(0));
+    assert_eq!(lbgk.object_id(&first), DEFAULT_OBJECT_ID);
+    assert_eq!(lbgk.object_id(&second), object::object_id(0));
+    assert!(lbgk.object(&first) && lbgk.object(&second));
+    assert_eq!(lbgk.object_id(&[0, 0]), 0);
+
+    lbgk.set_object(&second, false);
+    assert_eq!(lbgk.object_id(&second), 0);
+    assert!(!lbgk.object(&second));
+    assert_eq!(
+        lbgk.iter_cells()
+            .filter(|(_, cell)| cell.object())
+            .map(|(_, cell)| cell.object_id())
+            .collect::<Vec<_>>(),
+        vec![DEFAULT_OBJECT_ID]
+    );
+}
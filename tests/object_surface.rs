@@ -0,0 +1,182 @@
+//! Surfaces of objects: no-slip (at rest or moving), free-slip and porous.
+
+use wasm_lbm::lbgk::{BoundaryScheme, Lbgk, StreamingScheme, Surface};
+use wasm_lbm::object;
+use wasm_lbm::scenario::Scenario;
+
+const DENSITY: f32 = 1.0;
+const VELOCITY: f32 = 0.05;
+const RELAXATION_TIME: f32 = 1.0;
+const ITERATIONS: usize = 200;
+/// Largest difference of a velocity from that expected, relative to it.
+const TOLERANCE: f32 = 1.0e-3;
+
+/// Lattice periodic in both dimensions, of a uniform flow past a free-slip (or no-slip) wall on
+/// the first row.
+fn wall(surface: Surface) -> Lbgk<2, 9> {
+    let size = [32, 16];
+    let mut lbgk = Lbgk::new_d2q9(
+        &size,
+        &[[BoundaryScheme::Periodic; 2]; 2],
+        DENSITY,
+        &[VELOCITY, 0.0],
+    );
+    let id = object::object_id(0);
+    lbgk.set_object_surface(id, surface);
+    for x in 0..size[0] {
+        lbgk.set_object_id(&[x, 0], id);
+    }
+    lbgk
+}
+
+#[test]
+fn free_slip_wall() {
+    let mut lbgk = wall(Surface::FreeSlip);
+    for _ in 0..ITERATIONS {
+        lbgk.iterate(RELAXATION_TIME);
+    }
+
+    // note: the flow is undisturbed by a free-slip wall along it
+    for (pos, cell) in lbgk.iter_cells().filter(|(_, cell)| !cell.object()) {
+        let [u, v] = cell.velocity_vector();
+        assert!(
+            (u - VELOCITY).abs() <= TOLERANCE * VELOCITY && v.abs() <= TOLERANCE * VELOCITY,
+            "velocity ({u}, {v}) at {pos:?}"
+        );
+    }
+    assert_eq!(lbgk.object_force(object::object_id(0))[0], 0.0);
+}
+
+#[test]
+fn no_slip_wall() {
+    let mut lbgk = wall(Surface::NoSlip);
+    for _ in 0..ITERATIONS {
+        lbgk.iterate(RELAXATION_TIME);
+    }
+
+    let [u, _] = lbgk.velocity_vector(&[0, 1]);
+    assert!(u < 0.5 * VELOCITY, "velocity {u} next to the wall");
+    assert!(lbgk.object_force(object::object_id(0))[0] > 0.0);
+}
+
+#[test]
+fn moving_wall_couette_flow() {
+    let size = [4, 12];
+    for streaming_scheme in [
+        StreamingScheme::Push,
+        StreamingScheme::Pull,
+        StreamingScheme::Aa,
+    ] {
+        let mut lbgk = Lbgk::new_d2q9(
+            &size,
+            &[[BoundaryScheme::Periodic; 2]; 2],
+            DENSITY,
+            &[0.0; 2],
+        );
+        lbgk.set_streaming_scheme(streaming_scheme);
+        // note: a wall at rest on the first row, and a belt on the last row
+        let [wall, belt] = [0, 1].map(object::object_id);
+        for x in 0..size[0] {
+            lbgk.set_object_id(&[x, 0], wall);
+            lbgk.set_object_id(&[x, size[1] - 1], belt);
+            lbgk.set_wall_velocity(&[x, size[1] - 1], &[VELOCITY, 0.0]);
+        }
+        assert_eq!(lbgk.wall_velocity(&[0, size[1] - 1]), [VELOCITY, 0.0]);
+        assert_eq!(lbgk.wall_velocity(&[0, 0]), [0.0; 2]);
+        // note: the velocity is not kept by an object painted after releasing the position
+        let pos = [0, size[1] - 1];
+        lbgk.set_object_id(&pos, 0);
+        lbgk.set_object_id(&pos, belt);
+        assert_eq!(lbgk.wall_velocity(&pos), [0.0; 2]);
+        lbgk.set_wall_velocity(&pos, &[VELOCITY, 0.0]);
+        lbgk.set_object_id(&pos, belt);
+        assert_eq!(lbgk.wall_velocity(&pos), [VELOCITY, 0.0]);
+
+        for _ in 0..10 * ITERATIONS {
+            lbgk.iterate(RELAXATION_TIME);
+        }
+
+        // note: the surfaces are midway between the solid and fluid lattice positions
+        let height = (size[1] - 2) as f32;
+        for (pos, cell) in lbgk.iter_cells().filter(|(_, cell)| !cell.object()) {
+            let [u, v] = cell.velocity_vector();
+            let expected = VELOCITY * (pos[1] as f32 - 0.5) / height;
+            assert!(
+                (u - expected).abs() <= 1.0e-2 * VELOCITY && v.abs() <= 1.0e-2 * VELOCITY,
+                "velocity ({u}, {v}) at {pos:?}, expected {expected}"
+            );
+        }
+        // note: the belt drags the fluid along, which drags the wall
+        let [wall_force, belt_force] = [wall, belt].map(|id| lbgk.object_force(id)[0]);
+        assert!(
+            wall_force > 0.0 && belt_force < 0.0,
+            "{wall_force}, {belt_force}"
+        );
+        assert!((wall_force + belt_force).abs() <= 1.0e-2 * wall_force);
+    }
+}
+
+#[test]
+fn porous_darcy_flow() {
+    let size = [8, 8];
+    let (permeability, force_density) = (0.1, 1.0e-5);
+    let mut lbgk = Lbgk::new_d2q9(
+        &size,
+        &[[BoundaryScheme::Periodic; 2]; 2],
+        DENSITY,
+        &[0.0; 2],
+    );
+    let id = object::object_id(0);
+    lbgk.set_object_surface(id, Surface::Porous(permeability));
+    for pos in lbgk.positions() {
+        lbgk.set_object_id(&pos, id);
+        lbgk.add_force_density(&pos, &[force_density, 0.0]);
+    }
+    assert!(!lbgk.object(&[0, 0]));
+
+    for _ in 0..ITERATIONS {
+        lbgk.iterate(RELAXATION_TIME);
+    }
+
+    // note: by Darcy's law u = K F / (ρ ν)
+    let viscosity = (RELAXATION_TIME - 0.5) / 3.0;
+    let expected = permeability * force_density / (DENSITY * viscosity);
+    for (pos, cell) in lbgk.iter_cells() {
+        let [u, _] = cell.velocity_vector();
+        assert!(
+            (u - expected).abs() <= TOLERANCE * expected,
+            "velocity {u} at {pos:?}, expected {expected}"
+        );
+    }
+    // note: the momentum removed balances the force density added
+    let cells = (size[0] * size[1]) as f32;
+    let force = lbgk.object_force(id)[0];
+    assert!(
+        (force - force_density * cells).abs() <= TOLERANCE * force_density * cells,
+        "force {force}"
+    );
+}
+
+#[test]
+fn heterogeneous_twin_cylinders() {
+    let size = [96, 48];
+    let scenario = Scenario::TwinCylinders;
+    let mut lbgk = Lbgk::new_d2q9(
+        &size,
+        &scenario.boundary_schemes(),
+        DENSITY,
+        &[VELOCITY, 0.0],
+    );
+    lbgk.set_object_surface(object::object_id(1), Surface::FreeSlip);
+    scenario.set_objects(&mut lbgk);
+
+    for _ in 0..ITERATIONS {
+        lbgk.iterate(RELAXATION_TIME);
+    }
+
+    let [no_slip, free_slip] = [0, 1].map(|i| lbgk.object_force(object::object_id(i))[0]);
+    assert!(
+        0.0 < free_slip && free_slip < no_slip,
+        "drag {free_slip} of the free-slip cylinder, {no_slip} of the no-slip cylinder"
+    );
+}
@@ -0,0 +1,37 @@
+//! Objects painted (or erased) on the lattice of a simulation, overriding those of its scenario.
+
+use wasm_lbm::scenario::Scenario;
+use wasm_lbm::simulation::Simulation;
+
+const SIZE: [usize; 2] = [48, 24];
+const ITERATIONS: usize = 20;
+
+#[test]
+fn painted_objects() {
+    let oscillating_cylinder =
+        || Simulation::with_scenario(&SIZE, Scenario::OscillatingCylinder, 0.1, 100.0, 0);
+    let mut simulation = oscillating_cylinder();
+    let centre = [SIZE[0] / 4, SIZE[1] / 2];
+    assert!(simulation.object(centre[0], centre[1]));
+    assert!(!simulation.object(2, 2));
+
+    simulation.paint_object(&[2, 2], true);
+    simulation.paint_object(&centre, false);
+    // note: the painted objects are kept as the cylinder moves
+    simulation.run_for(ITERATIONS);
+    assert!(simulation.object(2, 2));
+    assert!(!simulation.object(centre[0], centre[1]));
+    assert!(simulation.stats().max_velocity.is_finite());
+
+    let painted_objects = simulation.painted_objects().to_vec();
+    let mut other = oscillating_cylinder();
+    assert!(!other.set_painted_objects(&painted_objects[1..]));
+    assert!(other.set_painted_objects(&painted_objects));
+    assert!(other.object(2, 2));
+    assert!(!other.object(centre[0], centre[1]));
+
+    // note: painted objects are cleared when resized
+    assert!(simulation.resize(SIZE[0], SIZE[1] + 1));
+    assert!(simulation.painted_objects().iter().all(Option::is_none));
+    assert!(!simulation.object(2, 2));
+}
@@ -0,0 +1,104 @@
+//! [Poiseuille flow](https://en.wikipedia.org/wiki/Hagen%E2%80%93Poiseuille_flow_from_the_Navier%E2%80%93Stokes_equations)
+//! between parallel plates driven by a body force, compared with the analytical parabolic
+//! velocity profile.
+
+use wasm_lbm::lbgk::{BoundaryScheme, Lbgk};
+
+const SIZE: [usize; 2] = [4, 21];
+const DENSITY: f32 = 1.0;
+const FORCE_DENSITY: f32 = 1e-5;
+const MAX_ITERATIONS: usize = 100_000;
+/// Largest change of the velocity over `CHECK_ITERATIONS`, relative to the peak velocity, at
+/// steady state.
+const STEADY_TOLERANCE: f32 = 1e-5;
+const CHECK_ITERATIONS: usize = 100;
+/// Largest error of the velocity, relative to the analytical peak velocity.
+const TOLERANCE: f32 = 0.02;
+
+/// Run to steady state, returning the velocity profile across the channel.
+fn steady_velocity_profile(relaxation_time: f32) -> Vec<f32> {
+    let mut lbgk = Lbgk::new_d2q9(
+        &SIZE,
+        &[
+            [BoundaryScheme::Periodic; 2],
+            [BoundaryScheme::BounceBack; 2],
+        ],
+        DENSITY,
+        &[0.0; 2],
+    );
+    for pos in lbgk.positions() {
+        lbgk.add_force_density(&pos, &[FORCE_DENSITY, 0.0]);
+    }
+
+    let profile = |lbgk: &Lbgk<2, 9>| {
+        (0..SIZE[1])
+            .map(|y| lbgk.velocity_vector(&[0, y])[0])
+            .collect::<Vec<_>>()
+    };
+    let mut previous = profile(&lbgk);
+    for _ in 0..MAX_ITERATIONS / CHECK_ITERATIONS {
+        for _ in 0..CHECK_ITERATIONS {
+            lbgk.iterate(relaxation_time);
+        }
+        let current = profile(&lbgk);
+        let peak = current.iter().fold(0.0f32, |acc, val| acc.max(val.abs()));
+        let change = current
+            .iter()
+            .zip(&previous)
+            .fold(0.0f32, |acc, (first, second)| {
+                acc.max((first - second).abs())
+            });
+        if peak > 0.0 && change <= STEADY_TOLERANCE * peak {
+            return current;
+        }
+        previous = current;
+    }
+    panic!("should reach steady state for relaxation time {relaxation_time}");
+}
+
+/// Assert the steady velocity profile matches the analytical profile.
+fn assert_parabolic_profile(relaxation_time: f32) {
+    let viscosity = (relaxation_time - 0.5) / 3.0;
+    // note: bounce-back walls are midway between lattice positions
+    let width = SIZE[1] as f32;
+    let analytical = |y: usize| {
+        let y = y as f32 + 0.5;
+        FORCE_DENSITY / (2.0 * DENSITY * viscosity) * y * (width - y)
+    };
+    let peak = analytical(SIZE[1] / 2);
+
+    let profile = steady_velocity_profile(relaxation_time);
+    for (y, val) in profile.iter().enumerate() {
+        let error = (val - analytical(y)).abs() / peak;
+        assert!(
+            error < TOLERANCE,
+            "relaxation time {relaxation_time}, y {y}: velocity {val}, analytical {}, relative error {error}",
+            analytical(y)
+        );
+    }
+}
+
+#[test]
+fn parabolic_profile_relaxation_time_0_6() {
+    assert_parabolic_profile(0.6);
+}
+
+#[test]
+fn parabolic_profile_relaxation_time_0_8() {
+    assert_parabolic_profile(0.8);
+}
+
+#[test]
+fn parabolic_profile_relaxation_time_1_0() {
+    assert_parabolic_profile(1.0);
+}
+
+#[test]
+fn parabolic_profile_relaxation_time_1_2() {
+    assert_parabolic_profile(1.2);
+}
+
+#[test]
+fn parabolic_profile_relaxation_time_1_5() {
+    assert_parabolic_profile(1.5);
+}
@@ -0,0 +1,80 @@
+//! Outlets adjusting their imposed density by feedback, to hold a pressure or mass flow.
+
+use wasm_lbm::lbgk::boundary_condition::{PressureOutlet, Setpoint};
+use wasm_lbm::lbgk::parameters::d2q9::CS2;
+use wasm_lbm::lbgk::{BoundaryScheme, Lbgk};
+
+use std::sync::Arc;
+
+const SIZE: [usize; 2] = [32, 9];
+const DENSITY: f32 = 1.0;
+const VELOCITY: f32 = 0.05;
+const RELAXATION_TIME: f32 = 0.8;
+const ITERATIONS: usize = 1000;
+const GAIN: f32 = 0.05;
+
+/// Channel of an inflow scheme of a velocity, and an outlet at the end.
+fn channel(inflow: BoundaryScheme, velocity: f32, outlet: &Arc<PressureOutlet>) -> Lbgk<2, 9> {
+    let mut lbgk = Lbgk::new_d2q9(
+        &SIZE,
+        &[
+            [inflow, BoundaryScheme::Outflow],
+            [BoundaryScheme::BounceBack; 2],
+        ],
+        DENSITY,
+        &[0.0; 2],
+    );
+    lbgk.set_source(DENSITY, &[velocity, 0.0]);
+    lbgk.set_boundary_condition(0, 1, outlet.clone());
+    for _ in 0..ITERATIONS {
+        lbgk.iterate(RELAXATION_TIME);
+    }
+    lbgk
+}
+
+#[test]
+fn pressure() {
+    let density = 1.01 * DENSITY;
+    let pressure = density * CS2;
+    let outlet = Arc::new(PressureOutlet::new(
+        Setpoint::Pressure(pressure),
+        GAIN,
+        DENSITY,
+    ));
+    let lbgk = channel(BoundaryScheme::ExtrapolatedInflow, VELOCITY, &outlet);
+
+    let mean_density = (0..SIZE[1])
+        .map(|y| lbgk.density(&[SIZE[0] - 2, y]))
+        .sum::<f32>()
+        / SIZE[1] as f32;
+    assert!(
+        (mean_density - density).abs() < 1e-4,
+        "mean density {mean_density}"
+    );
+    // note: the whole channel is raised to the pressure
+    assert!(lbgk.density(&[SIZE[0] / 2, SIZE[1] / 2]) > DENSITY);
+}
+
+#[test]
+fn mass_flow() {
+    // note: the fluid is drawn from a reservoir at rest, by the lower density at the outlet
+    let mass_flow = 0.1;
+    let outlet = Arc::new(PressureOutlet::new(
+        Setpoint::MassFlow(mass_flow),
+        GAIN,
+        DENSITY,
+    ));
+    let lbgk = channel(BoundaryScheme::Inflow, 0.0, &outlet);
+
+    let val = (0..SIZE[1])
+        .map(|y| {
+            let pos = [SIZE[0] - 2, y];
+            lbgk.density(&pos) * lbgk.velocity_vector(&pos)[0]
+        })
+        .sum::<f32>();
+    assert!(
+        (val - mass_flow).abs() < 1e-2 * mass_flow,
+        "mass flow {val}"
+    );
+    assert!(outlet.density() < DENSITY);
+}
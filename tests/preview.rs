@@ -0,0 +1,76 @@
+//! Coarse preview mode, entered manually or when the iteration rate is low.
+
+use wasm_lbm::profiling::IterationRate;
+use wasm_lbm::simulation::Simulation;
+
+const SIZE: [usize; 2] = [48, 25];
+const ITERATIONS: usize = 20;
+
+#[test]
+fn iteration_rate() {
+    let mut iteration_rate = IterationRate::new(1.0);
+    assert_eq!(iteration_rate.rate(), 0.0);
+    // note: an iteration every 10 ms
+    for i in 0..100 {
+        iteration_rate.record(10.0 * i as f64);
+    }
+    assert!(!iteration_rate.settled());
+    iteration_rate.record(1000.0);
+    assert!(iteration_rate.settled());
+    assert_eq!(iteration_rate.rate(), 100.0);
+
+    // note: an iteration every 20 ms, over the whole period
+    for i in 1..=50 {
+        iteration_rate.record(1000.0 + 20.0 * i as f64);
+    }
+    assert_eq!(iteration_rate.rate(), 50.0);
+
+    iteration_rate.clear();
+    assert_eq!(iteration_rate.rate(), 0.0);
+    assert!(!iteration_rate.settled());
+}
+
+#[test]
+fn manual() {
+    let mut simulation = Simulation::new(SIZE[0], SIZE[1], 0, 0.1, 100.0);
+    simulation.run_for(ITERATIONS);
+    let relaxation_time = simulation.relaxation_time();
+
+    simulation.set_preview(true);
+    assert!(simulation.preview());
+    assert_eq!([simulation.width(), simulation.height()], [24, 13]);
+    assert_eq!(simulation.iteration(), ITERATIONS);
+    simulation.run_for(ITERATIONS);
+    assert!(simulation.stats().max_velocity.is_finite());
+
+    simulation.set_preview(false);
+    assert!(!simulation.preview());
+    assert_eq!([simulation.width(), simulation.height()], SIZE);
+    assert_eq!(simulation.relaxation_time(), relaxation_time);
+    assert!(simulation.stats().max_velocity > 0.0);
+
+    // note: resizing leaves the preview mode, at the new size
+    simulation.set_preview(true);
+    assert!(simulation.resize(SIZE[0], SIZE[1]));
+    assert!(!simulation.preview());
+    assert_eq!([simulation.width(), simulation.height()], SIZE);
+}
+
+#[test]
+fn automatic() {
+    let mut simulation = Simulation::new(SIZE[0], SIZE[1], 0, 0.1, 100.0);
+    simulation.set_preview_threshold(f64::INFINITY);
+    // note: not entered until the rate has been measured for its period
+    simulation.run_for(ITERATIONS);
+    assert!(!simulation.preview());
+    simulation.run_for_duration(2100.0);
+    assert!(simulation.preview());
+    assert_eq!([simulation.width(), simulation.height()], [24, 13]);
+
+    // note: not entered if fast enough
+    let mut simulation = Simulation::new(SIZE[0], SIZE[1], 0, 0.1, 100.0);
+    simulation.set_preview_threshold(1.0);
+    simulation.run_for_duration(2100.0);
+    assert!(!simulation.preview());
+    assert!(simulation.iteration_rate() > 1.0);
+}
@@ -0,0 +1,48 @@
+//! Deterministic random numbers, reproducible from a seed.
+
+use wasm_lbm::random::{Random, DEFAULT_SEED};
+
+const COUNT: usize = 100000;
+
+#[test]
+fn reproducible() {
+    let sequence = |seed| {
+        let mut random = Random::new(seed);
+        (0..100).map(|_| random.next_u32()).collect::<Vec<_>>()
+    };
+    assert_eq!(sequence(DEFAULT_SEED), sequence(DEFAULT_SEED));
+    assert_ne!(sequence(DEFAULT_SEED), sequence(DEFAULT_SEED + 1));
+    // note: a seed of zero is valid
+    assert!(sequence(0).iter().any(|val| *val != 0));
+}
+
+#[test]
+fn uniform() {
+    let mut random = Random::new(DEFAULT_SEED);
+    let values = (0..COUNT).map(|_| random.uniform()).collect::<Vec<_>>();
+    assert!(values.iter().all(|val| (0.0..1.0).contains(val)));
+
+    // note: the mean and variance of the uniform distribution are 1/2 and 1/12
+    let mean = values.iter().sum::<f32>() / COUNT as f32;
+    let variance = values.iter().map(|val| (val - mean).powi(2)).sum::<f32>() / COUNT as f32;
+    assert!((mean - 0.5).abs() < 0.01, "mean {mean}");
+    assert!((variance - 1.0 / 12.0).abs() < 0.01, "variance {variance}");
+
+    // note: each tenth of the range is equally likely
+    let mut counts = [0usize; 10];
+    for val in &values {
+        counts[(val * 10.0) as usize] += 1;
+    }
+    for count in counts {
+        assert!(count.abs_diff(COUNT / 10) < COUNT / 100, "count {count}");
+    }
+}
+
+#[test]
+fn uniform_range() {
+    let mut random = Random::new(DEFAULT_SEED);
+    for _ in 0..1000 {
+        let val = random.uniform_range(-2.0, 3.0);
+        assert!((-2.0..3.0).contains(&val));
+    }
+}
@@ -0,0 +1,67 @@
+//! Recordings streamed in chunks to a sink, and exported in full.
+
+use wasm_lbm::lbgk::{BoundaryScheme, Field, Lbgk};
+use wasm_lbm::recording::{self, MemorySink, Recording};
+use wasm_lbm::snapshot::Snapshot;
+
+const SIZE: [usize; 2] = [16, 8];
+const ITERATIONS: usize = 25;
+const FIELDS: [Field; 2] = [Field::Density, Field::Vorticity];
+
+#[test]
+fn chunks() {
+    let mut lbgk = Lbgk::new_d2q9(&SIZE, &[[BoundaryScheme::Periodic; 2]; 2], 1.0, &[0.0; 2]);
+    let mut recording = Recording::new(10, &FIELDS, &["first", "second"], 4, MemorySink::default());
+    let mut expected_snapshots = Vec::new();
+    for iteration in 1..=ITERATIONS {
+        lbgk.iterate(0.6);
+        let values = [iteration as f32, -0.5 * iteration as f32];
+        recording.record(iteration, &lbgk, &values).unwrap();
+        if iteration.is_multiple_of(10) {
+            expected_snapshots.push((iteration, Snapshot::new(&lbgk, &FIELDS)));
+        }
+    }
+    // note: rows are written in chunks of four, so the last row is not yet written
+    let rows = |recording: &Recording<MemorySink>| {
+        let (time_series, _) = recording::export(recording.sink().chunks().to_vec());
+        time_series.lines().count()
+    };
+    assert_eq!(rows(&recording), 1 + 24);
+    recording.flush().unwrap();
+    assert_eq!(rows(&recording), 1 + ITERATIONS);
+
+    let (time_series, snapshots) = recording::export(recording.sink().chunks().to_vec());
+    let mut lines = time_series.lines();
+    assert_eq!(lines.next(), Some("iteration,first,second"));
+    for (iteration, line) in (1..).zip(lines) {
+        assert_eq!(
+            line,
+            format!("{iteration},{iteration},{}", -0.5 * iteration as f32)
+        );
+    }
+
+    let snapshots = recording::snapshots_from_bytes(&snapshots).unwrap();
+    assert_eq!(snapshots.len(), expected_snapshots.len());
+    for ((iteration, snapshot), (expected_iteration, expected)) in
+        snapshots.iter().zip(&expected_snapshots)
+    {
+        assert_eq!(iteration, expected_iteration);
+        assert!(snapshot == expected);
+    }
+}
+
+#[test]
+fn invalid() {
+    let (_, snapshots) = recording::export(Vec::new());
+    assert_eq!(
+        recording::snapshots_from_bytes(&snapshots).unwrap().len(),
+        0
+    );
+    assert!(recording::snapshots_from_bytes(b"LBMSNAP1").is_none());
+
+    let lbgk = Lbgk::new_d2q9(&SIZE, &[[BoundaryScheme::Periodic; 2]; 2], 1.0, &[0.0; 2]);
+    let mut recording = Recording::new(1, &FIELDS, &[], 1, MemorySink::default());
+    recording.record(1, &lbgk, &[]).unwrap();
+    let (_, snapshots) = recording::export(recording.sink().chunks().to_vec());
+    assert!(recording::snapshots_from_bytes(&snapshots[..snapshots.len() - 1]).is_none());
+}
@@ -0,0 +1,99 @@
+//! Resizing of the lattice at runtime, interpolating the fields.
+
+use wasm_lbm::scenario::Scenario;
+use wasm_lbm::simulation::Simulation;
+
+use std::f32::consts::TAU;
+
+const SIZE: [usize; 2] = [48, 24];
+const ITERATIONS: usize = 20;
+
+#[test]
+fn sizes() {
+    let mut simulation = Simulation::new(SIZE[0], SIZE[1], 0, 0.1, 100.0);
+    simulation.run_for(ITERATIONS);
+
+    assert!(!simulation.resize(0, SIZE[1]));
+    assert_eq!([simulation.width(), simulation.height()], SIZE);
+
+    assert!(simulation.resize(2 * SIZE[0], 2 * SIZE[1]));
+    assert_eq!(
+        [simulation.width(), simulation.height()],
+        SIZE.map(|val| 2 * val)
+    );
+    // note: the flow is not reset, and the Reynolds number is kept
+    assert_eq!(simulation.iteration(), ITERATIONS);
+    assert_eq!(
+        simulation.relaxation_time(),
+        Simulation::new(2 * SIZE[0], 2 * SIZE[1], 0, 0.1, 100.0).relaxation_time()
+    );
+    // note: the cylinder is at the same relative position
+    assert!(simulation.object(SIZE[0] / 2, SIZE[1]));
+    assert!(simulation.stats().max_velocity > 0.0);
+
+    simulation.run_for(ITERATIONS);
+    assert!(simulation.stats().max_velocity.is_finite());
+}
+
+#[test]
+fn uniform_flow() {
+    let velocity_vector = [0.05, 0.02];
+    let mut simulation =
+        Simulation::with_scenario(&SIZE, Scenario::DecayingTurbulence, 0.1, 100.0, 0);
+    let velocities = velocity_vector.repeat(SIZE[0] * SIZE[1]);
+    assert!(simulation.set_velocities(&velocities, false));
+
+    for size in [[64, 40], [20, 10]] {
+        assert!(simulation.resize(size[0], size[1]));
+        let lbgk = simulation.lbgk();
+        for pos in lbgk.positions() {
+            assert!((lbgk.density(&pos) - 1.0).abs() < 1e-5);
+            for (val, expected) in lbgk.velocity_vector(&pos).iter().zip(velocity_vector) {
+                assert!((val - expected).abs() < 1e-5, "{pos:?}");
+            }
+        }
+    }
+}
+
+#[test]
+fn smooth_flow() {
+    let mut simulation =
+        Simulation::with_scenario(&SIZE, Scenario::DecayingTurbulence, 0.1, 100.0, 0);
+    // note: a doubly periodic shear flow
+    let wave = |pos: usize, len: usize| 0.05 * (TAU * (pos as f32 + 0.5) / len as f32).sin();
+    let expected = simulation
+        .lbgk()
+        .positions()
+        .map(|[x, y]| [wave(y, SIZE[1]), wave(x, SIZE[0])])
+        .collect::<Vec<_>>();
+    assert!(simulation.set_velocities(expected.as_flattened(), false));
+
+    // note: doubling then halving averages adjacent interpolated values
+    assert!(simulation.resize(2 * SIZE[0], 2 * SIZE[1]));
+    assert!(simulation.resize(SIZE[0], SIZE[1]));
+    let lbgk = simulation.lbgk();
+    for (pos, expected) in lbgk.positions().zip(expected) {
+        for (val, expected) in lbgk.velocity_vector(&pos).iter().zip(expected) {
+            assert!((val - expected).abs() < 5e-3, "{pos:?}");
+        }
+    }
+}
+
+#[test]
+fn thermal() {
+    let mut simulation = Simulation::new_rayleigh_benard(SIZE[0], SIZE[1], 0.05, 1e4, 0.71);
+    simulation.run_for(ITERATIONS);
+    let temperatures = simulation.temperatures().unwrap();
+    let [min, max] = [f32::min, f32::max].map(|f| temperatures.iter().copied().reduce(f).unwrap());
+
+    assert!(simulation.resize(SIZE[0] / 2, SIZE[1] / 2));
+    let temperatures = simulation.temperatures().unwrap();
+    assert_eq!(temperatures.len(), SIZE[0] * SIZE[1] / 4);
+    // note: interpolated values are bounded by the old values
+    assert!(temperatures
+        .iter()
+        .all(|val| (min - 1e-6..=max + 1e-6).contains(val)));
+
+    simulation.run_for(ITERATIONS);
+    assert!(simulation.nusselt_number().unwrap().is_finite());
+}
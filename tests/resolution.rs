@@ -0,0 +1,32 @@
+//! Micro-benchmark of the throughput, to choose a lattice size.
+
+use wasm_lbm::resolution;
+
+#[test]
+fn measure_throughput() {
+    let throughput = resolution::measure_throughput(&[16, 8], 10);
+    assert!(throughput.is_finite() && throughput > 0.0);
+}
+
+#[test]
+fn recommended_size() {
+    // note: 1 MLUPS at 100 iterations per second is 10000 lattice updates an iteration
+    assert_eq!(
+        resolution::recommended_size(&[400, 100], 16, 1.0, 100.0),
+        [200, 50]
+    );
+    // note: no larger than the largest size
+    assert_eq!(
+        resolution::recommended_size(&[400, 100], 16, 1000.0, 100.0),
+        [400, 100]
+    );
+    // note: no smaller than the smallest size
+    assert_eq!(
+        resolution::recommended_size(&[400, 100], 16, 0.001, 100.0),
+        [16, 16]
+    );
+    assert_eq!(
+        resolution::recommended_size(&[8, 8], 16, 0.001, 100.0),
+        [8, 8]
+    );
+}
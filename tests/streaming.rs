@@ -0,0 +1,66 @@
+//! Messages of results streamed to an external collector, with field snapshots compressed
+//! losslessly.
+
+use wasm_lbm::compression;
+use wasm_lbm::lbgk::{BoundaryScheme, Field, Lbgk};
+use wasm_lbm::random::{Random, DEFAULT_SEED};
+use wasm_lbm::simulation::Simulation;
+use wasm_lbm::snapshot::Snapshot;
+use wasm_lbm::streaming;
+
+#[test]
+fn compression() {
+    let mut random = Random::new(DEFAULT_SEED);
+    let random_bytes = (0..1001)
+        .map(|_| random.next_u32() as u8)
+        .collect::<Vec<_>>();
+    let runs = (0..1000).map(|i| (i / 300) as u8).collect::<Vec<_>>();
+    for bytes in [&[][..], &[1], &[1, 1], &[1, 2, 2, 3], &random_bytes, &runs] {
+        assert_eq!(
+            compression::decompress(&compression::compress(bytes)).unwrap(),
+            bytes
+        );
+    }
+    // note: random bytes are expanded by at most a byte in 128
+    assert!(compression::compress(&random_bytes).len() <= 1001 + 8);
+    assert!(compression::compress(&runs).len() < 50);
+    // note: a truncated run
+    assert!(compression::decompress(&[0x81]).is_none());
+}
+
+#[test]
+fn snapshot_message() {
+    let mut simulation = Simulation::new(48, 24, 0, 0.1, 100.0);
+    simulation.run_for(20);
+    let snapshot = Snapshot::from_bytes(&simulation.snapshot()).unwrap();
+    let message = streaming::snapshot_message(20, &snapshot);
+    assert!(message.len() < snapshot.to_bytes().len());
+    let (iteration, decompressed) = streaming::snapshot_from_message(&message).unwrap();
+    assert_eq!(iteration, 20);
+    assert!(decompressed == snapshot);
+    assert!(streaming::snapshot_from_message(&message[..message.len() - 1]).is_none());
+
+    // note: a uniform lattice compresses well
+    let lbgk = Lbgk::new_d2q9(
+        &[64, 32],
+        &[[BoundaryScheme::Periodic; 2]; 2],
+        1.0,
+        &[0.0; 2],
+    );
+    let snapshot = Snapshot::new(&lbgk, &Field::ALL);
+    let message = streaming::snapshot_message(0, &snapshot);
+    assert!(message.len() < snapshot.to_bytes().len() / 50);
+}
+
+#[test]
+fn text_messages() {
+    assert_eq!(
+        streaming::stats_message(10, &[("drag coefficient", 1.5), ("residual", f32::NAN)]),
+        r#"{"type":"stats","iteration":10,"stats":{"drag coefficient":1.5,"residual":null}}"#
+    );
+    let values = [(1, &[0.5, -1.0][..]), (2, &[0.25, 2.0][..])];
+    assert_eq!(
+        streaming::probes_message(&[[1, 2], [3, 4]], values),
+        r#"{"type":"probes","positions":[[1,2],[3,4]],"iterations":[1,2],"values":[[0.5,-1],[0.25,2]]}"#
+    );
+}
@@ -0,0 +1,174 @@
+//! Streaming schemes, which traverse the lattice differently but give the same distributions.
+
+use wasm_lbm::lbgk::{BoundaryScheme, Lbgk, StreamingScheme, Surface, DEFAULT_OBJECT_ID};
+use wasm_lbm::scenario::Scenario;
+
+const SIZE: [usize; 2] = [48, 24];
+const DENSITY: f32 = 1.0;
+const VELOCITY: f32 = 0.05;
+const RELAXATION_TIME: f32 = 0.6;
+const ITERATIONS: usize = 20;
+/// Largest difference of the force on the objects, relative to its magnitude (as the momentum
+/// exchanged is summed in a different order).
+const FORCE_TOLERANCE: f32 = 1.0e-4;
+
+/// Lattice of boundary schemes, with the objects of a scenario.
+fn lbgk(boundary_schemes: &[[BoundaryScheme; 2]; 2], scenario: Option<Scenario>) -> Lbgk<2, 9> {
+    let velocity_vector = match scenario.is_some_and(|scenario| scenario.enclosed()) {
+        true => [0.0; 2],
+        false => [VELOCITY, 0.0],
+    };
+    let mut lbgk = Lbgk::new_d2q9(&SIZE, boundary_schemes, DENSITY, &velocity_vector);
+    lbgk.set_source(DENSITY, &[VELOCITY, 0.0]);
+    if let Some(scenario) = scenario {
+        scenario.set_objects(&mut lbgk);
+    }
+    lbgk
+}
+
+/// Assert pushing the distributions gives the same results as pulling them and as the AA
+/// pattern, for a surface of the objects.
+fn assert_same(
+    name: &str,
+    boundary_schemes: &[[BoundaryScheme; 2]; 2],
+    scenario: Option<Scenario>,
+    surface: Surface,
+) {
+    let mut push = lbgk(boundary_schemes, scenario);
+    push.set_object_surface(DEFAULT_OBJECT_ID, surface);
+    let mut others = [StreamingScheme::Pull, StreamingScheme::Aa].map(|streaming_scheme| {
+        let mut lbgk = lbgk(boundary_schemes, scenario);
+        lbgk.set_object_surface(DEFAULT_OBJECT_ID, surface);
+        lbgk.set_streaming_scheme(streaming_scheme);
+        lbgk
+    });
+
+    // note: compared after the last two iterations, as the AA pattern alternates between
+    // storing the distributions reversed and not
+    for iteration in 0..ITERATIONS {
+        push.iterate(RELAXATION_TIME);
+        for (scheme, other) in ["pull", "AA"].iter().zip(&mut others) {
+            other.iterate(RELAXATION_TIME);
+            if iteration + 2 < ITERATIONS {
+                continue;
+            }
+            for pos in push.positions() {
+                assert_eq!(
+                    push.distributions(&pos),
+                    other.distributions(&pos),
+                    "{name}, {scheme}, iteration {iteration}: at {pos:?}"
+                );
+            }
+            let (push_force, other_force) = (push.force(), other.force());
+            let magnitude = push_force.iter().map(|val| val.abs()).fold(0.0, f32::max);
+            for (push_force, other_force) in push_force.iter().zip(other_force) {
+                assert!(
+                    (push_force - other_force).abs() <= FORCE_TOLERANCE * magnitude,
+                    "{name}, {scheme}, iteration {iteration}: force {push_force}, other {other_force}"
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn scenarios() {
+    for scenario in Scenario::ALL {
+        assert_same(
+            &scenario.name(),
+            &scenario.boundary_schemes(),
+            Some(scenario),
+            Surface::NoSlip,
+        );
+    }
+}
+
+#[test]
+fn object_surfaces() {
+    for scenario in [Scenario::TwinCylinders, Scenario::Airfoil("2412", 8.0)] {
+        for surface in [Surface::FreeSlip, Surface::Porous(0.1)] {
+            assert_same(
+                &scenario.name(),
+                &scenario.boundary_schemes(),
+                Some(scenario),
+                surface,
+            );
+        }
+    }
+}
+
+#[test]
+fn boundary_schemes() {
+    for (i, boundary_schemes) in [
+        [
+            [BoundaryScheme::Periodic; 2],
+            [BoundaryScheme::SpecularReflection; 2],
+        ],
+        [
+            [BoundaryScheme::Inflow, BoundaryScheme::ExtrapolatedOutflow],
+            [BoundaryScheme::Periodic; 2],
+        ],
+        [
+            [BoundaryScheme::SpecularReflection, BoundaryScheme::Periodic],
+            [BoundaryScheme::MovingWall, BoundaryScheme::BounceBack],
+        ],
+    ]
+    .iter()
+    .enumerate()
+    {
+        assert_same(
+            &format!("boundary schemes {i}"),
+            boundary_schemes,
+            None,
+            Surface::NoSlip,
+        );
+    }
+}
+
+#[test]
+fn changes_between_iterations() {
+    let scenario = Scenario::Cylinder;
+    let mut push = lbgk(&scenario.boundary_schemes(), Some(scenario));
+    let mut aa = lbgk(&scenario.boundary_schemes(), Some(scenario));
+    aa.set_streaming_scheme(StreamingScheme::Aa);
+
+    // note: an odd number of iterations leaves the AA pattern storing the distributions
+    // reversed, so each change restores them first
+    let changes: [fn(&mut Lbgk<2, 9>); 4] = [
+        |lbgk| lbgk.set_object(&[4, 4], true),
+        |lbgk| lbgk.set_object_surface(DEFAULT_OBJECT_ID, Surface::FreeSlip),
+        |lbgk| {
+            lbgk.set_boundary_schemes(&[
+                [BoundaryScheme::Periodic; 2],
+                [BoundaryScheme::BounceBack; 2],
+            ])
+        },
+        |lbgk| lbgk.set_distributions(&[10, 10], &lbgk.distributions(&[11, 10])),
+    ];
+    for change in changes {
+        for _ in 0..ITERATIONS + 1 {
+            push.iterate(RELAXATION_TIME);
+            aa.iterate(RELAXATION_TIME);
+        }
+        change(&mut push);
+        change(&mut aa);
+        for pos in push.positions() {
+            assert_eq!(
+                push.distributions(&pos),
+                aa.distributions(&pos),
+                "at {pos:?}"
+            );
+        }
+    }
+    for _ in 0..ITERATIONS {
+        push.iterate(RELAXATION_TIME);
+        aa.iterate(RELAXATION_TIME);
+    }
+    for pos in push.positions() {
+        assert_eq!(
+            push.distributions(&pos),
+            aa.distributions(&pos),
+            "at {pos:?}"
+        );
+    }
+}
@@ -0,0 +1,72 @@
+//! [Taylor–Green vortex](https://en.wikipedia.org/wiki/Taylor%E2%80%93Green_vortex) decay,
+//! compared with the analytical viscous decay of the kinetic energy.
+
+use wasm_lbm::initial_condition;
+use wasm_lbm::lbgk::{BoundaryScheme, Lbgk};
+
+const SIZE: [usize; 2] = [32, 32];
+const DENSITY: f32 = 1.0;
+const VELOCITY: f32 = 0.01;
+/// Iterations before measuring the decay, for the initial transient to settle.
+const SETTLE_ITERATIONS: usize = 20;
+const ITERATIONS: usize = 100;
+/// Largest error of the decay rate, relative to the analytical decay rate.
+const TOLERANCE: f32 = 0.02;
+
+/// Kinetic energy of the fluid.
+fn kinetic_energy(lbgk: &Lbgk<2, 9>) -> f32 {
+    lbgk.iter_cells()
+        .map(|(_, cell)| {
+            let [u, v] = cell.velocity_vector();
+            0.5 * cell.density() * (u * u + v * v)
+        })
+        .sum()
+}
+
+/// Assert the kinetic energy decays at the analytical rate.
+fn assert_decay_rate(relaxation_time: f32) {
+    let mut lbgk = Lbgk::new_d2q9(
+        &SIZE,
+        &[[BoundaryScheme::Periodic; 2]; 2],
+        DENSITY,
+        &[0.0; 2],
+    );
+    initial_condition::taylor_green_vortex(&mut lbgk, DENSITY, VELOCITY);
+
+    for _ in 0..SETTLE_ITERATIONS {
+        lbgk.iterate(relaxation_time);
+    }
+    let initial = kinetic_energy(&lbgk);
+    for _ in 0..ITERATIONS {
+        lbgk.iterate(relaxation_time);
+    }
+    let decay_rate = (initial / kinetic_energy(&lbgk)).ln() / ITERATIONS as f32;
+
+    let viscosity = (relaxation_time - 0.5) / 3.0;
+    let analytical = 2.0 * initial_condition::taylor_green_decay_rate(SIZE, viscosity);
+    let error = (decay_rate - analytical).abs() / analytical;
+    assert!(
+        error < TOLERANCE,
+        "relaxation time {relaxation_time}: decay rate {decay_rate}, analytical {analytical}, relative error {error}"
+    );
+}
+
+#[test]
+fn decay_rate_relaxation_time_0_6() {
+    assert_decay_rate(0.6);
+}
+
+#[test]
+fn decay_rate_relaxation_time_0_8() {
+    assert_decay_rate(0.8);
+}
+
+#[test]
+fn decay_rate_relaxation_time_1_0() {
+    assert_decay_rate(1.0);
+}
+
+#[test]
+fn decay_rate_relaxation_time_1_5() {
+    assert_decay_rate(1.5);
+}
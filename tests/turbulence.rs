@@ -0,0 +1,127 @@
+//! Decaying [two dimensional turbulence](https://en.wikipedia.org/wiki/Two-dimensional_turbulence)
+//! from a random divergence free velocity field of a prescribed energy spectrum.
+
+use wasm_lbm::initial_condition;
+use wasm_lbm::lbgk::{BoundaryScheme, Lbgk};
+use wasm_lbm::random::Random;
+use wasm_lbm::spectrum::Spectra;
+
+use std::f32::consts::PI;
+
+const SIZE: [usize; 2] = [32, 32];
+const DENSITY: f32 = 1.0;
+const VELOCITY: f32 = 0.05;
+const PEAK_WAVELENGTH: f32 = 8.0;
+const SEED: u32 = 12345;
+const RELAXATION_TIME: f32 = 0.55;
+const ITERATIONS: usize = 100;
+
+fn turbulence(seed: u32) -> Lbgk<2, 9> {
+    let mut lbgk = Lbgk::new_d2q9(
+        &SIZE,
+        &[[BoundaryScheme::Periodic; 2]; 2],
+        DENSITY,
+        &[0.0; 2],
+    );
+    initial_condition::random_turbulence(
+        &mut lbgk,
+        VELOCITY,
+        2.0 * PI / PEAK_WAVELENGTH,
+        &mut Random::new(seed),
+    );
+    lbgk
+}
+
+#[test]
+fn divergence_free() {
+    let lbgk = turbulence(SEED);
+    let [width, height] = SIZE;
+    let mut mean_squared = 0.0;
+    for [x, y] in lbgk.positions() {
+        let [u, v] = lbgk.velocity_vector(&[x, y]);
+        mean_squared += u * u + v * v;
+
+        // note: by central differences
+        let divergence = 0.5
+            * (lbgk.velocity_vector(&[(x + 1) % width, y])[0]
+                - lbgk.velocity_vector(&[(x + width - 1) % width, y])[0]
+                + lbgk.velocity_vector(&[x, (y + 1) % height])[1]
+                - lbgk.velocity_vector(&[x, (y + height - 1) % height])[1]);
+        assert!(
+            divergence.abs() < 1e-6,
+            "divergence {divergence} at {x}, {y}"
+        );
+    }
+    let rms = (mean_squared / (width * height) as f32).sqrt();
+    assert!(
+        (rms - VELOCITY).abs() < 1e-4,
+        "root mean square velocity {rms}"
+    );
+}
+
+#[test]
+fn reproducible() {
+    let [first, second, other] = [SEED, SEED, SEED + 1].map(turbulence);
+    let velocity = |lbgk: &Lbgk<2, 9>| lbgk.velocity_vector(&[5, 7]);
+    assert_eq!(velocity(&first), velocity(&second));
+    assert_ne!(velocity(&first), velocity(&other));
+}
+
+#[test]
+fn spectra() {
+    let lbgk = turbulence(SEED);
+    let spectra = Spectra::new(&lbgk);
+
+    // note: the energy sums to the mean kinetic energy (per unit density)
+    let total = spectra.energy().iter().sum::<f32>();
+    assert!(
+        (total - 0.5 * VELOCITY * VELOCITY).abs() < 1e-5,
+        "energy {total}"
+    );
+    assert!(spectra.energy()[0] < 1e-6 * total);
+
+    // note: the energy peaks near the peak wavenumber, with nothing beyond three times it
+    let peak_wavenumber = 2.0 * PI / PEAK_WAVELENGTH;
+    let (peak, _) = spectra
+        .energy()
+        .iter()
+        .enumerate()
+        .max_by(|(_, first), (_, second)| first.total_cmp(second))
+        .unwrap();
+    assert!(
+        (spectra.wavenumber(peak) / peak_wavenumber - 1.0).abs() < 0.5,
+        "peak wavenumber {}",
+        spectra.wavenumber(peak)
+    );
+    for (shell, energy) in spectra.energy().iter().enumerate() {
+        if spectra.wavenumber(shell) > 3.5 * peak_wavenumber {
+            assert!(*energy < 1e-6 * total);
+        }
+    }
+
+    let csv = spectra.to_csv();
+    assert!(csv.starts_with("wavenumber,energy,enstrophy\n"));
+    assert_eq!(csv.lines().count(), spectra.energy().len() + 1);
+}
+
+#[test]
+fn decay() {
+    let mut lbgk = turbulence(SEED);
+    let total = |lbgk: &Lbgk<2, 9>| {
+        let spectra = Spectra::new(lbgk);
+        [spectra.energy(), spectra.enstrophy()].map(|values| values.iter().sum::<f32>())
+    };
+    let [energy, enstrophy] = total(&lbgk);
+    for _ in 0..ITERATIONS {
+        lbgk.iterate(RELAXATION_TIME);
+    }
+    let [new_energy, new_enstrophy] = total(&lbgk);
+    assert!(
+        new_energy < energy,
+        "energy {new_energy}, initially {energy}"
+    );
+    assert!(
+        new_enstrophy < enstrophy,
+        "enstrophy {new_enstrophy}, initially {enstrophy}"
+    );
+}
@@ -0,0 +1,90 @@
+//! Changes of configuration applied in place, without resetting the flow.
+
+use wasm_lbm::lbgk::BoundaryScheme;
+use wasm_lbm::simulation::{PartialConfig, Simulation};
+
+const SIZE: [usize; 2] = [48, 24];
+const ITERATIONS: usize = 20;
+
+/// Simulation of the first scenario (a cylinder in a channel) of a Reynolds number.
+fn cylinder(reynolds_number: f32) -> Simulation {
+    Simulation::new(SIZE[0], SIZE[1], 0, 0.1, reynolds_number)
+}
+
+#[test]
+fn flow_parameters() {
+    let mut simulation = cylinder(100.0);
+    simulation.run_for(ITERATIONS);
+    let snapshot = simulation.snapshot();
+
+    let partial_config: PartialConfig = serde_json::from_str(r#"{"reynoldsNumber":200}"#).unwrap();
+    simulation.update_config(&partial_config).unwrap();
+    assert_eq!(
+        simulation.relaxation_time(),
+        cylinder(200.0).relaxation_time()
+    );
+    assert_eq!(simulation.velocity(), 0.1);
+    assert_eq!(simulation.reynolds_number(), 200.0);
+    // note: the flow is not reset
+    assert_eq!(simulation.iteration(), ITERATIONS);
+    assert!(simulation.snapshot() == snapshot);
+
+    simulation.run_for(ITERATIONS);
+    assert!(simulation.stats().max_velocity.is_finite());
+}
+
+#[test]
+fn boundary_schemes() {
+    let mut simulation = cylinder(100.0);
+    simulation.run_for(ITERATIONS);
+    let partial_config: PartialConfig = serde_json::from_str(
+        r#"{"boundarySchemes":[["inflow","extrapolatedOutflow"],["bounceBack","bounceBack"]]}"#,
+    )
+    .unwrap();
+    simulation.update_config(&partial_config).unwrap();
+    simulation.run_for(ITERATIONS);
+    assert!(simulation.stats().max_velocity.is_finite());
+
+    // note: the flow differs from that of the boundary schemes of the scenario
+    let mut unchanged = cylinder(100.0);
+    unchanged.run_for(2 * ITERATIONS);
+    assert_eq!(unchanged.iteration(), simulation.iteration());
+    assert!(unchanged.snapshot() != simulation.snapshot());
+}
+
+#[test]
+fn invalid() {
+    let mut simulation = cylinder(100.0);
+    let relaxation_time = simulation.relaxation_time();
+    let periodic_one_side = PartialConfig {
+        reynolds_number: Some(200.0),
+        boundary_schemes: Some([
+            [BoundaryScheme::Periodic, BoundaryScheme::Outflow],
+            [BoundaryScheme::BounceBack; 2],
+        ]),
+        ..Default::default()
+    };
+    let not_positive = PartialConfig {
+        velocity: Some(0.0),
+        ..Default::default()
+    };
+    let not_a_number = PartialConfig {
+        prandtl_number: Some(f32::NAN),
+        ..Default::default()
+    };
+    let infinite = PartialConfig {
+        reynolds_number: Some(f32::INFINITY),
+        ..Default::default()
+    };
+    for partial_config in [periodic_one_side, not_positive, not_a_number, infinite] {
+        assert!(simulation.update_config(&partial_config).is_err());
+    }
+    // note: none of an invalid partial configuration is applied
+    assert_eq!(simulation.relaxation_time(), relaxation_time);
+
+    assert!(serde_json::from_str::<PartialConfig>(r#"{"size":[64,32]}"#).is_err());
+    assert!(serde_json::from_str::<PartialConfig>(
+        r#"{"boundarySchemes":[["wall","wall"],["wall","wall"]]}"#
+    )
+    .is_err());
+}
@@ -0,0 +1,268 @@
+//! Tests of the JavaScript facing API across the wasm boundary, run headlessly in the browser
+//! by `wasm-pack test --headless --firefox`, and natively by `cargo test`.
+
+use wasm_lbm::embedding::{self, Config, EmbeddedSimulation};
+use wasm_lbm::simulation::{PartialConfig, Simulation};
+use wasm_lbm::snapshot;
+
+use wasm_bindgen_test::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+const SIZE: [usize; 2] = [64, 32];
+const ITERATIONS: usize = 10;
+
+/// Simulation of the first scenario (a cylinder in a channel).
+fn simulation() -> Simulation {
+    Simulation::new(SIZE[0], SIZE[1], 0, 0.1, 100.0)
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+#[cfg_attr(not(target_arch = "wasm32"), test)]
+fn iterate() {
+    let mut simulation = simulation();
+    assert_eq!([simulation.width(), simulation.height()], SIZE);
+    assert!(simulation.relaxation_time() > 0.5);
+
+    simulation.run_for(ITERATIONS);
+    assert_eq!(simulation.iteration(), ITERATIONS);
+    assert!(simulation
+        .force_coefficients()
+        .iter()
+        .all(|val| val.is_finite()));
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+#[cfg_attr(not(target_arch = "wasm32"), test)]
+fn field_values() {
+    let mut simulation = simulation();
+    simulation.run_for(ITERATIONS);
+
+    let names = simulation.field_names();
+    for (field, name) in names.iter().enumerate() {
+        let values = simulation.field_values(field).unwrap();
+        assert_eq!(values.len(), SIZE[0] * SIZE[1], "{name}");
+        assert!(values.iter().all(|val| val.is_finite()), "{name}");
+    }
+    assert!(simulation.field_values(names.len()).is_none());
+
+    // note: the density of the fluid is about one
+    let density = simulation.field_values(0).unwrap();
+    let [x, y] = [1, SIZE[1] / 2];
+    assert!(!simulation.object(x, y));
+    assert!((density[SIZE[0] * y + x] - 1.0).abs() < 0.1);
+    assert!(!simulation.object(SIZE[0], 0));
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+#[cfg_attr(not(target_arch = "wasm32"), test)]
+fn snapshot() {
+    let mut simulation = simulation();
+    simulation.run_for(ITERATIONS);
+
+    let bytes = simulation.snapshot();
+    for (field, name) in simulation.field_names().iter().enumerate() {
+        assert_eq!(
+            snapshot::snapshot_values(&bytes, name),
+            simulation.field_values(field),
+            "{name}"
+        );
+    }
+    assert!(snapshot::snapshot_values(&bytes[1..], "Density").is_none());
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+#[cfg_attr(not(target_arch = "wasm32"), test)]
+fn run_for_duration() {
+    let mut simulation = simulation();
+    simulation.run_for(ITERATIONS);
+
+    let iterations = simulation.run_for_duration(10.0);
+    assert!(iterations >= 1);
+    assert_eq!(simulation.iteration(), ITERATIONS + iterations);
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+#[cfg_attr(not(target_arch = "wasm32"), test)]
+fn embedding() {
+    assert_eq!(embedding::api_version(), embedding::API_VERSION);
+    let scenario = &embedding::scenario_names()[0];
+    let create = |width, velocity| {
+        EmbeddedSimulation::create(width, SIZE[1], scenario, velocity, 100.0, None)
+    };
+    assert!(create(8, 0.1).is_err());
+    assert!(create(SIZE[0], 0.0).is_err());
+    assert!(create(SIZE[0], f32::NAN).is_err());
+    assert!(EmbeddedSimulation::create(SIZE[0], SIZE[1], "Unknown", 0.1, 100.0, None).is_err());
+
+    let mut embedded = create(SIZE[0], 0.1).unwrap();
+    assert_eq!([embedded.width(), embedded.height()], SIZE);
+    assert_eq!(&embedded.scenario(), scenario);
+    assert_eq!(embedded.step(ITERATIONS), ITERATIONS);
+    assert!(embedded.configure(0.1, -1.0).is_err());
+    embedded.configure(0.05, 200.0).unwrap();
+    assert_eq!(embedded.step(ITERATIONS), 2 * ITERATIONS);
+    assert_eq!(embedded.iteration(), 2 * ITERATIONS);
+
+    // note: the same as the headless simulation, until configured
+    let mut simulation = simulation();
+    simulation.run_for(ITERATIONS);
+    let embedded = create(SIZE[0], 0.1).unwrap();
+    embedded.step(ITERATIONS);
+    for (field, name) in embedded.field_names().iter().enumerate() {
+        assert_eq!(
+            embedded.field(name),
+            simulation.field_values(field),
+            "{name}"
+        );
+    }
+    assert!(embedded.field("Unknown").is_none());
+    assert_eq!(
+        embedded.force_coefficients(),
+        simulation.force_coefficients()
+    );
+    embedded.destroy();
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen_test]
+fn embedding_callbacks() {
+    let mut embedded =
+        EmbeddedSimulation::create(SIZE[0], SIZE[1], "Cylinder", 0.1, 100.0, None).unwrap();
+    let calls = js_sys::Array::new();
+    let callback =
+        js_sys::Function::new_with_args("iteration", "this.push(iteration)").bind0(&calls);
+    let handle = embedded.on_step(callback, 4);
+    embedded.step(ITERATIONS);
+    assert_eq!(calls.length(), 2);
+    assert_eq!(calls.get(1).as_f64(), Some(8.0));
+
+    assert!(embedded.remove_callback(handle));
+    assert!(!embedded.remove_callback(handle));
+    embedded.step(ITERATIONS);
+    assert_eq!(calls.length(), 2);
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen_test]
+fn embedding_callbacks_read_simulation() {
+    // note: called through JavaScript as an embedding would, where each method borrows the
+    // simulation
+    let simulation = wasm_bindgen::JsValue::from(
+        EmbeddedSimulation::create(SIZE[0], SIZE[1], "Cylinder", 0.1, 100.0, None).unwrap(),
+    );
+    let method = |name: &str| {
+        js_sys::Function::from(js_sys::Reflect::get(&simulation, &name.into()).unwrap())
+    };
+    let calls = js_sys::Array::new();
+    let callback = js_sys::Function::new_with_args(
+        "simulation, iteration",
+        "this.push([iteration, simulation.stats().iteration, simulation.field('Density').length])",
+    )
+    .bind1(&calls, &simulation);
+    method("onStep")
+        .call2(&simulation, &callback, &wasm_bindgen::JsValue::from(4))
+        .unwrap();
+    method("step")
+        .call1(&simulation, &wasm_bindgen::JsValue::from(ITERATIONS))
+        .unwrap();
+
+    assert_eq!(calls.length(), 2);
+    for (call, iteration) in calls.iter().zip([4.0, 8.0]) {
+        let call = js_sys::Array::from(&call);
+        assert_eq!(call.get(0).as_f64(), Some(iteration));
+        assert_eq!(call.get(1).as_f64(), Some(iteration));
+        assert_eq!(call.get(2).as_f64(), Some((SIZE[0] * SIZE[1]) as f64));
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+#[cfg_attr(not(target_arch = "wasm32"), test)]
+fn stats() {
+    let mut simulation = simulation();
+    simulation.run_for(ITERATIONS);
+    let stats = simulation.stats();
+    assert_eq!(stats.iteration, ITERATIONS);
+    assert_eq!(stats.relaxation_time, simulation.relaxation_time());
+    assert_eq!(
+        [stats.drag_coefficient, stats.lift_coefficient].to_vec(),
+        simulation.force_coefficients()
+    );
+    assert!((stats.mean_density - 1.0).abs() < 0.01);
+    assert!(stats.max_velocity > 0.0 && stats.max_mach_number < 1.0);
+    assert!(stats.nusselt_number.is_none());
+
+    // note: keys are camel case, as in JavaScript
+    let json = serde_json::to_value(&stats).unwrap();
+    for key in [
+        "iteration",
+        "dragCoefficient",
+        "maxMachNumber",
+        "nusseltNumber",
+    ] {
+        assert!(json.get(key).is_some(), "{key}");
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+#[cfg_attr(not(target_arch = "wasm32"), test)]
+fn embedding_config() {
+    let config: Config = serde_json::from_str(
+        r#"{"width":64,"height":32,"scenario":"Cylinder","velocity":0.1,"reynoldsNumber":100}"#,
+    )
+    .unwrap();
+    assert_eq!(config.seed, None);
+    assert!(serde_json::from_str::<Config>(r#"{"width":64,"height":32}"#).is_err());
+    let mut embedded = EmbeddedSimulation::from_config(&config).unwrap();
+    assert_eq!(embedded.config(), &config);
+
+    // note: omitted flow parameters are unchanged, and invalid ones are not set
+    let partial_config: PartialConfig = serde_json::from_str(r#"{"reynoldsNumber":200}"#).unwrap();
+    embedded.update_config(&partial_config).unwrap();
+    assert_eq!(embedded.config().velocity, 0.1);
+    assert_eq!(embedded.config().reynolds_number, 200.0);
+    assert!(serde_json::from_str::<PartialConfig>(r#"{"reynolds":200}"#).is_err());
+    let invalid = PartialConfig {
+        velocity: Some(-0.1),
+        ..Default::default()
+    };
+    assert!(embedded.update_config(&invalid).is_err());
+    assert_eq!(embedded.config().velocity, 0.1);
+
+    embedded.step(ITERATIONS);
+    assert_eq!(embedded.stats().iteration, ITERATIONS);
+
+    assert!(embedded.resize(8, 32).is_err());
+    embedded.resize(96, 48).unwrap();
+    assert_eq!(
+        [embedded.config().width, embedded.config().height],
+        [96, 48]
+    );
+    assert_eq!(embedded.step(ITERATIONS), 2 * ITERATIONS);
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen_test]
+fn embedding_objects() {
+    let get = |object: &wasm_bindgen::JsValue, key: &str| {
+        js_sys::Reflect::get(object, &key.into()).unwrap()
+    };
+    let config = js_sys::JSON::parse(
+        r#"{"width":64,"height":32,"scenario":"Cylinder","velocity":0.1,"reynoldsNumber":100}"#,
+    )
+    .unwrap();
+    let mut embedded = EmbeddedSimulation::from_config_object(config).unwrap();
+    let flow_config = js_sys::JSON::parse(r#"{"velocity":0.05}"#).unwrap();
+    embedded.configure_object(flow_config).unwrap();
+    assert_eq!(
+        get(&embedded.config_object().unwrap(), "velocity").as_f64(),
+        Some(0.05f32 as f64)
+    );
+    let unknown = js_sys::JSON::parse(r#"{"speed":0.05}"#).unwrap();
+    assert!(embedded.configure_object(unknown).is_err());
+
+    embedded.step(ITERATIONS);
+    let stats = embedded.stats_object().unwrap();
+    assert_eq!(get(&stats, "iteration").as_f64(), Some(ITERATIONS as f64));
+    assert!(get(&stats, "dragCoefficient").as_f64().is_some());
+}